@@ -0,0 +1,332 @@
+use crate::database::migrations::{self, SCHEMA_VERSION};
+use crate::database::models::{BackupInfo, BackupKind, RestoreReport};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::{Connection, OpenFlags};
+use std::path::{Path, PathBuf};
+
+const MANUAL_PREFIX: &str = "backup-";
+const DAILY_PREFIX: &str = "auto-daily-";
+const WEEKLY_PREFIX: &str = "auto-weekly-";
+const PRE_RESTORE_PREFIX: &str = "pre-restore-";
+const EXTENSION: &str = "db";
+
+/// How many automatic backups of each kind to keep. A count of 0 turns that
+/// kind off.
+#[derive(Debug, Clone, Copy)]
+pub struct Retention {
+    pub daily: usize,
+    pub weekly: usize,
+}
+
+/// The `backups` folder next to the database file.
+pub fn directory(conn: &Connection) -> anyhow::Result<PathBuf> {
+    let path = conn
+        .path()
+        .filter(|path| !path.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("The database isn't stored in a file"))?;
+    let parent = Path::new(path)
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("The database has no containing folder"))?;
+    Ok(parent.join("backups"))
+}
+
+/// Name for a manual backup taken now, in `dir`.
+pub fn manual_path(dir: &Path) -> PathBuf {
+    timestamped_path(dir, MANUAL_PREFIX)
+}
+
+/// A path in `dir` named after the current time, numbered when a backup
+/// was already taken that second.
+fn timestamped_path(dir: &Path, prefix: &str) -> PathBuf {
+    let stamp = Local::now().format("%Y-%m-%d-%H%M%S").to_string();
+    let mut path = dir.join(file_name(prefix, &stamp));
+    let mut attempt = 1;
+    while path.exists() {
+        attempt += 1;
+        path = dir.join(file_name(prefix, &format!("{}-{}", stamp, attempt)));
+    }
+    path
+}
+
+fn file_name(prefix: &str, stamp: &str) -> String {
+    format!("{}{}.{}", prefix, stamp, EXTENSION)
+}
+
+/// Copies the live database to `path` with SQLite's online backup. The copy
+/// is written beside the target and renamed into place, so a failed backup
+/// never leaves a partial file under the real name.
+pub fn create(conn: &Connection, path: &Path) -> anyhow::Result<BackupInfo> {
+    if let Some(live) = conn.path().filter(|live| !live.is_empty()) {
+        if same_file(Path::new(live), path) {
+            anyhow::bail!("Choose a different file than the live database");
+        }
+    }
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let partial = path.with_extension("partial");
+    let copied = (|| -> anyhow::Result<()> {
+        let mut target = Connection::open(&partial)?;
+        copy(conn, &mut target)
+    })();
+    if let Err(e) = copied {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, path)?;
+
+    info(path)
+}
+
+/// Copies every page in a single step; callers hold the connection lock for
+/// the duration anyway, so there's nothing to gain from smaller ones.
+fn copy(from: &Connection, to: &mut Connection) -> anyhow::Result<()> {
+    match Backup::new(from, to)?.step(-1)? {
+        StepResult::Done => Ok(()),
+        other => anyhow::bail!("The backup didn't finish: {:?}", other),
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Checks that `path` is an intact database this version can open, and
+/// returns its schema version.
+pub fn verify(path: &Path) -> anyhow::Result<i32> {
+    if !path.is_file() {
+        anyhow::bail!("Backup {} not found", path.display());
+    }
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| anyhow::anyhow!("Can't open the backup: {}", e))?;
+
+    let integrity: String = conn
+        .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .map_err(|e| anyhow::anyhow!("The backup isn't a valid database: {}", e))?;
+    if integrity != "ok" {
+        anyhow::bail!("The backup failed its integrity check: {}", integrity);
+    }
+
+    let tables: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master
+         WHERE type = 'table' AND name IN ('organizations', 'users', 'substitute_requests', 'settings')",
+        [],
+        |row| row.get(0),
+    )?;
+    if tables < 4 {
+        anyhow::bail!("The file isn't a Substitute Finder backup");
+    }
+
+    let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version > SCHEMA_VERSION {
+        anyhow::bail!(
+            "The backup is from a newer version of the app (schema {}, this version supports {})",
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    Ok(version)
+}
+
+/// Replaces the live database with the backup at `path`. The backup is
+/// verified first and the current data is saved to `safety_dir`, so a
+/// restore can itself be undone. Older backups are migrated afterwards.
+pub fn restore(conn: &mut Connection, path: &Path, safety_dir: &Path) -> anyhow::Result<RestoreReport> {
+    let schema_version = verify(path)?;
+    let safety_backup = create(conn, &timestamped_path(safety_dir, PRE_RESTORE_PREFIX))?;
+
+    {
+        // Copying into a live connection happens in one write transaction,
+        // so a failure leaves the current data as it was.
+        let source = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        copy(&source, conn)?;
+    }
+    conn.flush_prepared_statement_cache();
+    conn.execute_batch(include_str!("database/schema.sql"))?;
+    migrations::run(conn)?;
+
+    Ok(RestoreReport {
+        restored_from: path.to_string_lossy().to_string(),
+        schema_version,
+        safety_backup,
+    })
+}
+
+/// Backups in `dir`, newest first.
+pub fn list(dir: &Path) -> anyhow::Result<Vec<BackupInfo>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|extension| extension == EXTENSION) {
+            backups.push(info(&path)?);
+        }
+    }
+    backups.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| b.path.cmp(&a.path)));
+    Ok(backups)
+}
+
+fn info(path: &Path) -> anyhow::Result<BackupInfo> {
+    let metadata = std::fs::metadata(path)?;
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    let kind = if name.starts_with(DAILY_PREFIX) {
+        BackupKind::Daily
+    } else if name.starts_with(WEEKLY_PREFIX) {
+        BackupKind::Weekly
+    } else if name.starts_with(PRE_RESTORE_PREFIX) {
+        BackupKind::PreRestore
+    } else {
+        BackupKind::Manual
+    };
+
+    Ok(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        kind,
+        created_at: DateTime::<Utc>::from(metadata.modified()?),
+        size_bytes: metadata.len(),
+    })
+}
+
+/// Takes today's daily and this week's weekly backup if they're missing,
+/// then removes automatic backups beyond the retention counts. Returns the
+/// backups it took.
+pub fn run_scheduled(
+    conn: &Connection,
+    dir: &Path,
+    today: NaiveDate,
+    retention: Retention,
+) -> anyhow::Result<Vec<BackupInfo>> {
+    let mut taken = Vec::new();
+    let daily = dir.join(file_name(DAILY_PREFIX, &today.format("%Y-%m-%d").to_string()));
+    let weekly = dir.join(file_name(WEEKLY_PREFIX, &today.format("%G-W%V").to_string()));
+
+    if retention.daily > 0 && !daily.exists() {
+        taken.push(create(conn, &daily)?);
+    }
+    if retention.weekly > 0 && !weekly.exists() {
+        taken.push(create(conn, &weekly)?);
+    }
+
+    prune(dir, DAILY_PREFIX, retention.daily)?;
+    prune(dir, WEEKLY_PREFIX, retention.weekly)?;
+    Ok(taken)
+}
+
+/// Keeps the newest `keep` backups with the prefix. Their names end in a
+/// date, so name order is age order.
+fn prune(dir: &Path, prefix: &str, keep: usize) -> anyhow::Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    let mut names: Vec<String> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(prefix) && name.ends_with(&format!(".{}", EXTENSION)))
+        .collect();
+    names.sort_unstable_by(|a, b| b.cmp(a));
+    for name in names.into_iter().skip(keep) {
+        std::fs::remove_file(dir.join(name))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::fixtures::{insert_organization, test_connection};
+
+    fn count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM organizations", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_backup_and_restore_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = Connection::open(dir.path().join("database.db")).unwrap();
+        conn.execute_batch(include_str!("database/schema.sql")).unwrap();
+        migrations::run(&conn).unwrap();
+        insert_organization(&conn, "org-1");
+
+        let backups = directory(&conn).unwrap();
+        let backup = create(&conn, &manual_path(&backups)).unwrap();
+        assert_eq!(backup.kind, BackupKind::Manual);
+        assert_eq!(verify(Path::new(&backup.path)).unwrap(), SCHEMA_VERSION);
+        assert!(create(&conn, &dir.path().join("database.db")).is_err());
+
+        insert_organization(&conn, "org-2");
+        let report = restore(&mut conn, Path::new(&backup.path), &backups).unwrap();
+        assert_eq!(count(&conn), 1);
+        assert_eq!(report.safety_backup.kind, BackupKind::PreRestore);
+
+        let listed = list(&backups).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(listed.iter().all(|backup| !backup.path.ends_with(".partial")));
+
+        restore(&mut conn, Path::new(&report.safety_backup.path), &backups).unwrap();
+        assert_eq!(count(&conn), 2);
+    }
+
+    #[test]
+    fn test_verify_rejects_damaged_foreign_and_newer_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let garbage = dir.path().join("garbage.db");
+        std::fs::write(&garbage, "not a database at all, just some text".repeat(200)).unwrap();
+        assert!(verify(&garbage).is_err());
+
+        let foreign = dir.path().join("foreign.db");
+        Connection::open(&foreign).unwrap().execute_batch("CREATE TABLE notes (text TEXT)").unwrap();
+        assert!(verify(&foreign).unwrap_err().to_string().contains("isn't a Substitute Finder backup"));
+
+        let newer = dir.path().join("newer.db");
+        let conn = test_connection();
+        create(&conn, &newer).unwrap();
+        Connection::open(&newer)
+            .unwrap()
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(verify(&newer).unwrap_err().to_string().contains("newer version"));
+
+        let mut live = test_connection();
+        insert_organization(&live, "org-1");
+        assert!(restore(&mut live, &newer, dir.path()).is_err());
+        assert_eq!(count(&live), 1);
+    }
+
+    #[test]
+    fn test_scheduled_backups_keep_the_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = test_connection();
+        let retention = Retention { daily: 3, weekly: 2 };
+        let start = NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+
+        for day in 0..21 {
+            run_scheduled(&conn, dir.path(), start + chrono::Duration::days(day), retention).unwrap();
+        }
+        assert!(run_scheduled(&conn, dir.path(), start + chrono::Duration::days(20), retention)
+            .unwrap()
+            .is_empty());
+
+        let backups = list(dir.path()).unwrap();
+        let names = |kind: BackupKind| {
+            let mut names: Vec<String> = backups
+                .iter()
+                .filter(|backup| backup.kind == kind)
+                .map(|backup| Path::new(&backup.path).file_name().unwrap().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(BackupKind::Daily),
+            ["auto-daily-2030-01-19.db", "auto-daily-2030-01-20.db", "auto-daily-2030-01-21.db"]
+        );
+        assert_eq!(names(BackupKind::Weekly), ["auto-weekly-2030-W03.db", "auto-weekly-2030-W04.db"]);
+    }
+}
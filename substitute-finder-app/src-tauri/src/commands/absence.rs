@@ -0,0 +1,131 @@
+use crate::database::models::{
+    Absence, AbsenceWithRequests, CreateAbsenceRequest, SubstituteRequest, UpdateAbsenceRequest, WebhookEventType,
+};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::repository::AbsenceRepository;
+use crate::webhooks;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn create_absence(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    requested_by: String,
+    request: CreateAbsenceRequest,
+) -> Result<AbsenceWithRequests, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let created = AbsenceRepository::new(&conn)
+        .create(requested_by, request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &created.absence);
+    for request in &created.requests {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCreated, request);
+        events::emit_change(&app, ChangeKind::Created, request);
+    }
+
+    Ok(created)
+}
+
+#[tauri::command]
+pub fn get_absences(state: State<'_, AppState>) -> Result<Vec<Absence>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    AbsenceRepository::new(&conn).list().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_absence_by_id(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Option<AbsenceWithRequests>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    AbsenceRepository::new(&conn)
+        .find_with_requests(&id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_absence(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    request: UpdateAbsenceRequest,
+) -> Result<AbsenceWithRequests, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = AbsenceRepository::new(&conn);
+
+    let updated = repo
+        .update(&id, request)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Absence not found".to_string())?;
+    for request in &updated {
+        events::emit_change(&app, ChangeKind::Updated, request);
+    }
+
+    emit_absence(&app, &repo, &id)
+}
+
+#[tauri::command]
+pub fn cancel_absence(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<AbsenceWithRequests, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = AbsenceRepository::new(&conn);
+
+    let cancelled = repo
+        .cancel(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Absence not found".to_string())?;
+    for request in &cancelled {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCancelled, request);
+        events::emit_change(&app, ChangeKind::Updated, request);
+    }
+
+    emit_absence(&app, &repo, &id)
+}
+
+/// Accepts every remaining open day of the absence for one substitute.
+#[tauri::command]
+pub fn accept_absence(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    absence_id: String,
+    substitute_id: String,
+) -> Result<Vec<SubstituteRequest>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let accepted = AbsenceRepository::new(&conn)
+        .accept(&absence_id, &substitute_id)
+        .map_err(|e| e.to_string())?;
+    for request in &accepted {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestFilled, request);
+        events::emit_change(&app, ChangeKind::Accepted, request);
+    }
+
+    Ok(accepted)
+}
+
+fn emit_absence(
+    app: &AppHandle,
+    repo: &AbsenceRepository,
+    id: &str,
+) -> Result<AbsenceWithRequests, String> {
+    let absence = repo
+        .find_with_requests(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Absence not found".to_string())?;
+    events::emit_change(app, ChangeKind::Updated, &absence.absence);
+    Ok(absence)
+}
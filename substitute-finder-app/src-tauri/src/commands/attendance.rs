@@ -0,0 +1,111 @@
+use crate::database::connection::DbConnection;
+use crate::database::models::{AssignmentAttendance, AttendanceOverrideRequest, SubstituteRequest};
+use crate::commands::{require_manager, AppState};
+use crate::events::{self, ChangeKind};
+use crate::repository::{AttendanceRepository, SubstituteRequestRepository};
+use chrono::Utc;
+use rusqlite::Connection;
+use tauri::{AppHandle, State};
+
+/// How often assignments are checked for no-shows and missing check-outs.
+const SWEEP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+fn emit_attendance(app: &AppHandle, conn: &Connection, attendance: &AssignmentAttendance) -> Result<(), String> {
+    events::emit_change(app, ChangeKind::Updated, attendance);
+    if let Some(request) = SubstituteRequestRepository::new(conn)
+        .find_by_id(&attendance.request_id)
+        .map_err(|e| e.to_string())?
+    {
+        events::emit_change(app, ChangeKind::Updated, &request);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn check_in(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request_id: String,
+    substitute_id: String,
+) -> Result<AssignmentAttendance, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let attendance = AttendanceRepository::new(&conn)
+        .check_in(&request_id, &substitute_id, Utc::now())
+        .map_err(|e| e.to_string())?;
+    emit_attendance(&app, &conn, &attendance)?;
+
+    Ok(attendance)
+}
+
+#[tauri::command]
+pub fn check_out(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request_id: String,
+    substitute_id: String,
+) -> Result<AssignmentAttendance, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let attendance = AttendanceRepository::new(&conn)
+        .check_out(&request_id, &substitute_id, Utc::now())
+        .map_err(|e| e.to_string())?;
+    emit_attendance(&app, &conn, &attendance)?;
+
+    Ok(attendance)
+}
+
+/// Lets an org manager or admin correct the recorded times, with a reason.
+#[tauri::command]
+pub fn override_attendance(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: AttendanceOverrideRequest,
+) -> Result<AssignmentAttendance, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    require_manager(&conn, &request.manager_id, "override attendance")?;
+
+    let attendance = AttendanceRepository::new(&conn)
+        .override_attendance(request)
+        .map_err(|e| e.to_string())?;
+    emit_attendance(&app, &conn, &attendance)?;
+
+    Ok(attendance)
+}
+
+#[tauri::command]
+pub fn get_attendance(
+    state: State<'_, AppState>,
+    request_id: String,
+) -> Result<Option<AssignmentAttendance>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    AttendanceRepository::new(&conn)
+        .find(&request_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Marks no-shows and completes assignments left checked in, once a minute
+/// for as long as the app runs.
+pub fn spawn_sweeper(app: AppHandle, db: DbConnection) {
+    std::thread::spawn(move || loop {
+        let swept: anyhow::Result<Vec<SubstituteRequest>> = match db.lock() {
+            Ok(conn) => AttendanceRepository::new(&conn).sweep(Utc::now()),
+            Err(e) => Err(anyhow::anyhow!(e.to_string())),
+        };
+        match swept {
+            Ok(requests) => {
+                for request in &requests {
+                    events::emit_change(&app, ChangeKind::Updated, request);
+                }
+            }
+            Err(e) => eprintln!("Failed to sweep attendance: {}", e),
+        }
+        std::thread::sleep(SWEEP_INTERVAL);
+    });
+}
@@ -0,0 +1,72 @@
+use crate::backup::{self, Retention};
+use crate::database::connection::DbConnection;
+use crate::database::models::{BackupInfo, RestoreReport};
+use crate::commands::AppState;
+use crate::repository::setting::{BACKUP_DAILY_KEEP, BACKUP_WEEKLY_KEEP};
+use crate::repository::SettingsRepository;
+use chrono::Local;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::State;
+
+/// How often the scheduler checks whether today's backups are due.
+const SCHEDULE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Copies the database while the app keeps running. Without a path the
+/// backup goes to the backups folder next to the database.
+#[tauri::command]
+pub fn create_backup(state: State<'_, AppState>, path: Option<String>) -> Result<BackupInfo, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => backup::manual_path(&backup::directory(&conn).map_err(|e| e.to_string())?),
+    };
+    backup::create(&conn, &path).map_err(|e| e.to_string())
+}
+
+/// Backups in the backups folder, newest first.
+#[tauri::command]
+pub fn list_backups(state: State<'_, AppState>) -> Result<Vec<BackupInfo>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let dir = backup::directory(&conn).map_err(|e| e.to_string())?;
+    backup::list(&dir).map_err(|e| e.to_string())
+}
+
+/// Replaces the database with a verified backup, saving the current data
+/// to the backups folder first. The frontend should reload everything after.
+#[tauri::command]
+pub fn restore_backup(state: State<'_, AppState>, path: String) -> Result<RestoreReport, String> {
+    let conn = state.get_connection();
+    let mut conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let dir = backup::directory(&conn).map_err(|e| e.to_string())?;
+    backup::restore(&mut conn, Path::new(&path), &dir).map_err(|e| e.to_string())
+}
+
+/// Takes the daily and weekly backups in the background, per the retention
+/// settings.
+pub fn spawn_scheduler(db: DbConnection) {
+    std::thread::spawn(move || loop {
+        let scheduled: anyhow::Result<Vec<BackupInfo>> = match db.lock() {
+            Ok(conn) => run_scheduled(&conn),
+            Err(e) => Err(anyhow::anyhow!(e.to_string())),
+        };
+        if let Err(e) = scheduled {
+            eprintln!("Failed to take scheduled backup: {}", e);
+        }
+        std::thread::sleep(SCHEDULE_INTERVAL);
+    });
+}
+
+fn run_scheduled(conn: &rusqlite::Connection) -> anyhow::Result<Vec<BackupInfo>> {
+    let settings = SettingsRepository::new(conn);
+    let retention = Retention {
+        daily: settings.get_i64(BACKUP_DAILY_KEEP, 7)?.max(0) as usize,
+        weekly: settings.get_i64(BACKUP_WEEKLY_KEEP, 4)?.max(0) as usize,
+    };
+    backup::run_scheduled(conn, &backup::directory(conn)?, Local::now().date_naive(), retention)
+}
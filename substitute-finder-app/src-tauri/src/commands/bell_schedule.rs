@@ -0,0 +1,147 @@
+use crate::database::models::{BellSchedule, CreateBellScheduleRequest};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::repository::BellScheduleRepository;
+use chrono::NaiveDate;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn create_bell_schedule(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: CreateBellScheduleRequest,
+) -> Result<BellSchedule, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let schedule = BellScheduleRepository::new(&conn)
+        .create(request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &schedule);
+
+    Ok(schedule)
+}
+
+#[tauri::command]
+pub fn get_bell_schedules(
+    state: State<'_, AppState>,
+    organization_id: String,
+) -> Result<Vec<BellSchedule>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BellScheduleRepository::new(&conn)
+        .list_by_organization(&organization_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_bell_schedule_by_id(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Option<BellSchedule>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BellScheduleRepository::new(&conn)
+        .find_by_id(&id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_bell_schedule(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    request: CreateBellScheduleRequest,
+) -> Result<BellSchedule, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let schedule = BellScheduleRepository::new(&conn)
+        .update(&id, request)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Bell schedule not found".to_string())?;
+
+    events::emit_change(&app, ChangeKind::Updated, &schedule);
+
+    Ok(schedule)
+}
+
+#[tauri::command]
+pub fn delete_bell_schedule(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BellScheduleRepository::new(&conn)
+        .delete(&id)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_deleted::<BellSchedule>(&app, &id);
+
+    Ok(())
+}
+
+/// Runs a date on a non-default schedule, or back on the default when
+/// `schedule_id` is omitted.
+#[tauri::command]
+pub fn set_bell_schedule_for_date(
+    state: State<'_, AppState>,
+    organization_id: String,
+    date: NaiveDate,
+    schedule_id: Option<String>,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BellScheduleRepository::new(&conn)
+        .set_schedule_for_date(&organization_id, date, schedule_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_active_bell_schedule(
+    state: State<'_, AppState>,
+    organization_id: String,
+    date: NaiveDate,
+) -> Result<Option<BellSchedule>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BellScheduleRepository::new(&conn)
+        .active_schedule(&organization_id, date)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_class_periods(
+    state: State<'_, AppState>,
+    class_id: String,
+    period_names: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = BellScheduleRepository::new(&conn);
+
+    repo.set_class_periods(&class_id, &period_names)
+        .map_err(|e| e.to_string())?;
+    repo.class_periods(&class_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_class_periods(
+    state: State<'_, AppState>,
+    class_id: String,
+) -> Result<Vec<String>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BellScheduleRepository::new(&conn)
+        .class_periods(&class_id)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,98 @@
+use crate::database::models::{
+    BudgetUsage, BudgetWarning, CreateBudgetRequest, CreateSubstituteRequestRequest, SubstituteBudget,
+    UpdateBudgetRequest,
+};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::repository::BudgetRepository;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn create_budget(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: CreateBudgetRequest,
+) -> Result<SubstituteBudget, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let budget = BudgetRepository::new(&conn)
+        .create(request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &budget);
+
+    Ok(budget)
+}
+
+#[tauri::command]
+pub fn update_budget(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    request: UpdateBudgetRequest,
+) -> Result<SubstituteBudget, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let budget = BudgetRepository::new(&conn)
+        .update(&id, request)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Budget not found".to_string())?;
+
+    events::emit_change(&app, ChangeKind::Updated, &budget);
+
+    Ok(budget)
+}
+
+#[tauri::command]
+pub fn delete_budget(app: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BudgetRepository::new(&conn).delete(&id).map_err(|e| e.to_string())?;
+
+    events::emit_deleted::<SubstituteBudget>(&app, &id);
+
+    Ok(())
+}
+
+/// The organization's budgets with their spending, rolled up from the
+/// organizations below it.
+#[tauri::command]
+pub fn get_budgets(state: State<'_, AppState>, organization_id: String) -> Result<Vec<BudgetUsage>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BudgetRepository::new(&conn)
+        .usage_for_organization(&organization_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_budget_usage(state: State<'_, AppState>, id: String) -> Result<BudgetUsage, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let repo = BudgetRepository::new(&conn);
+    let budget = repo
+        .find_by_id(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Budget not found".to_string())?;
+    repo.usage(budget).map_err(|e| e.to_string())
+}
+
+/// Budgets a request would go over, for warning before it is submitted.
+/// Going over budget doesn't stop the request being created.
+#[tauri::command]
+pub fn check_request_budget(
+    state: State<'_, AppState>,
+    request: CreateSubstituteRequestRequest,
+) -> Result<Vec<BudgetWarning>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    BudgetRepository::new(&conn)
+        .warnings(&request)
+        .map_err(|e| e.to_string())
+}
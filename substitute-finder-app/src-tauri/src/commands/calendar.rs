@@ -0,0 +1,185 @@
+use crate::database::models::{
+    CalendarDay, CalendarEvent, CreateCalendarEventRequest, EmergencyClosure, WebhookEventType,
+};
+use crate::commands::notification::{log_notification, send_notification};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::repository::CalendarRepository;
+use crate::webhooks;
+use chrono::NaiveDate;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn create_calendar_event(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: CreateCalendarEventRequest,
+) -> Result<CalendarEvent, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let event = CalendarRepository::new(&conn)
+        .create(request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &event);
+
+    Ok(event)
+}
+
+/// Events defined on the organization itself; see `get_effective_calendar`
+/// for the inherited view.
+#[tauri::command]
+pub fn get_calendar_events(
+    state: State<'_, AppState>,
+    organization_id: String,
+) -> Result<Vec<CalendarEvent>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    CalendarRepository::new(&conn)
+        .list_by_organization(&organization_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_calendar_event(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    request: CreateCalendarEventRequest,
+) -> Result<CalendarEvent, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let event = CalendarRepository::new(&conn)
+        .update(&id, request)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Calendar event not found".to_string())?;
+
+    events::emit_change(&app, ChangeKind::Updated, &event);
+
+    Ok(event)
+}
+
+#[tauri::command]
+pub fn delete_calendar_event(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    CalendarRepository::new(&conn)
+        .delete(&id)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_deleted::<CalendarEvent>(&app, &id);
+
+    Ok(())
+}
+
+/// Opts a school out of (or back into) an event inherited from a parent
+/// organization.
+#[tauri::command]
+pub fn set_calendar_event_excluded(
+    state: State<'_, AppState>,
+    organization_id: String,
+    event_id: String,
+    excluded: bool,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    CalendarRepository::new(&conn)
+        .set_excluded(&organization_id, &event_id, excluded)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_effective_calendar(
+    state: State<'_, AppState>,
+    organization_id: String,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<CalendarEvent>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    CalendarRepository::new(&conn)
+        .effective_events(&organization_id, from, to)
+        .map_err(|e| e.to_string())
+}
+
+/// What the calendar says about one date, for warning before a request is
+/// submitted. Requests on closed days are rejected on creation regardless.
+#[tauri::command]
+pub fn get_calendar_day(
+    state: State<'_, AppState>,
+    organization_id: String,
+    date: NaiveDate,
+) -> Result<CalendarDay, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    CalendarRepository::new(&conn)
+        .day(&organization_id, date)
+        .map_err(|e| e.to_string())
+}
+
+/// Closes the organization and its children for the day, cancels that day's
+/// requests and notifies the substitutes who had been assigned.
+#[tauri::command]
+pub fn declare_emergency_closure(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    organization_id: String,
+    date: NaiveDate,
+    name: String,
+) -> Result<EmergencyClosure, String> {
+    let closure = {
+        let conn = state.get_connection();
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+
+        CalendarRepository::new(&conn)
+            .declare_emergency_closure(&organization_id, date, name)
+            .map_err(|e| e.to_string())?
+    };
+
+    events::emit_change(&app, ChangeKind::Created, &closure.event);
+    for request in &closure.cancelled_requests {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCancelled, request);
+        events::emit_change(&app, ChangeKind::Updated, request);
+
+        let Some(substitute_id) = &request.assigned_substitute_id else {
+            continue;
+        };
+        let body = format!(
+            "{} on {}: your assignment has been cancelled",
+            closure.event.name, request.date_needed
+        );
+        let (status, error) = match send_notification(
+            app.clone(),
+            "Assignment Cancelled".to_string(),
+            body,
+            Some(request.id.clone()),
+            Some(substitute_id.clone()),
+        ) {
+            Ok(_) => ("sent", None),
+            Err(e) => ("failed", Some(e)),
+        };
+        if let Err(e) = log_notification(
+            state.clone(),
+            substitute_id.clone(),
+            request.id.clone(),
+            "push".to_string(),
+            status.to_string(),
+            error,
+        ) {
+            eprintln!("Failed to log closure notification: {}", e);
+        }
+    }
+
+    Ok(closure)
+}
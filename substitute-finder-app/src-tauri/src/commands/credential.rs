@@ -0,0 +1,115 @@
+use crate::database::models::{
+    CreateCredentialRequest, ExpiringCredential, SubstituteCredential, UserRole, VerificationStatus,
+};
+use crate::commands::{find_user, require_admin, require_manager, AppState};
+use crate::events::{self, ChangeKind};
+use crate::repository::CredentialRepository;
+use chrono::Local;
+use rusqlite::Connection;
+use tauri::{AppHandle, State};
+
+/// Substitutes manage their own credentials; managers and admins anyone's.
+fn require_owner_or_manager(conn: &Connection, user_id: &str, substitute_id: &str) -> Result<(), String> {
+    let user = find_user(conn, user_id)?;
+    if matches!(user.role, UserRole::Substitute) && user.id != substitute_id {
+        return Err("Substitutes can only manage their own credentials".to_string());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn create_credential(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    user_id: String,
+    request: CreateCredentialRequest,
+) -> Result<SubstituteCredential, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_owner_or_manager(&conn, &user_id, &request.substitute_id)?;
+
+    let credential = CredentialRepository::new(&conn)
+        .create(request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &credential);
+
+    Ok(credential)
+}
+
+#[tauri::command]
+pub fn get_substitute_credentials(
+    state: State<'_, AppState>,
+    substitute_id: String,
+) -> Result<Vec<SubstituteCredential>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    CredentialRepository::new(&conn)
+        .list_for_substitute(&substitute_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Marks a credential verified or rejected after checking it. Managers and
+/// admins only.
+#[tauri::command]
+pub fn verify_credential(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    verifier_id: String,
+    id: String,
+    status: VerificationStatus,
+) -> Result<SubstituteCredential, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_manager(&conn, &verifier_id, "verify credentials")?;
+
+    let credential = CredentialRepository::new(&conn)
+        .set_verification(&id, status, &verifier_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Credential not found".to_string())?;
+
+    events::emit_change(&app, ChangeKind::Updated, &credential);
+
+    Ok(credential)
+}
+
+#[tauri::command]
+pub fn delete_credential(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    user_id: String,
+    id: String,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = CredentialRepository::new(&conn);
+
+    let credential = repo
+        .find_by_id(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Credential not found".to_string())?;
+    require_owner_or_manager(&conn, &user_id, &credential.substitute_id)?;
+
+    repo.delete(&id).map_err(|e| e.to_string())?;
+
+    events::emit_deleted::<SubstituteCredential>(&app, &id);
+
+    Ok(())
+}
+
+/// Verified credentials expiring within `days`, for admins to chase renewals.
+#[tauri::command]
+pub fn get_expiring_credentials(
+    state: State<'_, AppState>,
+    user_id: String,
+    days: u32,
+) -> Result<Vec<ExpiringCredential>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "view the credential expiry report")?;
+
+    CredentialRepository::new(&conn)
+        .expiring(Local::now().date_naive(), days)
+        .map_err(|e| e.to_string())
+}
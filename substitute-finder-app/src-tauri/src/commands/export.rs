@@ -0,0 +1,20 @@
+use crate::database::models::{ExportDataset, ExportRequest, ExportSummary};
+use crate::commands::AppState;
+use crate::export;
+use tauri::State;
+
+/// Writes a dataset to a CSV or XLSX file at the chosen path, streaming rows
+/// from the database rather than passing them through the webview.
+#[tauri::command]
+pub fn export_data(state: State<'_, AppState>, request: ExportRequest) -> Result<ExportSummary, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    export::export(&conn, &request).map_err(|e| e.to_string())
+}
+
+/// Column keys the dataset can export, in their default order.
+#[tauri::command]
+pub fn get_export_columns(dataset: ExportDataset) -> Vec<String> {
+    export::column_keys(dataset)
+}
@@ -0,0 +1,63 @@
+use crate::database::models::{AssignmentFeedback, CreateFeedbackRequest, SubstituteRating};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::repository::FeedbackRepository;
+use chrono::Local;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn submit_assignment_feedback(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: CreateFeedbackRequest,
+) -> Result<AssignmentFeedback, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let feedback = FeedbackRepository::new(&conn)
+        .create(request, Local::now().naive_local())
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &feedback);
+
+    Ok(feedback)
+}
+
+#[tauri::command]
+pub fn get_request_feedback(
+    state: State<'_, AppState>,
+    request_id: String,
+) -> Result<Vec<AssignmentFeedback>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    FeedbackRepository::new(&conn)
+        .list_for_request(&request_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_substitute_feedback(
+    state: State<'_, AppState>,
+    substitute_id: String,
+) -> Result<Vec<AssignmentFeedback>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    FeedbackRepository::new(&conn)
+        .list_for_substitute(&substitute_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_substitute_rating(
+    state: State<'_, AppState>,
+    substitute_id: String,
+) -> Result<SubstituteRating, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    FeedbackRepository::new(&conn)
+        .substitute_rating(&substitute_id)
+        .map_err(|e| e.to_string())
+}
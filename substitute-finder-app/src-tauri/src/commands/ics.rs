@@ -0,0 +1,115 @@
+use crate::database::connection::DbConnection;
+use crate::database::models::{
+    CalendarExportSummary, CalendarFeed, CalendarImportReport, CalendarImportRequest, WebhookEventType,
+};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::{ics, ics_import};
+use crate::repository::setting::{CALENDAR_FEED_PORT, CALENDAR_FEED_TOKEN};
+use crate::repository::SettingsRepository;
+use crate::webhooks;
+use rusqlite::Connection;
+use tauri::{AppHandle, State};
+
+/// Writes a substitute's assignments or an organization's requests to an
+/// `.ics` file at the chosen path.
+#[tauri::command]
+pub fn export_calendar(
+    state: State<'_, AppState>,
+    feed: CalendarFeed,
+    path: String,
+) -> Result<CalendarExportSummary, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    ics::export(&conn, &feed, &path).map_err(|e| e.to_string())
+}
+
+/// URL calendar apps can subscribe to, or `None` when the feed is off.
+#[tauri::command]
+pub fn get_calendar_feed_url(state: State<'_, AppState>, feed: CalendarFeed) -> Result<Option<String>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let Some(port) = feed_port(&conn).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let token = SettingsRepository::new(&conn)
+        .get(CALENDAR_FEED_TOKEN)
+        .map_err(|e| e.to_string())?
+        .map(|setting| setting.value.trim().to_string())
+        .unwrap_or_default();
+    if token.is_empty() {
+        return Err("Set a calendar feed token before subscribing".to_string());
+    }
+    Ok(Some(ics::feed_url(port, &token, &feed)))
+}
+
+/// Creates teacher absences from a shared leave calendar. Events are matched
+/// by UID, so importing the calendar again only applies what changed.
+#[tauri::command]
+pub fn import_calendar_absences(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: CalendarImportRequest,
+) -> Result<CalendarImportReport, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let imported = ics_import::run(&conn, &request).map_err(|e| e.to_string())?;
+
+    for absence in &imported.reported {
+        events::emit_change(&app, ChangeKind::Created, absence);
+    }
+    for absence in &imported.updated {
+        events::emit_change(&app, ChangeKind::Updated, absence);
+    }
+    for request in &imported.created_requests {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCreated, request);
+        events::emit_change(&app, ChangeKind::Created, request);
+    }
+    for request in &imported.updated_requests {
+        events::emit_change(&app, ChangeKind::Updated, request);
+    }
+    for request in &imported.cancelled_requests {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCancelled, request);
+        events::emit_change(&app, ChangeKind::Updated, request);
+    }
+    for absence in &imported.withdrawn {
+        events::emit_change(&app, ChangeKind::Updated, absence);
+    }
+
+    Ok(imported.report)
+}
+
+/// Starts the subscription feed when a port is configured.
+pub fn spawn_feed_server(db: DbConnection) {
+    let port = match db.lock() {
+        Ok(conn) => feed_port(&conn),
+        Err(e) => Err(anyhow::anyhow!(e.to_string())),
+    };
+    match port {
+        Ok(Some(port)) => {
+            if let Err(e) = ics::serve(db, port) {
+                eprintln!("Failed to start calendar feed on port {}: {}", port, e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Failed to read calendar feed port: {}", e),
+    }
+}
+
+fn feed_port(conn: &Connection) -> anyhow::Result<Option<u16>> {
+    let value = SettingsRepository::new(conn)
+        .get(CALENDAR_FEED_PORT)?
+        .map(|setting| setting.value)
+        .unwrap_or_default();
+    if value.trim().is_empty() {
+        return Ok(None);
+    }
+    let port = value
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Setting {} must be a port number, got {:?}", CALENDAR_FEED_PORT, value))?;
+    Ok(Some(port))
+}
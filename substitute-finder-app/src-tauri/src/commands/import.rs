@@ -0,0 +1,39 @@
+use crate::database::models::{ImportReport, ImportRequest, WebhookEventType};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::import;
+use crate::webhooks;
+use tauri::{AppHandle, State};
+
+/// Imports organizations, users, substitutes or classes from a CSV file.
+/// A dry run, or an import with any invalid row, saves nothing and reports
+/// the errors by line.
+#[tauri::command]
+pub fn import_csv(app: AppHandle, state: State<'_, AppState>, request: ImportRequest) -> Result<ImportReport, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let imported = import::run(&conn, &request).map_err(|e| e.to_string())?;
+
+    for organization in &imported.organizations.created {
+        events::emit_change(&app, ChangeKind::Created, organization);
+    }
+    for organization in &imported.organizations.updated {
+        events::emit_change(&app, ChangeKind::Updated, organization);
+    }
+    for user in &imported.users.created {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::UserCreated, user);
+        events::emit_change(&app, ChangeKind::Created, user);
+    }
+    for user in &imported.users.updated {
+        events::emit_change(&app, ChangeKind::Updated, user);
+    }
+    for class in &imported.classes.created {
+        events::emit_change(&app, ChangeKind::Created, class);
+    }
+    for class in &imported.classes.updated {
+        events::emit_change(&app, ChangeKind::Updated, class);
+    }
+
+    Ok(imported.report)
+}
@@ -5,8 +5,70 @@ pub mod substitute;
 pub mod auth;
 pub mod seed;
 pub mod notification;
+pub mod webhook;
+pub mod absence;
+pub mod teacher;
+pub mod bell_schedule;
+pub mod calendar;
+pub mod setting;
+pub mod unavailability;
+pub mod preference;
+pub mod feedback;
+pub mod attendance;
+pub mod payroll;
+pub mod budget;
+pub mod credential;
+pub mod requirement;
+pub mod report;
+pub mod export;
+pub mod import;
+pub mod roster;
+pub mod ics;
+pub mod backup;
 
 use crate::database::connection::DatabaseManager;
+use crate::database::models::{User, UserRole};
+use crate::repository::UserRepository;
+use rusqlite::Connection;
 use std::sync::Arc;
 
-pub type AppState = Arc<DatabaseManager>;
\ No newline at end of file
+pub type AppState = Arc<DatabaseManager>;
+
+pub fn find_user(conn: &Connection, user_id: &str) -> Result<User, String> {
+    UserRepository::new(conn)
+        .find_by_id(user_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "User not found".to_string())
+}
+
+/// The user when `allowed` accepts their role, otherwise an error saying
+/// only `who` can `action`.
+fn require_role(
+    conn: &Connection,
+    user_id: &str,
+    allowed: fn(&UserRole) -> bool,
+    who: &str,
+    action: &str,
+) -> Result<User, String> {
+    let user = find_user(conn, user_id)?;
+    if allowed(&user.role) {
+        Ok(user)
+    } else {
+        Err(format!("Only {} can {}", who, action))
+    }
+}
+
+/// Fails unless `user_id` is an admin. `action` completes "Only admins can".
+pub fn require_admin(conn: &Connection, user_id: &str, action: &str) -> Result<User, String> {
+    require_role(conn, user_id, |role| matches!(role, UserRole::Admin), "admins", action)
+}
+
+/// Fails unless `user_id` is an org manager or admin.
+pub fn require_manager(conn: &Connection, user_id: &str, action: &str) -> Result<User, String> {
+    require_role(conn, user_id, |role| !matches!(role, UserRole::Substitute), "managers", action)
+}
+
+/// Fails unless `user_id` is a substitute.
+pub fn require_substitute(conn: &Connection, user_id: &str, action: &str) -> Result<User, String> {
+    require_role(conn, user_id, |role| matches!(role, UserRole::Substitute), "substitutes", action)
+}
@@ -1,4 +1,10 @@
 use crate::commands::AppState;
+use crate::database::connection::DbConnection;
+use crate::database::models::{RequestStatus, SubstituteRequest};
+use crate::database::timestamp;
+use crate::repository::setting::PREFERRED_OFFER_MINUTES;
+use crate::repository::{PreferenceRepository, SettingsRepository, SubstituteRequestRepository};
+use rusqlite::Connection;
 use tauri::{State, Emitter, AppHandle};
 use uuid::Uuid;
 use chrono::Utc;
@@ -66,24 +72,36 @@ pub fn log_notification(
 ) -> Result<String, String> {
     let conn = state.get_connection();
     let conn = conn.lock().map_err(|e| e.to_string())?;
-    
+
+    insert_log(&conn, &user_id, &request_id, &notification_type, &status, error_message.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+fn insert_log(
+    conn: &Connection,
+    user_id: &str,
+    request_id: &str,
+    notification_type: &str,
+    status: &str,
+    error_message: Option<&str>,
+) -> rusqlite::Result<String> {
     let log_id = Uuid::new_v4().to_string();
     let sent_at = Utc::now();
-    
+
     conn.execute(
         "INSERT INTO notifications_log (id, user_id, request_id, notification_type, sent_at, status, error_message)
          VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         (
             &log_id,
-            &user_id,
-            &request_id,
-            &notification_type,
-            &sent_at.to_rfc3339(),
-            &status,
-            &error_message,
+            user_id,
+            request_id,
+            notification_type,
+            &timestamp::format(&sent_at),
+            status,
+            error_message,
         ),
-    ).map_err(|e| e.to_string())?;
-    
+    )?;
+
     Ok(log_id)
 }
 
@@ -128,6 +146,10 @@ pub fn get_notification_logs(
     Ok(result)
 }
 
+/// Offers a new request in two tiers: substitutes preferred by the school or
+/// teacher hear about it straight away, everyone else once
+/// `preferred_offer_minutes` pass with the request still open. Blocked,
+/// unavailable and uncredentialed substitutes are never notified.
 #[tauri::command]
 pub fn notify_substitute_request_created(
     app: AppHandle,
@@ -137,54 +159,92 @@ pub fn notify_substitute_request_created(
     date_needed: String,
     substitute_user_ids: Vec<String>,
 ) -> Result<Vec<String>, String> {
-    let title = "New Substitute Request";
+    let title = "New Substitute Request".to_string();
     let body = format!("Substitute needed for {} on {}", class_name, date_needed);
-    
+    let db = state.get_connection();
+
+    let (tiers, delay_minutes) = {
+        let conn = db.lock().map_err(|e| e.to_string())?;
+        let request = SubstituteRequestRepository::new(&conn)
+            .find_by_id(&request_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "Substitute request not found".to_string())?;
+        let tiers = PreferenceRepository::new(&conn)
+            .offer_tiers(substitute_user_ids, &request)
+            .map_err(|e| e.to_string())?;
+        let delay_minutes = SettingsRepository::new(&conn)
+            .get_i64(PREFERRED_OFFER_MINUTES, 0)
+            .map_err(|e| e.to_string())?;
+        (tiers, delay_minutes)
+    };
+
+    if tiers.preferred.is_empty() || tiers.others.is_empty() || delay_minutes <= 0 {
+        let everyone = tiers.preferred.into_iter().chain(tiers.others).collect();
+        return Ok(deliver(&app, &db, &request_id, &title, &body, everyone));
+    }
+
+    let notification_ids = deliver(&app, &db, &request_id, &title, &body, tiers.preferred);
+
+    let others = tiers.others;
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(delay_minutes as u64 * 60));
+        let still_open = match db.lock() {
+            Ok(conn) => matches!(
+                SubstituteRequestRepository::new(&conn).find_by_id(&request_id),
+                Ok(Some(SubstituteRequest { status: RequestStatus::Open, .. }))
+            ),
+            Err(_) => false,
+        };
+        if still_open {
+            deliver(&app, &db, &request_id, &title, &body, others);
+        }
+    });
+
+    Ok(notification_ids)
+}
+
+/// Sends `title`/`body` to each user and logs the outcome, returning the ids
+/// of the notifications that went out.
+fn deliver(
+    app: &AppHandle,
+    db: &DbConnection,
+    request_id: &str,
+    title: &str,
+    body: &str,
+    user_ids: Vec<String>,
+) -> Vec<String> {
     let mut notification_ids = Vec::new();
-    
-    for user_id in substitute_user_ids {
-        // Send notification
-        match send_notification(
+
+    for user_id in user_ids {
+        let (status, error) = match send_notification(
             app.clone(),
-            title.clone(),
-            body.clone(),
-            Some(request_id.clone()),
+            title.to_string(),
+            body.to_string(),
+            Some(request_id.to_string()),
             Some(user_id.clone()),
         ) {
             Ok(notification_id) => {
-                notification_ids.push(notification_id.clone());
-                
-                // Log successful notification
-                if let Err(e) = log_notification(
-                    state.clone(),
-                    user_id,
-                    request_id.clone(),
-                    "desktop".to_string(),
-                    "sent".to_string(),
-                    None,
-                ) {
-                    eprintln!("Failed to log notification: {}", e);
-                }
-            },
+                notification_ids.push(notification_id);
+                ("sent", None)
+            }
             Err(e) => {
                 eprintln!("Failed to send notification to user {}: {}", user_id, e);
-                
-                // Log failed notification
-                if let Err(log_err) = log_notification(
-                    state.clone(),
-                    user_id,
-                    request_id.clone(),
-                    "desktop".to_string(),
-                    "failed".to_string(),
-                    Some(e.clone()),
-                ) {
-                    eprintln!("Failed to log failed notification: {}", log_err);
-                }
+                ("failed", Some(e))
             }
+        };
+
+        let logged = db
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|conn| {
+                insert_log(&conn, &user_id, request_id, "push", status, error.as_deref()).map_err(|e| e.to_string())
+            });
+        if let Err(e) = logged {
+            eprintln!("Failed to log notification: {}", e);
         }
     }
-    
-    Ok(notification_ids)
+
+    notification_ids
 }
 
 #[tauri::command]
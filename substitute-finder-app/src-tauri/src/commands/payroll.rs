@@ -0,0 +1,101 @@
+use crate::database::models::{PayPolicy, SetPayPolicyRequest, Timesheet};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::payroll;
+use crate::repository::setting::PAYROLL_CSV_COLUMNS;
+use crate::repository::{PayrollRepository, SettingsRepository};
+use chrono::NaiveDate;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn set_pay_policy(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: SetPayPolicyRequest,
+) -> Result<PayPolicy, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let policy = PayrollRepository::new(&conn)
+        .set_policy(request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Updated, &policy);
+
+    Ok(policy)
+}
+
+/// The organization's own policy, if it has one.
+#[tauri::command]
+pub fn get_pay_policy(state: State<'_, AppState>, organization_id: String) -> Result<Option<PayPolicy>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    PayrollRepository::new(&conn)
+        .find_policy(&organization_id)
+        .map_err(|e| e.to_string())
+}
+
+/// The policy actually applied to the organization's assignments, which may
+/// be inherited from a parent.
+#[tauri::command]
+pub fn get_effective_pay_policy(state: State<'_, AppState>, organization_id: String) -> Result<PayPolicy, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    PayrollRepository::new(&conn)
+        .effective_policy(&organization_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_pay_policy(app: AppHandle, state: State<'_, AppState>, organization_id: String) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    PayrollRepository::new(&conn)
+        .delete_policy(&organization_id)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_deleted::<PayPolicy>(&app, &organization_id);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_timesheets(
+    state: State<'_, AppState>,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+    substitute_id: Option<String>,
+) -> Result<Vec<Timesheet>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    PayrollRepository::new(&conn)
+        .timesheets(period_start, period_end, substitute_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Timesheets for the period as CSV, in the `payroll_csv_columns` layout.
+#[tauri::command]
+pub fn export_payroll_csv(
+    state: State<'_, AppState>,
+    period_start: NaiveDate,
+    period_end: NaiveDate,
+) -> Result<String, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let layout = SettingsRepository::new(&conn)
+        .get(PAYROLL_CSV_COLUMNS)
+        .map_err(|e| e.to_string())?
+        .map(|setting| setting.value)
+        .unwrap_or_else(|| payroll::DEFAULT_COLUMNS.to_string());
+    let columns = payroll::parse_columns(&layout).map_err(|e| e.to_string())?;
+    let timesheets = PayrollRepository::new(&conn)
+        .timesheets(period_start, period_end, None)
+        .map_err(|e| e.to_string())?;
+
+    payroll::write_csv(&timesheets, &columns).map_err(|e| e.to_string())
+}
@@ -0,0 +1,81 @@
+use crate::database::models::{CreateSubstitutePreferenceRequest, OfferTiers, SubstitutePreference};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::repository::{PreferenceRepository, SubstituteRequestRepository};
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn create_substitute_preference(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: CreateSubstitutePreferenceRequest,
+) -> Result<SubstitutePreference, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let preference = PreferenceRepository::new(&conn)
+        .create(request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &preference);
+
+    Ok(preference)
+}
+
+/// Preferences set on an organization or on a regular teacher; give one of
+/// the two ids.
+#[tauri::command]
+pub fn get_substitute_preferences(
+    state: State<'_, AppState>,
+    organization_id: Option<String>,
+    teacher_id: Option<String>,
+) -> Result<Vec<SubstitutePreference>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = PreferenceRepository::new(&conn);
+
+    match (organization_id, teacher_id) {
+        (Some(organization_id), None) => repo.list_for_organization(&organization_id),
+        (None, Some(teacher_id)) => repo.list_for_teacher(&teacher_id),
+        _ => return Err("Give either an organization or a teacher".to_string()),
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_substitute_preference(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    PreferenceRepository::new(&conn)
+        .delete(&id)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_deleted::<SubstitutePreference>(&app, &id);
+
+    Ok(())
+}
+
+/// Who should be offered the request first and who after, leaving out blocked
+/// and unavailable substitutes.
+#[tauri::command]
+pub fn get_offer_tiers(
+    state: State<'_, AppState>,
+    request_id: String,
+    substitute_ids: Vec<String>,
+) -> Result<OfferTiers, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let request = SubstituteRequestRepository::new(&conn)
+        .find_by_id(&request_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Substitute request not found".to_string())?;
+    PreferenceRepository::new(&conn)
+        .offer_tiers(substitute_ids, &request)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,35 @@
+use crate::database::models::{CoverageReport, ReportFilter, SubstituteStats};
+use crate::commands::AppState;
+use crate::repository::ReportRepository;
+use tauri::State;
+
+/// Substitutes listed by `get_substitute_report` when no limit is given.
+const DEFAULT_SUBSTITUTE_LIMIT: u32 = 10;
+
+/// Fill rate, time-to-fill, unfilled requests by reason and requests by
+/// weekday for the filter's range and organization subtree.
+#[tauri::command]
+pub fn get_coverage_report(state: State<'_, AppState>, filter: ReportFilter) -> Result<CoverageReport, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    ReportRepository::new(&conn)
+        .coverage(filter)
+        .map_err(|e| e.to_string())
+}
+
+/// Substitutes with the most assignments in the filter's scope, with their
+/// decline rates.
+#[tauri::command]
+pub fn get_substitute_report(
+    state: State<'_, AppState>,
+    filter: ReportFilter,
+    limit: Option<u32>,
+) -> Result<Vec<SubstituteStats>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    ReportRepository::new(&conn)
+        .substitutes(&filter, limit.unwrap_or(DEFAULT_SUBSTITUTE_LIMIT))
+        .map_err(|e| e.to_string())
+}
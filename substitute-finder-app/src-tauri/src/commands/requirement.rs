@@ -0,0 +1,134 @@
+use crate::database::models::{
+    ClassRequirement, CreateClassRequirementRequest, RequirementOverride, RequirementOverrideRequest,
+    SaveVocabularyTermRequest, SubstituteRequest, VocabularyKind, VocabularyTerm, WebhookEventType,
+};
+use crate::commands::{require_admin, AppState};
+use crate::events::{self, ChangeKind};
+use crate::repository::{RequirementRepository, SubstituteRequestRepository, VocabularyRepository};
+use crate::webhooks;
+use tauri::{AppHandle, State};
+
+/// Subjects or grade bands to offer when creating or editing a class.
+#[tauri::command]
+pub fn get_class_vocabulary(
+    state: State<'_, AppState>,
+    kind: VocabularyKind,
+    include_inactive: Option<bool>,
+) -> Result<Vec<VocabularyTerm>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    VocabularyRepository::new(&conn)
+        .list(kind, include_inactive.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn save_vocabulary_term(
+    state: State<'_, AppState>,
+    user_id: String,
+    request: SaveVocabularyTermRequest,
+) -> Result<VocabularyTerm, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "change the class vocabulary")?;
+
+    VocabularyRepository::new(&conn)
+        .save(request)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn create_class_requirement(
+    state: State<'_, AppState>,
+    user_id: String,
+    request: CreateClassRequirementRequest,
+) -> Result<ClassRequirement, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "change class requirements")?;
+
+    RequirementRepository::new(&conn)
+        .create(request)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_class_requirements(
+    state: State<'_, AppState>,
+    class_id: String,
+) -> Result<Vec<ClassRequirement>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    RequirementRepository::new(&conn)
+        .list_for_class(&class_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_class_requirement(state: State<'_, AppState>, user_id: String, id: String) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "change class requirements")?;
+
+    RequirementRepository::new(&conn).delete(&id).map_err(|e| e.to_string())
+}
+
+/// Requirements of the request's class the substitute doesn't meet, for
+/// showing before an assignment is attempted.
+#[tauri::command]
+pub fn get_unmet_requirements(
+    state: State<'_, AppState>,
+    request_id: String,
+    substitute_id: String,
+) -> Result<Vec<String>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let request = SubstituteRequestRepository::new(&conn)
+        .find_by_id(&request_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Substitute request not found".to_string())?;
+    RequirementRepository::new(&conn)
+        .unmet(&substitute_id, &request)
+        .map_err(|e| e.to_string())
+}
+
+/// Assigns a substitute who doesn't meet the class's requirements, recording
+/// the admin's justification.
+#[tauri::command]
+pub fn override_requirements_and_assign(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: RequirementOverrideRequest,
+) -> Result<SubstituteRequest, String> {
+    let assigned = {
+        let conn = state.get_connection();
+        let conn = conn.lock().map_err(|e| e.to_string())?;
+        require_admin(&conn, &request.admin_id, "override class requirements")?;
+
+        let (_, assigned) = RequirementRepository::new(&conn)
+            .override_and_assign(request)
+            .map_err(|e| e.to_string())?;
+        assigned
+    };
+
+    webhooks::dispatch(state.get_connection(), WebhookEventType::RequestFilled, &assigned);
+    events::emit_change(&app, ChangeKind::Updated, &assigned);
+
+    Ok(assigned)
+}
+
+#[tauri::command]
+pub fn get_requirement_overrides(
+    state: State<'_, AppState>,
+    request_id: String,
+) -> Result<Vec<RequirementOverride>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    RequirementRepository::new(&conn)
+        .list_overrides_for_request(&request_id)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,48 @@
+use crate::database::models::{RosterImportReport, RosterImportRequest, WebhookEventType};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::oneroster;
+use crate::webhooks;
+use tauri::{AppHandle, State};
+
+/// Imports orgs, classes, teachers and teacher enrollments from a OneRoster
+/// CSV bundle, reporting what was created, updated or removed.
+#[tauri::command]
+pub fn import_roster(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: RosterImportRequest,
+) -> Result<RosterImportReport, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let imported = oneroster::run(&conn, &request).map_err(|e| e.to_string())?;
+
+    for organization in &imported.organizations.created {
+        events::emit_change(&app, ChangeKind::Created, organization);
+    }
+    for organization in &imported.organizations.updated {
+        events::emit_change(&app, ChangeKind::Updated, organization);
+    }
+    for class in &imported.classes.created {
+        events::emit_change(&app, ChangeKind::Created, class);
+    }
+    for class in &imported.classes.updated {
+        events::emit_change(&app, ChangeKind::Updated, class);
+    }
+    for user in &imported.users.created {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::UserCreated, user);
+        events::emit_change(&app, ChangeKind::Created, user);
+    }
+    for user in &imported.users.updated {
+        events::emit_change(&app, ChangeKind::Updated, user);
+    }
+    for teacher in &imported.teachers.created {
+        events::emit_change(&app, ChangeKind::Created, teacher);
+    }
+    for teacher in imported.teachers.updated.iter().chain(&imported.assigned) {
+        events::emit_change(&app, ChangeKind::Updated, teacher);
+    }
+
+    Ok(imported.report)
+}
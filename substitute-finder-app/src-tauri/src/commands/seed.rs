@@ -1,6 +1,6 @@
 use crate::database::models::{User, UserRole, Organization};
 use crate::commands::AppState;
-use chrono::Utc;
+use crate::database::timestamp;
 use uuid::Uuid;
 use tauri::State;
 
@@ -30,8 +30,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             &org_id,
             "Demo School District",
             "A sample school district for testing",
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -50,8 +50,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             "admin",
             &org_id,
             true,
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -70,8 +70,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             "org_manager",
             &org_id,
             true,
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -90,8 +90,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             "substitute",
             &org_id,
             true,
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -108,8 +108,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             "5th Grade",
             "Room 101",
             "Advanced mathematics for 5th grade students",
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -125,8 +125,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             "3rd Grade",
             "Lab B",
             "Hands-on science experiments for 3rd graders",
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -146,8 +146,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             "Sick Leave",
             "Please follow the lesson plan on the desk. Math worksheets are in the file cabinet.",
             "open",
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -167,8 +167,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             "Science lab safety rules posted on wall. No experiments scheduled for today.",
             "filled",
             &sub_id,
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -186,8 +186,8 @@ pub fn seed_database(state: State<'_, AppState>) -> Result<String, String> {
             "12:00",
             "Emergency",
             "cancelled",
-            &Utc::now().to_rfc3339(),
-            &Utc::now().to_rfc3339(),
+            &timestamp::now(),
+            &timestamp::now(),
         ),
     ).map_err(|e| e.to_string())?;
     
@@ -0,0 +1,70 @@
+use crate::database::models::Setting;
+use crate::commands::AppState;
+use crate::payroll;
+use crate::repository::credential::parse_credential_types;
+use crate::repository::setting::{
+    BACKUP_DAILY_KEEP, BACKUP_WEEKLY_KEEP, CALENDAR_FEED_PORT, CALENDAR_FEED_TOKEN, CHECK_IN_TOLERANCE_MINUTES,
+    ESTIMATED_HOURLY_RATE, NO_SHOW_GRACE_MINUTES, PAYROLL_CSV_COLUMNS, PREFERRED_OFFER_MINUTES, REQUIRED_CREDENTIALS,
+    TRAVEL_BUFFER_MINUTES,
+};
+use crate::repository::SettingsRepository;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_settings(state: State<'_, AppState>) -> Result<Vec<Setting>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    SettingsRepository::new(&conn).list().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_setting(
+    state: State<'_, AppState>,
+    key: String,
+    value: String,
+    description: Option<String>,
+) -> Result<Setting, String> {
+    let minute_settings = [
+        TRAVEL_BUFFER_MINUTES,
+        PREFERRED_OFFER_MINUTES,
+        NO_SHOW_GRACE_MINUTES,
+        CHECK_IN_TOLERANCE_MINUTES,
+    ];
+    if minute_settings.contains(&key.as_str()) && value.trim().parse::<u32>().is_err() {
+        return Err(format!("{} must be a whole number of minutes", key));
+    }
+    if [BACKUP_DAILY_KEEP, BACKUP_WEEKLY_KEEP].contains(&key.as_str()) && value.trim().parse::<u32>().is_err() {
+        return Err(format!("{} must be a whole number of backups", key));
+    }
+    if key == PAYROLL_CSV_COLUMNS {
+        payroll::parse_columns(&value).map_err(|e| e.to_string())?;
+    }
+    if key == ESTIMATED_HOURLY_RATE
+        && !value.trim().is_empty()
+        && !value.trim().parse::<f64>().is_ok_and(|rate| rate >= 0.0)
+    {
+        return Err(format!("{} must be blank or a non-negative amount", key));
+    }
+    if key == REQUIRED_CREDENTIALS {
+        parse_credential_types(&value).map_err(|e| e.to_string())?;
+    }
+    if key == CALENDAR_FEED_PORT
+        && !value.trim().is_empty()
+        && !value.trim().parse::<u16>().is_ok_and(|port| port >= 1024)
+    {
+        return Err(format!("{} must be blank or a port from 1024 to 65535", key));
+    }
+    if key == CALENDAR_FEED_TOKEN
+        && (value.trim().len() < 16 || !value.trim().chars().all(|ch| ch.is_ascii_alphanumeric()))
+    {
+        return Err(format!("{} must be at least 16 letters or digits", key));
+    }
+
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    SettingsRepository::new(&conn)
+        .set(&key, &value, description.as_deref())
+        .map_err(|e| e.to_string())
+}
@@ -1,11 +1,16 @@
-use crate::database::models::{SubstituteRequest, CreateSubstituteRequestRequest, RequestStatus};
+use crate::database::models::{
+    SubstituteRequest, CreateSubstituteRequestRequest, CreatePeriodSubstituteRequest, RequestStatus, ResponseType,
+    WebhookEventType,
+};
 use crate::commands::AppState;
-use chrono::Utc;
-use uuid::Uuid;
-use tauri::State;
+use crate::events::{self, ChangeKind};
+use crate::repository::{ResponseRepository, SubstituteRequestRepository};
+use crate::webhooks;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub fn create_substitute_request(
+    app: AppHandle,
     state: State<'_, AppState>,
     requested_by: String,
     request: CreateSubstituteRequestRequest,
@@ -13,39 +18,34 @@ pub fn create_substitute_request(
     let conn = state.get_connection();
     let conn = conn.lock().map_err(|e| e.to_string())?;
     
-    let substitute_request = SubstituteRequest {
-        id: Uuid::new_v4().to_string(),
-        class_id: request.class_id,
-        requested_by,
-        date_needed: request.date_needed,
-        start_time: request.start_time,
-        end_time: request.end_time,
-        reason: request.reason,
-        special_instructions: request.special_instructions,
-        status: RequestStatus::Open,
-        assigned_substitute_id: None,
-        created_at: Utc::now(),
-        updated_at: Utc::now(),
-    };
+    let substitute_request = SubstituteRequestRepository::new(&conn)
+        .create(requested_by, request)
+        .map_err(|e| e.to_string())?;
+
+    webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCreated, &substitute_request);
+    events::emit_change(&app, ChangeKind::Created, &substitute_request);
+
+    Ok(substitute_request)
+}
+
+/// Creates a request for class periods, timed by the bell schedule in effect
+/// on the requested date.
+#[tauri::command]
+pub fn create_substitute_request_for_periods(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    requested_by: String,
+    request: CreatePeriodSubstituteRequest,
+) -> Result<SubstituteRequest, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let substitute_request = SubstituteRequestRepository::new(&conn)
+        .create_for_periods(requested_by, request)
+        .map_err(|e| e.to_string())?;
 
-    conn.execute(
-        "INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
-        (
-            &substitute_request.id,
-            &substitute_request.class_id,
-            &substitute_request.requested_by,
-            &substitute_request.date_needed,
-            &substitute_request.start_time,
-            &substitute_request.end_time,
-            &substitute_request.reason,
-            &substitute_request.special_instructions,
-            &substitute_request.status.to_string(),
-            &substitute_request.assigned_substitute_id,
-            &substitute_request.created_at.to_rfc3339(),
-            &substitute_request.updated_at.to_rfc3339(),
-        ),
-    ).map_err(|e| e.to_string())?;
+    webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCreated, &substitute_request);
+    events::emit_change(&app, ChangeKind::Created, &substitute_request);
 
     Ok(substitute_request)
 }
@@ -57,34 +57,7 @@ pub fn get_substitute_requests(
     let conn = state.get_connection();
     let conn = conn.lock().map_err(|e| e.to_string())?;
     
-    let mut stmt = conn.prepare(
-        "SELECT id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, created_at, updated_at
-         FROM substitute_requests ORDER BY date_needed, start_time"
-    ).map_err(|e| e.to_string())?;
-    
-    let requests = stmt.query_map([], |row| {
-        Ok(SubstituteRequest {
-            id: row.get(0)?,
-            class_id: row.get(1)?,
-            requested_by: row.get(2)?,
-            date_needed: row.get(3)?,
-            start_time: row.get(4)?,
-            end_time: row.get(5)?,
-            reason: row.get(6)?,
-            special_instructions: row.get(7)?,
-            status: row.get::<_, String>(8)?.parse().unwrap_or(RequestStatus::Open),
-            assigned_substitute_id: row.get(9)?,
-            created_at: row.get::<_, String>(10)?.parse().unwrap_or_else(|_| Utc::now()),
-            updated_at: row.get::<_, String>(11)?.parse().unwrap_or_else(|_| Utc::now()),
-        })
-    }).map_err(|e| e.to_string())?;
-    
-    let mut result = Vec::new();
-    for request in requests {
-        result.push(request.map_err(|e| e.to_string())?);
-    }
-    
-    Ok(result)
+    SubstituteRequestRepository::new(&conn).list().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -95,62 +68,46 @@ pub fn get_substitute_requests_by_status(
     let conn = state.get_connection();
     let conn = conn.lock().map_err(|e| e.to_string())?;
     
-    let mut stmt = conn.prepare(
-        "SELECT id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, created_at, updated_at
-         FROM substitute_requests WHERE status = ?1 ORDER BY date_needed, start_time"
-    ).map_err(|e| e.to_string())?;
-    
-    let requests = stmt.query_map([&status], |row| {
-        Ok(SubstituteRequest {
-            id: row.get(0)?,
-            class_id: row.get(1)?,
-            requested_by: row.get(2)?,
-            date_needed: row.get(3)?,
-            start_time: row.get(4)?,
-            end_time: row.get(5)?,
-            reason: row.get(6)?,
-            special_instructions: row.get(7)?,
-            status: row.get::<_, String>(8)?.parse().unwrap_or(RequestStatus::Open),
-            assigned_substitute_id: row.get(9)?,
-            created_at: row.get::<_, String>(10)?.parse().unwrap_or_else(|_| Utc::now()),
-            updated_at: row.get::<_, String>(11)?.parse().unwrap_or_else(|_| Utc::now()),
-        })
-    }).map_err(|e| e.to_string())?;
-    
-    let mut result = Vec::new();
-    for request in requests {
-        result.push(request.map_err(|e| e.to_string())?);
-    }
-    
-    Ok(result)
+    SubstituteRequestRepository::new(&conn)
+        .list_by_status(&status)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn update_substitute_request_status(
+    app: AppHandle,
     state: State<'_, AppState>,
     id: String,
     status: String,
     assigned_substitute_id: Option<String>,
+) -> Result<SubstituteRequest, String> {
+    let request = set_request_status(&state, &id, &status, assigned_substitute_id.as_deref())?;
+    events::emit_change(&app, ChangeKind::Updated, &request);
+    Ok(request)
+}
+
+fn set_request_status(
+    state: &State<'_, AppState>,
+    id: &str,
+    status: &str,
+    assigned_substitute_id: Option<&str>,
 ) -> Result<SubstituteRequest, String> {
     let conn = state.get_connection();
     let conn = conn.lock().map_err(|e| e.to_string())?;
     
-    let updated_at = Utc::now();
-    
-    conn.execute(
-        "UPDATE substitute_requests SET status = ?1, assigned_substitute_id = ?2, updated_at = ?3 WHERE id = ?4",
-        (
-            &status,
-            &assigned_substitute_id,
-            &updated_at.to_rfc3339(),
-            &id,
-        ),
-    ).map_err(|e| e.to_string())?;
-    
-    match get_substitute_request_by_id(state, id)? {
-        Some(request) => Ok(request),
-        None => Err("Substitute request not found".to_string()),
+    let request = SubstituteRequestRepository::new(&conn)
+        .update_status(id, status, assigned_substitute_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Substitute request not found".to_string())?;
+
+    let db = state.get_connection();
+    match request.status {
+        RequestStatus::Filled => webhooks::dispatch(db, WebhookEventType::RequestFilled, &request),
+        RequestStatus::Cancelled => webhooks::dispatch(db, WebhookEventType::RequestCancelled, &request),
+        RequestStatus::Open | RequestStatus::InProgress | RequestStatus::Completed | RequestStatus::NoShow => {}
     }
+
+    Ok(request)
 }
 
 #[tauri::command]
@@ -161,53 +118,66 @@ pub fn get_substitute_request_by_id(
     let conn = state.get_connection();
     let conn = conn.lock().map_err(|e| e.to_string())?;
     
-    let mut stmt = conn.prepare(
-        "SELECT id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, created_at, updated_at
-         FROM substitute_requests WHERE id = ?1"
-    ).map_err(|e| e.to_string())?;
-    
-    let request = stmt.query_row([&id], |row| {
-        Ok(SubstituteRequest {
-            id: row.get(0)?,
-            class_id: row.get(1)?,
-            requested_by: row.get(2)?,
-            date_needed: row.get(3)?,
-            start_time: row.get(4)?,
-            end_time: row.get(5)?,
-            reason: row.get(6)?,
-            special_instructions: row.get(7)?,
-            status: row.get::<_, String>(8)?.parse().unwrap_or(RequestStatus::Open),
-            assigned_substitute_id: row.get(9)?,
-            created_at: row.get::<_, String>(10)?.parse().unwrap_or_else(|_| Utc::now()),
-            updated_at: row.get::<_, String>(11)?.parse().unwrap_or_else(|_| Utc::now()),
-        })
-    });
-    
-    match request {
-        Ok(req) => Ok(Some(req)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e.to_string()),
-    }
+    SubstituteRequestRepository::new(&conn)
+        .find_by_id(&id)
+        .map_err(|e| e.to_string())
+}
+
+/// Booked requests the substitute would clash with if assigned this one.
+#[tauri::command]
+pub fn get_assignment_conflicts(
+    state: State<'_, AppState>,
+    request_id: String,
+    substitute_id: String,
+) -> Result<Vec<SubstituteRequest>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = SubstituteRequestRepository::new(&conn);
+
+    let request = repo
+        .find_by_id(&request_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Substitute request not found".to_string())?;
+    repo.find_conflicts(&substitute_id, &request)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn accept_substitute_request(
+    app: AppHandle,
     state: State<'_, AppState>,
     request_id: String,
     substitute_id: String,
 ) -> Result<SubstituteRequest, String> {
-    update_substitute_request_status(state, request_id, "filled".to_string(), Some(substitute_id))
+    let request = set_request_status(&state, &request_id, "filled", Some(&substitute_id))?;
+    events::emit_change(&app, ChangeKind::Accepted, &request);
+    Ok(request)
 }
 
 #[tauri::command]
 pub fn decline_substitute_request(
+    app: AppHandle,
     state: State<'_, AppState>,
     request_id: String,
+    substitute_id: Option<String>,
+    notes: Option<String>,
 ) -> Result<SubstituteRequest, String> {
-    // For now, declining just keeps the request open for others
-    // In a more advanced system, we might track who declined
-    get_substitute_request_by_id(state, request_id)?
-        .ok_or_else(|| "Substitute request not found".to_string())
+    // Declining keeps the request open for others; who declined is recorded
+    // for the decline-rate reports
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let request = SubstituteRequestRepository::new(&conn)
+        .find_by_id(&request_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Substitute request not found".to_string())?;
+    if let Some(substitute_id) = substitute_id {
+        ResponseRepository::new(&conn)
+            .record(&request.id, &substitute_id, ResponseType::Declined, notes.as_deref())
+            .map_err(|e| e.to_string())?;
+    }
+    events::emit_change(&app, ChangeKind::Declined, &request);
+    Ok(request)
 }
 
 #[tauri::command]
@@ -219,85 +189,25 @@ pub fn get_substitute_requests_for_user(
     let conn = state.get_connection();
     let conn = conn.lock().map_err(|e| e.to_string())?;
     
-    let query = match user_role.as_str() {
-        "substitute" => {
-            // For substitutes, show open requests or requests assigned to them
-            "SELECT id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, created_at, updated_at
-             FROM substitute_requests 
-             WHERE status = 'open' OR assigned_substitute_id = ?1
-             ORDER BY date_needed, start_time"
-        },
-        "org_manager" => {
-            // For org managers, show requests they created
-            "SELECT id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, created_at, updated_at
-             FROM substitute_requests 
-             WHERE requested_by = ?1
-             ORDER BY date_needed, start_time"
-        },
-        _ => {
-            // For admins, show all requests
-            "SELECT id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, created_at, updated_at
-             FROM substitute_requests 
-             ORDER BY date_needed, start_time"
-        }
-    };
-    
-    let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
-    
-    let requests = if user_role == "admin" {
-        stmt.query_map([], |row| {
-            Ok(SubstituteRequest {
-                id: row.get(0)?,
-                class_id: row.get(1)?,
-                requested_by: row.get(2)?,
-                date_needed: row.get(3)?,
-                start_time: row.get(4)?,
-                end_time: row.get(5)?,
-                reason: row.get(6)?,
-                special_instructions: row.get(7)?,
-                status: row.get::<_, String>(8)?.parse().unwrap_or(RequestStatus::Open),
-                assigned_substitute_id: row.get(9)?,
-                created_at: row.get::<_, String>(10)?.parse().unwrap_or_else(|_| Utc::now()),
-                updated_at: row.get::<_, String>(11)?.parse().unwrap_or_else(|_| Utc::now()),
-            })
-        }).map_err(|e| e.to_string())?
-    } else {
-        stmt.query_map([&user_id], |row| {
-            Ok(SubstituteRequest {
-                id: row.get(0)?,
-                class_id: row.get(1)?,
-                requested_by: row.get(2)?,
-                date_needed: row.get(3)?,
-                start_time: row.get(4)?,
-                end_time: row.get(5)?,
-                reason: row.get(6)?,
-                special_instructions: row.get(7)?,
-                status: row.get::<_, String>(8)?.parse().unwrap_or(RequestStatus::Open),
-                assigned_substitute_id: row.get(9)?,
-                created_at: row.get::<_, String>(10)?.parse().unwrap_or_else(|_| Utc::now()),
-                updated_at: row.get::<_, String>(11)?.parse().unwrap_or_else(|_| Utc::now()),
-            })
-        }).map_err(|e| e.to_string())?
-    };
-    
-    let mut result = Vec::new();
-    for request in requests {
-        result.push(request.map_err(|e| e.to_string())?);
-    }
-    
-    Ok(result)
+    SubstituteRequestRepository::new(&conn)
+        .list_for_user(&user_id, &user_role)
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn delete_substitute_request(
+    app: AppHandle,
     state: State<'_, AppState>,
     id: String,
 ) -> Result<(), String> {
     let conn = state.get_connection();
     let conn = conn.lock().map_err(|e| e.to_string())?;
     
-    conn.execute("DELETE FROM substitute_requests WHERE id = ?1", [&id])
+    SubstituteRequestRepository::new(&conn)
+        .delete(&id)
         .map_err(|e| e.to_string())?;
     
+    events::emit_deleted::<SubstituteRequest>(&app, &id);
+
     Ok(())
-}
\ No newline at end of file
+}
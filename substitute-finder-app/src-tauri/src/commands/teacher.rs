@@ -0,0 +1,258 @@
+use crate::database::models::{
+    Class, CreateRegularTeacherRequest, CreateTeacherClassAssignmentRequest, RegularTeacher, ReportAbsenceRequest,
+    SubstituteRequest, TeacherAbsence, TeacherAbsenceWithRequests, TeacherClassAssignment, WebhookEventType,
+};
+use crate::commands::AppState;
+use crate::events::{self, ChangeKind};
+use crate::repository::{RegularTeacherRepository, TeacherAbsenceRepository};
+use crate::webhooks;
+use chrono::NaiveDate;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn create_regular_teacher(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: CreateRegularTeacherRequest,
+) -> Result<RegularTeacher, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let teacher = RegularTeacherRepository::new(&conn)
+        .create(request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &teacher);
+
+    Ok(teacher)
+}
+
+#[tauri::command]
+pub fn get_regular_teachers(state: State<'_, AppState>) -> Result<Vec<RegularTeacher>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    RegularTeacherRepository::new(&conn).list().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_regular_teacher_by_id(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Option<RegularTeacher>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    RegularTeacherRepository::new(&conn)
+        .find_by_id(&id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_regular_teacher(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+    request: CreateRegularTeacherRequest,
+) -> Result<RegularTeacher, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let teacher = RegularTeacherRepository::new(&conn)
+        .update(&id, request)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Teacher not found".to_string())?;
+
+    events::emit_change(&app, ChangeKind::Updated, &teacher);
+
+    Ok(teacher)
+}
+
+#[tauri::command]
+pub fn delete_regular_teacher(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    RegularTeacherRepository::new(&conn)
+        .delete(&id)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_deleted::<RegularTeacher>(&app, &id);
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn assign_teacher_to_class(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: CreateTeacherClassAssignmentRequest,
+) -> Result<TeacherClassAssignment, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = RegularTeacherRepository::new(&conn);
+
+    let assignment = repo.assign_class(request).map_err(|e| e.to_string())?;
+
+    if let Some(teacher) = repo.find_by_id(&assignment.teacher_id).map_err(|e| e.to_string())? {
+        events::emit_change(&app, ChangeKind::Updated, &teacher);
+    }
+
+    Ok(assignment)
+}
+
+#[tauri::command]
+pub fn get_teacher_class_assignments(
+    state: State<'_, AppState>,
+    teacher_id: String,
+) -> Result<Vec<TeacherClassAssignment>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    RegularTeacherRepository::new(&conn)
+        .list_assignments(&teacher_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_classes_for_teacher(
+    state: State<'_, AppState>,
+    teacher_id: String,
+) -> Result<Vec<Class>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    RegularTeacherRepository::new(&conn)
+        .classes_for_teacher(&teacher_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn remove_teacher_class_assignment(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    teacher_id: String,
+    assignment_id: String,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = RegularTeacherRepository::new(&conn);
+
+    repo.remove_assignment(&assignment_id).map_err(|e| e.to_string())?;
+
+    if let Some(teacher) = repo.find_by_id(&teacher_id).map_err(|e| e.to_string())? {
+        events::emit_change(&app, ChangeKind::Updated, &teacher);
+    }
+
+    Ok(())
+}
+
+/// Reports a teacher absent and opens a request for each of their classes on
+/// every school day in the range.
+#[tauri::command]
+pub fn report_absence(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    reported_by: String,
+    request: ReportAbsenceRequest,
+) -> Result<TeacherAbsenceWithRequests, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let reported = TeacherAbsenceRepository::new(&conn)
+        .report(reported_by, request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &reported.teacher_absence);
+    for request in &reported.requests {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCreated, request);
+        events::emit_change(&app, ChangeKind::Created, request);
+    }
+
+    Ok(reported)
+}
+
+#[tauri::command]
+pub fn get_teacher_absences(
+    state: State<'_, AppState>,
+    teacher_id: Option<String>,
+) -> Result<Vec<TeacherAbsence>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = TeacherAbsenceRepository::new(&conn);
+
+    match teacher_id {
+        Some(teacher_id) => repo.list_for_teacher(&teacher_id),
+        None => repo.list(),
+    }
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_teacher_absence_by_id(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Option<TeacherAbsenceWithRequests>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    TeacherAbsenceRepository::new(&conn)
+        .find_with_requests(&id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn cancel_teacher_absence(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<TeacherAbsenceWithRequests, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    let repo = TeacherAbsenceRepository::new(&conn);
+
+    let cancelled = repo
+        .cancel(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Teacher absence not found".to_string())?;
+    for request in &cancelled {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestCancelled, request);
+        events::emit_change(&app, ChangeKind::Updated, request);
+    }
+
+    let absence = repo
+        .find_with_requests(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Teacher absence not found".to_string())?;
+    events::emit_change(&app, ChangeKind::Updated, &absence.teacher_absence);
+
+    Ok(absence)
+}
+
+/// Lets one substitute take every class of the absence, for a single `date`
+/// or for all remaining days.
+#[tauri::command]
+pub fn accept_teacher_absence(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    teacher_absence_id: String,
+    substitute_id: String,
+    date: Option<NaiveDate>,
+) -> Result<Vec<SubstituteRequest>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    let accepted = TeacherAbsenceRepository::new(&conn)
+        .accept(&teacher_absence_id, &substitute_id, date)
+        .map_err(|e| e.to_string())?;
+    for request in &accepted {
+        webhooks::dispatch(state.get_connection(), WebhookEventType::RequestFilled, request);
+        events::emit_change(&app, ChangeKind::Accepted, request);
+    }
+
+    Ok(accepted)
+}
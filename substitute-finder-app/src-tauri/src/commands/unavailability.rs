@@ -0,0 +1,62 @@
+use crate::database::models::{CreateUnavailabilityRequest, SubstituteUnavailability};
+use crate::commands::{require_substitute, AppState};
+use crate::events::{self, ChangeKind};
+use crate::repository::UnavailabilityRepository;
+use tauri::{AppHandle, State};
+
+/// Time off is self-service: only substitutes manage it, and only their own.
+#[tauri::command]
+pub fn create_unavailability(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    user_id: String,
+    request: CreateUnavailabilityRequest,
+) -> Result<SubstituteUnavailability, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_substitute(&conn, &user_id, "manage time off")?;
+
+    let entry = UnavailabilityRepository::new(&conn)
+        .create(&user_id, request)
+        .map_err(|e| e.to_string())?;
+
+    events::emit_change(&app, ChangeKind::Created, &entry);
+
+    Ok(entry)
+}
+
+#[tauri::command]
+pub fn get_unavailability(
+    state: State<'_, AppState>,
+    user_id: String,
+) -> Result<Vec<SubstituteUnavailability>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+
+    UnavailabilityRepository::new(&conn)
+        .list_for_substitute(&user_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_unavailability(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    user_id: String,
+    id: String,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_substitute(&conn, &user_id, "manage time off")?;
+
+    if !UnavailabilityRepository::new(&conn)
+        .delete(&user_id, &id)
+        .map_err(|e| e.to_string())?
+    {
+        return Err("Time off entry not found".to_string());
+    }
+
+    events::emit_deleted::<SubstituteUnavailability>(&app, &id);
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use crate::database::models::{
+    CreateWebhookSubscriptionRequest, CreatedWebhookSubscription, WebhookDelivery, WebhookSubscription,
+};
+use crate::commands::{require_admin, AppState};
+use crate::repository::WebhookRepository;
+use tauri::State;
+
+fn validate_subscription(request: &CreateWebhookSubscriptionRequest) -> Result<(), String> {
+    if !(request.url.starts_with("http://") || request.url.starts_with("https://")) {
+        return Err("Webhook URL must start with http:// or https://".to_string());
+    }
+    if request.secret.trim().is_empty() {
+        return Err("Webhook secret is required".to_string());
+    }
+    if request.event_types.is_empty() {
+        return Err("At least one event type is required".to_string());
+    }
+    Ok(())
+}
+
+/// The response is the only place the new subscription's secret is shown.
+#[tauri::command]
+pub fn create_webhook_subscription(
+    state: State<'_, AppState>,
+    user_id: String,
+    request: CreateWebhookSubscriptionRequest,
+) -> Result<CreatedWebhookSubscription, String> {
+    validate_subscription(&request)?;
+
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "manage webhooks")?;
+
+    let subscription = WebhookRepository::new(&conn)
+        .create_subscription(request)
+        .map_err(|e| e.to_string())?;
+    Ok(CreatedWebhookSubscription { secret: subscription.secret.clone(), subscription })
+}
+
+#[tauri::command]
+pub fn get_webhook_subscriptions(
+    state: State<'_, AppState>,
+    user_id: String,
+) -> Result<Vec<WebhookSubscription>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "manage webhooks")?;
+
+    WebhookRepository::new(&conn).list_subscriptions().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn update_webhook_subscription(
+    state: State<'_, AppState>,
+    user_id: String,
+    id: String,
+    request: CreateWebhookSubscriptionRequest,
+    is_active: bool,
+) -> Result<WebhookSubscription, String> {
+    validate_subscription(&request)?;
+
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "manage webhooks")?;
+
+    WebhookRepository::new(&conn)
+        .update_subscription(&id, request, is_active)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Webhook subscription not found".to_string())
+}
+
+#[tauri::command]
+pub fn delete_webhook_subscription(
+    state: State<'_, AppState>,
+    user_id: String,
+    id: String,
+) -> Result<(), String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "manage webhooks")?;
+
+    WebhookRepository::new(&conn)
+        .delete_subscription(&id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_webhook_deliveries(
+    state: State<'_, AppState>,
+    user_id: String,
+    subscription_id: Option<String>,
+) -> Result<Vec<WebhookDelivery>, String> {
+    let conn = state.get_connection();
+    let conn = conn.lock().map_err(|e| e.to_string())?;
+    require_admin(&conn, &user_id, "manage webhooks")?;
+
+    WebhookRepository::new(&conn)
+        .list_deliveries(subscription_id.as_deref())
+        .map_err(|e| e.to_string())
+}
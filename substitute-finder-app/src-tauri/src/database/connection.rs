@@ -1,3 +1,4 @@
+use crate::database::migrations;
 use rusqlite::{Connection, Result};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
@@ -34,6 +35,7 @@ impl DatabaseManager {
     pub fn initialize_database(&self) -> Result<()> {
         let conn = self.connection.lock().unwrap();
         conn.execute_batch(include_str!("schema.sql"))?;
+        migrations::run(&conn)?;
         Ok(())
     }
 
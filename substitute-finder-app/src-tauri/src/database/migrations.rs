@@ -0,0 +1,465 @@
+use crate::database::timestamp;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use rusqlite::{Connection, Result};
+
+type Migration = fn(&Connection) -> Result<()>;
+
+/// Ordered data and schema migrations, applied after `schema.sql`. The index of
+/// each entry plus one is the `PRAGMA user_version` it brings the database to.
+///
+/// `schema.sql` always describes the latest schema, so every migration must be
+/// a no-op on a freshly created database.
+const MIGRATIONS: &[Migration] = &[
+    normalize_request_schedules,
+    link_requests_to_absences,
+    link_absences_to_teachers,
+    assign_substitutes_by_user,
+    track_assignment_progress,
+    adopt_class_vocabulary,
+    record_responses_by_user,
+    add_organization_address,
+];
+
+/// Schema version of a database that has every migration applied.
+pub const SCHEMA_VERSION: i32 = MIGRATIONS.len() as i32;
+
+pub fn run(conn: &Connection) -> Result<()> {
+    let current: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (index, migration) in MIGRATIONS.iter().enumerate() {
+        let version = index as i32 + 1;
+        if version <= current {
+            continue;
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        migration(&tx)?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in columns {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    if !column_exists(conn, table, column)? {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
+    Ok(())
+}
+
+fn parse_legacy_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    ["%Y-%m-%d", "%Y/%m/%d", "%m/%d/%Y", "%m-%d-%Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+        .or_else(|| {
+            ["%Y-%m-%dT%H:%M:%S%.fZ", "%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                .map(|datetime| datetime.date())
+        })
+}
+
+fn parse_legacy_time(value: &str) -> Option<NaiveTime> {
+    let value = value.trim();
+    ["%H:%M", "%H:%M:%S", "%H:%M:%S%.f", "%I:%M %p", "%I:%M%p", "%I %p", "%I%p"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+}
+
+/// v1: adds school hours to organizations and rewrites every request's
+/// `date_needed`, `start_time` and `end_time` into `YYYY-MM-DD` / `HH:MM`.
+///
+/// Rows whose values can't be understood are cancelled rather than dropped:
+/// their schedule is reset to a placeholder and the original text is kept in
+/// `special_instructions` so an admin can recreate them.
+fn normalize_request_schedules(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "organizations", "school_start_time", "TEXT")?;
+    add_column_if_missing(conn, "organizations", "school_end_time", "TEXT")?;
+
+    let rows = {
+        let mut stmt = conn.prepare(
+            "SELECT id, date_needed, start_time, end_time, special_instructions FROM substitute_requests"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+        rows.collect::<Result<Vec<_>>>()?
+    };
+
+    for (id, date_needed, start_time, end_time, special_instructions) in rows {
+        let parsed = (
+            parse_legacy_date(&date_needed),
+            parse_legacy_time(&start_time),
+            parse_legacy_time(&end_time),
+        );
+
+        match parsed {
+            (Some(date), Some(start), Some(end)) => {
+                conn.execute(
+                    "UPDATE substitute_requests SET date_needed = ?1, start_time = ?2, end_time = ?3 WHERE id = ?4",
+                    (
+                        timestamp::format_date(&date),
+                        timestamp::format_time(&start),
+                        timestamp::format_time(&end),
+                        &id,
+                    ),
+                )?;
+            }
+            _ => {
+                let note = format!(
+                    "[Cancelled during migration: unreadable schedule date_needed={:?} start_time={:?} end_time={:?}]",
+                    date_needed, start_time, end_time
+                );
+                let special_instructions = match special_instructions {
+                    Some(existing) if !existing.is_empty() => format!("{}\n{}", note, existing),
+                    _ => note,
+                };
+                conn.execute(
+                    "UPDATE substitute_requests SET date_needed = '1970-01-01', start_time = '00:00', end_time = '00:00',
+                     status = 'cancelled', special_instructions = ?1, updated_at = ?2 WHERE id = ?3",
+                    (special_instructions, timestamp::now(), &id),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// v2: links substitute requests to the multi-day absence they were expanded from.
+fn link_requests_to_absences(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "substitute_requests", "absence_id", "TEXT REFERENCES absences(id)")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_substitute_requests_absence ON substitute_requests(absence_id);"
+    )
+}
+
+/// v3: groups per-class absences under the teacher absence that produced them.
+fn link_absences_to_teachers(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "absences", "teacher_absence_id", "TEXT REFERENCES teacher_absences(id)")?;
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_absences_teacher_absence ON absences(teacher_absence_id);"
+    )
+}
+
+/// v4: `substitute_requests.assigned_substitute_id` referenced `teachers(id)`,
+/// but the app has always assigned substitutes by user id, so every accept
+/// failed the foreign key. Repoints the constraint at `users(id)` and converts
+/// any teacher ids already stored to their user ids.
+///
+/// Only the constraint text changes, so the table definition is edited in
+/// place rather than rebuilt; see [`replace_in_table_definition`].
+fn assign_substitutes_by_user(conn: &Connection) -> Result<()> {
+    if !replace_in_table_definition(
+        conn,
+        "substitute_requests",
+        "FOREIGN KEY (assigned_substitute_id) REFERENCES teachers(id)",
+        "FOREIGN KEY (assigned_substitute_id) REFERENCES users(id)",
+    )? {
+        return Ok(());
+    }
+
+    conn.execute(
+        "UPDATE substitute_requests
+         SET assigned_substitute_id = (SELECT user_id FROM teachers WHERE teachers.id = assigned_substitute_id)
+         WHERE assigned_substitute_id IN (SELECT id FROM teachers)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v5: lets requests move on from `filled` to `in_progress`, `completed` or
+/// `no_show` as the substitute checks in and out.
+fn track_assignment_progress(conn: &Connection) -> Result<()> {
+    replace_in_table_definition(
+        conn,
+        "substitute_requests",
+        "CHECK (status IN ('open', 'filled', 'cancelled'))",
+        "CHECK (status IN ('open', 'filled', 'in_progress', 'completed', 'no_show', 'cancelled'))",
+    )?;
+    Ok(())
+}
+
+/// v6: allows `certification` credentials, and adds the subjects and grade
+/// levels classes already use to the vocabulary so they stay valid.
+fn adopt_class_vocabulary(conn: &Connection) -> Result<()> {
+    replace_in_table_definition(
+        conn,
+        "substitute_credentials",
+        "CHECK (credential_type IN ('teaching_license', 'background_check', 'cpr', 'subject_endorsement'))",
+        "CHECK (credential_type IN ('teaching_license', 'background_check', 'cpr', 'subject_endorsement', 'certification'))",
+    )?;
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO class_vocabulary (kind, name, sort_order)
+             SELECT DISTINCT 'subject', subject, 1000 FROM classes WHERE subject IS NOT NULL AND subject != '';
+         INSERT OR IGNORE INTO class_vocabulary (kind, name, sort_order)
+             SELECT DISTINCT 'grade_band', grade_level, 1000 FROM classes WHERE grade_level IS NOT NULL AND grade_level != '';"
+    )
+}
+
+/// v7: `substitute_responses.substitute_id` referenced `teachers(id)`, the same
+/// mistake v4 fixed for assignments. Responses are recorded now, so the
+/// constraint is repointed at `users(id)` and any teacher ids converted.
+fn record_responses_by_user(conn: &Connection) -> Result<()> {
+    if !replace_in_table_definition(
+        conn,
+        "substitute_responses",
+        "FOREIGN KEY (substitute_id) REFERENCES teachers(id)",
+        "FOREIGN KEY (substitute_id) REFERENCES users(id)",
+    )? {
+        return Ok(());
+    }
+
+    conn.execute(
+        "UPDATE substitute_responses
+         SET substitute_id = (SELECT user_id FROM teachers WHERE teachers.id = substitute_id)
+         WHERE substitute_id IN (SELECT id FROM teachers)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// v8: gives organizations a street address for calendar event locations.
+fn add_organization_address(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "organizations", "address", "TEXT")
+}
+
+/// Swaps `old` for `new` in the stored `CREATE TABLE` statement, returning
+/// whether `old` was there. For constraint-only changes this edits the
+/// definition in place as described in
+/// <https://www.sqlite.org/lang_altertable.html> rather than rebuilding the
+/// table, which would trip the foreign keys pointing at it. Existing rows are
+/// not re-checked against `new`.
+fn replace_in_table_definition(conn: &Connection, table: &str, old: &str, new: &str) -> Result<bool> {
+    let sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get(0),
+    )?;
+    if !sql.contains(old) {
+        return Ok(false);
+    }
+
+    let schema_version: i32 = conn.pragma_query_value(None, "schema_version", |row| row.get(0))?;
+    conn.pragma_update(None, "writable_schema", true)?;
+    conn.execute(
+        "UPDATE sqlite_master SET sql = ?1 WHERE type = 'table' AND name = ?2",
+        [sql.replace(old, new), table.to_string()],
+    )?;
+    conn.pragma_update(None, "schema_version", schema_version + 1)?;
+    conn.pragma_update(None, "writable_schema", false)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("schema.sql")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO organizations (id, name) VALUES ('org-1', 'School');
+             INSERT INTO classes (id, name, organization_id) VALUES ('class-1', 'Math', 'org-1');
+             INSERT INTO users (id, username, password_hash, email, first_name, last_name, role)
+                 VALUES ('user-1', 'manager', 'hashed_manager', 'm@example.com', 'M', 'M', 'org_manager');"
+        ).unwrap();
+        conn
+    }
+
+    fn insert_request(conn: &Connection, id: &str, date: &str, start: &str, end: &str) {
+        conn.execute(
+            "INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, status)
+             VALUES (?1, 'class-1', 'user-1', ?2, ?3, ?4, 'open')",
+            [id, date, start, end],
+        ).unwrap();
+    }
+
+    fn schedule(conn: &Connection, id: &str) -> (String, String, String, String) {
+        conn.query_row(
+            "SELECT date_needed, start_time, end_time, status FROM substitute_requests WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_run_sets_user_version_and_is_idempotent() {
+        let conn = legacy_connection();
+        run(&conn).unwrap();
+        run(&conn).unwrap();
+
+        let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_normalizes_legacy_schedules() {
+        let conn = legacy_connection();
+        insert_request(&conn, "iso", "2030-01-15", "08:30", "15:00");
+        insert_request(&conn, "us", "1/5/2030", "8:30 AM", "3:00 PM");
+        insert_request(&conn, "seconds", "2030-1-5", "08:30:00", "15:00:00");
+        insert_request(&conn, "garbage", "tomorrow", "morning", "afternoon");
+
+        run(&conn).unwrap();
+
+        assert_eq!(schedule(&conn, "iso"), ("2030-01-15".into(), "08:30".into(), "15:00".into(), "open".into()));
+        assert_eq!(schedule(&conn, "us"), ("2030-01-05".into(), "08:30".into(), "15:00".into(), "open".into()));
+        assert_eq!(schedule(&conn, "seconds"), ("2030-01-05".into(), "08:30".into(), "15:00".into(), "open".into()));
+
+        let (date, _, _, status) = schedule(&conn, "garbage");
+        assert_eq!(date, "1970-01-01");
+        assert_eq!(status, "cancelled");
+        let note: String = conn.query_row(
+            "SELECT special_instructions FROM substitute_requests WHERE id = 'garbage'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert!(note.contains("tomorrow"));
+    }
+
+    #[test]
+    fn test_repoints_assigned_substitute_at_users() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&include_str!("schema.sql").replace(
+            "FOREIGN KEY (assigned_substitute_id) REFERENCES users(id)",
+            "FOREIGN KEY (assigned_substitute_id) REFERENCES teachers(id)",
+        )).unwrap();
+        conn.execute_batch(
+            "INSERT INTO organizations (id, name) VALUES ('org-1', 'School');
+             INSERT INTO classes (id, name, organization_id) VALUES ('class-1', 'Math', 'org-1');
+             INSERT INTO users (id, username, password_hash, email, first_name, last_name, role)
+                 VALUES ('user-1', 'sub', 'hashed_sub', 's@example.com', 'S', 'S', 'substitute');
+             INSERT INTO teachers (id, user_id) VALUES ('teacher-1', 'user-1');
+             INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, status, assigned_substitute_id)
+                 VALUES ('filled', 'class-1', 'user-1', '2030-01-15', '08:00', '15:00', 'filled', 'teacher-1');"
+        ).unwrap();
+
+        run(&conn).unwrap();
+
+        let assigned: String = conn.query_row(
+            "SELECT assigned_substitute_id FROM substitute_requests WHERE id = 'filled'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(assigned, "user-1");
+
+        // User ids are accepted now, teacher ids are not
+        conn.execute("UPDATE substitute_requests SET assigned_substitute_id = 'user-1' WHERE id = 'filled'", []).unwrap();
+        assert!(conn.execute("UPDATE substitute_requests SET assigned_substitute_id = 'teacher-1' WHERE id = 'filled'", []).is_err());
+        let violations: i64 = conn.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0)).unwrap();
+        assert_eq!(violations, 0);
+    }
+
+    #[test]
+    fn test_allows_attendance_statuses() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&include_str!("schema.sql").replace(
+            "CHECK (status IN ('open', 'filled', 'in_progress', 'completed', 'no_show', 'cancelled'))",
+            "CHECK (status IN ('open', 'filled', 'cancelled'))",
+        )).unwrap();
+        conn.execute_batch(
+            "INSERT INTO organizations (id, name) VALUES ('org-1', 'School');
+             INSERT INTO classes (id, name, organization_id) VALUES ('class-1', 'Math', 'org-1');
+             INSERT INTO users (id, username, password_hash, email, first_name, last_name, role)
+                 VALUES ('user-1', 'manager', 'hashed_manager', 'm@example.com', 'M', 'M', 'org_manager');"
+        ).unwrap();
+        insert_request(&conn, "r1", "2030-01-15", "08:00", "15:00");
+        let set_status = |status: &str| {
+            conn.execute("UPDATE substitute_requests SET status = ?1 WHERE id = 'r1'", [status])
+        };
+        assert!(set_status("no_show").is_err());
+
+        run(&conn).unwrap();
+
+        for status in ["in_progress", "completed", "no_show"] {
+            set_status(status).unwrap();
+        }
+        assert!(set_status("unknown").is_err());
+    }
+
+    #[test]
+    fn test_adopts_class_vocabulary() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&include_str!("schema.sql").replacen(
+            "'subject_endorsement', 'certification'))",
+            "'subject_endorsement'))",
+            1,
+        )).unwrap();
+        conn.execute_batch(
+            "INSERT INTO organizations (id, name) VALUES ('org-1', 'School');
+             INSERT INTO classes (id, name, organization_id, subject, grade_level)
+                 VALUES ('class-1', 'Robotics', 'org-1', 'Robotics', '5th Grade');
+             INSERT INTO users (id, username, password_hash, email, first_name, last_name, role)
+                 VALUES ('user-1', 'sub', 'hashed_sub', 's@example.com', 'S', 'S', 'substitute');"
+        ).unwrap();
+        let insert_certification = || {
+            conn.execute(
+                "INSERT INTO substitute_credentials (id, substitute_id, credential_type, issued_on)
+                 VALUES ('c1', 'user-1', 'certification', '2030-01-01')",
+                [],
+            )
+        };
+        assert!(insert_certification().is_err());
+
+        run(&conn).unwrap();
+
+        insert_certification().unwrap();
+        let subjects: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM class_vocabulary WHERE kind = 'subject' AND name IN ('Robotics', 'Mathematics')",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(subjects, 2);
+    }
+
+    #[test]
+    fn test_repoints_responses_at_users() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&include_str!("schema.sql").replace(
+            "    FOREIGN KEY (substitute_id) REFERENCES users(id),\n    UNIQUE(request_id, substitute_id)",
+            "    FOREIGN KEY (substitute_id) REFERENCES teachers(id),\n    UNIQUE(request_id, substitute_id)",
+        )).unwrap();
+        conn.execute_batch(
+            "INSERT INTO organizations (id, name) VALUES ('org-1', 'School');
+             INSERT INTO classes (id, name, organization_id) VALUES ('class-1', 'Math', 'org-1');
+             INSERT INTO users (id, username, password_hash, email, first_name, last_name, role)
+                 VALUES ('user-1', 'sub', 'hashed_sub', 's@example.com', 'S', 'S', 'substitute');
+             INSERT INTO teachers (id, user_id) VALUES ('teacher-1', 'user-1');
+             INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, status)
+                 VALUES ('r1', 'class-1', 'user-1', '2030-01-15', '08:00', '15:00', 'open');
+             INSERT INTO substitute_responses (id, request_id, substitute_id, response)
+                 VALUES ('resp-1', 'r1', 'teacher-1', 'declined');"
+        ).unwrap();
+
+        run(&conn).unwrap();
+
+        let substitute_id: String = conn.query_row(
+            "SELECT substitute_id FROM substitute_responses WHERE id = 'resp-1'",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(substitute_id, "user-1");
+        let violations: i64 = conn.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| row.get(0)).unwrap();
+        assert_eq!(violations, 0);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod models;
 pub mod connection;
+pub mod migrations;
+pub mod timestamp;
 
 use rusqlite::{Connection, Result};
 use std::path::Path;
@@ -18,6 +20,7 @@ impl Database {
 
     pub fn initialize_schema(&self) -> Result<()> {
         self.connection.execute_batch(include_str!("schema.sql"))?;
+        migrations::run(&self.connection)?;
         Ok(())
     }
 }
\ No newline at end of file
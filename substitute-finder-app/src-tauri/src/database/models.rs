@@ -1,5 +1,43 @@
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use std::collections::BTreeMap;
+
+/// Serde adapter that writes `NaiveTime` as `HH:MM`, matching the database and
+/// the `<input type="time">` values the UI sends.
+pub mod hhmm {
+    use crate::database::timestamp;
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&timestamp::format_time(time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        timestamp::parse_time(&value).map_err(serde::de::Error::custom)
+    }
+
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(time: &Option<NaiveTime>, serializer: S) -> Result<S::Ok, S::Error> {
+            match time {
+                Some(time) => super::serialize(time, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveTime>, D::Error> {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(value) if !value.is_empty() => {
+                    timestamp::parse_time(&value).map(Some).map_err(serde::de::Error::custom)
+                }
+                _ => Ok(None),
+            }
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Organization {
@@ -9,6 +47,11 @@ pub struct Organization {
     pub description: Option<String>,
     pub contact_email: Option<String>,
     pub contact_phone: Option<String>,
+    pub address: Option<String>,
+    #[serde(default, with = "hhmm::option")]
+    pub school_start_time: Option<NaiveTime>,
+    #[serde(default, with = "hhmm::option")]
+    pub school_end_time: Option<NaiveTime>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -17,6 +60,8 @@ pub struct Organization {
 pub struct User {
     pub id: String,
     pub username: String,
+    /// Never serialized, so it can't reach the webview or change events.
+    #[serde(skip_serializing, default)]
     pub password_hash: String,
     pub email: String,
     pub first_name: String,
@@ -25,104 +70,1078 @@ pub struct User {
     pub organization_id: Option<String>,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum UserRole {
+    Admin,
+    OrgManager,
+    Substitute,
+}
+
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserRole::Admin => write!(f, "admin"),
+            UserRole::OrgManager => write!(f, "org_manager"),
+            UserRole::Substitute => write!(f, "substitute"),
+        }
+    }
+}
+
+impl std::str::FromStr for UserRole {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "admin" => Ok(UserRole::Admin),
+            "org_manager" => Ok(UserRole::OrgManager),
+            "substitute" => Ok(UserRole::Substitute),
+            _ => Err(anyhow::anyhow!("Invalid user role: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Class {
+    pub id: String,
+    pub name: String,
+    pub organization_id: String,
+    pub subject: Option<String>,
+    pub grade_level: Option<String>,
+    pub room_number: Option<String>,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Teacher {
+    pub id: String,
+    pub user_id: String,
+    pub subjects: Vec<String>,
+    pub availability: serde_json::Value,
+    pub hourly_rate: Option<f64>,
+    pub qualifications: Vec<String>,
+    pub notes: Option<String>,
+    pub is_available: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegularTeacher {
+    pub id: String,
+    pub user_id: String,
+    pub organization_id: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeacherClassAssignment {
+    pub id: String,
+    pub teacher_id: String,
+    pub class_id: String,
+    pub is_primary: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A named set of periods. Periods are matched across schedules by name, so a
+/// class in "Period 3" follows whichever schedule is active on the day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BellSchedule {
+    pub id: String,
+    pub organization_id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub periods: Vec<Period>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Period {
+    pub id: String,
+    pub schedule_id: String,
+    pub name: String,
+    pub position: i32,
+    #[serde(with = "hhmm")]
+    pub start_time: NaiveTime,
+    #[serde(with = "hhmm")]
+    pub end_time: NaiveTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub id: String,
+    pub organization_id: String,
+    pub event_type: CalendarEventType,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    #[serde(default, with = "hhmm::option")]
+    pub dismissal_time: Option<NaiveTime>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CalendarEventType {
+    Holiday,
+    Closure,
+    EarlyRelease,
+    Term,
+}
+
+impl CalendarEventType {
+    /// Whether school is shut on the days the event covers.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, CalendarEventType::Holiday | CalendarEventType::Closure)
+    }
+}
+
+impl std::fmt::Display for CalendarEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalendarEventType::Holiday => write!(f, "holiday"),
+            CalendarEventType::Closure => write!(f, "closure"),
+            CalendarEventType::EarlyRelease => write!(f, "early_release"),
+            CalendarEventType::Term => write!(f, "term"),
+        }
+    }
+}
+
+impl std::str::FromStr for CalendarEventType {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "holiday" => Ok(CalendarEventType::Holiday),
+            "closure" => Ok(CalendarEventType::Closure),
+            "early_release" => Ok(CalendarEventType::EarlyRelease),
+            "term" => Ok(CalendarEventType::Term),
+            _ => Err(anyhow::anyhow!("Invalid calendar event type: {}", s)),
+        }
+    }
+}
+
+/// Everything on an organization's effective calendar for one date, so the
+/// frontend can warn before a request is submitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub is_closed: bool,
+    pub events: Vec<CalendarEvent>,
+}
+
+/// Result of declaring an emergency closure.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyClosure {
+    pub event: CalendarEvent,
+    pub cancelled_requests: Vec<SubstituteRequest>,
+}
+
+/// A stretch of days a substitute can't work, optionally only between
+/// `start_time` and `end_time` on each of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstituteUnavailability {
+    pub id: String,
+    pub substitute_id: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    #[serde(default, with = "hhmm::option")]
+    pub start_time: Option<NaiveTime>,
+    #[serde(default, with = "hhmm::option")]
+    pub end_time: Option<NaiveTime>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A substitute an organization (and everything below it) or a regular
+/// teacher wants offered first, or never wants sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstitutePreference {
+    pub id: String,
+    pub substitute_id: String,
+    pub organization_id: Option<String>,
+    pub teacher_id: Option<String>,
+    pub preference: PreferenceType,
+    pub reason: Option<String>,
+    pub effective_from: NaiveDate,
+    pub effective_until: Option<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SubstitutePreference {
+    pub fn is_effective_on(&self, date: NaiveDate) -> bool {
+        self.effective_from <= date && self.effective_until.is_none_or(|until| date <= until)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PreferenceType {
+    Preferred,
+    Blocked,
+}
+
+impl std::fmt::Display for PreferenceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreferenceType::Preferred => write!(f, "preferred"),
+            PreferenceType::Blocked => write!(f, "blocked"),
+        }
+    }
+}
+
+impl std::str::FromStr for PreferenceType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "preferred" => Ok(PreferenceType::Preferred),
+            "blocked" => Ok(PreferenceType::Blocked),
+            _ => Err(anyhow::anyhow!("Invalid preference type: {}", s)),
+        }
+    }
+}
+
+/// Substitutes to offer a request to, in the order they should hear about it.
+/// Blocked, unavailable and uncredentialed substitutes appear in neither tier.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OfferTiers {
+    pub preferred: Vec<String>,
+    pub others: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CredentialType {
+    TeachingLicense,
+    BackgroundCheck,
+    Cpr,
+    SubjectEndorsement,
+    /// Any other certification, named in the description.
+    Certification,
+}
+
+impl CredentialType {
+    /// How the credential is named in messages.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CredentialType::TeachingLicense => "teaching license",
+            CredentialType::BackgroundCheck => "background check",
+            CredentialType::Cpr => "CPR certification",
+            CredentialType::SubjectEndorsement => "subject endorsement",
+            CredentialType::Certification => "certification",
+        }
+    }
+}
+
+impl std::fmt::Display for CredentialType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CredentialType::TeachingLicense => write!(f, "teaching_license"),
+            CredentialType::BackgroundCheck => write!(f, "background_check"),
+            CredentialType::Cpr => write!(f, "cpr"),
+            CredentialType::SubjectEndorsement => write!(f, "subject_endorsement"),
+            CredentialType::Certification => write!(f, "certification"),
+        }
+    }
+}
+
+impl std::str::FromStr for CredentialType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "teaching_license" => Ok(CredentialType::TeachingLicense),
+            "background_check" => Ok(CredentialType::BackgroundCheck),
+            "cpr" => Ok(CredentialType::Cpr),
+            "subject_endorsement" => Ok(CredentialType::SubjectEndorsement),
+            "certification" => Ok(CredentialType::Certification),
+            _ => Err(anyhow::anyhow!("Invalid credential type: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationStatus {
+    Pending,
+    Verified,
+    Rejected,
+}
+
+impl std::fmt::Display for VerificationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerificationStatus::Pending => write!(f, "pending"),
+            VerificationStatus::Verified => write!(f, "verified"),
+            VerificationStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+impl std::str::FromStr for VerificationStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(VerificationStatus::Pending),
+            "verified" => Ok(VerificationStatus::Verified),
+            "rejected" => Ok(VerificationStatus::Rejected),
+            _ => Err(anyhow::anyhow!("Invalid verification status: {}", s)),
+        }
+    }
+}
+
+/// A license, check or certification a substitute holds. `description`
+/// carries the endorsed subject or license number.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstituteCredential {
+    pub id: String,
+    pub substitute_id: String,
+    pub credential_type: CredentialType,
+    pub description: Option<String>,
+    pub issued_on: NaiveDate,
+    /// `None` for credentials that don't expire.
+    pub expires_on: Option<NaiveDate>,
+    pub verification_status: VerificationStatus,
+    pub verified_by: Option<String>,
+    pub verified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SubstituteCredential {
+    /// Verified, issued and not yet expired on `date`.
+    pub fn is_valid_on(&self, date: NaiveDate) -> bool {
+        self.verification_status == VerificationStatus::Verified
+            && self.issued_on <= date
+            && self.expires_on.is_none_or(|expires_on| expires_on >= date)
+    }
+}
+
+/// A verified credential coming up for renewal, with who holds it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiringCredential {
+    pub credential: SubstituteCredential,
+    pub substitute_name: String,
+    pub substitute_email: String,
+    pub days_remaining: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VocabularyKind {
+    Subject,
+    GradeBand,
+}
+
+impl std::fmt::Display for VocabularyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VocabularyKind::Subject => write!(f, "subject"),
+            VocabularyKind::GradeBand => write!(f, "grade_band"),
+        }
+    }
+}
+
+impl std::str::FromStr for VocabularyKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "subject" => Ok(VocabularyKind::Subject),
+            "grade_band" => Ok(VocabularyKind::GradeBand),
+            _ => Err(anyhow::anyhow!("Invalid vocabulary kind: {}", s)),
+        }
+    }
+}
+
+/// An allowed value for a class's `subject` or `grade_level`. Inactive terms
+/// can't be given to new classes but stay valid on the classes that have them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VocabularyTerm {
+    pub kind: VocabularyKind,
+    pub name: String,
+    pub sort_order: i64,
+    pub is_active: bool,
+}
+
+/// Something a substitute needs to cover a class: either a current
+/// credential of a type, optionally with a matching description, or a
+/// subject listed on their profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassRequirement {
+    pub id: String,
+    pub class_id: String,
+    pub credential_type: Option<CredentialType>,
+    pub credential_description: Option<String>,
+    pub subject: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ClassRequirement {
+    /// How the requirement is named in messages.
+    pub fn describe(&self) -> String {
+        match (&self.credential_type, &self.credential_description, &self.subject) {
+            (Some(credential_type), Some(description), _) => {
+                format!("{} ({})", credential_type.label(), description)
+            }
+            (Some(credential_type), None, _) => credential_type.label().to_string(),
+            (None, _, Some(subject)) => format!("{} as a subject", subject),
+            (None, _, None) => "nothing".to_string(),
+        }
+    }
+}
+
+/// An admin's sign-off to assign a substitute who doesn't meet a class's
+/// requirements, with the requirements they were missing at the time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequirementOverride {
+    pub id: String,
+    pub request_id: String,
+    pub substitute_id: String,
+    pub approved_by: String,
+    pub justification: String,
+    pub unmet_requirements: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Actual arrival and departure of the assigned substitute.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignmentAttendance {
+    pub request_id: String,
+    pub substitute_id: String,
+    pub check_in_at: Option<DateTime<Utc>>,
+    pub check_out_at: Option<DateTime<Utc>>,
+    pub flags: Vec<AttendanceFlag>,
+    pub override_reason: Option<String>,
+    pub overridden_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AssignmentAttendance {
+    /// Time between check-in and check-out, once both are recorded.
+    pub fn time_worked(&self) -> Option<chrono::Duration> {
+        Some(self.check_out_at? - self.check_in_at?)
+    }
+}
+
+/// Ways the recorded times differ from the request's schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttendanceFlag {
+    LateCheckIn,
+    EarlyCheckOut,
+    /// Completed automatically after the substitute never checked out.
+    MissingCheckOut,
+}
+
+/// How an organization pays substitutes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayPolicy {
+    pub organization_id: String,
+    /// Pay checked-in hours when both check-in and check-out are recorded,
+    /// otherwise the scheduled hours.
+    pub use_actual_hours: bool,
+    pub rounding_minutes: u32,
+    pub rounding_mode: RoundingMode,
+    pub half_day_rate: Option<f64>,
+    pub half_day_max_hours: f64,
+    pub full_day_rate: Option<f64>,
+    pub long_term_after_days: Option<u32>,
+    pub long_term_multiplier: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PayPolicy {
+    /// Hourly pay on actual hours to the nearest quarter hour, used where no
+    /// organization up the tree has a policy.
+    pub fn default_for(organization_id: &str) -> Self {
+        PayPolicy {
+            organization_id: organization_id.to_string(),
+            use_actual_hours: true,
+            rounding_minutes: 15,
+            rounding_mode: RoundingMode::Nearest,
+            half_day_rate: None,
+            half_day_max_hours: 4.0,
+            full_day_rate: None,
+            long_term_after_days: None,
+            long_term_multiplier: 1.0,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundingMode {
+    Nearest,
+    Up,
+    Down,
+}
+
+impl std::fmt::Display for RoundingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RoundingMode::Nearest => write!(f, "nearest"),
+            RoundingMode::Up => write!(f, "up"),
+            RoundingMode::Down => write!(f, "down"),
+        }
+    }
+}
+
+impl std::str::FromStr for RoundingMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(RoundingMode::Nearest),
+            "up" => Ok(RoundingMode::Up),
+            "down" => Ok(RoundingMode::Down),
+            _ => Err(anyhow::anyhow!("Invalid rounding mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayBasis {
+    Hourly,
+    HalfDay,
+    FullDay,
+}
+
+impl std::fmt::Display for PayBasis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PayBasis::Hourly => write!(f, "hourly"),
+            PayBasis::HalfDay => write!(f, "half_day"),
+            PayBasis::FullDay => write!(f, "full_day"),
+        }
+    }
+}
+
+/// Pay for one completed assignment. `rate` and `amount` are `None` when it's
+/// paid hourly and the substitute has no hourly rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimesheetEntry {
+    pub request_id: String,
+    pub date_needed: NaiveDate,
+    pub organization_id: String,
+    pub organization_name: String,
+    pub class_id: String,
+    pub class_name: String,
+    pub scheduled_hours: f64,
+    pub actual_hours: Option<f64>,
+    pub paid_hours: f64,
+    pub basis: PayBasis,
+    pub rate: Option<f64>,
+    pub multiplier: f64,
+    pub amount: Option<f64>,
+}
+
+/// A substitute's completed assignments in one pay period.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Timesheet {
+    pub substitute_id: String,
+    pub substitute_name: String,
+    pub substitute_email: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub entries: Vec<TimesheetEntry>,
+    pub total_hours: f64,
+    pub total_amount: f64,
+}
+
+/// What an organization plans to spend on substitutes in a fiscal period.
+/// Spending at organizations below it counts against it too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstituteBudget {
+    pub id: String,
+    pub organization_id: String,
+    pub name: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub amount: f64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A budget against what has been spent and what is committed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetUsage {
+    pub budget: SubstituteBudget,
+    /// Paid for completed assignments, as on their timesheets.
+    pub actual_spend: f64,
+    /// Estimated cost of open, filled and in-progress requests.
+    pub committed_spend: f64,
+    pub projected_spend: f64,
+    pub remaining: f64,
+    /// Requests left out of `committed_spend` because no rate applies to them.
+    pub unpriced_requests: u32,
+    /// The same spending split by the organization it happened at.
+    pub by_organization: Vec<OrganizationSpend>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationSpend {
+    pub organization_id: String,
+    pub organization_name: String,
+    pub actual_spend: f64,
+    pub committed_spend: f64,
+}
+
+/// A budget a proposed request would take over its amount.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetWarning {
+    pub budget: SubstituteBudget,
+    pub projected_spend: f64,
+    pub request_cost: f64,
+    pub overage: f64,
+}
+
+/// Date range and organization subtree a report covers. Requests count by
+/// the date they are needed on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportFilter {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Limits the report to this organization and those below it.
+    pub organization_id: Option<String>,
+}
+
+/// How many requests were filled, and how quickly.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FillRateReport {
+    pub total_requests: u32,
+    /// Filled, in progress, completed or no-show: a substitute was found.
+    pub filled_requests: u32,
+    pub open_requests: u32,
+    pub cancelled_requests: u32,
+    /// Filled share of the requests that weren't cancelled.
+    pub fill_rate: Option<f64>,
+    /// Minutes from creation to the assigned substitute accepting.
+    pub median_minutes_to_fill: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReasonCount {
+    pub reason: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekdayCount {
+    /// 0 is Sunday, as in SQLite's `strftime('%w')`.
+    pub weekday: u32,
+    pub total: u32,
+    pub filled: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubstituteStats {
+    pub substitute_id: String,
+    pub substitute_name: String,
+    pub assignments: u32,
+    pub accepted: u32,
+    pub declined: u32,
+    /// Declined share of the substitute's responses.
+    pub decline_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub filter: ReportFilter,
+    pub fill_rate: FillRateReport,
+    /// Requests still open, by the reason given for the absence.
+    pub unfilled_by_reason: Vec<ReasonCount>,
+    pub by_weekday: Vec<WeekdayCount>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportDataset {
+    Requests,
+    /// Requests a substitute was found for, one row per assignment.
+    Assignments,
+    Users,
+    Classes,
+    NotificationLogs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+/// Narrows an export. Each dataset filters on its own date: the day needed
+/// for requests and assignments, the day sent for notification logs and the
+/// day created for users and classes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Limits the export to this organization and those below it.
+    pub organization_id: Option<String>,
+    /// Request status, or delivery status for notification logs.
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportRequest {
+    pub dataset: ExportDataset,
+    pub format: ExportFormat,
+    /// File to write, replacing any that is there.
+    pub path: String,
+    /// Column keys in the order wanted; all of the dataset's when omitted.
+    pub columns: Option<Vec<String>>,
+    #[serde(default)]
+    pub filter: ExportFilter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub columns: Vec<String>,
+    pub rows: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportKind {
+    Organizations,
+    Users,
+    /// Substitute users along with their subjects and hourly rate.
+    Substitutes,
+    Classes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRequest {
+    pub kind: ImportKind,
+    /// CSV file to read. Its first line names the columns.
+    pub path: String,
+    /// Field key to the file's column header, for columns not already named
+    /// after the field.
+    #[serde(default)]
+    pub mapping: BTreeMap<String, String>,
+    /// Validates every row and reports what would change without saving.
+    pub dry_run: bool,
+}
+
+/// A problem with one row. `line` is the row's line in the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRowError {
+    pub line: u64,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// What an import did or, for a dry run or one with errors, would have done.
+/// Nothing is saved unless every row is valid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub kind: ImportKind,
+    pub dry_run: bool,
+    pub applied: bool,
+    pub total_rows: u32,
+    pub created: u32,
+    /// Rows that matched an existing record by its natural key.
+    pub updated: u32,
+    pub errors: Vec<ImportRowError>,
+}
+
+/// A kind of record imported from a OneRoster bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RosterEntity {
+    Organization,
+    Class,
+    User,
+    /// The `regular_teachers` row of an imported teacher, keyed by the
+    /// teacher's user sourcedId.
+    Teacher,
+    Enrollment,
+}
+
+impl std::fmt::Display for RosterEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RosterEntity::Organization => write!(f, "organization"),
+            RosterEntity::Class => write!(f, "class"),
+            RosterEntity::User => write!(f, "user"),
+            RosterEntity::Teacher => write!(f, "teacher"),
+            RosterEntity::Enrollment => write!(f, "enrollment"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RosterImportRequest {
+    /// A OneRoster 1.1 CSV bundle: a zip file or the folder it unpacks to.
+    pub path: String,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RosterChanges {
+    pub created: u32,
+    pub updated: u32,
+    pub unchanged: u32,
+    pub removed: u32,
+}
+
+/// A problem with one line of one of the bundle's files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RosterIssue {
+    pub file: String,
+    pub line: u64,
+    pub message: String,
+}
+
+/// What a roster import changed or, for a dry run or one with errors, would
+/// have changed. Nothing is saved unless every row is valid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RosterImportReport {
+    pub dry_run: bool,
+    pub applied: bool,
+    pub organizations: RosterChanges,
+    pub classes: RosterChanges,
+    pub teachers: RosterChanges,
+    pub assignments: RosterChanges,
+    /// Rows for students and other roles the app doesn't track, and records
+    /// the bundle marks for deletion that aren't enrollments.
+    pub skipped: u32,
+    /// Values that were left out, such as subjects outside the vocabulary.
+    pub warnings: Vec<RosterIssue>,
+    pub errors: Vec<RosterIssue>,
+}
+
+/// Whose requests an iCalendar export or subscription covers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum CalendarFeed {
+    /// Requests assigned to this substitute's user id.
+    Substitute(String),
+    /// Requests for classes in this organization and the ones below it.
+    Organization(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarExportSummary {
+    pub path: String,
+    pub events: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarImportRequest {
+    /// Path of an `.ics` file, or an `http(s)://` or `webcal://` URL.
+    pub source: String,
+    /// User the imported absences are reported by.
+    pub reported_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarImportIssue {
+    /// UID of the event, when it has one.
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarImportReport {
+    pub total_events: u64,
+    pub created: u64,
+    pub updated: u64,
+    pub cancelled: u64,
+    pub unchanged: u64,
+    /// Events that aren't teacher absences or are already over.
+    pub skipped: Vec<CalendarImportIssue>,
+    pub errors: Vec<CalendarImportIssue>,
+}
+
+/// How a backup came to be, read from its file name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackupKind {
+    Manual,
+    Daily,
+    Weekly,
+    /// Taken automatically just before a restore replaced the database.
+    PreRestore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub kind: BackupKind,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreReport {
+    pub restored_from: String,
+    /// Schema version of the backup before it was brought up to date.
+    pub schema_version: i32,
+    /// Copy of the database as it was before the restore.
+    pub safety_backup: BackupInfo,
+}
+
+
+/// substitutes rate the assignment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignmentFeedback {
+    pub id: String,
+    pub request_id: String,
+    pub reviewer_id: String,
+    pub reviewer_role: ReviewerRole,
+    pub rating: u8,
+    pub comments: Option<String>,
+    pub flags: Vec<String>,
+    pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum UserRole {
-    Admin,
-    OrgManager,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewerRole {
+    Requester,
     Substitute,
 }
 
-impl std::fmt::Display for UserRole {
+impl std::fmt::Display for ReviewerRole {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            UserRole::Admin => write!(f, "admin"),
-            UserRole::OrgManager => write!(f, "org_manager"),
-            UserRole::Substitute => write!(f, "substitute"),
+            ReviewerRole::Requester => write!(f, "requester"),
+            ReviewerRole::Substitute => write!(f, "substitute"),
         }
     }
 }
 
-impl std::str::FromStr for UserRole {
+impl std::str::FromStr for ReviewerRole {
     type Err = anyhow::Error;
-    
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "admin" => Ok(UserRole::Admin),
-            "org_manager" => Ok(UserRole::OrgManager),
-            "substitute" => Ok(UserRole::Substitute),
-            _ => Err(anyhow::anyhow!("Invalid user role: {}", s)),
+            "requester" => Ok(ReviewerRole::Requester),
+            "substitute" => Ok(ReviewerRole::Substitute),
+            _ => Err(anyhow::anyhow!("Invalid reviewer role: {}", s)),
         }
     }
 }
 
+/// What requesters have said about a substitute, across every assignment
+/// they've been rated on. `average_rating` is `None` until the first rating.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Class {
-    pub id: String,
-    pub name: String,
-    pub organization_id: String,
-    pub subject: Option<String>,
-    pub grade_level: Option<String>,
-    pub room_number: Option<String>,
-    pub description: Option<String>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+pub struct SubstituteRating {
+    pub substitute_id: String,
+    pub average_rating: Option<f64>,
+    pub rating_count: u32,
+    pub flag_counts: BTreeMap<String, u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Teacher {
+pub struct SubstituteRequest {
     pub id: String,
-    pub user_id: String,
-    pub subjects: Vec<String>,
-    pub availability: serde_json::Value,
-    pub hourly_rate: Option<f64>,
-    pub qualifications: Vec<String>,
-    pub notes: Option<String>,
-    pub is_available: bool,
+    pub class_id: String,
+    pub requested_by: String,
+    pub date_needed: NaiveDate,
+    #[serde(with = "hhmm")]
+    pub start_time: NaiveTime,
+    #[serde(with = "hhmm")]
+    pub end_time: NaiveTime,
+    pub reason: Option<String>,
+    pub special_instructions: Option<String>,
+    pub status: RequestStatus,
+    pub assigned_substitute_id: Option<String>,
+    #[serde(default)]
+    pub absence_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// A block of days a class needs covering. Expands into one child
+/// `SubstituteRequest` per school day.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RegularTeacher {
+pub struct Absence {
     pub id: String,
-    pub user_id: String,
-    pub organization_id: String,
+    pub class_id: String,
+    pub requested_by: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    #[serde(with = "hhmm")]
+    pub start_time: NaiveTime,
+    #[serde(with = "hhmm")]
+    pub end_time: NaiveTime,
+    pub recurrence_rule: Option<String>,
+    pub reason: Option<String>,
+    pub special_instructions: Option<String>,
+    pub status: AbsenceStatus,
+    #[serde(default)]
+    pub teacher_absence_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AbsenceStatus {
+    Active,
+    Cancelled,
+}
+
+impl std::fmt::Display for AbsenceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AbsenceStatus::Active => write!(f, "active"),
+            AbsenceStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::str::FromStr for AbsenceStatus {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(AbsenceStatus::Active),
+            "cancelled" => Ok(AbsenceStatus::Cancelled),
+            _ => Err(anyhow::anyhow!("Invalid absence status: {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TeacherClassAssignment {
-    pub id: String,
-    pub teacher_id: String,
-    pub class_id: String,
-    pub is_primary: bool,
-    pub created_at: DateTime<Utc>,
+pub struct AbsenceWithRequests {
+    pub absence: Absence,
+    pub requests: Vec<SubstituteRequest>,
 }
 
+/// A regular teacher's absence. Each class they teach gets its own
+/// [`Absence`] linked back through `teacher_absence_id`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SubstituteRequest {
+pub struct TeacherAbsence {
     pub id: String,
-    pub class_id: String,
-    pub requested_by: String,
-    pub date_needed: String,
-    pub start_time: String,
-    pub end_time: String,
+    pub teacher_id: String,
+    pub reported_by: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
     pub reason: Option<String>,
-    pub special_instructions: Option<String>,
-    pub status: RequestStatus,
-    pub assigned_substitute_id: Option<String>,
+    pub status: AbsenceStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeacherAbsenceWithRequests {
+    pub teacher_absence: TeacherAbsence,
+    pub requests: Vec<SubstituteRequest>,
+}
+
+/// `Filled` moves to `InProgress` on check-in and `Completed` on check-out,
+/// or to `NoShow` when nobody checks in.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RequestStatus {
     Open,
     Filled,
+    InProgress,
+    Completed,
+    NoShow,
     Cancelled,
 }
 
@@ -131,6 +1150,9 @@ impl std::fmt::Display for RequestStatus {
         match self {
             RequestStatus::Open => write!(f, "open"),
             RequestStatus::Filled => write!(f, "filled"),
+            RequestStatus::InProgress => write!(f, "in_progress"),
+            RequestStatus::Completed => write!(f, "completed"),
+            RequestStatus::NoShow => write!(f, "no_show"),
             RequestStatus::Cancelled => write!(f, "cancelled"),
         }
     }
@@ -143,6 +1165,9 @@ impl std::str::FromStr for RequestStatus {
         match s {
             "open" => Ok(RequestStatus::Open),
             "filled" => Ok(RequestStatus::Filled),
+            "in_progress" => Ok(RequestStatus::InProgress),
+            "completed" => Ok(RequestStatus::Completed),
+            "no_show" => Ok(RequestStatus::NoShow),
             "cancelled" => Ok(RequestStatus::Cancelled),
             _ => Err(anyhow::anyhow!("Invalid request status: {}", s)),
         }
@@ -219,6 +1244,109 @@ pub struct Setting {
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    /// Only sent back once, in [`CreatedWebhookSubscription`].
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    pub event_types: Vec<WebhookEventType>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A new subscription with its signing secret, returned only on creation so
+/// the admin can configure the receiving end.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedWebhookSubscription {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "request.created")]
+    RequestCreated,
+    #[serde(rename = "request.filled")]
+    RequestFilled,
+    #[serde(rename = "request.cancelled")]
+    RequestCancelled,
+    #[serde(rename = "user.created")]
+    UserCreated,
+}
+
+impl std::fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebhookEventType::RequestCreated => write!(f, "request.created"),
+            WebhookEventType::RequestFilled => write!(f, "request.filled"),
+            WebhookEventType::RequestCancelled => write!(f, "request.cancelled"),
+            WebhookEventType::UserCreated => write!(f, "user.created"),
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEventType {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "request.created" => Ok(WebhookEventType::RequestCreated),
+            "request.filled" => Ok(WebhookEventType::RequestFilled),
+            "request.cancelled" => Ok(WebhookEventType::RequestCancelled),
+            "user.created" => Ok(WebhookEventType::UserCreated),
+            _ => Err(anyhow::anyhow!("Invalid webhook event type: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub event_type: WebhookEventType,
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub response_status: Option<u16>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl std::fmt::Display for DeliveryStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryStatus::Pending => write!(f, "pending"),
+            DeliveryStatus::Delivered => write!(f, "delivered"),
+            DeliveryStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl std::str::FromStr for DeliveryStatus {
+    type Err = anyhow::Error;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "delivered" => Ok(DeliveryStatus::Delivered),
+            "failed" => Ok(DeliveryStatus::Failed),
+            _ => Err(anyhow::anyhow!("Invalid delivery status: {}", s)),
+        }
+    }
+}
+
 // DTOs for API requests
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateOrganizationRequest {
@@ -227,6 +1355,12 @@ pub struct CreateOrganizationRequest {
     pub description: Option<String>,
     pub contact_email: Option<String>,
     pub contact_phone: Option<String>,
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default, with = "hhmm::option")]
+    pub school_start_time: Option<NaiveTime>,
+    #[serde(default, with = "hhmm::option")]
+    pub school_end_time: Option<NaiveTime>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -250,16 +1384,238 @@ pub struct CreateClassRequest {
     pub description: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCalendarEventRequest {
+    pub organization_id: String,
+    pub event_type: CalendarEventType,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    #[serde(default, with = "hhmm::option")]
+    pub dismissal_time: Option<NaiveTime>,
+}
+
+/// `end_date` defaults to `start_date`. Give both times or neither.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUnavailabilityRequest {
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    #[serde(default, with = "hhmm::option")]
+    pub start_time: Option<NaiveTime>,
+    #[serde(default, with = "hhmm::option")]
+    pub end_time: Option<NaiveTime>,
+    pub reason: Option<String>,
+}
+
+/// Set exactly one of `organization_id` and `teacher_id`. `effective_from`
+/// defaults to today.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSubstitutePreferenceRequest {
+    pub substitute_id: String,
+    pub organization_id: Option<String>,
+    pub teacher_id: Option<String>,
+    pub preference: PreferenceType,
+    pub reason: Option<String>,
+    pub effective_from: Option<NaiveDate>,
+    pub effective_until: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBudgetRequest {
+    pub organization_id: String,
+    pub name: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateBudgetRequest {
+    pub name: Option<String>,
+    pub amount: Option<f64>,
+}
+
+/// `sort_order` defaults to after the existing terms and `is_active` to true.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveVocabularyTermRequest {
+    pub kind: VocabularyKind,
+    pub name: String,
+    pub sort_order: Option<i64>,
+    pub is_active: Option<bool>,
+}
+
+/// Give `credential_type` (and optionally `credential_description`) or
+/// `subject`, not both.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateClassRequirementRequest {
+    pub class_id: String,
+    pub credential_type: Option<CredentialType>,
+    pub credential_description: Option<String>,
+    pub subject: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequirementOverrideRequest {
+    pub request_id: String,
+    pub substitute_id: String,
+    pub admin_id: String,
+    pub justification: String,
+}
+
+/// New credentials start out pending verification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCredentialRequest {
+    pub substitute_id: String,
+    pub credential_type: CredentialType,
+    pub description: Option<String>,
+    pub issued_on: NaiveDate,
+    pub expires_on: Option<NaiveDate>,
+}
+
+/// Omitted fields take the same defaults as [`PayPolicy::default_for`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPayPolicyRequest {
+    pub organization_id: String,
+    pub use_actual_hours: Option<bool>,
+    pub rounding_minutes: Option<u32>,
+    pub rounding_mode: Option<RoundingMode>,
+    pub half_day_rate: Option<f64>,
+    pub half_day_max_hours: Option<f64>,
+    pub full_day_rate: Option<f64>,
+    pub long_term_after_days: Option<u32>,
+    pub long_term_multiplier: Option<f64>,
+}
+
+/// A manager's correction of the recorded times. `reason` is required.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttendanceOverrideRequest {
+    pub request_id: String,
+    pub manager_id: String,
+    pub check_in_at: DateTime<Utc>,
+    pub check_out_at: Option<DateTime<Utc>>,
+    pub reason: String,
+}
+
+/// The reviewer must be the request's requester or its assigned substitute,
+/// which decides which side of the assignment they're rating.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateFeedbackRequest {
+    pub request_id: String,
+    pub reviewer_id: String,
+    pub rating: u8,
+    pub comments: Option<String>,
+    #[serde(default)]
+    pub flags: Vec<String>,
+}
+
+/// Periods are stored in the order given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateBellScheduleRequest {
+    pub organization_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub is_default: bool,
+    pub periods: Vec<CreatePeriodRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePeriodRequest {
+    pub name: String,
+    #[serde(with = "hhmm")]
+    pub start_time: NaiveTime,
+    #[serde(with = "hhmm")]
+    pub end_time: NaiveTime,
+}
+
+/// A request whose times come from the bell schedule active on `date_needed`.
+/// With no `period_names`, every period the class meets in is covered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatePeriodSubstituteRequest {
+    pub class_id: String,
+    pub date_needed: NaiveDate,
+    #[serde(default)]
+    pub period_names: Vec<String>,
+    pub reason: Option<String>,
+    pub special_instructions: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRegularTeacherRequest {
+    pub user_id: String,
+    pub organization_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTeacherClassAssignmentRequest {
+    pub teacher_id: String,
+    pub class_id: String,
+    #[serde(default)]
+    pub is_primary: bool,
+}
+
+/// Times default to the school hours of each class's organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportAbsenceRequest {
+    pub teacher_id: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    #[serde(default, with = "hhmm::option")]
+    pub start_time: Option<NaiveTime>,
+    #[serde(default, with = "hhmm::option")]
+    pub end_time: Option<NaiveTime>,
+    pub reason: Option<String>,
+    pub special_instructions: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateSubstituteRequestRequest {
     pub class_id: String,
-    pub date_needed: String,
-    pub start_time: String,
-    pub end_time: String,
+    pub date_needed: NaiveDate,
+    #[serde(with = "hhmm")]
+    pub start_time: NaiveTime,
+    #[serde(with = "hhmm")]
+    pub end_time: NaiveTime,
+    pub reason: Option<String>,
+    pub special_instructions: Option<String>,
+}
+
+/// Either `end_date` (every school day in the range) or `recurrence_rule`
+/// (its `UNTIL` bounds the block) must be given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAbsenceRequest {
+    pub class_id: String,
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,
+    #[serde(with = "hhmm")]
+    pub start_time: NaiveTime,
+    #[serde(with = "hhmm")]
+    pub end_time: NaiveTime,
+    pub recurrence_rule: Option<String>,
+    /// Additional days to skip, such as closures not yet in any calendar.
+    #[serde(default)]
+    pub excluded_dates: Vec<NaiveDate>,
     pub reason: Option<String>,
     pub special_instructions: Option<String>,
 }
 
+/// Changes applied to an absence and to its children that haven't happened yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateAbsenceRequest {
+    #[serde(with = "hhmm")]
+    pub start_time: NaiveTime,
+    #[serde(with = "hhmm")]
+    pub end_time: NaiveTime,
+    pub reason: Option<String>,
+    pub special_instructions: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWebhookSubscriptionRequest {
+    pub url: String,
+    pub secret: String,
+    pub event_types: Vec<WebhookEventType>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -308,6 +1664,21 @@ mod tests {
         assert!(ResponseType::from_str("invalid").is_err());
     }
 
+    #[test]
+    fn test_webhook_event_type_round_trip() {
+        for event in [
+            WebhookEventType::RequestCreated,
+            WebhookEventType::RequestFilled,
+            WebhookEventType::RequestCancelled,
+            WebhookEventType::UserCreated,
+        ] {
+            let json = serde_json::to_string(&event).unwrap();
+            assert_eq!(json, format!("\"{}\"", event));
+            assert_eq!(WebhookEventType::from_str(&event.to_string()).unwrap(), event);
+        }
+        assert!(WebhookEventType::from_str("request.deleted").is_err());
+    }
+
     #[test]
     fn test_serde_serialization() {
         let user_role = UserRole::Admin;
@@ -324,6 +1695,9 @@ mod tests {
             description: Some("A test school".to_string()),
             contact_email: Some("test@example.com".to_string()),
             contact_phone: Some("123-456-7890".to_string()),
+            address: None,
+            school_start_time: None,
+            school_end_time: None,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -332,6 +1706,29 @@ mod tests {
         assert_eq!(deserialized.contact_email, Some("test@example.com".to_string()));
     }
 
+    #[test]
+    fn test_create_substitute_request_uses_iso_dates_and_hhmm_times() {
+        let json = r#"{
+            "class_id": "class-1",
+            "date_needed": "2030-01-15",
+            "start_time": "08:30",
+            "end_time": "15:00:00",
+            "reason": null,
+            "special_instructions": null
+        }"#;
+        let request: CreateSubstituteRequestRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.date_needed, NaiveDate::from_ymd_opt(2030, 1, 15).unwrap());
+        assert_eq!(request.end_time, NaiveTime::from_hms_opt(15, 0, 0).unwrap());
+
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["date_needed"], "2030-01-15");
+        assert_eq!(value["start_time"], "08:30");
+        assert_eq!(value["end_time"], "15:00");
+
+        let invalid = json.replace("2030-01-15", "tomorrow");
+        assert!(serde_json::from_str::<CreateSubstituteRequestRequest>(&invalid).is_err());
+    }
+
     #[test]
     fn test_create_user_request_serialization() {
         let request = CreateUserRequest {
@@ -349,4 +1746,49 @@ mod tests {
         assert_eq!(deserialized.username, "testuser");
         assert!(matches!(deserialized.role, UserRole::Admin));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_user_serialization_omits_password_hash() {
+        let user = User {
+            id: "user-1".to_string(),
+            username: "testuser".to_string(),
+            password_hash: "hashed_secret".to_string(),
+            email: "test@example.com".to_string(),
+            first_name: "Test".to_string(),
+            last_name: "User".to_string(),
+            role: UserRole::Substitute,
+            organization_id: None,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let value = serde_json::to_value(&user).unwrap();
+        assert!(value.get("password_hash").is_none());
+        assert_eq!(value["username"], "testuser");
+        let deserialized: User = serde_json::from_value(value).unwrap();
+        assert!(deserialized.password_hash.is_empty());
+    }
+
+    #[test]
+    fn test_webhook_secret_is_only_serialized_on_creation() {
+        let subscription = WebhookSubscription {
+            id: "hook-1".to_string(),
+            url: "https://example.com/hook".to_string(),
+            secret: "signing-secret".to_string(),
+            event_types: vec![WebhookEventType::RequestCreated],
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        assert!(serde_json::to_value(&subscription).unwrap().get("secret").is_none());
+        let created = serde_json::to_value(CreatedWebhookSubscription {
+            secret: subscription.secret.clone(),
+            subscription,
+        })
+        .unwrap();
+        assert_eq!(created["secret"], "signing-secret");
+        assert_eq!(created["url"], "https://example.com/hook");
+    }
+}
@@ -6,8 +6,11 @@ CREATE TABLE IF NOT EXISTS organizations (
     description TEXT,
     contact_email TEXT,
     contact_phone TEXT,
-    created_at TEXT NOT NULL DEFAULT (datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    address TEXT,
+    school_start_time TEXT, -- Time in HH:MM format
+    school_end_time TEXT, -- Time in HH:MM format
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     FOREIGN KEY (parent_organization_id) REFERENCES organizations(id)
 );
 
@@ -22,8 +25,8 @@ CREATE TABLE IF NOT EXISTS users (
     role TEXT NOT NULL CHECK (role IN ('admin', 'org_manager', 'substitute')),
     organization_id TEXT,
     is_active BOOLEAN NOT NULL DEFAULT true,
-    created_at TEXT NOT NULL DEFAULT (datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     FOREIGN KEY (organization_id) REFERENCES organizations(id)
 );
 
@@ -36,8 +39,8 @@ CREATE TABLE IF NOT EXISTS classes (
     grade_level TEXT,
     room_number TEXT,
     description TEXT,
-    created_at TEXT NOT NULL DEFAULT (datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     FOREIGN KEY (organization_id) REFERENCES organizations(id)
 );
 
@@ -51,8 +54,8 @@ CREATE TABLE IF NOT EXISTS teachers (
     qualifications TEXT, -- JSON array of qualifications
     notes TEXT,
     is_available BOOLEAN NOT NULL DEFAULT true,
-    created_at TEXT NOT NULL DEFAULT (datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     FOREIGN KEY (user_id) REFERENCES users(id)
 );
 
@@ -61,8 +64,8 @@ CREATE TABLE IF NOT EXISTS regular_teachers (
     id TEXT PRIMARY KEY,
     user_id TEXT NOT NULL,
     organization_id TEXT NOT NULL,
-    created_at TEXT NOT NULL DEFAULT (datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     FOREIGN KEY (user_id) REFERENCES users(id),
     FOREIGN KEY (organization_id) REFERENCES organizations(id)
 );
@@ -73,12 +76,168 @@ CREATE TABLE IF NOT EXISTS teacher_class_assignments (
     teacher_id TEXT NOT NULL,
     class_id TEXT NOT NULL,
     is_primary BOOLEAN NOT NULL DEFAULT false,
-    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     FOREIGN KEY (teacher_id) REFERENCES regular_teachers(id),
     FOREIGN KEY (class_id) REFERENCES classes(id),
     UNIQUE(teacher_id, class_id)
 );
 
+-- Records imported from a student information system, by OneRoster
+-- sourcedId, so re-importing a bundle updates them instead of duplicating
+CREATE TABLE IF NOT EXISTS roster_links (
+    entity TEXT NOT NULL CHECK (entity IN ('organization', 'class', 'user', 'teacher', 'enrollment')),
+    sourced_id TEXT NOT NULL,
+    local_id TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (entity, sourced_id)
+);
+
+-- Teacher absences imported from shared iCalendar feeds, by event UID, so
+-- re-importing a feed updates or cancels them instead of duplicating
+CREATE TABLE IF NOT EXISTS calendar_absence_links (
+    uid TEXT PRIMARY KEY,
+    teacher_absence_id TEXT NOT NULL,
+    fingerprint TEXT NOT NULL, -- Teacher, dates, times and text the absence was made from
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (teacher_absence_id) REFERENCES teacher_absences(id)
+);
+
+-- Named bell schedules (regular, early release, assembly...) per organization
+CREATE TABLE IF NOT EXISTS bell_schedules (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT false,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id),
+    UNIQUE(organization_id, name)
+);
+
+-- Ordered periods of a bell schedule
+CREATE TABLE IF NOT EXISTS bell_schedule_periods (
+    id TEXT PRIMARY KEY,
+    schedule_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    start_time TEXT NOT NULL, -- Time in HH:MM format
+    end_time TEXT NOT NULL, -- Time in HH:MM format
+    FOREIGN KEY (schedule_id) REFERENCES bell_schedules(id),
+    UNIQUE(schedule_id, name),
+    UNIQUE(schedule_id, position)
+);
+
+-- Dates that run on a schedule other than the organization's default
+CREATE TABLE IF NOT EXISTS bell_schedule_days (
+    organization_id TEXT NOT NULL,
+    date TEXT NOT NULL, -- ISO date string
+    schedule_id TEXT NOT NULL,
+    PRIMARY KEY (organization_id, date),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id),
+    FOREIGN KEY (schedule_id) REFERENCES bell_schedules(id)
+);
+
+-- Periods a class meets in, by period name so they apply to every schedule
+CREATE TABLE IF NOT EXISTS class_periods (
+    class_id TEXT NOT NULL,
+    period_name TEXT NOT NULL,
+    PRIMARY KEY (class_id, period_name),
+    FOREIGN KEY (class_id) REFERENCES classes(id)
+);
+
+-- Holidays, closures, early-release days and academic terms. Events apply to
+-- the organization and everything below it in the tree.
+CREATE TABLE IF NOT EXISTS calendar_events (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL,
+    event_type TEXT NOT NULL CHECK (event_type IN ('holiday', 'closure', 'early_release', 'term')),
+    name TEXT NOT NULL,
+    start_date TEXT NOT NULL, -- ISO date string
+    end_date TEXT NOT NULL, -- ISO date string
+    dismissal_time TEXT, -- Time in HH:MM format, early-release days only
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id)
+);
+
+-- Inherited calendar events an organization (and its children) opts out of
+CREATE TABLE IF NOT EXISTS calendar_event_exclusions (
+    organization_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    PRIMARY KEY (organization_id, event_id),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id),
+    FOREIGN KEY (event_id) REFERENCES calendar_events(id)
+);
+
+-- Days a substitute can't work. Times narrow each day of the range to part of
+-- the day; without them the whole day is blocked.
+CREATE TABLE IF NOT EXISTS substitute_unavailability (
+    id TEXT PRIMARY KEY,
+    substitute_id TEXT NOT NULL, -- user_id of the substitute
+    start_date TEXT NOT NULL, -- ISO date string
+    end_date TEXT NOT NULL, -- ISO date string
+    start_time TEXT, -- Time in HH:MM format
+    end_time TEXT, -- Time in HH:MM format
+    reason TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (substitute_id) REFERENCES users(id)
+);
+
+-- Substitutes an organization or a regular teacher wants offered first, or
+-- never wants sent. Exactly one of organization_id and teacher_id is set.
+CREATE TABLE IF NOT EXISTS substitute_preferences (
+    id TEXT PRIMARY KEY,
+    substitute_id TEXT NOT NULL, -- user_id of the substitute
+    organization_id TEXT,
+    teacher_id TEXT,
+    preference TEXT NOT NULL CHECK (preference IN ('preferred', 'blocked')),
+    reason TEXT,
+    effective_from TEXT NOT NULL, -- ISO date string
+    effective_until TEXT, -- ISO date string, open-ended when NULL
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (substitute_id) REFERENCES users(id),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id),
+    FOREIGN KEY (teacher_id) REFERENCES regular_teachers(id),
+    CHECK ((organization_id IS NULL) != (teacher_id IS NULL))
+);
+
+-- A regular teacher being away, covering every class they teach
+CREATE TABLE IF NOT EXISTS teacher_absences (
+    id TEXT PRIMARY KEY,
+    teacher_id TEXT NOT NULL,
+    reported_by TEXT NOT NULL,
+    start_date TEXT NOT NULL, -- ISO date string
+    end_date TEXT NOT NULL, -- ISO date string
+    reason TEXT,
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'cancelled')),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (teacher_id) REFERENCES regular_teachers(id),
+    FOREIGN KEY (reported_by) REFERENCES users(id)
+);
+
+-- Multi-day or recurring absences that expand into one substitute request per day
+CREATE TABLE IF NOT EXISTS absences (
+    id TEXT PRIMARY KEY,
+    class_id TEXT NOT NULL,
+    requested_by TEXT NOT NULL,
+    start_date TEXT NOT NULL, -- ISO date string
+    end_date TEXT NOT NULL, -- ISO date string
+    start_time TEXT NOT NULL, -- Time in HH:MM format
+    end_time TEXT NOT NULL, -- Time in HH:MM format
+    recurrence_rule TEXT, -- RRULE subset, e.g. FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20240630
+    reason TEXT,
+    special_instructions TEXT,
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'cancelled')),
+    teacher_absence_id TEXT, -- Set when reported for a teacher rather than a single class
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (class_id) REFERENCES classes(id),
+    FOREIGN KEY (requested_by) REFERENCES users(id),
+    FOREIGN KEY (teacher_absence_id) REFERENCES teacher_absences(id)
+);
+
 -- Substitute requests
 CREATE TABLE IF NOT EXISTS substitute_requests (
     id TEXT PRIMARY KEY,
@@ -89,13 +248,15 @@ CREATE TABLE IF NOT EXISTS substitute_requests (
     end_time TEXT NOT NULL, -- Time in HH:MM format
     reason TEXT,
     special_instructions TEXT,
-    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'filled', 'cancelled')),
-    assigned_substitute_id TEXT,
-    created_at TEXT NOT NULL DEFAULT (datetime('now')),
-    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'filled', 'in_progress', 'completed', 'no_show', 'cancelled')),
+    assigned_substitute_id TEXT, -- user_id of the assigned substitute
+    absence_id TEXT, -- Parent absence when part of a multi-day block
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     FOREIGN KEY (class_id) REFERENCES classes(id),
     FOREIGN KEY (requested_by) REFERENCES users(id),
-    FOREIGN KEY (assigned_substitute_id) REFERENCES teachers(id)
+    FOREIGN KEY (assigned_substitute_id) REFERENCES users(id),
+    FOREIGN KEY (absence_id) REFERENCES absences(id)
 );
 
 -- Substitute responses to requests
@@ -104,20 +265,135 @@ CREATE TABLE IF NOT EXISTS substitute_responses (
     request_id TEXT NOT NULL,
     substitute_id TEXT NOT NULL,
     response TEXT NOT NULL CHECK (response IN ('accepted', 'declined')),
-    response_time TEXT NOT NULL DEFAULT (datetime('now')),
+    response_time TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     notes TEXT,
     FOREIGN KEY (request_id) REFERENCES substitute_requests(id),
-    FOREIGN KEY (substitute_id) REFERENCES teachers(id),
+    FOREIGN KEY (substitute_id) REFERENCES users(id),
     UNIQUE(request_id, substitute_id)
 );
 
+-- When the assigned substitute actually arrived and left
+CREATE TABLE IF NOT EXISTS assignment_attendance (
+    request_id TEXT PRIMARY KEY,
+    substitute_id TEXT NOT NULL, -- user_id of the substitute
+    check_in_at TEXT,
+    check_out_at TEXT,
+    flags TEXT NOT NULL DEFAULT '[]', -- JSON array of discrepancy flags
+    override_reason TEXT, -- Set when a manager corrected the times
+    overridden_by TEXT,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (request_id) REFERENCES substitute_requests(id),
+    FOREIGN KEY (substitute_id) REFERENCES users(id),
+    FOREIGN KEY (overridden_by) REFERENCES users(id)
+);
+
+-- How an organization pays substitutes; organizations without a row inherit
+-- their nearest ancestor's
+CREATE TABLE IF NOT EXISTS pay_policies (
+    organization_id TEXT PRIMARY KEY,
+    use_actual_hours BOOLEAN NOT NULL DEFAULT true, -- Checked-in hours when recorded, else scheduled
+    rounding_minutes INTEGER NOT NULL DEFAULT 15,
+    rounding_mode TEXT NOT NULL DEFAULT 'nearest' CHECK (rounding_mode IN ('nearest', 'up', 'down')),
+    half_day_rate REAL, -- Flat pay for assignments up to half_day_max_hours
+    half_day_max_hours REAL NOT NULL DEFAULT 4,
+    full_day_rate REAL, -- Flat pay for longer assignments
+    long_term_after_days INTEGER, -- Days on one absence before the long-term multiplier applies
+    long_term_multiplier REAL NOT NULL DEFAULT 1,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id)
+);
+
+-- Licenses, checks and certifications substitutes hold
+CREATE TABLE IF NOT EXISTS substitute_credentials (
+    id TEXT PRIMARY KEY,
+    substitute_id TEXT NOT NULL,
+    credential_type TEXT NOT NULL CHECK (credential_type IN ('teaching_license', 'background_check', 'cpr', 'subject_endorsement', 'certification')),
+    description TEXT, -- Endorsed subject, license number or certification name
+    issued_on TEXT NOT NULL,
+    expires_on TEXT, -- NULL for credentials that don't expire
+    verification_status TEXT NOT NULL DEFAULT 'pending' CHECK (verification_status IN ('pending', 'verified', 'rejected')),
+    verified_by TEXT,
+    verified_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (substitute_id) REFERENCES users(id),
+    FOREIGN KEY (verified_by) REFERENCES users(id),
+    CHECK (expires_on IS NULL OR expires_on >= issued_on)
+);
+
+-- Allowed values for classes' subject and grade_level
+CREATE TABLE IF NOT EXISTS class_vocabulary (
+    kind TEXT NOT NULL CHECK (kind IN ('subject', 'grade_band')),
+    name TEXT NOT NULL,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    is_active BOOLEAN NOT NULL DEFAULT true, -- Retired terms stay valid on classes that have them
+    PRIMARY KEY (kind, name)
+);
+
+-- What a substitute needs to cover a class, on top of the required_credentials setting
+CREATE TABLE IF NOT EXISTS class_requirements (
+    id TEXT PRIMARY KEY,
+    class_id TEXT NOT NULL,
+    credential_type TEXT CHECK (credential_type IN ('teaching_license', 'background_check', 'cpr', 'subject_endorsement', 'certification')),
+    credential_description TEXT, -- Must match the credential's description; NULL accepts any of the type
+    subject TEXT, -- Must be among the substitute's subjects
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (class_id) REFERENCES classes(id),
+    CHECK ((credential_type IS NULL) != (subject IS NULL))
+);
+
+-- Admin sign-off to assign a substitute who doesn't meet a class's requirements
+CREATE TABLE IF NOT EXISTS requirement_overrides (
+    id TEXT PRIMARY KEY,
+    request_id TEXT NOT NULL,
+    substitute_id TEXT NOT NULL,
+    approved_by TEXT NOT NULL,
+    justification TEXT NOT NULL,
+    unmet_requirements TEXT NOT NULL, -- JSON array of what was missing
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (request_id) REFERENCES substitute_requests(id),
+    FOREIGN KEY (substitute_id) REFERENCES users(id),
+    FOREIGN KEY (approved_by) REFERENCES users(id),
+    UNIQUE(request_id, substitute_id)
+);
+
+-- Planned substitute spend for an organization and everything below it
+CREATE TABLE IF NOT EXISTS substitute_budgets (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL,
+    name TEXT NOT NULL, -- Fiscal period label, e.g. "FY2030"
+    period_start TEXT NOT NULL,
+    period_end TEXT NOT NULL,
+    amount REAL NOT NULL CHECK (amount >= 0),
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (organization_id) REFERENCES organizations(id),
+    CHECK (period_end >= period_start)
+);
+
+-- How a filled request went: the requester rates the substitute and the
+-- substitute rates the assignment, once each
+CREATE TABLE IF NOT EXISTS assignment_feedback (
+    id TEXT PRIMARY KEY,
+    request_id TEXT NOT NULL,
+    reviewer_id TEXT NOT NULL,
+    reviewer_role TEXT NOT NULL CHECK (reviewer_role IN ('requester', 'substitute')),
+    rating INTEGER NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    comments TEXT,
+    flags TEXT NOT NULL DEFAULT '[]', -- JSON array of flag strings
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (request_id) REFERENCES substitute_requests(id),
+    FOREIGN KEY (reviewer_id) REFERENCES users(id),
+    UNIQUE(request_id, reviewer_role)
+);
+
 -- Notifications log
 CREATE TABLE IF NOT EXISTS notifications_log (
     id TEXT PRIMARY KEY,
     user_id TEXT NOT NULL,
     request_id TEXT NOT NULL,
     notification_type TEXT NOT NULL CHECK (notification_type IN ('email', 'push', 'sms')),
-    sent_at TEXT NOT NULL DEFAULT (datetime('now')),
+    sent_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
     status TEXT NOT NULL CHECK (status IN ('sent', 'failed', 'pending')),
     error_message TEXT,
     FOREIGN KEY (user_id) REFERENCES users(id),
@@ -129,7 +405,33 @@ CREATE TABLE IF NOT EXISTS settings (
     key TEXT PRIMARY KEY,
     value TEXT NOT NULL,
     description TEXT,
-    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Outbound webhook subscriptions
+CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+    id TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    event_types TEXT NOT NULL, -- JSON array of event names
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+
+-- Delivery log for outbound webhooks
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id TEXT PRIMARY KEY,
+    subscription_id TEXT NOT NULL,
+    event_type TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    response_status INTEGER,
+    error_message TEXT,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    last_attempt_at TEXT,
+    FOREIGN KEY (subscription_id) REFERENCES webhook_subscriptions(id)
 );
 
 -- Indexes for performance
@@ -140,19 +442,70 @@ CREATE INDEX IF NOT EXISTS idx_classes_organization ON classes(organization_id);
 CREATE INDEX IF NOT EXISTS idx_teachers_user ON teachers(user_id);
 CREATE INDEX IF NOT EXISTS idx_regular_teachers_user ON regular_teachers(user_id);
 CREATE INDEX IF NOT EXISTS idx_regular_teachers_org ON regular_teachers(organization_id);
+CREATE INDEX IF NOT EXISTS idx_substitute_unavailability_substitute ON substitute_unavailability(substitute_id, start_date);
+CREATE INDEX IF NOT EXISTS idx_substitute_preferences_substitute ON substitute_preferences(substitute_id);
+CREATE INDEX IF NOT EXISTS idx_assignment_feedback_request ON assignment_feedback(request_id);
+CREATE INDEX IF NOT EXISTS idx_substitute_credentials_substitute ON substitute_credentials(substitute_id, credential_type);
+CREATE INDEX IF NOT EXISTS idx_class_requirements_class ON class_requirements(class_id);
+CREATE INDEX IF NOT EXISTS idx_substitute_budgets_organization ON substitute_budgets(organization_id, period_start);
+CREATE INDEX IF NOT EXISTS idx_roster_links_local ON roster_links(entity, local_id);
 CREATE INDEX IF NOT EXISTS idx_teacher_class_assignments_teacher ON teacher_class_assignments(teacher_id);
 CREATE INDEX IF NOT EXISTS idx_teacher_class_assignments_class ON teacher_class_assignments(class_id);
 CREATE INDEX IF NOT EXISTS idx_substitute_requests_class ON substitute_requests(class_id);
 CREATE INDEX IF NOT EXISTS idx_substitute_requests_date ON substitute_requests(date_needed);
 CREATE INDEX IF NOT EXISTS idx_substitute_requests_status ON substitute_requests(status);
+CREATE INDEX IF NOT EXISTS idx_absences_class ON absences(class_id);
+CREATE INDEX IF NOT EXISTS idx_calendar_events_organization ON calendar_events(organization_id, start_date);
 CREATE INDEX IF NOT EXISTS idx_substitute_responses_request ON substitute_responses(request_id);
 CREATE INDEX IF NOT EXISTS idx_substitute_responses_substitute ON substitute_responses(substitute_id);
 CREATE INDEX IF NOT EXISTS idx_notifications_log_user ON notifications_log(user_id);
 CREATE INDEX IF NOT EXISTS idx_notifications_log_request ON notifications_log(request_id);
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_subscription ON webhook_deliveries(subscription_id);
 
 -- Insert default settings
 INSERT OR IGNORE INTO settings (key, value, description) VALUES
     ('app_name', 'Substitute Finder', 'Application name'),
     ('notification_enabled', 'true', 'Enable notifications'),
     ('auto_assign_substitutes', 'false', 'Automatically assign first available substitute'),
-    ('default_request_duration', '8', 'Default request duration in hours');
\ No newline at end of file
+    ('default_request_duration', '8', 'Default request duration in hours'),
+    ('preferred_offer_minutes', '15', 'Minutes preferred substitutes have a request to themselves before everyone else is notified'),
+    ('no_show_grace_minutes', '15', 'Minutes after the start time before an assignment nobody checked in to is a no-show'),
+    ('check_in_tolerance_minutes', '5', 'Minutes a check-in or check-out can be off schedule before it is flagged'),
+    ('payroll_csv_columns', 'substitute_name,substitute_email,date,organization,class,paid_hours,basis,rate,amount', 'Columns of the payroll CSV export, in order'),
+    ('estimated_hourly_rate', '', 'Hourly rate used to project the cost of requests nobody has accepted yet; blank leaves them out of budget projections'),
+    ('required_credentials', '', 'Comma-separated credential types substitutes need verified and unexpired to be offered or accept requests'),
+    ('calendar_feed_port', '', 'Local port serving calendar subscription feeds; blank turns the feed off. Takes effect on restart'),
+    ('calendar_feed_token', lower(hex(randomblob(16))), 'Secret included in calendar feed URLs; change it to revoke existing subscriptions'),
+    ('backup_daily_keep', '7', 'Automatic daily backups to keep; 0 turns them off'),
+    ('backup_weekly_keep', '4', 'Automatic weekly backups to keep; 0 turns them off');
+
+-- Default class vocabulary
+INSERT OR IGNORE INTO class_vocabulary (kind, name, sort_order) VALUES
+    ('subject', 'Mathematics', 1),
+    ('subject', 'English Language Arts', 2),
+    ('subject', 'Science', 3),
+    ('subject', 'Social Studies', 4),
+    ('subject', 'Physical Education', 5),
+    ('subject', 'Art', 6),
+    ('subject', 'Music', 7),
+    ('subject', 'Computer Science', 8),
+    ('subject', 'Foreign Language', 9),
+    ('subject', 'Health', 10),
+    ('subject', 'Library', 11),
+    ('subject', 'Special Education', 12),
+    ('subject', 'Other', 13),
+    ('grade_band', 'Pre-K', 1),
+    ('grade_band', 'Kindergarten', 2),
+    ('grade_band', '1st Grade', 3),
+    ('grade_band', '2nd Grade', 4),
+    ('grade_band', '3rd Grade', 5),
+    ('grade_band', '4th Grade', 6),
+    ('grade_band', '5th Grade', 7),
+    ('grade_band', '6th Grade', 8),
+    ('grade_band', '7th Grade', 9),
+    ('grade_band', '8th Grade', 10),
+    ('grade_band', '9th Grade', 11),
+    ('grade_band', '10th Grade', 12),
+    ('grade_band', '11th Grade', 13),
+    ('grade_band', '12th Grade', 14),
+    ('grade_band', 'Mixed Grades', 15);
@@ -0,0 +1,99 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+
+/// Canonical on-disk format: RFC 3339 in UTC with millisecond precision,
+/// e.g. `2024-09-01T14:30:00.000Z`. Every write goes through this, and the
+/// schema's column defaults use the equivalent `strftime('%Y-%m-%dT%H:%M:%fZ', 'now')`.
+pub fn format(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+pub fn now() -> String {
+    format(&Utc::now())
+}
+
+/// Parses a stored timestamp. Accepts RFC 3339 (what the commands have always
+/// written) as well as SQLite's `datetime('now')` output, `YYYY-MM-DD HH:MM:SS`,
+/// which older column defaults produced and which is always UTC.
+pub fn parse(value: &str) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f")
+        .map(|naive| naive.and_utc())
+        .map_err(|_| anyhow::anyhow!("Invalid timestamp: {:?}", value))
+}
+
+/// Calendar dates are stored as ISO 8601, `YYYY-MM-DD`.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+/// Times of day are stored as 24-hour `HH:MM`.
+pub const TIME_FORMAT: &str = "%H:%M";
+
+pub fn format_date(date: &NaiveDate) -> String {
+    date.format(DATE_FORMAT).to_string()
+}
+
+pub fn format_time(time: &NaiveTime) -> String {
+    time.format(TIME_FORMAT).to_string()
+}
+
+pub fn parse_date(value: &str) -> anyhow::Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+        .map_err(|_| anyhow::anyhow!("Invalid date {:?}, expected YYYY-MM-DD", value))
+}
+
+/// Parses `HH:MM`, also accepting `HH:MM:SS` as sent by `<input type="time" step>`.
+pub fn parse_time(value: &str) -> anyhow::Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, TIME_FORMAT)
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+        .map_err(|_| anyhow::anyhow!("Invalid time {:?}, expected HH:MM", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_format_is_millisecond_rfc3339() {
+        let timestamp = Utc.with_ymd_and_hms(2024, 9, 1, 14, 30, 0).unwrap();
+        assert_eq!(format(&timestamp), "2024-09-01T14:30:00.000Z");
+    }
+
+    #[test]
+    fn test_parse_accepts_rfc3339_and_sqlite_formats() {
+        let expected = Utc.with_ymd_and_hms(2024, 9, 1, 14, 30, 0).unwrap();
+
+        assert_eq!(parse("2024-09-01T14:30:00.000Z").unwrap(), expected);
+        assert_eq!(parse("2024-09-01T14:30:00+00:00").unwrap(), expected);
+        assert_eq!(parse("2024-09-01T16:30:00+02:00").unwrap(), expected);
+        assert_eq!(parse("2024-09-01 14:30:00").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(parse("").is_err());
+        assert!(parse("yesterday").is_err());
+        assert!(parse("2024-13-01 00:00:00").is_err());
+    }
+
+    #[test]
+    fn test_parse_date_and_time() {
+        assert_eq!(parse_date("2024-09-01").unwrap(), NaiveDate::from_ymd_opt(2024, 9, 1).unwrap());
+        assert!(parse_date("tomorrow").is_err());
+        assert!(parse_date("2024-02-30").is_err());
+
+        let half_past_eight = NaiveTime::from_hms_opt(8, 30, 0).unwrap();
+        assert_eq!(parse_time("08:30").unwrap(), half_past_eight);
+        assert_eq!(parse_time("08:30:00").unwrap(), half_past_eight);
+        assert_eq!(format_time(&half_past_eight), "08:30");
+        assert!(parse_time("25:00").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let now = Utc::now();
+        let parsed = parse(&format(&now)).unwrap();
+        assert_eq!(parsed.timestamp_millis(), now.timestamp_millis());
+    }
+}
@@ -0,0 +1,283 @@
+//! Typed data-change events pushed to the frontend after every mutating command.
+//!
+//! Each entity has its own channel and payload type so list views can subscribe
+//! with `listen<SubstituteRequestChangedEvent>("substitute-request-changed", ...)`
+//! instead of re-polling:
+//!
+//! | Channel                         | Payload                              |
+//! |---------------------------------|--------------------------------------|
+//! | `organization-changed`          | [`OrganizationChangedEvent`]         |
+//! | `class-changed`                 | [`ClassChangedEvent`]                |
+//! | `user-changed`                  | [`UserChangedEvent`]                 |
+//! | `substitute-request-changed`    | [`SubstituteRequestChangedEvent`]    |
+//! | `absence-changed`               | [`AbsenceChangedEvent`]              |
+//! | `regular-teacher-changed`       | [`RegularTeacherChangedEvent`]       |
+//! | `teacher-absence-changed`       | [`TeacherAbsenceChangedEvent`]       |
+//! | `bell-schedule-changed`         | [`BellScheduleChangedEvent`]         |
+//! | `calendar-event-changed`        | [`CalendarEventChangedEvent`]        |
+//! | `unavailability-changed`        | [`UnavailabilityChangedEvent`]       |
+//! | `substitute-preference-changed` | [`SubstitutePreferenceChangedEvent`] |
+//! | `feedback-changed`              | [`FeedbackChangedEvent`]             |
+//! | `attendance-changed`            | [`AttendanceChangedEvent`]           |
+//! | `pay-policy-changed`            | [`PayPolicyChangedEvent`]            |
+//! | `budget-changed`                | [`BudgetChangedEvent`]               |
+//! | `credential-changed`            | [`CredentialChangedEvent`]           |
+
+use crate::database::models::{
+    Absence, AssignmentAttendance, AssignmentFeedback, BellSchedule, CalendarEvent, Class, Organization, PayPolicy,
+    RegularTeacher, SubstituteBudget, SubstituteCredential, SubstitutePreference, SubstituteRequest,
+    SubstituteUnavailability, TeacherAbsence, User,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    Organization,
+    Class,
+    User,
+    SubstituteRequest,
+    Absence,
+    RegularTeacher,
+    TeacherAbsence,
+    BellSchedule,
+    CalendarEvent,
+    Unavailability,
+    SubstitutePreference,
+    Feedback,
+    Attendance,
+    PayPolicy,
+    Budget,
+    Credential,
+}
+
+impl EntityType {
+    /// Name of the Tauri event channel the entity's changes are emitted on.
+    pub fn channel(&self) -> &'static str {
+        match self {
+            EntityType::Organization => "organization-changed",
+            EntityType::Class => "class-changed",
+            EntityType::User => "user-changed",
+            EntityType::SubstituteRequest => "substitute-request-changed",
+            EntityType::Absence => "absence-changed",
+            EntityType::RegularTeacher => "regular-teacher-changed",
+            EntityType::TeacherAbsence => "teacher-absence-changed",
+            EntityType::BellSchedule => "bell-schedule-changed",
+            EntityType::CalendarEvent => "calendar-event-changed",
+            EntityType::Unavailability => "unavailability-changed",
+            EntityType::SubstitutePreference => "substitute-preference-changed",
+            EntityType::Feedback => "feedback-changed",
+            EntityType::Attendance => "attendance-changed",
+            EntityType::PayPolicy => "pay-policy-changed",
+            EntityType::Budget => "budget-changed",
+            EntityType::Credential => "credential-changed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+    Accepted,
+    Declined,
+}
+
+/// Payload of every data-change event. `data` holds the entity's new state and
+/// is `None` for deletions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataChangeEvent<T> {
+    pub entity_type: EntityType,
+    pub entity_id: String,
+    pub change: ChangeKind,
+    pub data: Option<T>,
+}
+
+pub type OrganizationChangedEvent = DataChangeEvent<Organization>;
+pub type ClassChangedEvent = DataChangeEvent<Class>;
+pub type UserChangedEvent = DataChangeEvent<User>;
+pub type SubstituteRequestChangedEvent = DataChangeEvent<SubstituteRequest>;
+pub type AbsenceChangedEvent = DataChangeEvent<Absence>;
+pub type RegularTeacherChangedEvent = DataChangeEvent<RegularTeacher>;
+pub type TeacherAbsenceChangedEvent = DataChangeEvent<TeacherAbsence>;
+pub type BellScheduleChangedEvent = DataChangeEvent<BellSchedule>;
+pub type CalendarEventChangedEvent = DataChangeEvent<CalendarEvent>;
+pub type UnavailabilityChangedEvent = DataChangeEvent<SubstituteUnavailability>;
+pub type SubstitutePreferenceChangedEvent = DataChangeEvent<SubstitutePreference>;
+pub type FeedbackChangedEvent = DataChangeEvent<AssignmentFeedback>;
+pub type AttendanceChangedEvent = DataChangeEvent<AssignmentAttendance>;
+pub type PayPolicyChangedEvent = DataChangeEvent<PayPolicy>;
+pub type BudgetChangedEvent = DataChangeEvent<SubstituteBudget>;
+pub type CredentialChangedEvent = DataChangeEvent<SubstituteCredential>;
+
+/// Entities that can be announced through [`emit_change`].
+pub trait ChangeEntity: Serialize + Clone {
+    const ENTITY_TYPE: EntityType;
+
+    fn entity_id(&self) -> &str;
+}
+
+impl ChangeEntity for Organization {
+    const ENTITY_TYPE: EntityType = EntityType::Organization;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for Class {
+    const ENTITY_TYPE: EntityType = EntityType::Class;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for User {
+    const ENTITY_TYPE: EntityType = EntityType::User;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for SubstituteRequest {
+    const ENTITY_TYPE: EntityType = EntityType::SubstituteRequest;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for Absence {
+    const ENTITY_TYPE: EntityType = EntityType::Absence;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for RegularTeacher {
+    const ENTITY_TYPE: EntityType = EntityType::RegularTeacher;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for TeacherAbsence {
+    const ENTITY_TYPE: EntityType = EntityType::TeacherAbsence;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for BellSchedule {
+    const ENTITY_TYPE: EntityType = EntityType::BellSchedule;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for CalendarEvent {
+    const ENTITY_TYPE: EntityType = EntityType::CalendarEvent;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for SubstituteUnavailability {
+    const ENTITY_TYPE: EntityType = EntityType::Unavailability;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for SubstitutePreference {
+    const ENTITY_TYPE: EntityType = EntityType::SubstitutePreference;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for AssignmentFeedback {
+    const ENTITY_TYPE: EntityType = EntityType::Feedback;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Keyed by the request it belongs to.
+impl ChangeEntity for AssignmentAttendance {
+    const ENTITY_TYPE: EntityType = EntityType::Attendance;
+
+    fn entity_id(&self) -> &str {
+        &self.request_id
+    }
+}
+
+/// Keyed by the organization it applies to.
+impl ChangeEntity for PayPolicy {
+    const ENTITY_TYPE: EntityType = EntityType::PayPolicy;
+
+    fn entity_id(&self) -> &str {
+        &self.organization_id
+    }
+}
+
+impl ChangeEntity for SubstituteBudget {
+    const ENTITY_TYPE: EntityType = EntityType::Budget;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl ChangeEntity for SubstituteCredential {
+    const ENTITY_TYPE: EntityType = EntityType::Credential;
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Emits the new state of `entity` on its channel.
+pub fn emit_change<T: ChangeEntity>(app: &AppHandle, change: ChangeKind, entity: &T) {
+    emit(
+        app,
+        DataChangeEvent {
+            entity_type: T::ENTITY_TYPE,
+            entity_id: entity.entity_id().to_string(),
+            change,
+            data: Some(entity.clone()),
+        },
+    );
+}
+
+/// Emits a deletion of the `T` with the given id.
+pub fn emit_deleted<T: ChangeEntity>(app: &AppHandle, id: &str) {
+    emit::<T>(
+        app,
+        DataChangeEvent {
+            entity_type: T::ENTITY_TYPE,
+            entity_id: id.to_string(),
+            change: ChangeKind::Deleted,
+            data: None,
+        },
+    );
+}
+
+fn emit<T: ChangeEntity>(app: &AppHandle, event: DataChangeEvent<T>) {
+    if let Err(e) = app.emit(T::ENTITY_TYPE.channel(), &event) {
+        eprintln!("Failed to emit {} event: {}", T::ENTITY_TYPE.channel(), e);
+    }
+}
@@ -0,0 +1,507 @@
+use crate::database::models::{ExportDataset, ExportFilter, ExportFormat, ExportRequest, ExportSummary};
+use crate::database::timestamp;
+use crate::repository::OrganizationRepository;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// An exportable column: its key, which heads it in the file, and the SQL
+/// that reads it.
+struct Column {
+    key: &'static str,
+    sql: &'static str,
+}
+
+const fn column(key: &'static str, sql: &'static str) -> Column {
+    Column { key, sql }
+}
+
+/// Where a dataset's rows come from and which of its columns the filter
+/// applies to.
+struct Dataset {
+    /// Sheet name in XLSX exports.
+    name: &'static str,
+    from: &'static str,
+    /// Condition every exported row meets, filter or not.
+    condition: Option<&'static str>,
+    /// Yields an ISO date.
+    date: &'static str,
+    organization: &'static str,
+    status: Option<&'static str>,
+    /// Ends with a unique column so repeated exports list rows in the same order.
+    order_by: &'static str,
+    columns: &'static [Column],
+}
+
+const REQUEST_FROM: &str = "substitute_requests r
+     JOIN classes c ON c.id = r.class_id
+     JOIN organizations o ON o.id = c.organization_id
+     LEFT JOIN users rb ON rb.id = r.requested_by
+     LEFT JOIN users s ON s.id = r.assigned_substitute_id";
+
+const REQUESTS: Dataset = Dataset {
+    name: "Requests",
+    from: REQUEST_FROM,
+    condition: None,
+    date: "r.date_needed",
+    organization: "c.organization_id",
+    status: Some("r.status"),
+    order_by: "r.date_needed, r.start_time, r.id",
+    columns: &[
+        column("id", "r.id"),
+        column("organization", "o.name"),
+        column("class", "c.name"),
+        column("date", "r.date_needed"),
+        column("start_time", "r.start_time"),
+        column("end_time", "r.end_time"),
+        column("status", "r.status"),
+        column("requested_by", "rb.last_name || ', ' || rb.first_name"),
+        column("substitute", "s.last_name || ', ' || s.first_name"),
+        column("reason", "r.reason"),
+        column("special_instructions", "r.special_instructions"),
+        column("created_at", "r.created_at"),
+        column("updated_at", "r.updated_at"),
+    ],
+};
+
+const ASSIGNMENTS: Dataset = Dataset {
+    name: "Assignments",
+    from: "substitute_requests r
+     JOIN classes c ON c.id = r.class_id
+     JOIN organizations o ON o.id = c.organization_id
+     JOIN users s ON s.id = r.assigned_substitute_id
+     LEFT JOIN assignment_attendance a ON a.request_id = r.id",
+    condition: Some("r.status IN ('filled', 'in_progress', 'completed', 'no_show')"),
+    date: "r.date_needed",
+    organization: "c.organization_id",
+    status: Some("r.status"),
+    order_by: "r.date_needed, r.start_time, r.id",
+    columns: &[
+        column("request_id", "r.id"),
+        column("substitute_id", "s.id"),
+        column("substitute_name", "s.last_name || ', ' || s.first_name"),
+        column("substitute_email", "s.email"),
+        column("organization", "o.name"),
+        column("class", "c.name"),
+        column("date", "r.date_needed"),
+        column("start_time", "r.start_time"),
+        column("end_time", "r.end_time"),
+        column("status", "r.status"),
+        column("check_in_at", "a.check_in_at"),
+        column("check_out_at", "a.check_out_at"),
+    ],
+};
+
+const USERS: Dataset = Dataset {
+    name: "Users",
+    from: "users u LEFT JOIN organizations o ON o.id = u.organization_id",
+    condition: None,
+    date: "substr(u.created_at, 1, 10)",
+    organization: "u.organization_id",
+    status: None,
+    order_by: "u.last_name, u.first_name, u.id",
+    columns: &[
+        column("id", "u.id"),
+        column("username", "u.username"),
+        column("email", "u.email"),
+        column("first_name", "u.first_name"),
+        column("last_name", "u.last_name"),
+        column("role", "u.role"),
+        column("organization", "o.name"),
+        column("is_active", "u.is_active"),
+        column("created_at", "u.created_at"),
+    ],
+};
+
+const CLASSES: Dataset = Dataset {
+    name: "Classes",
+    from: "classes c JOIN organizations o ON o.id = c.organization_id",
+    condition: None,
+    date: "substr(c.created_at, 1, 10)",
+    organization: "c.organization_id",
+    status: None,
+    order_by: "o.name, c.name, c.id",
+    columns: &[
+        column("id", "c.id"),
+        column("name", "c.name"),
+        column("organization", "o.name"),
+        column("subject", "c.subject"),
+        column("grade_level", "c.grade_level"),
+        column("room_number", "c.room_number"),
+        column("description", "c.description"),
+        column("created_at", "c.created_at"),
+    ],
+};
+
+const NOTIFICATION_LOGS: Dataset = Dataset {
+    name: "Notifications",
+    from: "notifications_log n
+     JOIN users u ON u.id = n.user_id
+     JOIN substitute_requests r ON r.id = n.request_id
+     JOIN classes c ON c.id = r.class_id",
+    condition: None,
+    date: "substr(n.sent_at, 1, 10)",
+    organization: "c.organization_id",
+    status: Some("n.status"),
+    order_by: "n.sent_at, n.id",
+    columns: &[
+        column("id", "n.id"),
+        column("user_name", "u.last_name || ', ' || u.first_name"),
+        column("user_email", "u.email"),
+        column("request_id", "n.request_id"),
+        column("notification_type", "n.notification_type"),
+        column("status", "n.status"),
+        column("sent_at", "n.sent_at"),
+        column("error_message", "n.error_message"),
+    ],
+};
+
+fn dataset(dataset: ExportDataset) -> &'static Dataset {
+    match dataset {
+        ExportDataset::Requests => &REQUESTS,
+        ExportDataset::Assignments => &ASSIGNMENTS,
+        ExportDataset::Users => &USERS,
+        ExportDataset::Classes => &CLASSES,
+        ExportDataset::NotificationLogs => &NOTIFICATION_LOGS,
+    }
+}
+
+/// Keys of every column the dataset can export, in their default order.
+pub fn column_keys(export: ExportDataset) -> Vec<String> {
+    dataset(export).columns.iter().map(|column| column.key.to_string()).collect()
+}
+
+fn select_columns<'d>(dataset: &'d Dataset, keys: Option<&[String]>) -> anyhow::Result<Vec<&'d Column>> {
+    let Some(keys) = keys else {
+        return Ok(dataset.columns.iter().collect());
+    };
+    let columns = keys
+        .iter()
+        .map(|key| {
+            dataset
+                .columns
+                .iter()
+                .find(|column| column.key == key.trim())
+                .ok_or_else(|| anyhow::anyhow!("Invalid export column: {}", key))
+        })
+        .collect::<anyhow::Result<Vec<&Column>>>()?;
+    if columns.is_empty() {
+        anyhow::bail!("Export needs at least one column");
+    }
+    Ok(columns)
+}
+
+/// The dataset's query under `filter`, with its parameters.
+fn query(
+    conn: &Connection,
+    dataset: &Dataset,
+    columns: &[&Column],
+    filter: &ExportFilter,
+) -> anyhow::Result<(String, Vec<String>)> {
+    let mut conditions: Vec<String> = dataset.condition.iter().map(|condition| condition.to_string()).collect();
+    let mut params = Vec::new();
+
+    if let (Some(from), Some(to)) = (filter.from, filter.to) {
+        if to < from {
+            anyhow::bail!("Export range ends before it starts");
+        }
+    }
+    if let Some(from) = filter.from {
+        conditions.push(format!("{} >= ?", dataset.date));
+        params.push(timestamp::format_date(&from));
+    }
+    if let Some(to) = filter.to {
+        conditions.push(format!("{} <= ?", dataset.date));
+        params.push(timestamp::format_date(&to));
+    }
+    if let Some(organization_id) = &filter.organization_id {
+        let organization_ids = OrganizationRepository::new(conn).subtree_ids(organization_id)?;
+        conditions.push(format!(
+            "{} IN ({})",
+            dataset.organization,
+            vec!["?"; organization_ids.len()].join(", ")
+        ));
+        params.extend(organization_ids);
+    }
+    if let Some(status) = &filter.status {
+        let Some(column) = dataset.status else {
+            anyhow::bail!("{} can't be filtered by status", dataset.name);
+        };
+        conditions.push(format!("{} = ?", column));
+        params.push(status.clone());
+    }
+
+    let selected = columns.iter().map(|column| column.sql).collect::<Vec<_>>().join(", ");
+    let mut sql = format!("SELECT {} FROM {}", selected, dataset.from);
+    if !conditions.is_empty() {
+        sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
+    }
+    sql.push_str(&format!(" ORDER BY {}", dataset.order_by));
+    Ok((sql, params))
+}
+
+/// Prefixes text a spreadsheet would read as a formula with an apostrophe,
+/// so opening an export can't run anything a user typed into a field.
+fn neutralize(text: &str) -> Cow<'_, str> {
+    if text.starts_with(['=', '+', '-', '@']) {
+        Cow::Owned(format!("'{}", text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Receives the header and then each row of an export.
+trait Sink {
+    fn header(&mut self, keys: &[&str]) -> anyhow::Result<()>;
+    fn row(&mut self, values: &[Value]) -> anyhow::Result<()>;
+    fn finish(self: Box<Self>) -> anyhow::Result<()>;
+}
+
+struct CsvSink {
+    writer: csv::Writer<BufWriter<File>>,
+}
+
+impl Sink for CsvSink {
+    fn header(&mut self, keys: &[&str]) -> anyhow::Result<()> {
+        self.writer.write_record(keys)?;
+        Ok(())
+    }
+
+    fn row(&mut self, values: &[Value]) -> anyhow::Result<()> {
+        self.writer.write_record(values.iter().map(|value| match value {
+            Value::Null | Value::Blob(_) => String::new(),
+            Value::Integer(number) => number.to_string(),
+            Value::Real(number) => number.to_string(),
+            Value::Text(text) => neutralize(text).into_owned(),
+        }))?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> anyhow::Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes through a constant-memory worksheet, which flushes each row to a
+/// temporary file rather than holding the sheet in memory.
+struct XlsxSink {
+    workbook: rust_xlsxwriter::Workbook,
+    path: PathBuf,
+    next_row: u32,
+}
+
+impl XlsxSink {
+    fn new(path: &Path, sheet_name: &str) -> anyhow::Result<Self> {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        workbook.add_worksheet_with_constant_memory().set_name(sheet_name)?;
+        Ok(XlsxSink { workbook, path: path.to_path_buf(), next_row: 0 })
+    }
+}
+
+impl Sink for XlsxSink {
+    fn header(&mut self, keys: &[&str]) -> anyhow::Result<()> {
+        let bold = rust_xlsxwriter::Format::new().set_bold();
+        let sheet = self.workbook.worksheet_from_index(0)?;
+        sheet.write_row_with_format(0, 0, keys.iter().copied(), &bold)?;
+        sheet.set_freeze_panes(1, 0)?;
+        self.next_row = 1;
+        Ok(())
+    }
+
+    fn row(&mut self, values: &[Value]) -> anyhow::Result<()> {
+        let sheet = self.workbook.worksheet_from_index(0)?;
+        for (index, value) in values.iter().enumerate() {
+            let col = u16::try_from(index)?;
+            match value {
+                Value::Null | Value::Blob(_) => {}
+                Value::Integer(number) => {
+                    sheet.write_number(self.next_row, col, *number as f64)?;
+                }
+                Value::Real(number) => {
+                    sheet.write_number(self.next_row, col, *number)?;
+                }
+                Value::Text(text) => {
+                    sheet.write_string(self.next_row, col, neutralize(text))?;
+                }
+            }
+        }
+        self.next_row += 1;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> anyhow::Result<()> {
+        self.workbook.save(&self.path)?;
+        Ok(())
+    }
+}
+
+/// Writes the requested dataset to `request.path`, reading rows from the
+/// database one at a time. Rows go to a partial file next to the target that
+/// only replaces it once complete, so a failed export leaves any existing
+/// file as it was.
+pub fn export(conn: &Connection, request: &ExportRequest) -> anyhow::Result<ExportSummary> {
+    let dataset = dataset(request.dataset);
+    let columns = select_columns(dataset, request.columns.as_deref())?;
+    let (sql, params) = query(conn, dataset, &columns, &request.filter)?;
+    if request.path.trim().is_empty() {
+        anyhow::bail!("Export path is required");
+    }
+
+    let path = Path::new(&request.path);
+    let partial = path.with_extension("partial");
+    let rows = match write(conn, dataset, &columns, &sql, &params, request.format, &partial) {
+        Ok(rows) => rows,
+        Err(error) => {
+            let _ = std::fs::remove_file(&partial);
+            return Err(error);
+        }
+    };
+    std::fs::rename(&partial, path)?;
+
+    Ok(ExportSummary {
+        path: request.path.clone(),
+        columns: columns.iter().map(|column| column.key.to_string()).collect(),
+        rows,
+    })
+}
+
+fn write(
+    conn: &Connection,
+    dataset: &Dataset,
+    columns: &[&Column],
+    sql: &str,
+    params: &[String],
+    format: ExportFormat,
+    path: &Path,
+) -> anyhow::Result<u64> {
+    let mut sink: Box<dyn Sink> = match format {
+        ExportFormat::Csv => Box::new(CsvSink {
+            writer: csv::Writer::from_writer(BufWriter::new(File::create(path)?)),
+        }),
+        ExportFormat::Xlsx => Box::new(XlsxSink::new(path, dataset.name)?),
+    };
+    sink.header(&columns.iter().map(|column| column.key).collect::<Vec<_>>())?;
+
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(rusqlite::params_from_iter(params.iter()))?;
+    let mut count = 0;
+    let mut values = Vec::with_capacity(columns.len());
+    while let Some(row) = rows.next()? {
+        values.clear();
+        for index in 0..columns.len() {
+            values.push(row.get::<_, Value>(index)?);
+        }
+        sink.row(&values)?;
+        count += 1;
+    }
+    sink.finish()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::fixtures::{insert_class, insert_organization, insert_user, test_connection};
+
+    fn request(dataset: ExportDataset, format: ExportFormat, path: &Path) -> ExportRequest {
+        ExportRequest {
+            dataset,
+            format,
+            path: path.to_string_lossy().to_string(),
+            columns: None,
+            filter: ExportFilter::default(),
+        }
+    }
+
+    #[test]
+    fn test_exports_filtered_columns_with_escaping() {
+        let conn = test_connection();
+        insert_organization(&conn, "district");
+        insert_organization(&conn, "north");
+        insert_organization(&conn, "south");
+        conn.execute("UPDATE organizations SET parent_organization_id = 'district' WHERE id = 'north'", [])
+            .unwrap();
+        insert_class(&conn, "north-math", "north");
+        insert_class(&conn, "south-math", "south");
+        insert_user(&conn, "manager-1", "org_manager");
+        conn.execute_batch(
+            "INSERT INTO substitute_requests
+                 (id, class_id, requested_by, date_needed, start_time, end_time, reason, status)
+             VALUES
+                 ('r1', 'north-math', 'manager-1', '2030-01-15', '08:00', '15:00', 'Sick, \"flu\"', 'open'),
+                 ('r2', 'north-math', 'manager-1', '2030-01-14', '08:00', '15:00', NULL, 'cancelled'),
+                 ('r3', 'south-math', 'manager-1', '2030-01-14', '08:00', '15:00', NULL, 'open'),
+                 ('r4', 'north-math', 'manager-1', '2030-03-01', '08:00', '15:00', NULL, 'open');",
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("requests.csv");
+
+        let mut export_request = request(ExportDataset::Requests, ExportFormat::Csv, &path);
+        export_request.columns = Some(vec!["id".to_string(), "date".to_string(), "reason".to_string()]);
+        export_request.filter = ExportFilter {
+            from: timestamp::parse_date("2030-01-01").ok(),
+            to: timestamp::parse_date("2030-01-31").ok(),
+            organization_id: Some("district".to_string()),
+            status: None,
+        };
+        let summary = export(&conn, &export_request).unwrap();
+        assert_eq!(summary.rows, 2);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "id,date,reason\nr2,2030-01-14,\nr1,2030-01-15,\"Sick, \"\"flu\"\"\"\n"
+        );
+
+        export_request.filter.status = Some("open".to_string());
+        assert_eq!(export(&conn, &export_request).unwrap().rows, 1);
+
+        let xlsx = dir.path().join("classes.xlsx");
+        let summary = export(&conn, &request(ExportDataset::Classes, ExportFormat::Xlsx, &xlsx)).unwrap();
+        assert_eq!(summary.rows, 2);
+        assert_eq!(summary.columns, column_keys(ExportDataset::Classes));
+        assert!(std::fs::metadata(&xlsx).unwrap().len() > 0);
+
+        export_request.columns = Some(vec!["password_hash".to_string()]);
+        assert!(export(&conn, &export_request).is_err());
+        let mut users = request(ExportDataset::Users, ExportFormat::Csv, &path);
+        users.filter.status = Some("active".to_string());
+        assert!(export(&conn, &users).is_err());
+    }
+
+    #[test]
+    fn test_neutralizes_formulas_and_keeps_existing_file_on_failure() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "math", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        conn.execute_batch(
+            "INSERT INTO substitute_requests
+                 (id, class_id, requested_by, date_needed, start_time, end_time, reason, status)
+             VALUES
+                 ('r1', 'math', 'manager-1', '2030-01-14', '08:00', '15:00', '=HYPERLINK(\"http://x\")', 'open'),
+                 ('r2', 'math', 'manager-1', '2030-01-15', '08:00', '15:00', '@SUM(A1)', 'open'),
+                 ('r3', 'math', 'manager-1', '2030-01-16', '08:00', '15:00', 'Flu - 2 days', 'open');",
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("requests.csv");
+
+        let mut export_request = request(ExportDataset::Requests, ExportFormat::Csv, &path);
+        export_request.columns = Some(vec!["id".to_string(), "reason".to_string()]);
+        export(&conn, &export_request).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "id,reason\nr1,\"'=HYPERLINK(\"\"http://x\"\")\"\nr2,'@SUM(A1)\nr3,Flu - 2 days\n"
+        );
+
+        std::fs::write(&path, "previous export").unwrap();
+        conn.execute_batch("DROP TABLE substitute_responses; DROP TABLE substitute_requests;").unwrap();
+        assert!(export(&conn, &export_request).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "previous export");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,479 @@
+use crate::database::connection::DbConnection;
+use crate::database::models::{CalendarExportSummary, CalendarFeed};
+use crate::database::timestamp;
+use crate::repository::setting::CALENDAR_FEED_TOKEN;
+use crate::repository::{OrganizationRepository, SettingsRepository, UserRepository};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use rusqlite::{Connection, Row};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+
+/// Days of past requests a calendar keeps, so long-running subscriptions
+/// don't grow without bound.
+pub const FEED_HISTORY_DAYS: i64 = 90;
+
+/// Longest content line RFC 5545 allows before folding, in octets.
+const LINE_LIMIT: usize = 75;
+
+const UID_DOMAIN: &str = "substitute-finder";
+
+/// A request as it appears on a calendar.
+struct Event {
+    id: String,
+    date: NaiveDate,
+    start_time: NaiveTime,
+    end_time: NaiveTime,
+    status: String,
+    class: String,
+    room: Option<String>,
+    organization: String,
+    address: Option<String>,
+    substitute: Option<String>,
+    reason: Option<String>,
+    special_instructions: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+const EVENT_SQL: &str = "SELECT r.id, r.date_needed, r.start_time, r.end_time, r.status, c.name, c.room_number,
+            o.name, o.address, s.last_name || ', ' || s.first_name, r.reason, r.special_instructions,
+            r.created_at, r.updated_at
+     FROM substitute_requests r
+     JOIN classes c ON c.id = r.class_id
+     JOIN organizations o ON o.id = c.organization_id
+     LEFT JOIN users s ON s.id = r.assigned_substitute_id";
+
+impl Event {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let date: String = row.get(1)?;
+        let start_time: String = row.get(2)?;
+        let end_time: String = row.get(3)?;
+        let created_at: String = row.get(12)?;
+        let updated_at: String = row.get(13)?;
+        let invalid = |index: usize, e: anyhow::Error| {
+            rusqlite::Error::FromSqlConversionFailure(index, rusqlite::types::Type::Text, e.into())
+        };
+
+        Ok(Event {
+            id: row.get(0)?,
+            date: timestamp::parse_date(&date).map_err(|e| invalid(1, e))?,
+            start_time: timestamp::parse_time(&start_time).map_err(|e| invalid(2, e))?,
+            end_time: timestamp::parse_time(&end_time).map_err(|e| invalid(3, e))?,
+            status: row.get(4)?,
+            class: row.get(5)?,
+            room: row.get(6)?,
+            organization: row.get(7)?,
+            address: row.get(8)?,
+            substitute: row.get(9)?,
+            reason: row.get(10)?,
+            special_instructions: row.get(11)?,
+            created_at: timestamp::parse(&created_at).map_err(|e| invalid(12, e))?,
+            updated_at: timestamp::parse(&updated_at).map_err(|e| invalid(13, e))?,
+        })
+    }
+}
+
+/// The feed's calendar as iCalendar text, using the machine's time zone for
+/// request times.
+pub fn calendar(conn: &Connection, feed: &CalendarFeed) -> anyhow::Result<Option<String>> {
+    Ok(build(conn, feed, Utc::now(), &Local)?.map(|(text, _)| text))
+}
+
+/// Writes the feed's calendar to `path` for importing into a calendar app.
+pub fn export(conn: &Connection, feed: &CalendarFeed, path: &str) -> anyhow::Result<CalendarExportSummary> {
+    let Some((text, events)) = build(conn, feed, Utc::now(), &Local)? else {
+        anyhow::bail!("{} not found", feed_kind(feed));
+    };
+    std::fs::write(path, text)?;
+    Ok(CalendarExportSummary {
+        path: path.to_string(),
+        events,
+    })
+}
+
+/// Where the subscription server on `port` serves the feed.
+pub fn feed_url(port: u16, token: &str, feed: &CalendarFeed) -> String {
+    let (kind, id) = match feed {
+        CalendarFeed::Substitute(id) => ("substitutes", id),
+        CalendarFeed::Organization(id) => ("organizations", id),
+    };
+    format!("http://{}:{}/{}/{}.ics?token={}", Ipv4Addr::LOCALHOST, port, kind, id, token)
+}
+
+fn feed_kind(feed: &CalendarFeed) -> &'static str {
+    match feed {
+        CalendarFeed::Substitute(_) => "Substitute",
+        CalendarFeed::Organization(_) => "Organization",
+    }
+}
+
+/// The calendar and its number of events, or `None` when the substitute or
+/// organization doesn't exist.
+fn build<Tz: TimeZone>(
+    conn: &Connection,
+    feed: &CalendarFeed,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> anyhow::Result<Option<(String, u64)>> {
+    let earliest = now.with_timezone(tz).date_naive() - Duration::days(FEED_HISTORY_DAYS);
+    let (name, condition, id) = match feed {
+        CalendarFeed::Substitute(id) => {
+            let Some(user) = UserRepository::new(conn).find_by_id(id)? else {
+                return Ok(None);
+            };
+            (
+                format!("Substitute assignments: {}, {}", user.last_name, user.first_name),
+                "r.assigned_substitute_id = ?1
+                 AND r.status IN ('filled', 'in_progress', 'completed', 'no_show', 'cancelled')",
+                id,
+            )
+        }
+        CalendarFeed::Organization(id) => {
+            let Some(organization) = OrganizationRepository::new(conn).find_by_id(id)? else {
+                return Ok(None);
+            };
+            (
+                format!("Substitute requests: {}", organization.name),
+                "c.organization_id IN (
+                     WITH RECURSIVE subtree(id) AS (
+                         SELECT ?1
+                         UNION
+                         SELECT o2.id FROM organizations o2 JOIN subtree ON o2.parent_organization_id = subtree.id
+                     )
+                     SELECT id FROM subtree
+                 )",
+                id,
+            )
+        }
+    };
+
+    let sql = format!(
+        "{} WHERE {} AND r.date_needed >= ?2 ORDER BY r.date_needed, r.start_time, r.id",
+        EVENT_SQL, condition
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let events = stmt
+        .query_map((id, timestamp::format_date(&earliest)), Event::from_row)?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let substitute_feed = matches!(feed, CalendarFeed::Substitute(_));
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//Substitute Finder//Requests//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape(&name)),
+    ] {
+        push_line(&mut out, &line);
+    }
+    for event in &events {
+        push_event(&mut out, event, substitute_feed, now, tz);
+    }
+    push_line(&mut out, "END:VCALENDAR");
+
+    Ok(Some((out, events.len() as u64)))
+}
+
+fn push_event<Tz: TimeZone>(out: &mut String, event: &Event, substitute_feed: bool, now: DateTime<Utc>, tz: &Tz) {
+    let summary = if substitute_feed {
+        format!("Substitute: {} at {}", event.class, event.organization)
+    } else {
+        format!("{}: {}", event.class, event.substitute.as_deref().unwrap_or("unfilled"))
+    };
+    let location = [
+        event.room.as_ref().map(|room| format!("Room {}", room)),
+        Some(event.organization.clone()),
+        event.address.clone(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|part| !part.trim().is_empty())
+    .collect::<Vec<_>>()
+    .join(", ");
+
+    let mut description = vec![format!("Class: {}", event.class), format!("Organization: {}", event.organization)];
+    if !substitute_feed {
+        description.push(format!("Substitute: {}", event.substitute.as_deref().unwrap_or("Unfilled")));
+    }
+    if let Some(reason) = &event.reason {
+        description.push(format!("Reason: {}", reason));
+    }
+    if let Some(instructions) = &event.special_instructions {
+        description.push(format!("Special instructions: {}", instructions));
+    }
+
+    let status = match event.status.as_str() {
+        "cancelled" => "CANCELLED",
+        "open" => "TENTATIVE",
+        _ => "CONFIRMED",
+    };
+    // Calendar apps only apply an update whose SEQUENCE went up, and every
+    // change to a request moves its updated_at forward.
+    let sequence = (event.updated_at - event.created_at).num_seconds().max(0);
+
+    for line in [
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@{}", event.id, UID_DOMAIN),
+        format!("DTSTAMP:{}", utc_stamp(&now)),
+        format!("LAST-MODIFIED:{}", utc_stamp(&event.updated_at)),
+        format!("SEQUENCE:{}", sequence),
+        format!("DTSTART:{}", utc_stamp(&local_instant(tz, event.date, event.start_time))),
+        format!("DTEND:{}", utc_stamp(&local_instant(tz, event.date, event.end_time))),
+        format!("SUMMARY:{}", escape(&summary)),
+        format!("LOCATION:{}", escape(&location)),
+        format!("DESCRIPTION:{}", escape(&description.join("\n"))),
+        format!("STATUS:{}", status),
+        "TRANSP:OPAQUE".to_string(),
+        "END:VEVENT".to_string(),
+    ] {
+        push_line(out, &line);
+    }
+}
+
+/// Request times are wall-clock times at the school. A time skipped by a
+/// daylight saving change is read as the hour after it, as clocks show it.
+fn local_instant<Tz: TimeZone>(tz: &Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let naive = NaiveDateTime::new(date, time);
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(naive + Duration::hours(1))).earliest())
+        .map(|instant| instant.with_timezone(&Utc))
+        .unwrap_or_else(|| naive.and_utc())
+}
+
+fn utc_stamp(instant: &DateTime<Utc>) -> String {
+    instant.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value (RFC 5545 §3.3.11).
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Appends a content line, folded at 75 octets without splitting a UTF-8
+/// character (RFC 5545 §3.1).
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > LINE_LIMIT {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += ch.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Serves subscription feeds on `127.0.0.1:port` from a background thread.
+/// Each request must carry the current feed token.
+pub fn serve(db: DbConnection, port: u16) -> std::io::Result<()> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let handled = stream.and_then(|stream| handle(&db, stream));
+            if let Err(e) = handled {
+                eprintln!("Calendar feed request failed: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+fn handle(db: &DbConnection, stream: TcpStream) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Drain the headers; none of them matter here.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let (status, content_type, body) = respond(db, &request_line);
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+fn respond(db: &DbConnection, request_line: &str) -> (&'static str, &'static str, String) {
+    const TEXT: &str = "text/plain; charset=utf-8";
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return ("400 Bad Request", TEXT, "Bad request".to_string());
+    };
+    if method != "GET" {
+        return ("405 Method Not Allowed", TEXT, "Only GET is supported".to_string());
+    }
+    let Some((feed, token)) = route(target) else {
+        return ("404 Not Found", TEXT, "Not found".to_string());
+    };
+
+    let conn = match db.lock() {
+        Ok(conn) => conn,
+        Err(e) => return ("500 Internal Server Error", TEXT, e.to_string()),
+    };
+    let expected = match SettingsRepository::new(&conn).get(CALENDAR_FEED_TOKEN) {
+        Ok(setting) => setting.map(|setting| setting.value).unwrap_or_default(),
+        Err(e) => return ("500 Internal Server Error", TEXT, e.to_string()),
+    };
+    if expected.trim().is_empty() || token != Some(expected.trim()) {
+        return ("403 Forbidden", TEXT, "Invalid feed token".to_string());
+    }
+
+    match calendar(&conn, &feed) {
+        Ok(Some(text)) => ("200 OK", "text/calendar; charset=utf-8", text),
+        Ok(None) => ("404 Not Found", TEXT, format!("{} not found", feed_kind(&feed))),
+        Err(e) => ("500 Internal Server Error", TEXT, e.to_string()),
+    }
+}
+
+/// Reads `/substitutes/<id>.ics?token=…` or `/organizations/<id>.ics?token=…`.
+fn route(target: &str) -> Option<(CalendarFeed, Option<&str>)> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let token = query.split('&').find_map(|pair| pair.strip_prefix("token="));
+    let (kind, file) = path.strip_prefix('/')?.split_once('/')?;
+    let id = file.strip_suffix(".ics").filter(|id| !id.is_empty() && !id.contains('/'))?;
+
+    let feed = match kind {
+        "substitutes" => CalendarFeed::Substitute(id.to_string()),
+        "organizations" => CalendarFeed::Organization(id.to_string()),
+        _ => return None,
+    };
+    Some((feed, token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::fixtures::{insert_class, insert_organization, insert_user, test_connection};
+    use chrono::FixedOffset;
+
+    /// Content lines with folding undone.
+    fn unfold(text: &str) -> Vec<String> {
+        text.replace("\r\n ", "").split("\r\n").map(str::to_string).collect()
+    }
+
+    fn seed(conn: &Connection) {
+        insert_organization(conn, "district");
+        insert_organization(conn, "north");
+        conn.execute_batch(
+            "UPDATE organizations SET parent_organization_id = 'district', name = 'North Elementary',
+                 address = '1 School Rd, Springfield' WHERE id = 'north';",
+        )
+        .unwrap();
+        insert_class(conn, "math", "north");
+        conn.execute("UPDATE classes SET room_number = '12' WHERE id = 'math'", []).unwrap();
+        insert_user(conn, "manager-1", "org_manager");
+        insert_user(conn, "sub-1", "substitute");
+        conn.execute_batch(
+            "INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time,
+                 reason, special_instructions, status, assigned_substitute_id, created_at, updated_at)
+             VALUES
+                 ('r1', 'math', 'manager-1', '2030-01-15', '08:00', '15:00', 'Sick', 'Keys at office; lesson plan, p.3',
+                  'filled', 'sub-1', '2030-01-01T00:00:00.000Z', '2030-01-01T00:01:40.000Z'),
+                 ('r2', 'math', 'manager-1', '2030-01-16', '08:00', '15:00', NULL, NULL, 'open', NULL,
+                  '2030-01-01T00:00:00.000Z', '2030-01-01T00:00:00.000Z'),
+                 ('r3', 'math', 'manager-1', '2030-01-17', '08:00', '15:00', NULL, NULL, 'cancelled', 'sub-1',
+                  '2030-01-01T00:00:00.000Z', '2030-01-02T00:00:00.000Z'),
+                 ('r4', 'math', 'manager-1', '2029-01-17', '08:00', '15:00', NULL, NULL, 'completed', 'sub-1',
+                  '2029-01-01T00:00:00.000Z', '2029-01-01T00:00:00.000Z');",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_substitute_calendar_has_stable_uids_and_utc_times() {
+        let conn = test_connection();
+        seed(&conn);
+        let now = Utc.with_ymd_and_hms(2030, 1, 10, 12, 0, 0).unwrap();
+        let eastern = FixedOffset::west_opt(5 * 3600).unwrap();
+
+        let (text, events) = build(&conn, &CalendarFeed::Substitute("sub-1".to_string()), now, &eastern)
+            .unwrap()
+            .unwrap();
+        assert_eq!(events, 2);
+        assert!(text.lines().all(|line| line.len() <= LINE_LIMIT + 1));
+        let lines = unfold(&text);
+        assert!(lines.contains(&"UID:r1@substitute-finder".to_string()));
+        assert!(lines.contains(&"DTSTART:20300115T130000Z".to_string()));
+        assert!(lines.contains(&"DTEND:20300115T200000Z".to_string()));
+        assert!(lines.contains(&"SEQUENCE:100".to_string()));
+        assert!(lines.contains(&"LOCATION:Room 12\\, North Elementary\\, 1 School Rd\\, Springfield".to_string()));
+        assert!(lines.contains(
+            &"DESCRIPTION:Class: math\\nOrganization: North Elementary\\nReason: Sick\\nSpecial instructions: \
+              Keys at office\\; lesson plan\\, p.3"
+                .to_string()
+        ));
+        assert!(lines.contains(&"STATUS:CANCELLED".to_string()));
+        assert!(!lines.contains(&"UID:r2@substitute-finder".to_string()));
+        assert!(!lines.contains(&"UID:r4@substitute-finder".to_string()));
+    }
+
+    #[test]
+    fn test_organization_calendar_covers_descendants() {
+        let conn = test_connection();
+        seed(&conn);
+        let now = Utc.with_ymd_and_hms(2030, 1, 10, 12, 0, 0).unwrap();
+
+        let (text, events) = build(&conn, &CalendarFeed::Organization("district".to_string()), now, &Utc)
+            .unwrap()
+            .unwrap();
+        assert_eq!(events, 3);
+        let lines = unfold(&text);
+        assert!(lines.contains(&"SUMMARY:math: sub-1\\, Test".to_string()));
+        assert!(lines.contains(&"SUMMARY:math: unfilled".to_string()));
+        assert!(lines.contains(&"STATUS:TENTATIVE".to_string()));
+        assert!(lines.contains(&"X-WR-CALNAME:Substitute requests: district".to_string()));
+
+        assert!(build(&conn, &CalendarFeed::Organization("missing".to_string()), now, &Utc)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_lines_fold_without_splitting_characters() {
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "é".repeat(60)));
+        let lines: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() <= LINE_LIMIT));
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(unfold(&out)[0], format!("SUMMARY:{}", "é".repeat(60)));
+    }
+
+    #[test]
+    fn test_route_reads_feed_and_token() {
+        let (feed, token) = route("/substitutes/sub-1.ics?token=abc").unwrap();
+        assert!(matches!(feed, CalendarFeed::Substitute(id) if id == "sub-1"));
+        assert_eq!(token, Some("abc"));
+        assert!(matches!(route("/organizations/org-1.ics"), Some((CalendarFeed::Organization(_), None))));
+        assert!(route("/teachers/t-1.ics").is_none());
+        assert!(route("/substitutes/.ics").is_none());
+        assert_eq!(
+            feed_url(8765, "abc", &CalendarFeed::Organization("org-1".to_string())),
+            "http://127.0.0.1:8765/organizations/org-1.ics?token=abc"
+        );
+    }
+}
@@ -0,0 +1,641 @@
+use crate::database::models::{
+    AbsenceStatus, CalendarImportIssue, CalendarImportReport, CalendarImportRequest, RegularTeacher,
+    ReportAbsenceRequest, SubstituteRequest, TeacherAbsence,
+};
+use crate::database::timestamp;
+use crate::repository::{AbsenceLinkRepository, RegularTeacherRepository, TeacherAbsenceRepository, UserRepository};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use rusqlite::Connection;
+
+const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// An event's start or end. Times are wall-clock times at the school.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum When {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+/// The VEVENT properties the import reads, with folding and escaping undone.
+#[derive(Debug, Default)]
+struct Event {
+    uid: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    status: Option<String>,
+    start: Option<When>,
+    end: Option<When>,
+    organizer: Option<String>,
+    attendees: Vec<String>,
+    /// Has an RRULE or is an override of one occurrence.
+    recurring: bool,
+    /// Why a property couldn't be read, reported instead of importing the event.
+    invalid: Option<String>,
+}
+
+/// What the import changed, for the caller to announce.
+pub struct Imported {
+    pub report: CalendarImportReport,
+    pub reported: Vec<TeacherAbsence>,
+    pub updated: Vec<TeacherAbsence>,
+    pub withdrawn: Vec<TeacherAbsence>,
+    pub created_requests: Vec<SubstituteRequest>,
+    /// Given new text, or kept filled and moved onto a rescheduled absence.
+    pub updated_requests: Vec<SubstituteRequest>,
+    pub cancelled_requests: Vec<SubstituteRequest>,
+}
+
+/// Reads the calendar and imports its events as teacher absences. Times in
+/// UTC are converted to the machine's time zone; times with a TZID are taken
+/// as they are, since shared school calendars use the school's own zone.
+pub fn run(conn: &Connection, request: &CalendarImportRequest) -> anyhow::Result<Imported> {
+    let text = read_source(&request.source)?;
+    import(conn, &text, &request.reported_by, Local::now().date_naive(), &Local)
+}
+
+fn read_source(source: &str) -> anyhow::Result<String> {
+    let source = source.trim();
+    let lower = source.to_ascii_lowercase();
+    let url = if lower.starts_with("webcal://") {
+        Some(format!("http://{}", &source["webcal://".len()..]))
+    } else if lower.starts_with("http://") || lower.starts_with("https://") {
+        Some(source.to_string())
+    } else {
+        None
+    };
+
+    match url {
+        Some(url) => {
+            let agent = ureq::AgentBuilder::new().timeout(FETCH_TIMEOUT).build();
+            Ok(agent.get(&url).call()?.into_string()?)
+        }
+        None => Ok(std::fs::read_to_string(source)?),
+    }
+}
+
+fn import<Tz: TimeZone>(
+    conn: &Connection,
+    text: &str,
+    reported_by: &str,
+    today: NaiveDate,
+    tz: &Tz,
+) -> anyhow::Result<Imported> {
+    let events = parse(text, tz)?;
+    let mut importer = Importer {
+        conn,
+        reported_by,
+        today,
+        imported: Imported {
+            report: CalendarImportReport {
+                total_events: events.len() as u64,
+                created: 0,
+                updated: 0,
+                cancelled: 0,
+                unchanged: 0,
+                skipped: Vec::new(),
+                errors: Vec::new(),
+            },
+            reported: Vec::new(),
+            updated: Vec::new(),
+            withdrawn: Vec::new(),
+            created_requests: Vec::new(),
+            updated_requests: Vec::new(),
+            cancelled_requests: Vec::new(),
+        },
+    };
+
+    for event in &events {
+        let issue = |message: String| CalendarImportIssue {
+            uid: event.uid.clone(),
+            summary: event.summary.clone(),
+            message,
+        };
+        match importer.event(event) {
+            Ok(Change::Created) => importer.imported.report.created += 1,
+            Ok(Change::Updated) => importer.imported.report.updated += 1,
+            Ok(Change::Cancelled) => importer.imported.report.cancelled += 1,
+            Ok(Change::Unchanged) => importer.imported.report.unchanged += 1,
+            Err(Rejection::Skipped(message)) => importer.imported.report.skipped.push(issue(message)),
+            Err(Rejection::Failed(message)) => importer.imported.report.errors.push(issue(message)),
+        }
+    }
+    Ok(importer.imported)
+}
+
+enum Change {
+    Created,
+    Updated,
+    Cancelled,
+    Unchanged,
+}
+
+enum Rejection {
+    /// Not something to import, such as an event for nobody on staff.
+    Skipped(String),
+    Failed(String),
+}
+
+impl From<anyhow::Error> for Rejection {
+    fn from(error: anyhow::Error) -> Self {
+        Rejection::Failed(error.to_string())
+    }
+}
+
+struct Importer<'a> {
+    conn: &'a Connection,
+    reported_by: &'a str,
+    today: NaiveDate,
+    imported: Imported,
+}
+
+impl Importer<'_> {
+    fn event(&mut self, event: &Event) -> Result<Change, Rejection> {
+        if let Some(message) = &event.invalid {
+            return Err(Rejection::Failed(message.clone()));
+        }
+        let Some(uid) = event.uid.as_deref() else {
+            return Err(Rejection::Failed("Event has no UID".to_string()));
+        };
+        if event.recurring {
+            return Err(Rejection::Skipped("Recurring events aren't imported".to_string()));
+        }
+
+        let links = AbsenceLinkRepository::new(self.conn);
+        let link = links.find_link(uid)?;
+        if event.status.as_deref().is_some_and(|status| status.eq_ignore_ascii_case("CANCELLED")) {
+            let Some(link) = link else {
+                return Err(Rejection::Skipped("Cancelled before it was imported".to_string()));
+            };
+            // Forgetting the fingerprint makes the event count as changed if
+            // it's reinstated, so it gets a new absence.
+            links.save_link(uid, &link.teacher_absence_id, "")?;
+            return self.withdraw(&link.teacher_absence_id);
+        }
+
+        let teacher = self.teacher(event)?;
+        let (start_date, end_date, times) = schedule(event)?;
+        if end_date < self.today {
+            return Err(Rejection::Skipped("Already over".to_string()));
+        }
+
+        // Who is away and when, then what the event says about it
+        let schedule_fingerprint = [
+            teacher.id.clone(),
+            timestamp::format_date(&start_date),
+            timestamp::format_date(&end_date),
+            times
+                .map(|(start, end)| format!("{}-{}", timestamp::format_time(&start), timestamp::format_time(&end)))
+                .unwrap_or_default(),
+        ]
+        .join("\u{1f}");
+        let fingerprint = [
+            schedule_fingerprint.clone(),
+            event.summary.clone().unwrap_or_default(),
+            event.description.clone().unwrap_or_default(),
+        ]
+        .join("\u{1e}");
+        if link.as_ref().is_some_and(|link| link.fingerprint == fingerprint) {
+            return Ok(Change::Unchanged);
+        }
+
+        // A new summary or description is applied to the active absence in
+        // place, keeping whoever fills its days.
+        let absences = TeacherAbsenceRepository::new(self.conn);
+        if let Some(link) = &link {
+            let same_schedule = link.fingerprint.split('\u{1e}').next() == Some(schedule_fingerprint.as_str());
+            let active = absences
+                .find_by_id(&link.teacher_absence_id)?
+                .is_some_and(|absence| absence.status == AbsenceStatus::Active);
+            if same_schedule && active {
+                if let Some(updated) = absences.update_details(
+                    &link.teacher_absence_id,
+                    event.summary.clone(),
+                    event.description.clone(),
+                )? {
+                    links.save_link(uid, &link.teacher_absence_id, &fingerprint)?;
+                    self.imported.updated.push(updated.teacher_absence);
+                    self.imported.updated_requests.extend(updated.requests);
+                    return Ok(Change::Updated);
+                }
+            }
+        }
+
+        // The replacement is reported before the old absence is withdrawn,
+        // so an update that can't be applied leaves the old one in place.
+        // Days still on the same class and times keep their substitute.
+        let reported = absences.report(
+            self.reported_by.to_string(),
+            ReportAbsenceRequest {
+                teacher_id: teacher.id,
+                start_date,
+                end_date,
+                start_time: times.map(|(start, _)| start),
+                end_time: times.map(|(_, end)| end),
+                reason: event.summary.clone(),
+                special_instructions: event.description.clone(),
+            },
+        )?;
+        let (moved, replaced) = match &link {
+            Some(link) => absences.carry_over_filled(&link.teacher_absence_id, &reported.teacher_absence.id)?,
+            None => (Vec::new(), Vec::new()),
+        };
+        links.save_link(uid, &reported.teacher_absence.id, &fingerprint)?;
+        self.imported.reported.push(reported.teacher_absence);
+        self.imported
+            .created_requests
+            .extend(reported.requests.into_iter().filter(|request| !replaced.contains(&request.id)));
+        self.imported.updated_requests.extend(moved);
+
+        match link {
+            Some(link) => {
+                self.withdraw(&link.teacher_absence_id)?;
+                Ok(Change::Updated)
+            }
+            None => Ok(Change::Created),
+        }
+    }
+
+    /// Cancels the imported absence if it's still active.
+    fn withdraw(&mut self, teacher_absence_id: &str) -> Result<Change, Rejection> {
+        let absences = TeacherAbsenceRepository::new(self.conn);
+        let Some(absence) = absences.find_by_id(teacher_absence_id)? else {
+            return Ok(Change::Unchanged);
+        };
+        if absence.status == AbsenceStatus::Cancelled {
+            return Ok(Change::Unchanged);
+        }
+
+        let cancelled = absences.cancel(teacher_absence_id)?.unwrap_or_default();
+        self.imported.cancelled_requests.extend(cancelled);
+        if let Some(absence) = absences.find_by_id(teacher_absence_id)? {
+            self.imported.withdrawn.push(absence);
+        }
+        Ok(Change::Cancelled)
+    }
+
+    /// The one regular teacher among the organizer and attendees.
+    fn teacher(&self, event: &Event) -> Result<RegularTeacher, Rejection> {
+        let users = UserRepository::new(self.conn);
+        let teachers = RegularTeacherRepository::new(self.conn);
+        let mut found: Vec<(String, RegularTeacher)> = Vec::new();
+        for email in event.organizer.iter().chain(&event.attendees) {
+            let Some(user) = users.find_by_email(email)? else {
+                continue;
+            };
+            if let Some(teacher) = teachers.find_by_user(&user.id)? {
+                if !found.iter().any(|(_, known)| known.id == teacher.id) {
+                    found.push((email.clone(), teacher));
+                }
+            }
+        }
+
+        match found.len() {
+            0 => Err(Rejection::Skipped(
+                "Neither the organizer nor any attendee is a regular teacher".to_string(),
+            )),
+            1 => Ok(found.remove(0).1),
+            _ => Err(Rejection::Failed(format!(
+                "Several regular teachers are on the event: {}",
+                found.iter().map(|(email, _)| email.as_str()).collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+}
+
+/// First and last day of an absence, and its hours when it's part of one day.
+type Schedule = (NaiveDate, NaiveDate, Option<(NaiveTime, NaiveTime)>);
+
+/// All-day and multi-day events use school hours.
+fn schedule(event: &Event) -> anyhow::Result<Schedule> {
+    let Some(start) = event.start else {
+        anyhow::bail!("Event has no DTSTART");
+    };
+    match (start, event.end) {
+        // DTEND of an all-day event is the day after it ends.
+        (When::Date(start), None) => Ok((start, start, None)),
+        (When::Date(start), Some(When::Date(end))) => {
+            let last = end - Duration::days(1);
+            if last < start {
+                anyhow::bail!("Event ends before it starts");
+            }
+            Ok((start, last, None))
+        }
+        (When::DateTime(start), Some(When::DateTime(end))) => {
+            if end <= start {
+                anyhow::bail!("Event ends before it starts");
+            }
+            if start.date() == end.date() {
+                Ok((start.date(), start.date(), Some((start.time(), end.time()))))
+            } else if end.time() == NaiveTime::MIN {
+                Ok((start.date(), end.date() - Duration::days(1), None))
+            } else {
+                Ok((start.date(), end.date(), None))
+            }
+        }
+        (When::DateTime(_), None) => anyhow::bail!("Timed event has no DTEND"),
+        _ => anyhow::bail!("DTSTART and DTEND must both be dates or both be times"),
+    }
+}
+
+/// Reads every VEVENT, ignoring nested components such as alarms.
+fn parse<Tz: TimeZone>(text: &str, tz: &Tz) -> anyhow::Result<Vec<Event>> {
+    let lines = unfold(text);
+    if !lines.first().is_some_and(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        anyhow::bail!("Not an iCalendar file");
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<Event> = None;
+    let mut nested = 0;
+    for line in &lines {
+        let Some((name, params, value)) = property(line) else {
+            continue;
+        };
+        match (name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => current = Some(Event::default()),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => events.extend(current.take()),
+            (_, Some(event)) if nested == 0 => read_property(event, &name, &params, value, tz),
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+fn read_property<Tz: TimeZone>(event: &mut Event, name: &str, params: &[(String, String)], value: &str, tz: &Tz) {
+    let text = || Some(unescape(value)).filter(|text| !text.trim().is_empty());
+    match name {
+        "UID" => event.uid = text(),
+        "SUMMARY" => event.summary = text(),
+        "DESCRIPTION" => event.description = text(),
+        "STATUS" => event.status = text(),
+        "ORGANIZER" => event.organizer = mailto(value),
+        "ATTENDEE" => event.attendees.extend(mailto(value)),
+        "RRULE" | "RECURRENCE-ID" => event.recurring = true,
+        "DTSTART" | "DTEND" => match when(value, params, tz) {
+            Ok(when) if name == "DTSTART" => event.start = Some(when),
+            Ok(when) => event.end = Some(when),
+            Err(e) => event.invalid = Some(format!("{}: {}", name, e)),
+        },
+        _ => {}
+    }
+}
+
+/// Joins continuation lines, which start with a space or tab (RFC 5545 §3.1).
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Upper-cased parameter names with their unquoted values.
+type Params = Vec<(String, String)>;
+
+/// Splits a content line into its upper-cased name, parameters and value.
+fn property(line: &str) -> Option<(String, Params, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, ch)| {
+        match ch {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some(index),
+            _ => {}
+        }
+        None
+    })?;
+
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, value))
+}
+
+fn unescape(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            text.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push('\n'),
+            Some(escaped) => text.push(escaped),
+            None => text.push('\\'),
+        }
+    }
+    text
+}
+
+fn mailto(value: &str) -> Option<String> {
+    let value = value.trim();
+    value
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        .map(|_| value[7..].trim().to_string())
+        .filter(|email| !email.is_empty())
+}
+
+fn when<Tz: TimeZone>(value: &str, params: &[(String, String)], tz: &Tz) -> anyhow::Result<When> {
+    let value = value.trim();
+    let is_date = params.iter().any(|(key, kind)| key == "VALUE" && kind.eq_ignore_ascii_case("DATE"));
+    if is_date || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map(When::Date)
+            .map_err(|_| anyhow::anyhow!("Invalid date {:?}", value));
+    }
+
+    let (local, utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(local) => (local, true),
+        None => (value, false),
+    };
+    let parsed = NaiveDateTime::parse_from_str(local, "%Y%m%dT%H%M%S")
+        .map_err(|_| anyhow::anyhow!("Invalid date-time {:?}", value))?;
+    if utc {
+        Ok(When::DateTime(Utc.from_utc_datetime(&parsed).with_timezone(tz).naive_local()))
+    } else {
+        Ok(When::DateTime(parsed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::RequestStatus;
+    use crate::repository::fixtures::{future_date, insert_class, insert_organization, insert_user, test_connection};
+    use crate::repository::SubstituteRequestRepository;
+    use chrono::{Datelike, FixedOffset};
+
+    fn date(value: &str) -> NaiveDate {
+        future_date(value)
+    }
+
+    fn fixture() -> Connection {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        conn.execute(
+            "UPDATE organizations SET school_start_time = '08:00', school_end_time = '15:00' WHERE id = 'org-1'",
+            [],
+        )
+        .unwrap();
+        insert_class(&conn, "math", "org-1");
+        insert_class(&conn, "science", "org-1");
+        insert_user(&conn, "secretary", "org_manager");
+        insert_user(&conn, "jane", "org_manager");
+        conn.execute_batch(
+            "INSERT INTO regular_teachers (id, user_id, organization_id) VALUES ('teacher-1', 'jane', 'org-1');
+             INSERT INTO teacher_class_assignments (id, teacher_id, class_id) VALUES ('a-1', 'teacher-1', 'math');
+             INSERT INTO teacher_class_assignments (id, teacher_id, class_id) VALUES ('a-2', 'teacher-1', 'science');",
+        )
+        .unwrap();
+        conn
+    }
+
+    /// Wraps the events in a calendar, moving their 2029 and 2030 dates as
+    /// far as [`future_date`] moves them.
+    fn calendar(events: &[&str]) -> String {
+        let years = future_date("2030-01-01").year() - 2030;
+        let events = events
+            .concat()
+            .replace("2029", &(2029 + years).to_string())
+            .replace("2030", &(2030 + years).to_string());
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)
+    }
+
+    fn run_import(conn: &Connection, text: &str) -> Imported {
+        import(conn, text, "secretary", date("2030-01-01"), &Utc).unwrap()
+    }
+
+    const CONFERENCE: &str = "BEGIN:VEVENT\r\nUID:leave-1@example.com\r\nSUMMARY:Conference\\, day 1\r\n\
+        DTSTART;VALUE=DATE:20300114\r\nDTEND;VALUE=DATE:20300116\r\n\
+        ORGANIZER;CN=\"Office: Main\":mailto:secretary@example.com\r\nATTENDEE;CN=Jane:MAILTO:JANE@example.com\r\n\
+        DESCRIPTION:Plans are in\r\n  the top drawer\r\nBEGIN:VALARM\r\nDESCRIPTION:Reminder\r\nEND:VALARM\r\n\
+        END:VEVENT\r\n";
+
+    #[test]
+    fn test_import_is_idempotent_on_uid() {
+        let conn = fixture();
+
+        let imported = run_import(&conn, &calendar(&[CONFERENCE]));
+        assert_eq!(imported.report.created, 1);
+        assert!(imported.report.errors.is_empty(), "{:?}", imported.report.errors);
+        assert_eq!(imported.created_requests.len(), 4);
+        let absence = &imported.reported[0];
+        assert_eq!(absence.teacher_id, "teacher-1");
+        assert_eq!(absence.reported_by, "secretary");
+        assert_eq!(absence.end_date, date("2030-01-15"));
+        assert_eq!(absence.reason.as_deref(), Some("Conference, day 1"));
+        assert_eq!(
+            imported.created_requests[0].special_instructions.as_deref(),
+            Some("Plans are in the top drawer")
+        );
+
+        let again = run_import(&conn, &calendar(&[CONFERENCE]));
+        assert_eq!(again.report.unchanged, 1);
+        assert!(again.created_requests.is_empty());
+        assert_eq!(TeacherAbsenceRepository::new(&conn).list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_updated_and_cancelled_events_replace_and_cancel_absences() {
+        let conn = fixture();
+        let first = run_import(&conn, &calendar(&[CONFERENCE]));
+        let old_id = first.reported[0].id.clone();
+
+        let moved = CONFERENCE.replace("20300116", "20300115");
+        let updated = run_import(&conn, &calendar(&[&moved]));
+        assert_eq!(updated.report.updated, 1);
+        assert_eq!(updated.created_requests.len(), 2);
+        assert_eq!(updated.cancelled_requests.len(), 4);
+        let absences = TeacherAbsenceRepository::new(&conn);
+        assert_eq!(absences.find_by_id(&old_id).unwrap().unwrap().status, AbsenceStatus::Cancelled);
+
+        let cancelled = moved.replace("END:VEVENT", "STATUS:CANCELLED\r\nEND:VEVENT");
+        let withdrawn = run_import(&conn, &calendar(&[&cancelled]));
+        assert_eq!(withdrawn.report.cancelled, 1);
+        assert_eq!(withdrawn.withdrawn.len(), 1);
+        let requests = SubstituteRequestRepository::new(&conn).list().unwrap();
+        assert!(requests.iter().all(|r| matches!(r.status, RequestStatus::Cancelled)));
+
+        assert_eq!(run_import(&conn, &calendar(&[&cancelled])).report.unchanged, 1);
+
+        let reinstated = run_import(&conn, &calendar(&[&moved]));
+        assert_eq!(reinstated.report.updated, 1);
+        assert_eq!(reinstated.created_requests.len(), 2);
+    }
+
+    #[test]
+    fn test_edits_keep_filled_days_that_still_match() {
+        let conn = fixture();
+        insert_user(&conn, "sub-1", "substitute");
+        let first = run_import(&conn, &calendar(&[CONFERENCE]));
+        let id = first.reported[0].id.clone();
+        let absences = TeacherAbsenceRepository::new(&conn);
+        absences.accept(&id, "sub-1", Some(date("2030-01-14"))).unwrap();
+
+        // New text only: same absence, still filled
+        let renamed = CONFERENCE.replace("Conference\\, day 1", "Regional conference");
+        let updated = run_import(&conn, &calendar(&[&renamed]));
+        assert_eq!(updated.report.updated, 1);
+        assert!(updated.created_requests.is_empty() && updated.cancelled_requests.is_empty());
+        assert_eq!(updated.updated[0].id, id);
+        assert_eq!(updated.updated[0].reason.as_deref(), Some("Regional conference"));
+        assert_eq!(updated.updated_requests.len(), 4);
+        assert!(updated.updated_requests.iter().all(|r| r.reason.as_deref() == Some("Regional conference")));
+        assert_eq!(
+            updated.updated_requests.iter().filter(|r| matches!(r.status, RequestStatus::Filled)).count(),
+            2
+        );
+
+        // Shortened to the filled day: its requests move to the new absence
+        let shortened = renamed.replace("20300116", "20300115");
+        let replaced = run_import(&conn, &calendar(&[&shortened]));
+        assert_eq!(replaced.report.updated, 1);
+        assert!(replaced.created_requests.is_empty());
+        assert_eq!(replaced.cancelled_requests.len(), 2);
+        assert_eq!(replaced.updated_requests.len(), 2);
+        let kept = absences.find_with_requests(&replaced.reported[0].id).unwrap().unwrap().requests;
+        assert_eq!(kept.len(), 2);
+        assert!(kept.iter().all(|r| r.assigned_substitute_id.as_deref() == Some("sub-1")));
+        assert!(kept.iter().all(|r| matches!(r.status, RequestStatus::Filled)));
+    }
+
+    #[test]
+    fn test_timed_events_use_their_hours_and_unknown_people_are_skipped() {
+        let conn = fixture();
+        let timed = "BEGIN:VEVENT\r\nUID:leave-2\r\nSUMMARY:Dentist\r\nDTSTART:20300114T140000Z\r\n\
+            DTEND:20300114T170000Z\r\nORGANIZER:mailto:jane@example.com\r\nEND:VEVENT\r\n";
+        let stranger = "BEGIN:VEVENT\r\nUID:leave-3\r\nDTSTART;VALUE=DATE:20300114\r\n\
+            ORGANIZER:mailto:nobody@example.com\r\nEND:VEVENT\r\n";
+        let past = "BEGIN:VEVENT\r\nUID:leave-4\r\nDTSTART;VALUE=DATE:20291214\r\n\
+            ORGANIZER:mailto:jane@example.com\r\nEND:VEVENT\r\n";
+        let eastern = FixedOffset::west_opt(5 * 3600).unwrap();
+
+        let imported = import(&conn, &calendar(&[timed, stranger, past]), "secretary", date("2030-01-01"), &eastern)
+            .unwrap();
+        assert_eq!(imported.report.total_events, 3);
+        assert_eq!(imported.report.created, 1);
+        assert_eq!(imported.report.skipped.len(), 2);
+        let request = &imported.created_requests[0];
+        assert_eq!(timestamp::format_time(&request.start_time), "09:00");
+        assert_eq!(timestamp::format_time(&request.end_time), "12:00");
+    }
+
+    #[test]
+    fn test_parse_rejects_other_files() {
+        assert!(parse("name,date\n", &Utc).is_err());
+        let events = parse(&calendar(&[CONFERENCE]), &Utc).unwrap();
+        assert_eq!(events[0].organizer.as_deref(), Some("secretary@example.com"));
+        assert_eq!(events[0].attendees, vec!["JANE@example.com".to_string()]);
+    }
+}
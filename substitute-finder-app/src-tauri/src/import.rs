@@ -0,0 +1,568 @@
+use crate::database::models::{
+    Class, CreateClassRequest, CreateOrganizationRequest, CreateUserRequest, ImportKind, ImportReport, ImportRequest,
+    ImportRowError, Organization, User, UserRole,
+};
+use crate::database::timestamp;
+use crate::repository::{ClassRepository, OrganizationRepository, UserRepository};
+use rusqlite::Connection;
+use std::collections::{BTreeMap, HashSet};
+
+/// A value an import reads from each row. Required fields need a column and
+/// a value on every row.
+struct Field {
+    key: &'static str,
+    required: bool,
+}
+
+const fn field(key: &'static str, required: bool) -> Field {
+    Field { key, required }
+}
+
+const ORGANIZATION_FIELDS: &[Field] = &[
+    field("name", true),
+    field("parent", false),
+    field("description", false),
+    field("contact_email", false),
+    field("contact_phone", false),
+    field("address", false),
+    field("school_start_time", false),
+    field("school_end_time", false),
+];
+
+const USER_FIELDS: &[Field] = &[
+    field("username", true),
+    field("email", true),
+    field("first_name", true),
+    field("last_name", true),
+    field("role", true),
+    field("organization", false),
+    field("password", false),
+];
+
+const SUBSTITUTE_FIELDS: &[Field] = &[
+    field("username", true),
+    field("email", true),
+    field("first_name", true),
+    field("last_name", true),
+    field("organization", false),
+    field("password", false),
+    field("subjects", false),
+    field("hourly_rate", false),
+];
+
+const CLASS_FIELDS: &[Field] = &[
+    field("name", true),
+    field("organization", true),
+    field("subject", false),
+    field("grade_level", false),
+    field("room_number", false),
+    field("description", false),
+];
+
+fn fields(kind: ImportKind) -> &'static [Field] {
+    match kind {
+        ImportKind::Organizations => ORGANIZATION_FIELDS,
+        ImportKind::Users => USER_FIELDS,
+        ImportKind::Substitutes => SUBSTITUTE_FIELDS,
+        ImportKind::Classes => CLASS_FIELDS,
+    }
+}
+
+/// Headers match field keys ignoring case and with spaces or dashes read as
+/// underscores, so "First Name" fills `first_name`.
+fn normalize(header: &str) -> String {
+    header.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+/// One row of the file, keyed by field. Blank cells are left out.
+struct Record {
+    line: u64,
+    values: BTreeMap<&'static str, String>,
+}
+
+impl Record {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    fn required(&self, key: &'static str) -> Result<&str, RowError> {
+        self.get(key).ok_or_else(|| RowError::invalid(key, format!("{} is required", key)))
+    }
+}
+
+fn read(path: &str, kind: ImportKind, mapping: &BTreeMap<String, String>) -> anyhow::Result<Vec<Record>> {
+    let fields = fields(kind);
+    if let Some(key) = mapping.keys().find(|key| !fields.iter().any(|field| field.key == key.as_str())) {
+        anyhow::bail!("Unknown import field: {}", key);
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)?;
+    let headers: Vec<String> = reader.headers()?.iter().map(normalize).collect();
+
+    let mut columns = Vec::new();
+    for field in fields {
+        let header = mapping.get(field.key).map(String::as_str).unwrap_or(field.key);
+        match headers.iter().position(|candidate| *candidate == normalize(header)) {
+            Some(index) => columns.push((field.key, index)),
+            None if mapping.contains_key(field.key) => anyhow::bail!("Column not found: {}", header),
+            None if field.required => anyhow::bail!("No column for required field: {}", field.key),
+            None => {}
+        }
+    }
+
+    let mut records = Vec::new();
+    for row in reader.records() {
+        let row = row?;
+        let values = columns
+            .iter()
+            .filter_map(|(key, index)| {
+                row.get(*index)
+                    .filter(|value| !value.is_empty())
+                    .map(|value| (*key, value.to_string()))
+            })
+            .collect();
+        records.push(Record { line: row.position().map_or(0, |position| position.line()), values });
+    }
+    Ok(records)
+}
+
+struct RowError {
+    field: Option<&'static str>,
+    message: String,
+}
+
+impl RowError {
+    fn invalid(field: &'static str, message: impl Into<String>) -> Self {
+        RowError { field: Some(field), message: message.into() }
+    }
+}
+
+impl From<anyhow::Error> for RowError {
+    fn from(error: anyhow::Error) -> Self {
+        RowError { field: None, message: error.to_string() }
+    }
+}
+
+/// Whether a row created a record or updated the one its natural key matched.
+enum Outcome<T> {
+    Created(T),
+    Updated(T),
+}
+
+/// Records an import saved, split by whether their row created or updated them.
+pub struct Changes<T> {
+    pub created: Vec<T>,
+    pub updated: Vec<T>,
+}
+
+impl<T> Default for Changes<T> {
+    fn default() -> Self {
+        Changes { created: Vec::new(), updated: Vec::new() }
+    }
+}
+
+impl<T> Changes<T> {
+    fn push(&mut self, outcome: Outcome<T>, report: &mut ImportReport) {
+        match outcome {
+            Outcome::Created(record) => {
+                report.created += 1;
+                self.created.push(record);
+            }
+            Outcome::Updated(record) => {
+                report.updated += 1;
+                self.updated.push(record);
+            }
+        }
+    }
+}
+
+/// What the import changed, for the caller to announce. Dry runs and imports
+/// with errors save nothing, so their changes are empty.
+pub struct Imported {
+    pub report: ImportReport,
+    pub organizations: Changes<Organization>,
+    pub users: Changes<User>,
+    pub classes: Changes<Class>,
+}
+
+/// Reads the file and checks every row, saving the rows in one transaction
+/// when none has an error and the request isn't a dry run. Rows are applied
+/// as they are checked, so later rows see earlier ones, and a dry run rolls
+/// them back.
+pub fn run(conn: &Connection, request: &ImportRequest) -> anyhow::Result<Imported> {
+    let records = read(&request.path, request.kind, &request.mapping)?;
+    let mut report = ImportReport {
+        kind: request.kind,
+        dry_run: request.dry_run,
+        applied: false,
+        total_rows: u32::try_from(records.len())?,
+        created: 0,
+        updated: 0,
+        errors: Vec::new(),
+    };
+
+    let mut organizations = Changes::default();
+    let mut users = Changes::default();
+    let mut classes = Changes::default();
+
+    let tx = conn.unchecked_transaction()?;
+    let mut seen = HashSet::new();
+    for record in &records {
+        let result = match request.kind {
+            ImportKind::Organizations => {
+                import_organization(conn, record, &mut seen).map(|outcome| organizations.push(outcome, &mut report))
+            }
+            ImportKind::Users | ImportKind::Substitutes => {
+                import_user(conn, request.kind, record, &mut seen).map(|outcome| users.push(outcome, &mut report))
+            }
+            ImportKind::Classes => {
+                import_class(conn, record, &mut seen).map(|outcome| classes.push(outcome, &mut report))
+            }
+        };
+        if let Err(error) = result {
+            report.errors.push(ImportRowError {
+                line: record.line,
+                field: error.field.map(str::to_string),
+                message: error.message,
+            });
+        }
+    }
+
+    if request.dry_run || !report.errors.is_empty() {
+        tx.rollback()?;
+        return Ok(Imported {
+            report,
+            organizations: Changes::default(),
+            users: Changes::default(),
+            classes: Changes::default(),
+        });
+    }
+    tx.commit()?;
+    report.applied = true;
+    Ok(Imported { report, organizations, users, classes })
+}
+
+/// Fails on a natural key already used by an earlier row of the file.
+fn claim(seen: &mut HashSet<String>, field: &'static str, key: String, value: &str) -> Result<(), RowError> {
+    if seen.insert(key) {
+        Ok(())
+    } else {
+        Err(RowError::invalid(field, format!("Duplicate {} in file: {}", field, value)))
+    }
+}
+
+/// An organization named by id or, failing that, by name.
+fn resolve_organization(conn: &Connection, field: &'static str, value: &str) -> Result<String, RowError> {
+    let organizations = OrganizationRepository::new(conn);
+    if let Some(organization) = organizations.find_by_id(value)? {
+        return Ok(organization.id);
+    }
+    let mut matches = organizations.find_by_name(value)?;
+    match matches.len() {
+        0 => Err(RowError::invalid(field, format!("Unknown organization: {}", value))),
+        1 => Ok(matches.remove(0).id),
+        _ => Err(RowError::invalid(field, format!("Organization name is ambiguous, use its id: {}", value))),
+    }
+}
+
+fn optional_time(record: &Record, field: &'static str) -> Result<Option<chrono::NaiveTime>, RowError> {
+    record
+        .get(field)
+        .map(|value| {
+            timestamp::parse_time(value).map_err(|_| RowError::invalid(field, format!("Invalid time: {}", value)))
+        })
+        .transpose()
+}
+
+/// Matched by name. Blank cells keep an existing organization's values.
+fn import_organization(
+    conn: &Connection,
+    record: &Record,
+    seen: &mut HashSet<String>,
+) -> Result<Outcome<Organization>, RowError> {
+    let organizations = OrganizationRepository::new(conn);
+    let name = record.required("name")?;
+    claim(seen, "name", name.to_lowercase(), name)?;
+    let parent_id = record.get("parent").map(|value| resolve_organization(conn, "parent", value)).transpose()?;
+    let school_start_time = optional_time(record, "school_start_time")?;
+    let school_end_time = optional_time(record, "school_end_time")?;
+
+    let mut existing = organizations.find_by_name(name)?;
+    if existing.len() > 1 {
+        return Err(RowError::invalid("name", format!("Organization name is ambiguous: {}", name)));
+    }
+    let text = |key: &str| record.get(key).map(str::to_string);
+    match existing.pop() {
+        Some(current) => {
+            if parent_id.as_deref() == Some(current.id.as_str()) {
+                return Err(RowError::invalid("parent", "An organization can't be its own parent"));
+            }
+            let organization = organizations.update(
+                &current.id,
+                CreateOrganizationRequest {
+                    name: name.to_string(),
+                    parent_organization_id: parent_id.or(current.parent_organization_id),
+                    description: text("description").or(current.description),
+                    contact_email: text("contact_email").or(current.contact_email),
+                    contact_phone: text("contact_phone").or(current.contact_phone),
+                    address: text("address").or(current.address),
+                    school_start_time: school_start_time.or(current.school_start_time),
+                    school_end_time: school_end_time.or(current.school_end_time),
+                },
+            )?;
+            Ok(Outcome::Updated(organization.ok_or_else(|| anyhow::anyhow!("Organization not found"))?))
+        }
+        None => {
+            let organization = organizations.create(CreateOrganizationRequest {
+                name: name.to_string(),
+                parent_organization_id: parent_id,
+                description: text("description"),
+                contact_email: text("contact_email"),
+                contact_phone: text("contact_phone"),
+                address: text("address"),
+                school_start_time,
+                school_end_time,
+            })?;
+            Ok(Outcome::Created(organization))
+        }
+    }
+}
+
+/// Matched by username. Existing users keep their password; new ones need
+/// one. Substitute imports also set the substitute's subjects, separated by
+/// semicolons, and hourly rate.
+fn import_user(
+    conn: &Connection,
+    kind: ImportKind,
+    record: &Record,
+    seen: &mut HashSet<String>,
+) -> Result<Outcome<User>, RowError> {
+    let users = UserRepository::new(conn);
+    let username = record.required("username")?;
+    let email = record.required("email")?;
+    if !email.contains('@') {
+        return Err(RowError::invalid("email", format!("Invalid email: {}", email)));
+    }
+    claim(seen, "username", format!("username:{}", username), username)?;
+    claim(seen, "email", format!("email:{}", email.to_lowercase()), email)?;
+    let role = match kind {
+        ImportKind::Substitutes => UserRole::Substitute,
+        _ => record
+            .required("role")?
+            .parse()
+            .map_err(|error: anyhow::Error| RowError::invalid("role", error.to_string()))?,
+    };
+    let organization_id = record
+        .get("organization")
+        .map(|value| resolve_organization(conn, "organization", value))
+        .transpose()?;
+    let hourly_rate = record
+        .get("hourly_rate")
+        .map(|value| match value.parse::<f64>() {
+            Ok(rate) if rate >= 0.0 => Ok(rate),
+            _ => Err(RowError::invalid("hourly_rate", format!("Invalid hourly rate: {}", value))),
+        })
+        .transpose()?;
+    let subjects: Option<Vec<String>> = record.get("subjects").map(|value| {
+        value
+            .split(';')
+            .map(str::trim)
+            .filter(|subject| !subject.is_empty())
+            .map(str::to_string)
+            .collect()
+    });
+
+    let existing = users.find_by_username(username)?;
+    if let Some(owner) = users.find_by_email(email)? {
+        if existing.as_ref().map(|user| &user.id) != Some(&owner.id) {
+            return Err(RowError::invalid("email", format!("Email belongs to another user: {}", email)));
+        }
+    }
+
+    let mut request = CreateUserRequest {
+        username: username.to_string(),
+        password: String::new(),
+        email: email.to_string(),
+        first_name: record.required("first_name")?.to_string(),
+        last_name: record.required("last_name")?.to_string(),
+        role,
+        organization_id,
+    };
+    let outcome = match existing {
+        Some(current) => {
+            if matches!(kind, ImportKind::Substitutes) && !matches!(current.role, UserRole::Substitute) {
+                let message = format!("{} is a {}, not a substitute", username, current.role);
+                return Err(RowError::invalid("username", message));
+            }
+            request.organization_id = request.organization_id.or(current.organization_id);
+            let user = users.update(&current.id, request)?.ok_or_else(|| anyhow::anyhow!("User not found"))?;
+            Outcome::Updated(user)
+        }
+        None => {
+            request.password = record
+                .get("password")
+                .ok_or_else(|| RowError::invalid("password", "password is required for new users"))?
+                .to_string();
+            Outcome::Created(users.create(request)?)
+        }
+    };
+
+    if matches!(kind, ImportKind::Substitutes) {
+        let (Outcome::Created(user) | Outcome::Updated(user)) = &outcome;
+        users.save_substitute_profile(&user.id, subjects.as_deref(), hourly_rate)?;
+    }
+    Ok(outcome)
+}
+
+/// Matched by organization and name. Blank cells keep an existing class's
+/// values.
+fn import_class(conn: &Connection, record: &Record, seen: &mut HashSet<String>) -> Result<Outcome<Class>, RowError> {
+    let classes = ClassRepository::new(conn);
+    let name = record.required("name")?;
+    let organization_id = resolve_organization(conn, "organization", record.required("organization")?)?;
+    claim(seen, "name", format!("{}:{}", organization_id, name.to_lowercase()), name)?;
+
+    let text = |key: &str| record.get(key).map(str::to_string);
+    match classes.find_by_name(&organization_id, name)? {
+        Some(current) => {
+            let class = classes.update(
+                &current.id,
+                CreateClassRequest {
+                    name: name.to_string(),
+                    organization_id,
+                    subject: text("subject").or(current.subject),
+                    grade_level: text("grade_level").or(current.grade_level),
+                    room_number: text("room_number").or(current.room_number),
+                    description: text("description").or(current.description),
+                },
+            )?;
+            Ok(Outcome::Updated(class.ok_or_else(|| anyhow::anyhow!("Class not found"))?))
+        }
+        None => {
+            let class = classes.create(CreateClassRequest {
+                name: name.to_string(),
+                organization_id,
+                subject: text("subject"),
+                grade_level: text("grade_level"),
+                room_number: text("room_number"),
+                description: text("description"),
+            })?;
+            Ok(Outcome::Created(class))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::fixtures::{insert_organization, insert_user, test_connection};
+
+    fn import(conn: &Connection, kind: ImportKind, csv: &str, dry_run: bool) -> anyhow::Result<ImportReport> {
+        import_changes(conn, kind, csv, dry_run).map(|imported| imported.report)
+    }
+
+    fn import_changes(conn: &Connection, kind: ImportKind, csv: &str, dry_run: bool) -> anyhow::Result<Imported> {
+        let mapping = match kind {
+            ImportKind::Users => BTreeMap::from([("email".to_string(), "E-mail Address".to_string())]),
+            _ => BTreeMap::new(),
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("import.csv");
+        std::fs::write(&path, csv).unwrap();
+        run(
+            conn,
+            &ImportRequest {
+                kind,
+                path: path.to_string_lossy().to_string(),
+                mapping,
+                dry_run,
+            },
+        )
+    }
+
+    fn user_count(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_users_dry_run_reports_row_errors_and_import_upserts() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_user(&conn, "jdoe", "org_manager");
+
+        let csv = "Username,E-mail Address,First Name,Last Name,Role,Organization,Password
+jdoe,jdoe@example.com,Jane,Doe,admin,,
+asmith,asmith@example.com,Al,Smith,substitute,org-1,secret
+asmith,other@example.com,Al,Smith,substitute,org-1,secret
+bkim,jdoe@example.com,Bo,Kim,teacher,Nowhere,secret
+";
+        let report = import(&conn, ImportKind::Users, csv, true).unwrap();
+        assert!(!report.applied);
+        assert_eq!((report.total_rows, report.created, report.updated), (4, 1, 1));
+        let errors: Vec<_> = report.errors.iter().map(|e| (e.line, e.field.as_deref())).collect();
+        assert_eq!(errors, vec![(4, Some("username")), (5, Some("email"))]);
+        assert_eq!(user_count(&conn), 1);
+
+        assert!(import_changes(&conn, ImportKind::Users, csv, true).unwrap().users.created.is_empty());
+
+        let first_rows = csv.lines().take(3).collect::<Vec<_>>().join("\n");
+        let imported = import_changes(&conn, ImportKind::Users, &first_rows, false).unwrap();
+        let report = &imported.report;
+        assert!(report.applied && report.errors.is_empty());
+        assert_eq!((report.created, report.updated), (1, 1));
+        assert_eq!(imported.users.created[0].username, "asmith");
+        assert_eq!(imported.users.updated[0].username, "jdoe");
+        let jdoe = UserRepository::new(&conn).find_by_username("jdoe").unwrap().unwrap();
+        assert!(matches!(jdoe.role, UserRole::Admin));
+        assert_eq!(jdoe.first_name, "Jane");
+        assert_eq!(jdoe.password_hash, "hashed_password");
+
+        // A row without a password for a new user fails the whole import
+        let csv = "username,e-mail address,first_name,last_name,role\nnew,n@example.com,N,N,admin\n";
+        let report = import(&conn, ImportKind::Users, csv, false).unwrap();
+        assert!(!report.applied);
+        assert_eq!(report.errors[0].field.as_deref(), Some("password"));
+        assert_eq!(user_count(&conn), 2);
+
+        assert!(import(&conn, ImportKind::Users, "username,first_name\njdoe,Jane\n", true).is_err());
+    }
+
+    #[test]
+    fn test_organizations_and_classes_resolve_by_name() {
+        let conn = test_connection();
+        let report = import(
+            &conn,
+            ImportKind::Organizations,
+            "name,parent,school_start_time\nDistrict,,\nNorth High,District,08:00\nSouth High,District,8am\n",
+            false,
+        )
+        .unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].field.as_deref(), Some("school_start_time"));
+
+        let report = import(&conn, ImportKind::Organizations, "name,parent\nDistrict,\nNorth High,District\n", false)
+            .unwrap();
+        assert!(report.applied);
+        let north = OrganizationRepository::new(&conn).find_by_name("north high").unwrap().remove(0);
+        assert!(north.parent_organization_id.is_some());
+
+        let report = import(
+            &conn,
+            ImportKind::Classes,
+            "name,organization,subject
+Algebra,North High,Mathematics
+Algebra,North High,Mathematics
+Biology,Elsewhere,Science
+Chemistry,North High,Alchemy
+",
+            true,
+        )
+        .unwrap();
+        let errors: Vec<_> = report.errors.iter().map(|e| (e.line, e.field.as_deref())).collect();
+        assert_eq!(errors, vec![(3, Some("name")), (4, Some("organization")), (5, None)]);
+        assert_eq!(report.created, 1);
+    }
+}
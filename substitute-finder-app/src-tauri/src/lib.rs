@@ -1,5 +1,16 @@
 mod database;
 mod commands;
+mod backup;
+mod events;
+mod export;
+mod ics;
+mod ics_import;
+mod import;
+mod oneroster;
+mod payroll;
+mod recurrence;
+mod repository;
+mod webhooks;
 
 use database::connection::DatabaseManager;
 use std::sync::Arc;
@@ -13,11 +24,18 @@ fn greet(name: &str) -> String {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let db_manager = DatabaseManager::new().expect("Failed to initialize database");
+    let db = db_manager.get_connection();
     
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
         .manage(Arc::new(db_manager))
+        .setup(move |app| {
+            commands::ics::spawn_feed_server(db.clone());
+            commands::backup::spawn_scheduler(db.clone());
+            commands::attendance::spawn_sweeper(app.handle().clone(), db);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             // Organization commands
@@ -36,10 +54,13 @@ pub fn run() {
             // User commands
             commands::user::create_user,
             commands::user::get_users,
+            commands::user::update_user,
+            commands::user::delete_user,
             // Auth commands
             commands::auth::login,
             // Substitute request commands
             commands::substitute::create_substitute_request,
+            commands::substitute::create_substitute_request_for_periods,
             commands::substitute::get_substitute_requests,
             commands::substitute::get_substitute_requests_by_status,
             commands::substitute::get_substitute_request_by_id,
@@ -48,12 +69,128 @@ pub fn run() {
             commands::substitute::accept_substitute_request,
             commands::substitute::decline_substitute_request,
             commands::substitute::get_substitute_requests_for_user,
+            commands::substitute::get_assignment_conflicts,
+            // Absence commands
+            commands::absence::create_absence,
+            commands::absence::get_absences,
+            commands::absence::get_absence_by_id,
+            commands::absence::update_absence,
+            commands::absence::cancel_absence,
+            commands::absence::accept_absence,
+            // Regular teacher commands
+            commands::teacher::create_regular_teacher,
+            commands::teacher::get_regular_teachers,
+            commands::teacher::get_regular_teacher_by_id,
+            commands::teacher::update_regular_teacher,
+            commands::teacher::delete_regular_teacher,
+            commands::teacher::assign_teacher_to_class,
+            commands::teacher::get_teacher_class_assignments,
+            commands::teacher::get_classes_for_teacher,
+            commands::teacher::remove_teacher_class_assignment,
+            commands::teacher::report_absence,
+            commands::teacher::get_teacher_absences,
+            commands::teacher::get_teacher_absence_by_id,
+            commands::teacher::cancel_teacher_absence,
+            commands::teacher::accept_teacher_absence,
+            // Bell schedule commands
+            commands::bell_schedule::create_bell_schedule,
+            commands::bell_schedule::get_bell_schedules,
+            commands::bell_schedule::get_bell_schedule_by_id,
+            commands::bell_schedule::update_bell_schedule,
+            commands::bell_schedule::delete_bell_schedule,
+            commands::bell_schedule::set_bell_schedule_for_date,
+            commands::bell_schedule::get_active_bell_schedule,
+            commands::bell_schedule::set_class_periods,
+            commands::bell_schedule::get_class_periods,
+            // Calendar commands
+            commands::calendar::create_calendar_event,
+            commands::calendar::get_calendar_events,
+            commands::calendar::update_calendar_event,
+            commands::calendar::delete_calendar_event,
+            commands::calendar::set_calendar_event_excluded,
+            commands::calendar::get_effective_calendar,
+            commands::calendar::get_calendar_day,
+            commands::calendar::declare_emergency_closure,
+            // Substitute time-off commands
+            commands::unavailability::create_unavailability,
+            commands::unavailability::get_unavailability,
+            commands::unavailability::delete_unavailability,
+            // Preferred and blocked substitute commands
+            commands::preference::create_substitute_preference,
+            commands::preference::get_substitute_preferences,
+            commands::preference::delete_substitute_preference,
+            commands::preference::get_offer_tiers,
+            // Feedback commands
+            commands::feedback::submit_assignment_feedback,
+            commands::feedback::get_request_feedback,
+            commands::feedback::get_substitute_feedback,
+            commands::feedback::get_substitute_rating,
+            // Attendance commands
+            commands::attendance::check_in,
+            commands::attendance::check_out,
+            commands::attendance::override_attendance,
+            commands::attendance::get_attendance,
+            // Payroll commands
+            commands::payroll::set_pay_policy,
+            commands::payroll::get_pay_policy,
+            commands::payroll::get_effective_pay_policy,
+            commands::payroll::delete_pay_policy,
+            commands::payroll::get_timesheets,
+            commands::payroll::export_payroll_csv,
+            // Budget commands
+            commands::budget::create_budget,
+            commands::budget::update_budget,
+            commands::budget::delete_budget,
+            commands::budget::get_budgets,
+            commands::budget::get_budget_usage,
+            commands::budget::check_request_budget,
+            // Credential commands
+            commands::credential::create_credential,
+            commands::credential::get_substitute_credentials,
+            commands::credential::verify_credential,
+            commands::credential::delete_credential,
+            commands::credential::get_expiring_credentials,
+            // Class requirement commands
+            commands::requirement::get_class_vocabulary,
+            commands::requirement::save_vocabulary_term,
+            commands::requirement::create_class_requirement,
+            commands::requirement::get_class_requirements,
+            commands::requirement::delete_class_requirement,
+            commands::requirement::get_unmet_requirements,
+            commands::requirement::override_requirements_and_assign,
+            commands::requirement::get_requirement_overrides,
+            // Report commands
+            commands::report::get_coverage_report,
+            commands::report::get_substitute_report,
+            // Export commands
+            commands::export::export_data,
+            commands::export::get_export_columns,
+            // Import commands
+            commands::import::import_csv,
+            commands::roster::import_roster,
+            // iCalendar commands
+            commands::ics::export_calendar,
+            commands::ics::get_calendar_feed_url,
+            commands::ics::import_calendar_absences,
+            // Backup commands
+            commands::backup::create_backup,
+            commands::backup::list_backups,
+            commands::backup::restore_backup,
             // Notification commands
             commands::notification::send_notification,
             commands::notification::log_notification,
             commands::notification::get_notification_logs,
             commands::notification::notify_substitute_request_created,
             commands::notification::request_notification_permission,
+            // Webhook commands
+            commands::webhook::create_webhook_subscription,
+            commands::webhook::get_webhook_subscriptions,
+            commands::webhook::update_webhook_subscription,
+            commands::webhook::delete_webhook_subscription,
+            commands::webhook::get_webhook_deliveries,
+            // Settings commands
+            commands::setting::get_settings,
+            commands::setting::update_setting,
             // Seed command
             commands::seed::seed_database,
         ])
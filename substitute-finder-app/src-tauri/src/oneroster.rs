@@ -0,0 +1,844 @@
+use crate::database::models::{
+    Class, CreateClassRequest, CreateOrganizationRequest, CreateRegularTeacherRequest,
+    CreateTeacherClassAssignmentRequest, CreateUserRequest, Organization, RegularTeacher, RosterChanges, RosterEntity,
+    RosterImportReport, RosterImportRequest, RosterIssue, User, UserRole, VocabularyKind,
+};
+use crate::import::Changes;
+use crate::repository::{
+    ClassRepository, OrganizationRepository, RegularTeacherRepository, RosterRepository, UserRepository,
+    VocabularyRepository,
+};
+use rusqlite::Connection;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use uuid::Uuid;
+
+const ORGS: &str = "orgs.csv";
+const CLASSES: &str = "classes.csv";
+const USERS: &str = "users.csv";
+const ENROLLMENTS: &str = "enrollments.csv";
+
+/// Reads the bundle's files from a zip or a folder. Only `orgs.csv` is
+/// required; the others are imported when present.
+fn read_bundle(path: &Path) -> anyhow::Result<BTreeMap<&'static str, String>> {
+    let mut files = BTreeMap::new();
+    if path.is_dir() {
+        for name in [ORGS, CLASSES, USERS, ENROLLMENTS] {
+            let file = path.join(name);
+            if file.exists() {
+                files.insert(name, std::fs::read_to_string(file)?);
+            }
+        }
+    } else {
+        let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+        for name in [ORGS, CLASSES, USERS, ENROLLMENTS] {
+            if let Ok(mut entry) = archive.by_name(name) {
+                let mut text = String::new();
+                entry.read_to_string(&mut text)?;
+                files.insert(name, text);
+            }
+        }
+    }
+    if !files.contains_key(ORGS) {
+        anyhow::bail!("The roster bundle has no {}", ORGS);
+    }
+    Ok(files)
+}
+
+/// One line of a OneRoster file, keyed by its camelCase header.
+struct Row {
+    line: u64,
+    values: HashMap<String, String>,
+}
+
+impl Row {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str).filter(|value| !value.is_empty())
+    }
+
+    fn required(&self, key: &str) -> anyhow::Result<&str> {
+        self.get(key).ok_or_else(|| anyhow::anyhow!("{} is required", key))
+    }
+
+    fn is_deleted(&self) -> bool {
+        self.get("status") == Some("tobedeleted")
+    }
+
+    /// A comma-separated list field such as `orgSourcedIds`.
+    fn list(&self, key: &str) -> Vec<&str> {
+        self.get(key)
+            .map(|value| value.split(',').map(str::trim).filter(|item| !item.is_empty()).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn parse(text: &str) -> anyhow::Result<Vec<Row>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers = reader.headers()?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            Ok(Row {
+                line: record.position().map_or(0, |position| position.line()),
+                values: headers.iter().zip(record.iter()).map(|(h, v)| (h.to_string(), v.to_string())).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Grade band for OneRoster grade codes: `PK`, `KG` and `01` to `12`. Several
+/// grades make "Mixed Grades".
+fn grade_band(codes: &[&str]) -> Option<String> {
+    let mut bands: Vec<String> = Vec::new();
+    for code in codes {
+        let band = match *code {
+            "PK" => "Pre-K".to_string(),
+            "KG" => "Kindergarten".to_string(),
+            code => {
+                let grade: u32 = code.parse().ok().filter(|grade| (1..=12).contains(grade))?;
+                let suffix = match grade {
+                    1 => "st",
+                    2 => "nd",
+                    3 => "rd",
+                    _ => "th",
+                };
+                format!("{}{} Grade", grade, suffix)
+            }
+        };
+        if !bands.contains(&band) {
+            bands.push(band);
+        }
+    }
+    match bands.len() {
+        0 => None,
+        1 => bands.pop(),
+        _ => Some("Mixed Grades".to_string()),
+    }
+}
+
+/// What a row did, with the id of the record it saved. Enrollments carry the
+/// id of their teacher.
+enum Change {
+    Created(String),
+    Updated(String),
+    Unchanged(String),
+    Removed,
+    Skipped,
+}
+
+/// Ids of the records rows created, updated or removed. Enrollments save the
+/// ids of the teachers whose assignments they touched.
+#[derive(Default)]
+struct Ids {
+    created: Vec<String>,
+    updated: Vec<String>,
+    removed: Vec<String>,
+}
+
+/// What the import changed, for the caller to announce. Dry runs and imports
+/// with errors save nothing, so their changes are empty.
+pub struct Imported {
+    pub report: RosterImportReport,
+    pub organizations: Changes<Organization>,
+    pub classes: Changes<Class>,
+    pub users: Changes<User>,
+    pub teachers: Changes<RegularTeacher>,
+    /// Teachers whose class assignments were added, changed or removed.
+    pub assigned: Vec<RegularTeacher>,
+}
+
+struct Importer<'a> {
+    conn: &'a Connection,
+    links: RosterRepository<'a>,
+    report: RosterImportReport,
+    organization_ids: Ids,
+    class_ids: Ids,
+    user_ids: Ids,
+    teacher_ids: Ids,
+    assigned_teacher_ids: Ids,
+}
+
+impl<'a> Importer<'a> {
+    fn record(&mut self, entity: RosterEntity, file: &str, line: u64, result: anyhow::Result<Change>) {
+        let (changes, saved) = match entity {
+            RosterEntity::Organization => (&mut self.report.organizations, &mut self.organization_ids),
+            RosterEntity::Class => (&mut self.report.classes, &mut self.class_ids),
+            RosterEntity::User | RosterEntity::Teacher => (&mut self.report.teachers, &mut self.teacher_ids),
+            RosterEntity::Enrollment => (&mut self.report.assignments, &mut self.assigned_teacher_ids),
+        };
+        match result {
+            Ok(Change::Created(id)) => {
+                changes.created += 1;
+                saved.created.push(id);
+            }
+            Ok(Change::Updated(id)) => {
+                changes.updated += 1;
+                saved.updated.push(id);
+            }
+            Ok(Change::Unchanged(_)) => changes.unchanged += 1,
+            Ok(Change::Removed) => changes.removed += 1,
+            Ok(Change::Skipped) => self.report.skipped += 1,
+            Err(error) => self.report.errors.push(issue(file, line, error.to_string())),
+        }
+    }
+
+    fn warn(&mut self, file: &str, line: u64, message: String) {
+        self.report.warnings.push(issue(file, line, message));
+    }
+
+    /// Id of the record last imported from `sourced_id`.
+    fn linked(&self, entity: RosterEntity, sourced_id: &str) -> anyhow::Result<Option<String>> {
+        self.links.find_link(entity, sourced_id)
+    }
+
+    /// Whether an existing record is free to adopt: not already imported
+    /// from a different sourcedId.
+    fn adoptable(&self, entity: RosterEntity, local_id: &str) -> anyhow::Result<bool> {
+        Ok(self.links.find_sourced_id(entity, local_id)?.is_none())
+    }
+
+    /// Organizations are saved first and given their parents in a second
+    /// pass, so a parent can come after its children in the file.
+    fn organizations(&mut self, rows: &[Row]) {
+        let mut changes = Vec::new();
+        for row in rows {
+            let change = self.organization(row);
+            changes.push(change);
+        }
+        for (row, change) in rows.iter().zip(changes) {
+            let change = match change {
+                Ok(Change::Unchanged(_)) | Ok(Change::Updated(_)) | Ok(Change::Created(_)) => {
+                    match (self.organization_parent(row), change) {
+                        (Err(error), _) => Err(error),
+                        (Ok(true), Ok(Change::Unchanged(id))) => Ok(Change::Updated(id)),
+                        (_, change) => change,
+                    }
+                }
+                change => change,
+            };
+            self.record(RosterEntity::Organization, ORGS, row.line, change);
+        }
+    }
+
+    /// Matched by sourcedId, or on the first import by a unique name.
+    fn organization(&self, row: &Row) -> anyhow::Result<Change> {
+        if row.is_deleted() {
+            return Ok(Change::Skipped);
+        }
+        let organizations = OrganizationRepository::new(self.conn);
+        let sourced_id = row.required("sourcedId")?;
+        let name = row.required("name")?;
+
+        let mut existing = match self.linked(RosterEntity::Organization, sourced_id)? {
+            Some(id) => organizations.find_by_id(&id)?,
+            None => None,
+        };
+        if existing.is_none() {
+            let mut matches = organizations.find_by_name(name)?;
+            if matches.len() == 1 && self.adoptable(RosterEntity::Organization, &matches[0].id)? {
+                existing = matches.pop();
+            }
+        }
+
+        let change = match existing {
+            Some(current) if current.name == name => {
+                self.links.save_link(RosterEntity::Organization, sourced_id, &current.id)?;
+                Change::Unchanged(current.id)
+            }
+            Some(current) => {
+                self.links.save_link(RosterEntity::Organization, sourced_id, &current.id)?;
+                let parent_id = current.parent_organization_id.clone();
+                organizations.update(&current.id, organization_request(current.clone(), name, parent_id))?;
+                Change::Updated(current.id)
+            }
+            None => {
+                let organization = organizations.create(CreateOrganizationRequest {
+                    name: name.to_string(),
+                    parent_organization_id: None,
+                    description: None,
+                    contact_email: None,
+                    contact_phone: None,
+                    address: None,
+                    school_start_time: None,
+                    school_end_time: None,
+                })?;
+                self.links.save_link(RosterEntity::Organization, sourced_id, &organization.id)?;
+                Change::Created(organization.id)
+            }
+        };
+        Ok(change)
+    }
+
+    /// Points the organization at its `parentSourcedId`, returning whether
+    /// that changed anything. Organizations without one keep their parent.
+    fn organization_parent(&self, row: &Row) -> anyhow::Result<bool> {
+        let Some(parent) = row.get("parentSourcedId") else {
+            return Ok(false);
+        };
+        let organizations = OrganizationRepository::new(self.conn);
+        let Some(current) = self
+            .linked(RosterEntity::Organization, row.required("sourcedId")?)?
+            .map(|id| organizations.find_by_id(&id))
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(false);
+        };
+        let parent_id = self
+            .linked(RosterEntity::Organization, parent)?
+            .ok_or_else(|| anyhow::anyhow!("Unknown parent org: {}", parent))?;
+        if parent_id == current.id {
+            anyhow::bail!("An org can't be its own parent");
+        }
+        if current.parent_organization_id.as_deref() == Some(parent_id.as_str()) {
+            return Ok(false);
+        }
+        let (id, name) = (current.id.clone(), current.name.clone());
+        organizations.update(&id, organization_request(current, &name, Some(parent_id)))?;
+        Ok(true)
+    }
+
+    fn classes(&mut self, rows: &[Row]) {
+        for row in rows {
+            let result = self.class(row);
+            self.record(RosterEntity::Class, CLASSES, row.line, result);
+        }
+    }
+
+    /// Matched by sourcedId, or on the first import by name within the
+    /// school. Subjects and grades outside the class vocabulary are left out
+    /// with a warning.
+    fn class(&mut self, row: &Row) -> anyhow::Result<Change> {
+        if row.is_deleted() {
+            return Ok(Change::Skipped);
+        }
+        let classes = ClassRepository::new(self.conn);
+        let sourced_id = row.required("sourcedId")?;
+        let title = row.required("title")?;
+        let school = row.required("schoolSourcedId")?;
+        let organization_id = self
+            .linked(RosterEntity::Organization, school)?
+            .ok_or_else(|| anyhow::anyhow!("Unknown school: {}", school))?;
+
+        let subjects = row.list("subjects");
+        let subject = self.subject(&subjects)?;
+        if subject.is_none() && !subjects.is_empty() {
+            self.warn(CLASSES, row.line, format!("No subject in the class vocabulary: {}", subjects.join(", ")));
+        }
+        let grades = row.list("grades");
+        let grade_level = grade_band(&grades);
+        if grade_level.is_none() && !grades.is_empty() {
+            self.warn(CLASSES, row.line, format!("Unrecognized grades: {}", grades.join(", ")));
+        }
+        let room_number = row.get("location").map(str::to_string);
+
+        let mut existing = match self.linked(RosterEntity::Class, sourced_id)? {
+            Some(id) => classes.find_by_id(&id)?,
+            None => None,
+        };
+        if existing.is_none() {
+            if let Some(class) = classes.find_by_name(&organization_id, title)? {
+                if self.adoptable(RosterEntity::Class, &class.id)? {
+                    existing = Some(class);
+                }
+            }
+        }
+
+        let change = match existing {
+            Some(current) => {
+                let request = CreateClassRequest {
+                    name: title.to_string(),
+                    organization_id,
+                    subject: subject.or_else(|| current.subject.clone()),
+                    grade_level: grade_level.or_else(|| current.grade_level.clone()),
+                    room_number: room_number.or_else(|| current.room_number.clone()),
+                    description: current.description.clone(),
+                };
+                self.links.save_link(RosterEntity::Class, sourced_id, &current.id)?;
+                if class_matches(&current, &request) {
+                    Change::Unchanged(current.id)
+                } else {
+                    classes.update(&current.id, request)?;
+                    Change::Updated(current.id)
+                }
+            }
+            None => {
+                let class = classes.create(CreateClassRequest {
+                    name: title.to_string(),
+                    organization_id,
+                    subject,
+                    grade_level,
+                    room_number,
+                    description: None,
+                })?;
+                self.links.save_link(RosterEntity::Class, sourced_id, &class.id)?;
+                Change::Created(class.id)
+            }
+        };
+        Ok(change)
+    }
+
+    /// The first of the class's subjects that is an active vocabulary term,
+    /// ignoring case.
+    fn subject(&self, subjects: &[&str]) -> anyhow::Result<Option<String>> {
+        let terms = VocabularyRepository::new(self.conn).list(VocabularyKind::Subject, false)?;
+        Ok(subjects.iter().find_map(|subject| {
+            terms
+                .iter()
+                .find(|term| term.name.eq_ignore_ascii_case(subject))
+                .map(|term| term.name.clone())
+        }))
+    }
+
+    fn users(&mut self, rows: &[Row]) {
+        for row in rows {
+            let result = self.teacher(row);
+            self.record(RosterEntity::Teacher, USERS, row.line, result);
+        }
+    }
+
+    /// Imports teachers as a user plus a teacher record at their first
+    /// imported org; other roles are skipped. Users are matched by sourcedId,
+    /// or on the first import by username or email. New teachers join as org
+    /// managers, the role that requests substitutes, with a random password
+    /// an admin resets; existing users keep their role and password. The row
+    /// counts as created when it creates the teacher record, even for an
+    /// existing user.
+    fn teacher(&mut self, row: &Row) -> anyhow::Result<Change> {
+        if row.is_deleted() || row.get("role") != Some("teacher") {
+            return Ok(Change::Skipped);
+        }
+        let users = UserRepository::new(self.conn);
+        let teachers = RegularTeacherRepository::new(self.conn);
+        let sourced_id = row.required("sourcedId")?;
+        let username = row.required("username")?;
+        let email = row.required("email")?;
+        let first_name = row.required("givenName")?;
+        let last_name = row.required("familyName")?;
+        let mut organization_id = None;
+        for org in row.list("orgSourcedIds") {
+            if let Some(id) = self.linked(RosterEntity::Organization, org)? {
+                organization_id = Some(id);
+                break;
+            }
+        }
+        let organization_id =
+            organization_id.ok_or_else(|| anyhow::anyhow!("None of the teacher's orgs were imported"))?;
+
+        let mut existing = match self.linked(RosterEntity::User, sourced_id)? {
+            Some(id) => users.find_by_id(&id)?,
+            None => None,
+        };
+        if existing.is_none() {
+            let candidate = match users.find_by_username(username)? {
+                Some(user) => Some(user),
+                None => users.find_by_email(email)?,
+            };
+            if let Some(user) = candidate {
+                if self.adoptable(RosterEntity::User, &user.id)? {
+                    existing = Some(user);
+                }
+            }
+        }
+        if let Some(owner) = users.find_by_email(email)? {
+            if existing.as_ref().map(|user| &user.id) != Some(&owner.id) {
+                anyhow::bail!("Email belongs to another user: {}", email);
+            }
+        }
+
+        let request = CreateUserRequest {
+            username: username.to_string(),
+            password: String::new(),
+            email: email.to_string(),
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+            role: UserRole::OrgManager,
+            organization_id: Some(organization_id.clone()),
+        };
+        let mut user_changed = true;
+        let user_id = match existing {
+            Some(current) => {
+                let request = CreateUserRequest { role: current.role.clone(), ..request };
+                if user_matches(&current, &request) {
+                    user_changed = false;
+                } else {
+                    users.update(&current.id, request)?;
+                    self.user_ids.updated.push(current.id.clone());
+                }
+                current.id
+            }
+            None => {
+                let user = users.create(CreateUserRequest { password: Uuid::new_v4().to_string(), ..request })?;
+                self.user_ids.created.push(user.id.clone());
+                user.id
+            }
+        };
+        self.links.save_link(RosterEntity::User, sourced_id, &user_id)?;
+
+        let teacher = match self.linked(RosterEntity::Teacher, sourced_id)? {
+            Some(id) => teachers.find_by_id(&id)?,
+            None => teachers.find_by_user(&user_id)?,
+        };
+        let teacher_request = CreateRegularTeacherRequest { user_id: user_id.clone(), organization_id };
+        let (teacher_id, change): (String, fn(String) -> Change) = match teacher {
+            Some(current) => {
+                let moved = current.user_id != teacher_request.user_id
+                    || current.organization_id != teacher_request.organization_id;
+                if moved {
+                    teachers.update(&current.id, teacher_request)?;
+                }
+                let change = if moved || user_changed { Change::Updated } else { Change::Unchanged };
+                (current.id, change)
+            }
+            None => (teachers.create(teacher_request)?.id, Change::Created),
+        };
+        self.links.save_link(RosterEntity::Teacher, sourced_id, &teacher_id)?;
+        Ok(change(teacher_id))
+    }
+
+    fn enrollments(&mut self, rows: &[Row]) {
+        for row in rows {
+            let result = self.enrollment(row);
+            self.record(RosterEntity::Enrollment, ENROLLMENTS, row.line, result);
+        }
+    }
+
+    /// Teacher enrollments become class assignments; student enrollments
+    /// are skipped. An enrollment marked `tobedeleted` removes its assignment.
+    fn enrollment(&mut self, row: &Row) -> anyhow::Result<Change> {
+        if row.get("role") != Some("teacher") {
+            return Ok(Change::Skipped);
+        }
+        let teachers = RegularTeacherRepository::new(self.conn);
+        let sourced_id = row.required("sourcedId")?;
+        let linked = self.linked(RosterEntity::Enrollment, sourced_id)?;
+        if row.is_deleted() {
+            let Some(id) = linked else {
+                return Ok(Change::Skipped);
+            };
+            if let Some(assignment) = teachers.find_assignment(&id)? {
+                self.assigned_teacher_ids.removed.push(assignment.teacher_id);
+            }
+            teachers.remove_assignment(&id)?;
+            self.links.remove_link(RosterEntity::Enrollment, sourced_id)?;
+            return Ok(Change::Removed);
+        }
+
+        let class = row.required("classSourcedId")?;
+        let class_id = self
+            .linked(RosterEntity::Class, class)?
+            .ok_or_else(|| anyhow::anyhow!("Unknown class: {}", class))?;
+        let user = row.required("userSourcedId")?;
+        let teacher_id = self
+            .linked(RosterEntity::Teacher, user)?
+            .ok_or_else(|| anyhow::anyhow!("Unknown teacher: {}", user))?;
+        let is_primary = row.get("primary").is_some_and(|value| value.eq_ignore_ascii_case("true"));
+
+        let current = match linked {
+            Some(id) => teachers.find_assignment(&id)?,
+            None => teachers
+                .list_assignments(&teacher_id)?
+                .into_iter()
+                .find(|assignment| assignment.class_id == class_id),
+        };
+        let request = CreateTeacherClassAssignmentRequest { teacher_id: teacher_id.clone(), class_id, is_primary };
+        let (assignment_id, change): (String, fn(String) -> Change) = match current {
+            Some(current)
+                if current.teacher_id == request.teacher_id
+                    && current.class_id == request.class_id
+                    && current.is_primary == request.is_primary =>
+            {
+                (current.id, Change::Unchanged)
+            }
+            Some(current) => {
+                // Moving an enrollment to another teacher changes both teachers' classes
+                if current.teacher_id != request.teacher_id {
+                    self.assigned_teacher_ids.updated.push(current.teacher_id);
+                }
+                teachers.remove_assignment(&current.id)?;
+                (teachers.assign_class(request)?.id, Change::Updated)
+            }
+            None => (teachers.assign_class(request)?.id, Change::Created),
+        };
+        self.links.save_link(RosterEntity::Enrollment, sourced_id, &assignment_id)?;
+        Ok(change(teacher_id))
+    }
+}
+
+fn issue(file: &str, line: u64, message: String) -> RosterIssue {
+    RosterIssue { file: file.to_string(), line, message }
+}
+
+fn organization_request(
+    current: Organization,
+    name: &str,
+    parent_organization_id: Option<String>,
+) -> CreateOrganizationRequest {
+    CreateOrganizationRequest {
+        name: name.to_string(),
+        parent_organization_id,
+        description: current.description,
+        contact_email: current.contact_email,
+        contact_phone: current.contact_phone,
+        address: current.address,
+        school_start_time: current.school_start_time,
+        school_end_time: current.school_end_time,
+    }
+}
+
+fn class_matches(current: &Class, request: &CreateClassRequest) -> bool {
+    current.name == request.name
+        && current.organization_id == request.organization_id
+        && current.subject == request.subject
+        && current.grade_level == request.grade_level
+        && current.room_number == request.room_number
+}
+
+fn user_matches(current: &User, request: &CreateUserRequest) -> bool {
+    current.username == request.username
+        && current.email == request.email
+        && current.first_name == request.first_name
+        && current.last_name == request.last_name
+        && current.organization_id == request.organization_id
+}
+
+/// Imports orgs, classes, teachers and teacher enrollments from a OneRoster
+/// 1.1 CSV bundle. Records are matched by sourcedId, so importing the same
+/// bundle again changes nothing. Everything runs in one transaction that a
+/// dry run, or any row error, rolls back.
+pub fn run(conn: &Connection, request: &RosterImportRequest) -> anyhow::Result<Imported> {
+    let files = read_bundle(Path::new(&request.path))?;
+    let mut tables = BTreeMap::new();
+    for (name, text) in &files {
+        tables.insert(*name, parse(text).map_err(|e| anyhow::anyhow!("{}: {}", name, e))?);
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let mut importer = Importer {
+        conn,
+        links: RosterRepository::new(conn),
+        report: RosterImportReport {
+            dry_run: request.dry_run,
+            applied: false,
+            organizations: RosterChanges::default(),
+            classes: RosterChanges::default(),
+            teachers: RosterChanges::default(),
+            assignments: RosterChanges::default(),
+            skipped: 0,
+            warnings: Vec::new(),
+            errors: Vec::new(),
+        },
+        organization_ids: Ids::default(),
+        class_ids: Ids::default(),
+        user_ids: Ids::default(),
+        teacher_ids: Ids::default(),
+        assigned_teacher_ids: Ids::default(),
+    };
+    let empty = Vec::new();
+    importer.organizations(tables.get(ORGS).unwrap_or(&empty));
+    importer.classes(tables.get(CLASSES).unwrap_or(&empty));
+    importer.users(tables.get(USERS).unwrap_or(&empty));
+    importer.enrollments(tables.get(ENROLLMENTS).unwrap_or(&empty));
+
+    let Importer { mut report, organization_ids, class_ids, user_ids, teacher_ids, assigned_teacher_ids, .. } =
+        importer;
+    if request.dry_run || !report.errors.is_empty() {
+        tx.rollback()?;
+        return Ok(Imported {
+            report,
+            organizations: Changes::default(),
+            classes: Changes::default(),
+            users: Changes::default(),
+            teachers: Changes::default(),
+            assigned: Vec::new(),
+        });
+    }
+    tx.commit()?;
+    report.applied = true;
+
+    let organizations = OrganizationRepository::new(conn);
+    let classes = ClassRepository::new(conn);
+    let users = UserRepository::new(conn);
+    let teachers = RegularTeacherRepository::new(conn);
+    let Ids { created, updated, removed } = assigned_teacher_ids;
+    let mut assigned = [created, updated, removed].concat();
+    assigned.sort();
+    assigned.dedup();
+    Ok(Imported {
+        report,
+        organizations: load(organization_ids, |id| organizations.find_by_id(id))?,
+        classes: load(class_ids, |id| classes.find_by_id(id))?,
+        users: load(user_ids, |id| users.find_by_id(id))?,
+        teachers: load(teacher_ids, |id| teachers.find_by_id(id))?,
+        assigned: find_all(assigned, |id| teachers.find_by_id(id))?,
+    })
+}
+
+/// Reads back what the rows saved, now that it's committed.
+fn load<T>(ids: Ids, find: impl Fn(&str) -> anyhow::Result<Option<T>>) -> anyhow::Result<Changes<T>> {
+    Ok(Changes { created: find_all(ids.created, &find)?, updated: find_all(ids.updated, &find)? })
+}
+
+fn find_all<T>(ids: Vec<String>, find: impl Fn(&str) -> anyhow::Result<Option<T>>) -> anyhow::Result<Vec<T>> {
+    let mut records = Vec::new();
+    for id in ids {
+        records.extend(find(&id)?);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::fixtures::test_connection;
+
+    const ORGS_CSV: &str = "sourcedId,status,dateLastModified,name,type,identifier,parentSourcedId
+sch-1,active,,North High,school,,dist-1
+dist-1,active,,Springfield District,district,,
+";
+    const CLASSES_CSV: &str = "sourcedId,status,title,grades,schoolSourcedId,subjects,location
+cls-1,active,Algebra I,09,sch-1,\"math,Mathematics\",Room 12
+cls-2,active,Homeroom,\"09,10\",sch-1,Advisory,
+";
+    const USERS_CSV: &str = "sourcedId,status,enabledUser,orgSourcedIds,role,username,givenName,familyName,email
+usr-1,active,true,sch-1,teacher,jsmith,Jo,Smith,jsmith@example.com
+usr-2,active,true,sch-1,student,kid,Kid,Student,kid@example.com
+";
+    const ENROLLMENTS_CSV: &str = "sourcedId,status,classSourcedId,schoolSourcedId,userSourcedId,role,primary
+enr-1,active,cls-1,sch-1,usr-1,teacher,true
+enr-2,active,cls-1,sch-1,usr-2,student,false
+";
+
+    fn write_bundle(dir: &Path, orgs: &str, enrollments: &str) {
+        std::fs::write(dir.join(ORGS), orgs).unwrap();
+        std::fs::write(dir.join(CLASSES), CLASSES_CSV).unwrap();
+        std::fs::write(dir.join(USERS), USERS_CSV).unwrap();
+        std::fs::write(dir.join(ENROLLMENTS), enrollments).unwrap();
+    }
+
+    fn import(conn: &Connection, dir: &Path, dry_run: bool) -> RosterImportReport {
+        import_changes(conn, dir, dry_run).report
+    }
+
+    fn import_changes(conn: &Connection, dir: &Path, dry_run: bool) -> Imported {
+        run(conn, &RosterImportRequest { path: dir.to_string_lossy().to_string(), dry_run }).unwrap()
+    }
+
+    fn counts(changes: &RosterChanges) -> (u32, u32, u32, u32) {
+        (changes.created, changes.updated, changes.unchanged, changes.removed)
+    }
+
+    #[test]
+    fn test_grade_band() {
+        assert_eq!(grade_band(&["KG"]).as_deref(), Some("Kindergarten"));
+        assert_eq!(grade_band(&["02"]).as_deref(), Some("2nd Grade"));
+        assert_eq!(grade_band(&["11", "11"]).as_deref(), Some("11th Grade"));
+        assert_eq!(grade_band(&["09", "10"]).as_deref(), Some("Mixed Grades"));
+        assert_eq!(grade_band(&["13"]), None);
+        assert_eq!(grade_band(&[]), None);
+    }
+
+    #[test]
+    fn test_reimport_is_idempotent_and_reports_changes() {
+        let conn = test_connection();
+        let dir = tempfile::tempdir().unwrap();
+        write_bundle(dir.path(), ORGS_CSV, ENROLLMENTS_CSV);
+
+        let report = import(&conn, dir.path(), true);
+        assert!(!report.applied && report.errors.is_empty());
+        assert_eq!(counts(&report.organizations), (2, 0, 0, 0));
+        let organizations: i64 = conn.query_row("SELECT COUNT(*) FROM organizations", [], |row| row.get(0)).unwrap();
+        assert_eq!(organizations, 0);
+
+        let imported = import_changes(&conn, dir.path(), false);
+        let report = &imported.report;
+        assert!(report.applied, "{:?}", report.errors);
+        assert_eq!(imported.organizations.created.len(), 2);
+        assert_eq!(imported.users.created[0].username, "jsmith");
+        assert_eq!(imported.teachers.created.len(), 1);
+        assert_eq!(imported.assigned.len(), 1);
+        assert_eq!(counts(&report.organizations), (2, 0, 0, 0));
+        assert_eq!(counts(&report.classes), (2, 0, 0, 0));
+        assert_eq!(counts(&report.teachers), (1, 0, 0, 0));
+        assert_eq!(counts(&report.assignments), (1, 0, 0, 0));
+        assert_eq!(report.skipped, 2);
+        assert_eq!(report.warnings.len(), 1);
+
+        let links = RosterRepository::new(&conn);
+        let school = links.find_link(RosterEntity::Organization, "sch-1").unwrap().unwrap();
+        let district = links.find_link(RosterEntity::Organization, "dist-1").unwrap().unwrap();
+        let school = OrganizationRepository::new(&conn).find_by_id(&school).unwrap().unwrap();
+        assert_eq!(school.parent_organization_id.as_deref(), Some(district.as_str()));
+        let algebra = links.find_link(RosterEntity::Class, "cls-1").unwrap().unwrap();
+        let algebra = ClassRepository::new(&conn).find_by_id(&algebra).unwrap().unwrap();
+        assert_eq!(algebra.subject.as_deref(), Some("Mathematics"));
+        assert_eq!(algebra.grade_level.as_deref(), Some("9th Grade"));
+        let user = UserRepository::new(&conn).find_by_username("jsmith").unwrap().unwrap();
+        assert!(matches!(user.role, UserRole::OrgManager));
+
+        let imported = import_changes(&conn, dir.path(), false);
+        let report = &imported.report;
+        assert!(report.applied);
+        assert!(imported.organizations.updated.is_empty() && imported.assigned.is_empty());
+        assert_eq!(counts(&report.organizations), (0, 0, 2, 0));
+        assert_eq!(counts(&report.classes), (0, 0, 2, 0));
+        assert_eq!(counts(&report.teachers), (0, 0, 1, 0));
+        assert_eq!(counts(&report.assignments), (0, 0, 1, 0));
+
+        let renamed = ORGS_CSV.replace("North High", "North High School");
+        let dropped = ENROLLMENTS_CSV.replace("enr-1,active", "enr-1,tobedeleted");
+        write_bundle(dir.path(), &renamed, &dropped);
+        let imported = import_changes(&conn, dir.path(), false);
+        let report = &imported.report;
+        assert_eq!(counts(&report.organizations), (0, 1, 1, 0));
+        assert_eq!(counts(&report.assignments), (0, 0, 0, 1));
+        assert_eq!(imported.organizations.updated[0].name, "North High School");
+        let teacher = links.find_link(RosterEntity::Teacher, "usr-1").unwrap().unwrap();
+        assert_eq!(imported.assigned[0].id, teacher);
+        assert!(RegularTeacherRepository::new(&conn).list_assignments(&teacher).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_references_fail_the_import() {
+        let conn = test_connection();
+        let dir = tempfile::tempdir().unwrap();
+        let orgs = ORGS_CSV.replace(",dist-1\n", ",dist-9\n");
+        write_bundle(dir.path(), &orgs, ENROLLMENTS_CSV);
+
+        let report = import(&conn, dir.path(), false);
+        assert!(!report.applied);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!((report.errors[0].file.as_str(), report.errors[0].line), (ORGS, 2));
+        assert!(RosterRepository::new(&conn).find_link(RosterEntity::Organization, "sch-1").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_teacher_for_existing_user_counts_as_created() {
+        let conn = test_connection();
+        UserRepository::new(&conn)
+            .create(CreateUserRequest {
+                username: "jsmith".to_string(),
+                password: "secret".to_string(),
+                email: "jsmith@example.com".to_string(),
+                first_name: "Jo".to_string(),
+                last_name: "Smith".to_string(),
+                role: UserRole::Admin,
+                organization_id: None,
+            })
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        write_bundle(dir.path(), ORGS_CSV, ENROLLMENTS_CSV);
+
+        let imported = import_changes(&conn, dir.path(), false);
+        assert_eq!(counts(&imported.report.teachers), (1, 0, 0, 0));
+        assert!(imported.users.created.is_empty());
+        assert_eq!(imported.users.updated[0].username, "jsmith");
+        assert_eq!(imported.teachers.created.len(), 1);
+    }
+}
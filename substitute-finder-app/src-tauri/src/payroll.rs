@@ -0,0 +1,295 @@
+use crate::database::models::{PayBasis, PayPolicy, RoundingMode, Timesheet, TimesheetEntry};
+use crate::database::timestamp;
+
+/// Rounds `hours` to a multiple of `increment_minutes`. An increment of zero
+/// leaves them as they are.
+pub fn round_hours(hours: f64, increment_minutes: u32, mode: RoundingMode) -> f64 {
+    if increment_minutes == 0 {
+        return hours;
+    }
+    let increment = f64::from(increment_minutes);
+    // Nudge by a hair so hours that are already a multiple, give or take
+    // float error, don't get pushed to the next one
+    let units = hours * 60.0 / increment;
+    let units = match mode {
+        RoundingMode::Nearest => units.round(),
+        RoundingMode::Up => (units - 1e-9).ceil(),
+        RoundingMode::Down => (units + 1e-9).floor(),
+    };
+    units * increment / 60.0
+}
+
+/// A duration as fractional hours, to the minute.
+pub fn hours(duration: chrono::Duration) -> f64 {
+    duration.num_minutes() as f64 / 60.0
+}
+
+fn to_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Hours covered by the windows together, counting overlapping time once.
+pub fn covered_hours<T>(mut windows: Vec<(T, T)>) -> f64
+where
+    T: Copy + Ord + std::ops::Sub<Output = chrono::Duration>,
+{
+    windows.sort();
+    let mut total = chrono::Duration::zero();
+    let mut current: Option<(T, T)> = None;
+    for (start, end) in windows {
+        current = match current {
+            Some((current_start, current_end)) if start <= current_end => {
+                Some((current_start, current_end.max(end)))
+            }
+            Some((current_start, current_end)) => {
+                total += current_end - current_start;
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((start, end)) = current {
+        total += end - start;
+    }
+    hours(total)
+}
+
+/// `amount` shared out to the cent in proportion to `weights`, evenly when
+/// they're all zero. The last share takes the rounding so the shares add up.
+pub fn apportion(amount: f64, weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    let mut shares: Vec<f64> = weights
+        .iter()
+        .map(|weight| {
+            if total > 0.0 {
+                to_cents(amount * weight / total)
+            } else {
+                to_cents(amount / weights.len() as f64)
+            }
+        })
+        .collect();
+    if let Some((last, rest)) = shares.split_last_mut() {
+        *last = to_cents(amount - rest.iter().sum::<f64>());
+    }
+    shares
+}
+
+/// Basis, rate and amount for `paid_hours` under `policy`. Half-day and
+/// full-day flat rates take precedence over the hourly rate when the policy
+/// has them; `multiplier` scales whichever applies.
+pub fn price(
+    policy: &PayPolicy,
+    paid_hours: f64,
+    hourly_rate: Option<f64>,
+    multiplier: f64,
+) -> (PayBasis, Option<f64>, Option<f64>) {
+    let (basis, rate, amount) = match (policy.half_day_rate, policy.full_day_rate) {
+        (Some(rate), _) if paid_hours <= policy.half_day_max_hours => (PayBasis::HalfDay, Some(rate), Some(rate)),
+        (_, Some(rate)) => (PayBasis::FullDay, Some(rate), Some(rate)),
+        _ => (PayBasis::Hourly, hourly_rate, hourly_rate.map(|rate| rate * paid_hours)),
+    };
+    (basis, rate, amount.map(|amount| to_cents(amount * multiplier)))
+}
+
+/// Column layout used when the `payroll_csv_columns` setting is missing.
+pub const DEFAULT_COLUMNS: &str = "substitute_name,substitute_email,date,organization,class,paid_hours,basis,rate,amount";
+
+/// A column of the payroll CSV export, named by its key in the
+/// `payroll_csv_columns` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayrollColumn {
+    SubstituteId,
+    SubstituteName,
+    SubstituteEmail,
+    PeriodStart,
+    PeriodEnd,
+    RequestId,
+    Date,
+    Organization,
+    Class,
+    ScheduledHours,
+    ActualHours,
+    PaidHours,
+    Basis,
+    Rate,
+    Multiplier,
+    Amount,
+}
+
+impl PayrollColumn {
+    const ALL: [PayrollColumn; 16] = [
+        PayrollColumn::SubstituteId,
+        PayrollColumn::SubstituteName,
+        PayrollColumn::SubstituteEmail,
+        PayrollColumn::PeriodStart,
+        PayrollColumn::PeriodEnd,
+        PayrollColumn::RequestId,
+        PayrollColumn::Date,
+        PayrollColumn::Organization,
+        PayrollColumn::Class,
+        PayrollColumn::ScheduledHours,
+        PayrollColumn::ActualHours,
+        PayrollColumn::PaidHours,
+        PayrollColumn::Basis,
+        PayrollColumn::Rate,
+        PayrollColumn::Multiplier,
+        PayrollColumn::Amount,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            PayrollColumn::SubstituteId => "substitute_id",
+            PayrollColumn::SubstituteName => "substitute_name",
+            PayrollColumn::SubstituteEmail => "substitute_email",
+            PayrollColumn::PeriodStart => "period_start",
+            PayrollColumn::PeriodEnd => "period_end",
+            PayrollColumn::RequestId => "request_id",
+            PayrollColumn::Date => "date",
+            PayrollColumn::Organization => "organization",
+            PayrollColumn::Class => "class",
+            PayrollColumn::ScheduledHours => "scheduled_hours",
+            PayrollColumn::ActualHours => "actual_hours",
+            PayrollColumn::PaidHours => "paid_hours",
+            PayrollColumn::Basis => "basis",
+            PayrollColumn::Rate => "rate",
+            PayrollColumn::Multiplier => "multiplier",
+            PayrollColumn::Amount => "amount",
+        }
+    }
+
+    fn value(&self, timesheet: &Timesheet, entry: &TimesheetEntry) -> String {
+        let number = |value: f64| format!("{:.2}", value);
+        match self {
+            PayrollColumn::SubstituteId => timesheet.substitute_id.clone(),
+            PayrollColumn::SubstituteName => timesheet.substitute_name.clone(),
+            PayrollColumn::SubstituteEmail => timesheet.substitute_email.clone(),
+            PayrollColumn::PeriodStart => timestamp::format_date(&timesheet.period_start),
+            PayrollColumn::PeriodEnd => timestamp::format_date(&timesheet.period_end),
+            PayrollColumn::RequestId => entry.request_id.clone(),
+            PayrollColumn::Date => timestamp::format_date(&entry.date_needed),
+            PayrollColumn::Organization => entry.organization_name.clone(),
+            PayrollColumn::Class => entry.class_name.clone(),
+            PayrollColumn::ScheduledHours => number(entry.scheduled_hours),
+            PayrollColumn::ActualHours => entry.actual_hours.map(number).unwrap_or_default(),
+            PayrollColumn::PaidHours => number(entry.paid_hours),
+            PayrollColumn::Basis => entry.basis.to_string(),
+            PayrollColumn::Rate => entry.rate.map(number).unwrap_or_default(),
+            PayrollColumn::Multiplier => number(entry.multiplier),
+            PayrollColumn::Amount => entry.amount.map(number).unwrap_or_default(),
+        }
+    }
+}
+
+impl std::str::FromStr for PayrollColumn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PayrollColumn::ALL
+            .into_iter()
+            .find(|column| column.key() == s)
+            .ok_or_else(|| anyhow::anyhow!("Invalid payroll column: {}", s))
+    }
+}
+
+/// Parses a comma-separated column layout such as `date,class,amount`.
+pub fn parse_columns(layout: &str) -> anyhow::Result<Vec<PayrollColumn>> {
+    let columns = layout
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::parse)
+        .collect::<anyhow::Result<Vec<PayrollColumn>>>()?;
+    if columns.is_empty() {
+        anyhow::bail!("Payroll export needs at least one column");
+    }
+    Ok(columns)
+}
+
+/// One row per timesheet entry, headed by the column keys.
+pub fn write_csv(timesheets: &[Timesheet], columns: &[PayrollColumn]) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(columns.iter().map(PayrollColumn::key))?;
+    for timesheet in timesheets {
+        for entry in &timesheet.entries {
+            writer.write_record(columns.iter().map(|column| column.value(timesheet, entry)))?;
+        }
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_round_hours() {
+        assert_eq!(round_hours(6.9, 15, RoundingMode::Nearest), 7.0);
+        assert_eq!(round_hours(6.6, 15, RoundingMode::Down), 6.5);
+        assert_eq!(round_hours(6.51, 15, RoundingMode::Up), 6.75);
+        assert_eq!(round_hours(6.5, 15, RoundingMode::Up), 6.5);
+        assert_eq!(round_hours(6.6, 0, RoundingMode::Up), 6.6);
+    }
+
+    #[test]
+    fn test_covered_hours_and_apportion() {
+        let time = |value| crate::database::timestamp::parse_time(value).unwrap();
+        let windows = vec![
+            (time("09:00"), time("09:50")),
+            (time("08:00"), time("15:00")),
+            (time("15:30"), time("16:00")),
+        ];
+        assert_eq!(covered_hours(windows), 7.5);
+        assert_eq!(covered_hours::<NaiveDate>(Vec::new()), 0.0);
+
+        assert_eq!(apportion(150.0, &[7.0, 0.75]), vec![135.48, 14.52]);
+        assert_eq!(apportion(100.0, &[0.0, 0.0, 0.0]), vec![33.33, 33.33, 33.34]);
+    }
+
+    #[test]
+    fn test_price_prefers_flat_rates() {
+        let mut policy = PayPolicy::default_for("org-1");
+        assert_eq!(price(&policy, 6.5, Some(20.0), 1.0), (PayBasis::Hourly, Some(20.0), Some(130.0)));
+        assert_eq!(price(&policy, 6.5, None, 1.0), (PayBasis::Hourly, None, None));
+
+        policy.half_day_rate = Some(80.0);
+        policy.full_day_rate = Some(150.0);
+        assert_eq!(price(&policy, 3.5, Some(20.0), 1.0), (PayBasis::HalfDay, Some(80.0), Some(80.0)));
+        assert_eq!(price(&policy, 6.5, Some(20.0), 1.1), (PayBasis::FullDay, Some(150.0), Some(165.0)));
+    }
+
+    #[test]
+    fn test_write_csv_uses_column_layout() {
+        let date = NaiveDate::from_ymd_opt(2030, 1, 15).unwrap();
+        let timesheet = Timesheet {
+            substitute_id: "sub-1".to_string(),
+            substitute_name: "Smith, Jo".to_string(),
+            substitute_email: "jo@example.com".to_string(),
+            period_start: date,
+            period_end: date,
+            entries: vec![TimesheetEntry {
+                request_id: "r1".to_string(),
+                date_needed: date,
+                organization_id: "org-1".to_string(),
+                organization_name: "North".to_string(),
+                class_id: "math".to_string(),
+                class_name: "Math".to_string(),
+                scheduled_hours: 7.0,
+                actual_hours: None,
+                paid_hours: 7.0,
+                basis: PayBasis::Hourly,
+                rate: None,
+                multiplier: 1.0,
+                amount: None,
+            }],
+            total_hours: 7.0,
+            total_amount: 0.0,
+        };
+
+        let columns = parse_columns("substitute_name, date,paid_hours,amount").unwrap();
+        let csv = write_csv(&[timesheet], &columns).unwrap();
+        assert_eq!(csv, "substitute_name,date,paid_hours,amount\n\"Smith, Jo\",2030-01-15,7.00,\n");
+        assert!(parse_columns("date,salary").is_err());
+        assert!(parse_columns(" , ").is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// The subset of RFC 5545 RRULE supported for absences:
+/// `FREQ=WEEKLY;BYDAY=MO,WE,FR;UNTIL=20240630` with an optional `INTERVAL=n`.
+/// `BYDAY` defaults to the weekday of the first date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub by_day: Vec<Weekday>,
+    pub until: NaiveDate,
+    pub interval: u32,
+}
+
+/// Longest span a single absence may cover, to keep expansion bounded.
+const MAX_SPAN_DAYS: i64 = 366;
+
+fn parse_weekday(value: &str) -> anyhow::Result<Weekday> {
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(anyhow::anyhow!("Invalid BYDAY value: {}", value)),
+    }
+}
+
+fn format_weekday(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_until(value: &str) -> anyhow::Result<NaiveDate> {
+    // UNTIL may be a DATE (20240630) or a UTC DATE-TIME (20240630T235959Z)
+    let date = value.split('T').next().unwrap_or(value);
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .map_err(|_| anyhow::anyhow!("Invalid UNTIL value: {}", value))
+}
+
+impl RecurrenceRule {
+    pub fn parse(rule: &str, start: NaiveDate) -> anyhow::Result<Self> {
+        let rule = rule.trim().trim_start_matches("RRULE:");
+        let mut freq = None;
+        let mut by_day = Vec::new();
+        let mut until = None;
+        let mut interval = 1;
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid recurrence rule part: {}", part))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => freq = Some(value.to_ascii_uppercase()),
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(|day| parse_weekday(&day.trim().to_ascii_uppercase()))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                }
+                "UNTIL" => until = Some(parse_until(value)?),
+                "INTERVAL" => {
+                    interval = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| anyhow::anyhow!("Invalid INTERVAL value: {}", value))?;
+                }
+                other => anyhow::bail!("Unsupported recurrence rule part: {}", other),
+            }
+        }
+
+        if freq.as_deref() != Some("WEEKLY") {
+            anyhow::bail!("Only FREQ=WEEKLY recurrence rules are supported");
+        }
+        let until = until.ok_or_else(|| anyhow::anyhow!("Recurrence rule must have an UNTIL date"))?;
+        if until < start {
+            anyhow::bail!("Recurrence rule UNTIL date is before the start date");
+        }
+        if (until - start).num_days() > MAX_SPAN_DAYS {
+            anyhow::bail!("Recurrence rule may span at most {} days", MAX_SPAN_DAYS);
+        }
+        if by_day.is_empty() {
+            by_day.push(start.weekday());
+        }
+
+        Ok(RecurrenceRule { by_day, until, interval })
+    }
+
+    /// Dates from `start` through `until` that fall on one of `by_day`, in
+    /// weeks counted from the week containing `start`.
+    pub fn occurrences(&self, start: NaiveDate) -> Vec<NaiveDate> {
+        let first_monday = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+        let mut dates = Vec::new();
+        let mut date = start;
+
+        while date <= self.until {
+            let week = (date - first_monday).num_days() / 7;
+            if week % self.interval as i64 == 0 && self.by_day.contains(&date.weekday()) {
+                dates.push(date);
+            }
+            date += Duration::days(1);
+        }
+
+        dates
+    }
+}
+
+impl std::fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days: Vec<&str> = self.by_day.iter().map(|day| format_weekday(*day)).collect();
+        write!(f, "FREQ=WEEKLY;BYDAY={};UNTIL={}", days.join(","), self.until.format("%Y%m%d"))?;
+        if self.interval > 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every date from `start` through `end` inclusive.
+pub fn date_range(start: NaiveDate, end: NaiveDate) -> anyhow::Result<Vec<NaiveDate>> {
+    if end < start {
+        anyhow::bail!("End date must not be before start date");
+    }
+    if (end - start).num_days() > MAX_SPAN_DAYS {
+        anyhow::bail!("An absence may span at most {} days", MAX_SPAN_DAYS);
+    }
+    Ok(start.iter_days().take_while(|date| *date <= end).collect())
+}
+
+pub fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_weekly_by_day_until() {
+        // 2030-01-07 is a Monday
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20300116", date("2030-01-07")).unwrap();
+        assert_eq!(
+            rule.occurrences(date("2030-01-07")),
+            vec![date("2030-01-07"), date("2030-01-09"), date("2030-01-14"), date("2030-01-16")]
+        );
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20300116");
+    }
+
+    #[test]
+    fn test_defaults_and_interval() {
+        let rule = RecurrenceRule::parse("RRULE:FREQ=WEEKLY;INTERVAL=2;UNTIL=20300131T235959Z", date("2030-01-08")).unwrap();
+        assert_eq!(rule.by_day, vec![Weekday::Tue]);
+        assert_eq!(
+            rule.occurrences(date("2030-01-08")),
+            vec![date("2030-01-08"), date("2030-01-22")]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsupported_rules() {
+        let start = date("2030-01-07");
+        assert!(RecurrenceRule::parse("FREQ=DAILY;UNTIL=20300131", start).is_err());
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=MO", start).is_err());
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=XX;UNTIL=20300131", start).is_err());
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;COUNT=3;UNTIL=20300131", start).is_err());
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;UNTIL=20290101", start).is_err());
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;UNTIL=20350101", start).is_err());
+    }
+
+    #[test]
+    fn test_date_range() {
+        let dates = date_range(date("2030-01-10"), date("2030-01-14")).unwrap();
+        assert_eq!(dates.len(), 5);
+        assert_eq!(dates.iter().filter(|d| !is_weekend(**d)).count(), 3);
+        assert!(date_range(date("2030-01-14"), date("2030-01-10")).is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use crate::database::models::{
+    Absence, AbsenceStatus, AbsenceWithRequests, CreateAbsenceRequest, CreateSubstituteRequestRequest, ResponseType,
+    SubstituteRequest, UpdateAbsenceRequest,
+};
+use crate::database::timestamp;
+use crate::recurrence::{self, RecurrenceRule};
+use crate::repository::substitute_request::AssignmentConflict;
+use crate::repository::{
+    date_column, enum_column, query_all, query_optional, time_column, timestamp_column, CalendarRepository,
+    ClassRepository, FromRow, ResponseRepository, SubstituteRequestRepository,
+};
+use chrono::{Local, NaiveDate, Utc};
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for Absence {
+    const COLUMNS: &'static str =
+        "id, class_id, requested_by, start_date, end_date, start_time, end_time, recurrence_rule, reason, special_instructions, status, teacher_absence_id, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Absence {
+            id: row.get(0)?,
+            class_id: row.get(1)?,
+            requested_by: row.get(2)?,
+            start_date: date_column(row, 3)?,
+            end_date: date_column(row, 4)?,
+            start_time: time_column(row, 5)?,
+            end_time: time_column(row, 6)?,
+            recurrence_rule: row.get(7)?,
+            reason: row.get(8)?,
+            special_instructions: row.get(9)?,
+            status: enum_column(row, 10)?,
+            teacher_absence_id: row.get(11)?,
+            created_at: timestamp_column(row, 12)?,
+            updated_at: timestamp_column(row, 13)?,
+        })
+    }
+}
+
+pub struct AbsenceRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> AbsenceRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        AbsenceRepository { conn }
+    }
+
+    /// Creates the absence and one open substitute request per school day it
+    /// covers, in a single transaction. Weekends, holidays and closures on the
+    /// school calendar, and `excluded_dates` are skipped.
+    pub fn create(
+        &self,
+        requested_by: String,
+        request: CreateAbsenceRequest,
+    ) -> anyhow::Result<AbsenceWithRequests> {
+        let tx = self.conn.unchecked_transaction()?;
+        let created = self.insert(requested_by, request, None)?;
+        tx.commit()?;
+        Ok(created)
+    }
+
+    /// Validates and inserts an absence with its children. Runs inside the
+    /// caller's transaction so a teacher absence can add several atomically.
+    pub fn insert(
+        &self,
+        requested_by: String,
+        request: CreateAbsenceRequest,
+        teacher_absence_id: Option<&str>,
+    ) -> anyhow::Result<AbsenceWithRequests> {
+        let (dates, end_date, recurrence_rule) = match &request.recurrence_rule {
+            Some(rule) => {
+                let rule = RecurrenceRule::parse(rule, request.start_date)?;
+                (rule.occurrences(request.start_date), rule.until, Some(rule.to_string()))
+            }
+            None => {
+                let end_date = request
+                    .end_date
+                    .ok_or_else(|| anyhow::anyhow!("An absence needs an end date or a recurrence rule"))?;
+                (recurrence::date_range(request.start_date, end_date)?, end_date, None)
+            }
+        };
+
+        let class = ClassRepository::new(self.conn)
+            .find_by_id(&request.class_id)?
+            .ok_or_else(|| anyhow::anyhow!("Class not found"))?;
+        let calendar = CalendarRepository::new(self.conn);
+        let mut school_days: Vec<NaiveDate> = Vec::new();
+        for date in dates {
+            if recurrence::is_weekend(date) || request.excluded_dates.contains(&date) {
+                continue;
+            }
+            if calendar.closure_on(&class.organization_id, date)?.is_none() {
+                school_days.push(date);
+            }
+        }
+        let dates = school_days;
+        if dates.is_empty() {
+            anyhow::bail!("The absence does not cover any school days");
+        }
+
+        let now = Local::now().naive_local();
+        let requests = SubstituteRequestRepository::new(self.conn);
+        for date in &dates {
+            requests.validate_schedule(&request.class_id, *date, request.start_time, request.end_time, now)?;
+        }
+
+        let absence = Absence {
+            id: Uuid::new_v4().to_string(),
+            class_id: request.class_id,
+            requested_by,
+            start_date: request.start_date,
+            end_date,
+            start_time: request.start_time,
+            end_time: request.end_time,
+            recurrence_rule,
+            reason: request.reason,
+            special_instructions: request.special_instructions,
+            status: AbsenceStatus::Active,
+            teacher_absence_id: teacher_absence_id.map(str::to_string),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO absences (id, class_id, requested_by, start_date, end_date, start_time, end_time, recurrence_rule, reason, special_instructions, status, teacher_absence_id, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            (
+                &absence.id,
+                &absence.class_id,
+                &absence.requested_by,
+                &timestamp::format_date(&absence.start_date),
+                &timestamp::format_date(&absence.end_date),
+                &timestamp::format_time(&absence.start_time),
+                &timestamp::format_time(&absence.end_time),
+                &absence.recurrence_rule,
+                &absence.reason,
+                &absence.special_instructions,
+                &absence.status.to_string(),
+                &absence.teacher_absence_id,
+                &timestamp::format(&absence.created_at),
+                &timestamp::format(&absence.updated_at),
+            ),
+        )?;
+
+        let children = dates
+            .into_iter()
+            .map(|date_needed| {
+                requests.insert(
+                    absence.requested_by.clone(),
+                    CreateSubstituteRequestRequest {
+                        class_id: absence.class_id.clone(),
+                        date_needed,
+                        start_time: absence.start_time,
+                        end_time: absence.end_time,
+                        reason: absence.reason.clone(),
+                        special_instructions: absence.special_instructions.clone(),
+                    },
+                    Some(&absence.id),
+                )
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(AbsenceWithRequests { absence, requests: children })
+    }
+
+    pub fn list(&self) -> anyhow::Result<Vec<Absence>> {
+        let sql = format!("SELECT {} FROM absences ORDER BY start_date, start_time", Absence::COLUMNS);
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<Absence>> {
+        let sql = format!("SELECT {} FROM absences WHERE id = ?1", Absence::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    pub fn find_with_requests(&self, id: &str) -> anyhow::Result<Option<AbsenceWithRequests>> {
+        let Some(absence) = self.find_by_id(id)? else {
+            return Ok(None);
+        };
+        let sql = format!(
+            "SELECT {} FROM substitute_requests WHERE absence_id = ?1 ORDER BY date_needed",
+            SubstituteRequest::COLUMNS
+        );
+        let requests = query_all(self.conn, &sql, [id])?;
+        Ok(Some(AbsenceWithRequests { absence, requests }))
+    }
+
+    /// Children dated today or later that are still open or filled — the part
+    /// of the block that edits and cancellation apply to.
+    fn remaining_requests(&self, id: &str, today: NaiveDate) -> anyhow::Result<Vec<SubstituteRequest>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE absence_id = ?1 AND date_needed >= ?2 AND status IN ('open', 'filled')
+             ORDER BY date_needed",
+            SubstituteRequest::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [id, &timestamp::format_date(&today)])?)
+    }
+
+    /// Applies new times, reason and instructions to the absence and its
+    /// remaining children. Returns the updated children, or `None` when no
+    /// absence has the given id. Fails with [`AssignmentConflict`] when the
+    /// new times would double-book a substitute already filling a day.
+    pub fn update(
+        &self,
+        id: &str,
+        request: UpdateAbsenceRequest,
+    ) -> anyhow::Result<Option<Vec<SubstituteRequest>>> {
+        let Some(absence) = self.find_by_id(id)? else {
+            return Ok(None);
+        };
+        if absence.status == AbsenceStatus::Cancelled {
+            anyhow::bail!("Cannot edit a cancelled absence");
+        }
+
+        let now = Local::now().naive_local();
+        let remaining = self.remaining_requests(id, now.date())?;
+        let requests = SubstituteRequestRepository::new(self.conn);
+        for child in &remaining {
+            requests.validate_schedule(&absence.class_id, child.date_needed, request.start_time, request.end_time, now)?;
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        for child in &remaining {
+            let Some(substitute_id) = &child.assigned_substitute_id else {
+                continue;
+            };
+            let moved = SubstituteRequest {
+                start_time: request.start_time,
+                end_time: request.end_time,
+                ..child.clone()
+            };
+            let conflicts = requests.find_conflicts(substitute_id, &moved)?;
+            if !conflicts.is_empty() {
+                return Err(AssignmentConflict { conflicts }.into());
+            }
+        }
+        let updated_at = timestamp::now();
+        self.conn.execute(
+            "UPDATE absences SET start_time = ?1, end_time = ?2, reason = ?3, special_instructions = ?4, updated_at = ?5
+             WHERE id = ?6",
+            (
+                &timestamp::format_time(&request.start_time),
+                &timestamp::format_time(&request.end_time),
+                &request.reason,
+                &request.special_instructions,
+                &updated_at,
+                id,
+            ),
+        )?;
+        for child in &remaining {
+            self.conn.execute(
+                "UPDATE substitute_requests SET start_time = ?1, end_time = ?2, reason = ?3, special_instructions = ?4, updated_at = ?5
+                 WHERE id = ?6",
+                (
+                    &timestamp::format_time(&request.start_time),
+                    &timestamp::format_time(&request.end_time),
+                    &request.reason,
+                    &request.special_instructions,
+                    &updated_at,
+                    &child.id,
+                ),
+            )?;
+        }
+        tx.commit()?;
+
+        SubstituteRequestRepository::new(self.conn).reload(&remaining).map(Some)
+    }
+
+    /// Cancels the absence and its remaining children; days already past are
+    /// left as they were. Returns the cancelled children, or `None` when no
+    /// absence has the given id.
+    pub fn cancel(&self, id: &str) -> anyhow::Result<Option<Vec<SubstituteRequest>>> {
+        if self.find_by_id(id)?.is_none() {
+            return Ok(None);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let remaining = self.cancel_remaining(id)?;
+        tx.commit()?;
+
+        SubstituteRequestRepository::new(self.conn).reload(&remaining).map(Some)
+    }
+
+    /// Marks the absence and its remaining children cancelled without opening
+    /// a transaction, returning the children as they were before.
+    pub fn cancel_remaining(&self, id: &str) -> anyhow::Result<Vec<SubstituteRequest>> {
+        let remaining = self.remaining_requests(id, Local::now().date_naive())?;
+        let updated_at = timestamp::now();
+        self.conn.execute(
+            "UPDATE absences SET status = ?1, updated_at = ?2 WHERE id = ?3",
+            (AbsenceStatus::Cancelled.to_string(), &updated_at, id),
+        )?;
+        for child in &remaining {
+            self.conn.execute(
+                "UPDATE substitute_requests SET status = 'cancelled', updated_at = ?1 WHERE id = ?2",
+                (&updated_at, &child.id),
+            )?;
+        }
+        Ok(remaining)
+    }
+
+    /// Assigns the substitute to every remaining open day of the block. Days
+    /// someone else already took are left alone; single days can still be
+    /// accepted individually through the substitute request commands.
+    pub fn accept(&self, id: &str, substitute_id: &str) -> anyhow::Result<Vec<SubstituteRequest>> {
+        let absence = self
+            .find_by_id(id)?
+            .ok_or_else(|| anyhow::anyhow!("Absence not found"))?;
+        if absence.status == AbsenceStatus::Cancelled {
+            anyhow::bail!("Cannot accept a cancelled absence");
+        }
+
+        let open: Vec<SubstituteRequest> = self
+            .remaining_requests(id, Local::now().date_naive())?
+            .into_iter()
+            .filter(|child| child.assigned_substitute_id.is_none())
+            .collect();
+        if open.is_empty() {
+            anyhow::bail!("No open days left in this absence");
+        }
+
+        let requests = SubstituteRequestRepository::new(self.conn);
+        let responses = ResponseRepository::new(self.conn);
+        let tx = self.conn.unchecked_transaction()?;
+        let updated_at = timestamp::now();
+        for child in &open {
+            requests.ensure_available(substitute_id, child)?;
+            self.conn.execute(
+                "UPDATE substitute_requests SET status = 'filled', assigned_substitute_id = ?1, updated_at = ?2 WHERE id = ?3",
+                (substitute_id, &updated_at, &child.id),
+            )?;
+            responses.record(&child.id, substitute_id, ResponseType::Accepted, None)?;
+        }
+        tx.commit()?;
+
+        SubstituteRequestRepository::new(self.conn).reload(&open)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::RequestStatus;
+    use crate::repository::fixtures::{future_date, future_day, insert_class, insert_organization, insert_user, test_connection};
+
+    fn setup() -> Connection {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "class-1", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        conn
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        future_date(value)
+    }
+
+    fn create_absence(start: &str, end: Option<&str>, rule: Option<&str>) -> CreateAbsenceRequest {
+        CreateAbsenceRequest {
+            class_id: "class-1".to_string(),
+            start_date: date(start),
+            end_date: end.map(date),
+            start_time: timestamp::parse_time("08:00").unwrap(),
+            end_time: timestamp::parse_time("15:00").unwrap(),
+            recurrence_rule: rule.map(str::to_string),
+            excluded_dates: Vec::new(),
+            reason: Some("Medical leave".to_string()),
+            special_instructions: None,
+        }
+    }
+
+    #[test]
+    fn test_date_range_skips_weekends_closures_and_excluded_dates() {
+        let conn = setup();
+        conn.execute(
+            "INSERT INTO calendar_events (id, organization_id, event_type, name, start_date, end_date)
+             VALUES ('mlk', 'org-1', 'holiday', 'MLK Day', ?1, ?1)",
+            [future_day("2030-01-21")],
+        )
+        .unwrap();
+        let repo = AbsenceRepository::new(&conn);
+
+        // Thursday 2030-01-10 through Tuesday 2030-01-22
+        let mut request = create_absence("2030-01-10", Some("2030-01-22"), None);
+        request.excluded_dates = vec![date("2030-01-14"), date("2030-01-16"), date("2030-01-17"), date("2030-01-18")];
+        let created = repo.create("manager-1".to_string(), request).unwrap();
+
+        let dates: Vec<NaiveDate> = created.requests.iter().map(|r| r.date_needed).collect();
+        assert_eq!(dates, vec![date("2030-01-10"), date("2030-01-11"), date("2030-01-15"), date("2030-01-22")]);
+        assert!(created.requests.iter().all(|r| r.absence_id.as_deref() == Some(created.absence.id.as_str())));
+
+        let found = repo.find_with_requests(&created.absence.id).unwrap().unwrap();
+        assert_eq!(found.requests.len(), 4);
+    }
+
+    #[test]
+    fn test_recurring_absence() {
+        let conn = setup();
+        let repo = AbsenceRepository::new(&conn);
+
+        let rule = format!("FREQ=WEEKLY;BYDAY=MO,FR;UNTIL={}", date("2030-01-18").format("%Y%m%d"));
+        let created = repo.create("manager-1".to_string(), create_absence("2030-01-07", None, Some(&rule))).unwrap();
+
+        assert_eq!(created.absence.end_date, date("2030-01-18"));
+        assert_eq!(created.absence.recurrence_rule.as_deref(), Some(rule.as_str()));
+        assert_eq!(created.requests.len(), 4);
+    }
+
+    #[test]
+    fn test_invalid_child_rolls_back_whole_block() {
+        let conn = setup();
+        let repo = AbsenceRepository::new(&conn);
+
+        assert!(repo.create("manager-1".to_string(), create_absence("2030-01-12", Some("2030-01-13"), None)).is_err());
+
+        let mut backwards = create_absence("2030-01-10", Some("2030-01-11"), None);
+        backwards.end_time = timestamp::parse_time("07:00").unwrap();
+        assert!(repo.create("manager-1".to_string(), backwards).is_err());
+
+        assert!(repo.list().unwrap().is_empty());
+        assert!(SubstituteRequestRepository::new(&conn).list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_accept_update_and_cancel_apply_to_remaining_days() {
+        let conn = setup();
+        let repo = AbsenceRepository::new(&conn);
+        let created = repo
+            .create("manager-1".to_string(), create_absence("2030-01-07", Some("2030-01-09"), None))
+            .unwrap();
+        let id = &created.absence.id;
+
+        // One day is taken individually before the block is accepted
+        SubstituteRequestRepository::new(&conn)
+            .update_status(&created.requests[0].id, "filled", Some("sub-1"))
+            .unwrap();
+        let accepted = repo.accept(id, "sub-1").unwrap();
+        assert_eq!(accepted.len(), 2);
+        assert!(accepted.iter().all(|r| matches!(r.status, RequestStatus::Filled)));
+        assert!(repo.accept(id, "sub-1").is_err());
+
+        let updated = repo
+            .update(
+                id,
+                UpdateAbsenceRequest {
+                    start_time: timestamp::parse_time("09:00").unwrap(),
+                    end_time: timestamp::parse_time("12:00").unwrap(),
+                    reason: Some("Half days".to_string()),
+                    special_instructions: None,
+                },
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.len(), 3);
+        assert!(updated.iter().all(|r| timestamp::format_time(&r.start_time) == "09:00"));
+
+        let cancelled = repo.cancel(id).unwrap().unwrap();
+        assert_eq!(cancelled.len(), 3);
+        assert!(cancelled.iter().all(|r| matches!(r.status, RequestStatus::Cancelled)));
+        assert_eq!(repo.find_by_id(id).unwrap().unwrap().status, AbsenceStatus::Cancelled);
+        assert!(repo.cancel("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_update_rejects_times_that_double_book_a_filled_day() {
+        let conn = setup();
+        let repo = AbsenceRepository::new(&conn);
+        let created = repo
+            .create("manager-1".to_string(), create_absence("2030-01-07", Some("2030-01-07"), None))
+            .unwrap();
+        repo.accept(&created.absence.id, "sub-1").unwrap();
+
+        let requests = SubstituteRequestRepository::new(&conn);
+        let afternoon = requests
+            .create(
+                "manager-1".to_string(),
+                CreateSubstituteRequestRequest {
+                    class_id: "class-1".to_string(),
+                    date_needed: date("2030-01-07"),
+                    start_time: timestamp::parse_time("15:30").unwrap(),
+                    end_time: timestamp::parse_time("17:00").unwrap(),
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap();
+        requests.update_status(&afternoon.id, "filled", Some("sub-1")).unwrap();
+
+        let error = repo
+            .update(
+                &created.absence.id,
+                UpdateAbsenceRequest {
+                    start_time: timestamp::parse_time("08:00").unwrap(),
+                    end_time: timestamp::parse_time("16:00").unwrap(),
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap_err();
+        let conflict = error.downcast_ref::<AssignmentConflict>().unwrap();
+        assert_eq!(conflict.conflicts[0].id, afternoon.id);
+
+        let child = requests.find_by_id(&created.requests[0].id).unwrap().unwrap();
+        assert_eq!(timestamp::format_time(&child.end_time), "15:00");
+        assert_eq!(timestamp::format_time(&repo.find_by_id(&created.absence.id).unwrap().unwrap().end_time), "15:00");
+    }
+}
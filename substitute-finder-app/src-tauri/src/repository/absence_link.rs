@@ -0,0 +1,47 @@
+use crate::database::timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+/// The teacher absence an iCalendar event was imported as, and a fingerprint
+/// of the event's fields at the time.
+pub struct AbsenceLink {
+    pub teacher_absence_id: String,
+    pub fingerprint: String,
+}
+
+/// Links between iCalendar event UIDs and the teacher absences imported from them.
+pub struct AbsenceLinkRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> AbsenceLinkRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        AbsenceLinkRepository { conn }
+    }
+
+    pub fn find_link(&self, uid: &str) -> anyhow::Result<Option<AbsenceLink>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT teacher_absence_id, fingerprint FROM calendar_absence_links WHERE uid = ?1",
+                [uid],
+                |row| {
+                    Ok(AbsenceLink {
+                        teacher_absence_id: row.get(0)?,
+                        fingerprint: row.get(1)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    pub fn save_link(&self, uid: &str, teacher_absence_id: &str, fingerprint: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT INTO calendar_absence_links (uid, teacher_absence_id, fingerprint, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(uid) DO UPDATE SET teacher_absence_id = excluded.teacher_absence_id,
+                 fingerprint = excluded.fingerprint, updated_at = excluded.updated_at",
+            (uid, teacher_absence_id, fingerprint, &timestamp::now()),
+        )?;
+        Ok(())
+    }
+}
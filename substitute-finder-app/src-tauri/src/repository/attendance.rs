@@ -0,0 +1,371 @@
+use crate::database::models::{
+    AssignmentAttendance, AttendanceFlag, AttendanceOverrideRequest, RequestStatus, SubstituteRequest,
+};
+use crate::database::timestamp;
+use crate::repository::setting::{CHECK_IN_TOLERANCE_MINUTES, NO_SHOW_GRACE_MINUTES};
+use crate::repository::{
+    json_column, optional_timestamp_column, query_all, query_optional, timestamp_column, FromRow, SettingsRepository,
+    SubstituteRequestRepository,
+};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, Utc};
+use rusqlite::{Connection, Row};
+
+impl FromRow for AssignmentAttendance {
+    const COLUMNS: &'static str =
+        "request_id, substitute_id, check_in_at, check_out_at, flags, override_reason, overridden_by, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(AssignmentAttendance {
+            request_id: row.get(0)?,
+            substitute_id: row.get(1)?,
+            check_in_at: optional_timestamp_column(row, 2)?,
+            check_out_at: optional_timestamp_column(row, 3)?,
+            flags: json_column(row, 4)?,
+            override_reason: row.get(5)?,
+            overridden_by: row.get(6)?,
+            updated_at: timestamp_column(row, 7)?,
+        })
+    }
+}
+
+/// Schedules are in school-local time, check-ins are UTC instants.
+fn local(at: DateTime<Utc>) -> NaiveDateTime {
+    at.with_timezone(&Local).naive_local()
+}
+
+/// Check-in and check-out of assigned substitutes, and the status changes
+/// they drive.
+pub struct AttendanceRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> AttendanceRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        AttendanceRepository { conn }
+    }
+
+    pub fn find(&self, request_id: &str) -> anyhow::Result<Option<AssignmentAttendance>> {
+        let sql = format!("SELECT {} FROM assignment_attendance WHERE request_id = ?1", AssignmentAttendance::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [request_id])?)
+    }
+
+    /// Records the substitute's arrival on the day of the assignment and moves
+    /// the request to in progress.
+    pub fn check_in(
+        &self,
+        request_id: &str,
+        substitute_id: &str,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<AssignmentAttendance> {
+        let request = self.assigned_request(request_id, substitute_id)?;
+        match request.status {
+            RequestStatus::Filled => {}
+            RequestStatus::InProgress | RequestStatus::Completed => anyhow::bail!("Already checked in"),
+            _ => anyhow::bail!("Request is not awaiting check-in"),
+        }
+        if local(now).date() != request.date_needed {
+            anyhow::bail!("Check-in is only open on the day of the assignment");
+        }
+
+        let mut attendance = AssignmentAttendance {
+            request_id: request.id.clone(),
+            substitute_id: substitute_id.to_string(),
+            check_in_at: Some(now),
+            check_out_at: None,
+            flags: Vec::new(),
+            override_reason: None,
+            overridden_by: None,
+            updated_at: Utc::now(),
+        };
+        attendance.flags = self.discrepancies(&request, &attendance)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.save(&attendance)?;
+        self.set_status(&request.id, RequestStatus::InProgress)?;
+        tx.commit()?;
+
+        Ok(attendance)
+    }
+
+    /// Records the substitute leaving and completes the request.
+    pub fn check_out(
+        &self,
+        request_id: &str,
+        substitute_id: &str,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<AssignmentAttendance> {
+        let request = self.assigned_request(request_id, substitute_id)?;
+        let mut attendance = match (&request.status, self.find(request_id)?) {
+            (RequestStatus::InProgress, Some(attendance)) => attendance,
+            (RequestStatus::Completed, _) => anyhow::bail!("Already checked out"),
+            _ => anyhow::bail!("Check in before checking out"),
+        };
+        if attendance.check_in_at.is_some_and(|check_in_at| now <= check_in_at) {
+            anyhow::bail!("Check-out must be after check-in");
+        }
+
+        attendance.check_out_at = Some(now);
+        attendance.flags = self.discrepancies(&request, &attendance)?;
+        attendance.updated_at = Utc::now();
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.save(&attendance)?;
+        self.set_status(&request.id, RequestStatus::Completed)?;
+        tx.commit()?;
+
+        Ok(attendance)
+    }
+
+    /// Replaces the recorded times with a manager's, keeping their reason.
+    /// The request is completed when a check-out is given, otherwise in
+    /// progress; this also clears a no-show.
+    pub fn override_attendance(&self, request: AttendanceOverrideRequest) -> anyhow::Result<AssignmentAttendance> {
+        if request.reason.trim().is_empty() {
+            anyhow::bail!("A reason is required to override attendance");
+        }
+        if request.check_out_at.is_some_and(|check_out_at| check_out_at <= request.check_in_at) {
+            anyhow::bail!("Check-out must be after check-in");
+        }
+
+        let substitute_request = SubstituteRequestRepository::new(self.conn)
+            .find_by_id(&request.request_id)?
+            .ok_or_else(|| anyhow::anyhow!("Substitute request not found"))?;
+        let substitute_id = match (&substitute_request.status, &substitute_request.assigned_substitute_id) {
+            (
+                RequestStatus::Filled | RequestStatus::InProgress | RequestStatus::Completed | RequestStatus::NoShow,
+                Some(substitute_id),
+            ) => substitute_id.clone(),
+            _ => anyhow::bail!("Only assigned requests have attendance"),
+        };
+
+        let mut attendance = AssignmentAttendance {
+            request_id: substitute_request.id.clone(),
+            substitute_id,
+            check_in_at: Some(request.check_in_at),
+            check_out_at: request.check_out_at,
+            flags: Vec::new(),
+            override_reason: Some(request.reason),
+            overridden_by: Some(request.manager_id),
+            updated_at: Utc::now(),
+        };
+        attendance.flags = self.discrepancies(&substitute_request, &attendance)?;
+        let status = match attendance.check_out_at {
+            Some(_) => RequestStatus::Completed,
+            None => RequestStatus::InProgress,
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.save(&attendance)?;
+        self.set_status(&substitute_request.id, status)?;
+        tx.commit()?;
+
+        Ok(attendance)
+    }
+
+    /// Marks filled requests nobody checked in to within the grace period as
+    /// no-shows, and completes in-progress ones the grace period past their
+    /// end time, flagging the missing check-out. Returns the requests changed.
+    ///
+    /// Only today's and yesterday's filled requests can become no-shows, so
+    /// assignments from before check-ins were recorded keep their status.
+    pub fn sweep(&self, now: DateTime<Utc>) -> anyhow::Result<Vec<SubstituteRequest>> {
+        let grace = Duration::minutes(SettingsRepository::new(self.conn).get_i64(NO_SHOW_GRACE_MINUTES, 15)?);
+        let now_local = local(now);
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE date_needed <= ?1 AND (status = 'in_progress' OR (status = 'filled' AND date_needed >= ?2))
+             ORDER BY date_needed, start_time",
+            SubstituteRequest::COLUMNS
+        );
+        let candidates: Vec<SubstituteRequest> = query_all(
+            self.conn,
+            &sql,
+            [
+                timestamp::format_date(&now_local.date()),
+                timestamp::format_date(&(now_local.date() - Duration::days(1))),
+            ],
+        )?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut changed = Vec::new();
+        for request in candidates {
+            match request.status {
+                RequestStatus::Filled if request.date_needed.and_time(request.start_time) + grace < now_local => {
+                    self.set_status(&request.id, RequestStatus::NoShow)?;
+                }
+                RequestStatus::InProgress if request.date_needed.and_time(request.end_time) + grace < now_local => {
+                    if let Some(mut attendance) = self.find(&request.id)? {
+                        attendance.flags.push(AttendanceFlag::MissingCheckOut);
+                        attendance.updated_at = Utc::now();
+                        self.save(&attendance)?;
+                    }
+                    self.set_status(&request.id, RequestStatus::Completed)?;
+                }
+                _ => continue,
+            }
+            changed.push(request);
+        }
+        tx.commit()?;
+
+        SubstituteRequestRepository::new(self.conn).reload(&changed)
+    }
+
+    fn assigned_request(&self, request_id: &str, substitute_id: &str) -> anyhow::Result<SubstituteRequest> {
+        let request = SubstituteRequestRepository::new(self.conn)
+            .find_by_id(request_id)?
+            .ok_or_else(|| anyhow::anyhow!("Substitute request not found"))?;
+        if request.assigned_substitute_id.as_deref() != Some(substitute_id) {
+            anyhow::bail!("Only the assigned substitute can check in or out");
+        }
+        Ok(request)
+    }
+
+    /// Flags times further off the schedule than the configured tolerance.
+    fn discrepancies(
+        &self,
+        request: &SubstituteRequest,
+        attendance: &AssignmentAttendance,
+    ) -> anyhow::Result<Vec<AttendanceFlag>> {
+        let tolerance =
+            Duration::minutes(SettingsRepository::new(self.conn).get_i64(CHECK_IN_TOLERANCE_MINUTES, 5)?);
+        let mut flags = Vec::new();
+        if attendance
+            .check_in_at
+            .is_some_and(|at| local(at) > request.date_needed.and_time(request.start_time) + tolerance)
+        {
+            flags.push(AttendanceFlag::LateCheckIn);
+        }
+        if attendance
+            .check_out_at
+            .is_some_and(|at| local(at) < request.date_needed.and_time(request.end_time) - tolerance)
+        {
+            flags.push(AttendanceFlag::EarlyCheckOut);
+        }
+        Ok(flags)
+    }
+
+    fn save(&self, attendance: &AssignmentAttendance) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT INTO assignment_attendance (request_id, substitute_id, check_in_at, check_out_at, flags, override_reason, overridden_by, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(request_id) DO UPDATE SET substitute_id = excluded.substitute_id,
+                 check_in_at = excluded.check_in_at, check_out_at = excluded.check_out_at, flags = excluded.flags,
+                 override_reason = excluded.override_reason, overridden_by = excluded.overridden_by,
+                 updated_at = excluded.updated_at",
+            (
+                &attendance.request_id,
+                &attendance.substitute_id,
+                &attendance.check_in_at.as_ref().map(timestamp::format),
+                &attendance.check_out_at.as_ref().map(timestamp::format),
+                &serde_json::to_string(&attendance.flags)?,
+                &attendance.override_reason,
+                &attendance.overridden_by,
+                &timestamp::format(&attendance.updated_at),
+            ),
+        )?;
+        Ok(())
+    }
+
+    fn set_status(&self, request_id: &str, status: RequestStatus) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE substitute_requests SET status = ?1, updated_at = ?2 WHERE id = ?3",
+            (&status.to_string(), &timestamp::now(), request_id),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::CreateSubstituteRequestRequest;
+    use crate::repository::fixtures::{
+        future_date, future_day, insert_class, insert_organization, insert_user, test_connection,
+    };
+    use chrono::TimeZone;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        let (date, time) = value.split_once(' ').unwrap();
+        let naive = future_date(date).and_time(timestamp::parse_time(time).unwrap());
+        Local.from_local_datetime(&naive).unwrap().with_timezone(&Utc)
+    }
+
+    fn status(conn: &Connection, id: &str) -> String {
+        SubstituteRequestRepository::new(conn).find_by_id(id).unwrap().unwrap().status.to_string()
+    }
+
+    #[test]
+    fn test_check_in_check_out_and_no_show() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "class-1", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        insert_user(&conn, "sub-2", "substitute");
+        let requests = SubstituteRequestRepository::new(&conn);
+        let repo = AttendanceRepository::new(&conn);
+
+        let mut ids = Vec::new();
+        for date in ["2030-01-15", "2030-01-16"] {
+            let request = requests
+                .create(
+                    "manager-1".to_string(),
+                    CreateSubstituteRequestRequest {
+                        class_id: "class-1".to_string(),
+                        date_needed: future_date(date),
+                        start_time: timestamp::parse_time("08:00").unwrap(),
+                        end_time: timestamp::parse_time("15:00").unwrap(),
+                        reason: None,
+                        special_instructions: None,
+                    },
+                )
+                .unwrap();
+            requests.update_status(&request.id, "filled", Some("sub-1")).unwrap();
+            ids.push(request.id);
+        }
+
+        assert!(repo.check_in(&ids[0], "sub-2", at("2030-01-15 07:55")).is_err());
+        assert!(repo.check_in(&ids[0], "sub-1", at("2030-01-14 07:55")).is_err());
+        assert!(repo.check_out(&ids[0], "sub-1", at("2030-01-15 15:00")).is_err());
+
+        let checked_in = repo.check_in(&ids[0], "sub-1", at("2030-01-15 08:20")).unwrap();
+        assert_eq!(checked_in.flags, vec![AttendanceFlag::LateCheckIn]);
+        assert_eq!(status(&conn, &ids[0]), "in_progress");
+        assert!(repo.check_in(&ids[0], "sub-1", at("2030-01-15 08:30")).is_err());
+
+        let checked_out = repo.check_out(&ids[0], "sub-1", at("2030-01-15 14:00")).unwrap();
+        assert_eq!(checked_out.flags, vec![AttendanceFlag::LateCheckIn, AttendanceFlag::EarlyCheckOut]);
+        assert_eq!(checked_out.time_worked(), Some(Duration::minutes(340)));
+        assert_eq!(status(&conn, &ids[0]), "completed");
+
+        // Nobody checks in on the second day, nor on an assignment filled
+        // before check-ins were recorded, which is left as it was
+        conn.execute(
+            "INSERT INTO substitute_requests
+                 (id, class_id, requested_by, date_needed, start_time, end_time, status, assigned_substitute_id)
+             VALUES ('old', 'class-1', 'manager-1', ?1, '08:00', '15:00', 'filled', 'sub-1')",
+            [future_day("2030-01-14")],
+        )
+        .unwrap();
+        assert!(repo.sweep(at("2030-01-16 08:10")).unwrap().is_empty());
+        let swept = repo.sweep(at("2030-01-16 08:16")).unwrap();
+        assert_eq!(swept.len(), 1);
+        assert!(matches!(swept[0].status, RequestStatus::NoShow));
+        assert_eq!(status(&conn, "old"), "filled");
+
+        // The manager confirms they came after all
+        let override_request = |reason: &str| AttendanceOverrideRequest {
+            request_id: ids[1].clone(),
+            manager_id: "manager-1".to_string(),
+            check_in_at: at("2030-01-16 08:00"),
+            check_out_at: Some(at("2030-01-16 15:00")),
+            reason: reason.to_string(),
+        };
+        assert!(repo.override_attendance(override_request(" ")).is_err());
+        let corrected = repo.override_attendance(override_request("Signed in at the front office")).unwrap();
+        assert!(corrected.flags.is_empty());
+        assert_eq!(corrected.overridden_by.as_deref(), Some("manager-1"));
+        assert_eq!(status(&conn, &ids[1]), "completed");
+        assert_eq!(repo.find(&ids[1]).unwrap().unwrap().time_worked(), Some(Duration::hours(7)));
+    }
+}
@@ -0,0 +1,396 @@
+use crate::database::models::{BellSchedule, CreateBellScheduleRequest, Period};
+use crate::database::timestamp;
+use crate::repository::{
+    query_all, query_optional, time_column, timestamp_column, ClassRepository, FromRow, OrganizationRepository,
+};
+use chrono::{NaiveDate, NaiveTime, Utc};
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for BellSchedule {
+    const COLUMNS: &'static str = "id, organization_id, name, is_default, created_at, updated_at";
+
+    /// Periods are loaded separately by the repository.
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(BellSchedule {
+            id: row.get(0)?,
+            organization_id: row.get(1)?,
+            name: row.get(2)?,
+            is_default: row.get(3)?,
+            periods: Vec::new(),
+            created_at: timestamp_column(row, 4)?,
+            updated_at: timestamp_column(row, 5)?,
+        })
+    }
+}
+
+impl FromRow for Period {
+    const COLUMNS: &'static str = "id, schedule_id, name, position, start_time, end_time";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Period {
+            id: row.get(0)?,
+            schedule_id: row.get(1)?,
+            name: row.get(2)?,
+            position: row.get(3)?,
+            start_time: time_column(row, 4)?,
+            end_time: time_column(row, 5)?,
+        })
+    }
+}
+
+/// Rejects schedules without periods, with repeated period names, or whose
+/// periods end before they start or overlap the previous one.
+fn validate_schedule(request: &CreateBellScheduleRequest) -> anyhow::Result<()> {
+    if request.name.trim().is_empty() {
+        anyhow::bail!("Schedule name is required");
+    }
+    if request.periods.is_empty() {
+        anyhow::bail!("A bell schedule needs at least one period");
+    }
+
+    let mut previous_end: Option<NaiveTime> = None;
+    for (index, period) in request.periods.iter().enumerate() {
+        if request.periods[..index].iter().any(|p| p.name == period.name) {
+            anyhow::bail!("Period {} appears more than once", period.name);
+        }
+        if period.end_time <= period.start_time {
+            anyhow::bail!("Period {} must end after it starts", period.name);
+        }
+        if previous_end.is_some_and(|end| period.start_time < end) {
+            anyhow::bail!("Period {} overlaps the period before it", period.name);
+        }
+        previous_end = Some(period.end_time);
+    }
+
+    Ok(())
+}
+
+pub struct BellScheduleRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> BellScheduleRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        BellScheduleRepository { conn }
+    }
+
+    pub fn create(&self, request: CreateBellScheduleRequest) -> anyhow::Result<BellSchedule> {
+        validate_schedule(&request)?;
+        let id = Uuid::new_v4().to_string();
+        let now = timestamp::format(&Utc::now());
+
+        let tx = self.conn.unchecked_transaction()?;
+        if request.is_default {
+            self.clear_default(&request.organization_id)?;
+        }
+        self.conn.execute(
+            "INSERT INTO bell_schedules (id, organization_id, name, is_default, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+            (&id, &request.organization_id, &request.name, &request.is_default, &now),
+        )?;
+        self.insert_periods(&id, &request)?;
+        tx.commit()?;
+
+        self.find_by_id(&id)?
+            .ok_or_else(|| anyhow::anyhow!("Bell schedule not found after insert"))
+    }
+
+    fn clear_default(&self, organization_id: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE bell_schedules SET is_default = false WHERE organization_id = ?1",
+            [organization_id],
+        )?;
+        Ok(())
+    }
+
+    fn insert_periods(&self, schedule_id: &str, request: &CreateBellScheduleRequest) -> anyhow::Result<()> {
+        for (position, period) in request.periods.iter().enumerate() {
+            self.conn.execute(
+                "INSERT INTO bell_schedule_periods (id, schedule_id, name, position, start_time, end_time)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                (
+                    &Uuid::new_v4().to_string(),
+                    schedule_id,
+                    &period.name,
+                    &(position as i32 + 1),
+                    &timestamp::format_time(&period.start_time),
+                    &timestamp::format_time(&period.end_time),
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn with_periods(&self, mut schedule: BellSchedule) -> anyhow::Result<BellSchedule> {
+        let sql = format!(
+            "SELECT {} FROM bell_schedule_periods WHERE schedule_id = ?1 ORDER BY position",
+            Period::COLUMNS
+        );
+        schedule.periods = query_all(self.conn, &sql, [&schedule.id])?;
+        Ok(schedule)
+    }
+
+    pub fn list_by_organization(&self, organization_id: &str) -> anyhow::Result<Vec<BellSchedule>> {
+        let sql = format!(
+            "SELECT {} FROM bell_schedules WHERE organization_id = ?1 ORDER BY is_default DESC, name",
+            BellSchedule::COLUMNS
+        );
+        query_all(self.conn, &sql, [organization_id])?
+            .into_iter()
+            .map(|schedule| self.with_periods(schedule))
+            .collect()
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<BellSchedule>> {
+        let sql = format!("SELECT {} FROM bell_schedules WHERE id = ?1", BellSchedule::COLUMNS);
+        query_optional(self.conn, &sql, [id])?
+            .map(|schedule| self.with_periods(schedule))
+            .transpose()
+    }
+
+    /// Replaces the schedule's name, default flag and periods. Returns `None`
+    /// when no schedule has the given id.
+    pub fn update(
+        &self,
+        id: &str,
+        request: CreateBellScheduleRequest,
+    ) -> anyhow::Result<Option<BellSchedule>> {
+        validate_schedule(&request)?;
+        if self.find_by_id(id)?.is_none() {
+            return Ok(None);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        if request.is_default {
+            self.clear_default(&request.organization_id)?;
+        }
+        self.conn.execute(
+            "UPDATE bell_schedules SET organization_id = ?1, name = ?2, is_default = ?3, updated_at = ?4 WHERE id = ?5",
+            (&request.organization_id, &request.name, &request.is_default, &timestamp::now(), id),
+        )?;
+        self.conn.execute("DELETE FROM bell_schedule_periods WHERE schedule_id = ?1", [id])?;
+        self.insert_periods(id, &request)?;
+        tx.commit()?;
+
+        self.find_by_id(id)
+    }
+
+    /// Deletes the schedule, its periods and any dates assigned to it.
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute("DELETE FROM bell_schedule_days WHERE schedule_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM bell_schedule_periods WHERE schedule_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM bell_schedules WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Runs `date` on `schedule_id` instead of the default, or back on the
+    /// default when `schedule_id` is `None`.
+    pub fn set_schedule_for_date(
+        &self,
+        organization_id: &str,
+        date: NaiveDate,
+        schedule_id: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let date = timestamp::format_date(&date);
+        match schedule_id {
+            Some(schedule_id) => {
+                self.conn.execute(
+                    "INSERT INTO bell_schedule_days (organization_id, date, schedule_id) VALUES (?1, ?2, ?3)
+                     ON CONFLICT(organization_id, date) DO UPDATE SET schedule_id = excluded.schedule_id",
+                    (organization_id, &date, schedule_id),
+                )?;
+            }
+            None => {
+                self.conn.execute(
+                    "DELETE FROM bell_schedule_days WHERE organization_id = ?1 AND date = ?2",
+                    (organization_id, &date),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The schedule in effect for the organization on `date`: the nearest
+    /// organization up the tree with a schedule assigned to that date, or
+    /// failing that with a default schedule.
+    pub fn active_schedule(&self, organization_id: &str, date: NaiveDate) -> anyhow::Result<Option<BellSchedule>> {
+        let date = timestamp::format_date(&date);
+        let assigned_sql = format!(
+            "SELECT {} FROM bell_schedules
+             WHERE id = (SELECT schedule_id FROM bell_schedule_days WHERE organization_id = ?1 AND date = ?2)",
+            BellSchedule::COLUMNS
+        );
+        let default_sql = format!(
+            "SELECT {} FROM bell_schedules WHERE organization_id = ?1 AND is_default",
+            BellSchedule::COLUMNS
+        );
+
+        for organization in OrganizationRepository::new(self.conn).ancestors(organization_id)? {
+            let schedule = match query_optional(self.conn, &assigned_sql, [&organization.id, &date])? {
+                Some(schedule) => Some(schedule),
+                None => query_optional(self.conn, &default_sql, [&organization.id])?,
+            };
+            if let Some(schedule) = schedule {
+                return self.with_periods(schedule).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    pub fn set_class_periods(&self, class_id: &str, period_names: &[String]) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute("DELETE FROM class_periods WHERE class_id = ?1", [class_id])?;
+        for name in period_names {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO class_periods (class_id, period_name) VALUES (?1, ?2)",
+                (class_id, name),
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn class_periods(&self, class_id: &str) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT period_name FROM class_periods WHERE class_id = ?1 ORDER BY period_name")?;
+        let names = stmt
+            .query_map([class_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(names)
+    }
+
+    /// Start and end of the given periods (or all of the class's periods when
+    /// `period_names` is empty) under the schedule active on `date`.
+    pub fn period_window(
+        &self,
+        class_id: &str,
+        date: NaiveDate,
+        period_names: &[String],
+    ) -> anyhow::Result<(NaiveTime, NaiveTime)> {
+        let class = ClassRepository::new(self.conn)
+            .find_by_id(class_id)?
+            .ok_or_else(|| anyhow::anyhow!("Class not found"))?;
+
+        let names = if period_names.is_empty() {
+            self.class_periods(class_id)?
+        } else {
+            period_names.to_vec()
+        };
+        if names.is_empty() {
+            anyhow::bail!("{} is not attached to any periods", class.name);
+        }
+
+        let schedule = self
+            .active_schedule(&class.organization_id, date)?
+            .ok_or_else(|| anyhow::anyhow!("No bell schedule is in effect on {}", timestamp::format_date(&date)))?;
+
+        let mut window: Option<(NaiveTime, NaiveTime)> = None;
+        for name in &names {
+            let period = schedule
+                .periods
+                .iter()
+                .find(|period| &period.name == name)
+                .ok_or_else(|| anyhow::anyhow!("The {} schedule has no period named {}", schedule.name, name))?;
+            window = Some(match window {
+                Some((start, end)) => (start.min(period.start_time), end.max(period.end_time)),
+                None => (period.start_time, period.end_time),
+            });
+        }
+
+        window.ok_or_else(|| anyhow::anyhow!("No periods selected"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::CreatePeriodRequest;
+    use crate::repository::fixtures::{insert_class, insert_organization, test_connection};
+
+    fn time(value: &str) -> NaiveTime {
+        timestamp::parse_time(value).unwrap()
+    }
+
+    fn date(value: &str) -> NaiveDate {
+        timestamp::parse_date(value).unwrap()
+    }
+
+    fn schedule(organization_id: &str, name: &str, is_default: bool, periods: &[(&str, &str, &str)]) -> CreateBellScheduleRequest {
+        CreateBellScheduleRequest {
+            organization_id: organization_id.to_string(),
+            name: name.to_string(),
+            is_default,
+            periods: periods
+                .iter()
+                .map(|(name, start, end)| CreatePeriodRequest {
+                    name: name.to_string(),
+                    start_time: time(start),
+                    end_time: time(end),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_periods() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        let repo = BellScheduleRepository::new(&conn);
+
+        assert!(repo.create(schedule("org-1", "Empty", false, &[])).is_err());
+        assert!(repo.create(schedule("org-1", "Backwards", false, &[("1", "09:00", "08:00")])).is_err());
+        assert!(repo
+            .create(schedule("org-1", "Overlap", false, &[("1", "08:00", "09:00"), ("2", "08:30", "09:30")]))
+            .is_err());
+        assert!(repo
+            .create(schedule("org-1", "Repeat", false, &[("1", "08:00", "09:00"), ("1", "09:00", "10:00")]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_active_schedule_and_period_window() {
+        let conn = test_connection();
+        insert_organization(&conn, "district");
+        insert_organization(&conn, "school");
+        conn.execute("UPDATE organizations SET parent_organization_id = 'district' WHERE id = 'school'", [])
+            .unwrap();
+        insert_class(&conn, "math", "school");
+        let repo = BellScheduleRepository::new(&conn);
+
+        let regular = repo
+            .create(schedule("district", "Regular", true, &[("1", "08:00", "08:50"), ("2", "09:00", "09:50"), ("3", "10:00", "10:50")]))
+            .unwrap();
+        let early = repo
+            .create(schedule("school", "Early release", false, &[("1", "08:00", "08:30"), ("2", "08:35", "09:05"), ("3", "09:10", "09:40")]))
+            .unwrap();
+        assert_eq!(regular.periods.len(), 3);
+        assert_eq!(regular.periods[2].position, 3);
+
+        repo.set_class_periods("math", &["2".to_string(), "3".to_string()]).unwrap();
+        repo.set_schedule_for_date("school", date("2030-01-15"), Some(&early.id)).unwrap();
+
+        // Inherited default schedule
+        assert_eq!(
+            repo.period_window("math", date("2030-01-14"), &[]).unwrap(),
+            (time("09:00"), time("10:50"))
+        );
+        // Early release on the assigned date
+        assert_eq!(
+            repo.period_window("math", date("2030-01-15"), &[]).unwrap(),
+            (time("08:35"), time("09:40"))
+        );
+        assert_eq!(
+            repo.period_window("math", date("2030-01-15"), &["1".to_string()]).unwrap(),
+            (time("08:00"), time("08:30"))
+        );
+        assert!(repo.period_window("math", date("2030-01-14"), &["7".to_string()]).is_err());
+
+        repo.set_schedule_for_date("school", date("2030-01-15"), None).unwrap();
+        assert_eq!(repo.active_schedule("school", date("2030-01-15")).unwrap().unwrap().id, regular.id);
+    }
+}
@@ -0,0 +1,456 @@
+use crate::database::models::{
+    BudgetUsage, BudgetWarning, CreateBudgetRequest, CreateSubstituteRequestRequest, OrganizationSpend,
+    SubstituteBudget, UpdateBudgetRequest,
+};
+use crate::database::timestamp;
+use crate::payroll::{covered_hours, hours};
+use crate::repository::setting::ESTIMATED_HOURLY_RATE;
+use crate::repository::{
+    date_column, query_all, query_optional, time_column, timestamp_column, ClassRepository, FromRow,
+    OrganizationRepository, PayrollRepository, SettingsRepository,
+};
+use chrono::{NaiveDate, NaiveTime, Utc};
+use rusqlite::{Connection, Row};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+impl FromRow for SubstituteBudget {
+    const COLUMNS: &'static str = "id, organization_id, name, period_start, period_end, amount, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(SubstituteBudget {
+            id: row.get(0)?,
+            organization_id: row.get(1)?,
+            name: row.get(2)?,
+            period_start: date_column(row, 3)?,
+            period_end: date_column(row, 4)?,
+            amount: row.get(5)?,
+            created_at: timestamp_column(row, 6)?,
+            updated_at: timestamp_column(row, 7)?,
+        })
+    }
+}
+
+/// Substitute budgets and spending against them.
+pub struct BudgetRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> BudgetRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        BudgetRepository { conn }
+    }
+
+    /// An organization can have one budget for any given day.
+    pub fn create(&self, request: CreateBudgetRequest) -> anyhow::Result<SubstituteBudget> {
+        if request.name.trim().is_empty() {
+            anyhow::bail!("Budget name is required");
+        }
+        if request.period_end < request.period_start {
+            anyhow::bail!("Budget period must not end before it starts");
+        }
+        if request.amount < 0.0 {
+            anyhow::bail!("Budget amount must not be negative");
+        }
+
+        let overlapping: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM substitute_budgets
+                           WHERE organization_id = ?1 AND period_start <= ?3 AND period_end >= ?2)",
+            (
+                &request.organization_id,
+                &timestamp::format_date(&request.period_start),
+                &timestamp::format_date(&request.period_end),
+            ),
+            |row| row.get(0),
+        )?;
+        if overlapping {
+            anyhow::bail!("The organization already has a budget overlapping that period");
+        }
+
+        let now = Utc::now();
+        let budget = SubstituteBudget {
+            id: Uuid::new_v4().to_string(),
+            organization_id: request.organization_id,
+            name: request.name.trim().to_string(),
+            period_start: request.period_start,
+            period_end: request.period_end,
+            amount: request.amount,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.conn.execute(
+            "INSERT INTO substitute_budgets (id, organization_id, name, period_start, period_end, amount, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (
+                &budget.id,
+                &budget.organization_id,
+                &budget.name,
+                &timestamp::format_date(&budget.period_start),
+                &timestamp::format_date(&budget.period_end),
+                &budget.amount,
+                &timestamp::format(&budget.created_at),
+                &timestamp::format(&budget.updated_at),
+            ),
+        )?;
+
+        Ok(budget)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<SubstituteBudget>> {
+        let sql = format!("SELECT {} FROM substitute_budgets WHERE id = ?1", SubstituteBudget::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    pub fn list_for_organization(&self, organization_id: &str) -> anyhow::Result<Vec<SubstituteBudget>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_budgets WHERE organization_id = ?1 ORDER BY period_start",
+            SubstituteBudget::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [organization_id])?)
+    }
+
+    pub fn update(&self, id: &str, request: UpdateBudgetRequest) -> anyhow::Result<Option<SubstituteBudget>> {
+        if request.name.as_deref().is_some_and(|name| name.trim().is_empty()) {
+            anyhow::bail!("Budget name is required");
+        }
+        if request.amount.is_some_and(|amount| amount < 0.0) {
+            anyhow::bail!("Budget amount must not be negative");
+        }
+
+        self.conn.execute(
+            "UPDATE substitute_budgets SET name = COALESCE(?1, name), amount = COALESCE(?2, amount), updated_at = ?3
+             WHERE id = ?4",
+            (
+                request.name.as_deref().map(str::trim),
+                &request.amount,
+                &timestamp::format(&Utc::now()),
+                id,
+            ),
+        )?;
+
+        self.find_by_id(id)
+    }
+
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM substitute_budgets WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Spending in the budget's period at its organization and everything
+    /// below it. Completed assignments count at what they paid; requests still
+    /// to happen count at their estimated cost, with the classes of a teacher
+    /// absence on one day estimated together as the single day they'll be paid as.
+    pub fn usage(&self, budget: SubstituteBudget) -> anyhow::Result<BudgetUsage> {
+        let organizations = OrganizationRepository::new(self.conn);
+        let payroll = PayrollRepository::new(self.conn);
+        let subtree = organizations.subtree_ids(&budget.organization_id)?;
+
+        // (actual, committed) per organization
+        let mut spend: BTreeMap<String, (f64, f64)> = BTreeMap::new();
+        for timesheet in payroll.timesheets(budget.period_start, budget.period_end, None)? {
+            for entry in timesheet.entries {
+                if subtree.contains(&entry.organization_id) {
+                    spend.entry(entry.organization_id).or_default().0 += entry.amount.unwrap_or(0.0);
+                }
+            }
+        }
+
+        let fallback_rate = self.estimated_hourly_rate()?;
+        let mut unpriced_requests = 0;
+        let mut stmt = self.conn.prepare(
+            "SELECT c.organization_id, r.start_time, r.end_time, r.assigned_substitute_id,
+                    COALESCE(a.teacher_absence_id, r.id), r.date_needed
+             FROM substitute_requests r JOIN classes c ON c.id = r.class_id
+             LEFT JOIN absences a ON a.id = r.absence_id
+             WHERE r.status IN ('open', 'filled', 'in_progress') AND r.date_needed BETWEEN ?1 AND ?2
+             ORDER BY r.date_needed, r.start_time",
+        )?;
+        let pending = stmt
+            .query_map(
+                (
+                    timestamp::format_date(&budget.period_start),
+                    timestamp::format_date(&budget.period_end),
+                ),
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        time_column(row, 1)?,
+                        time_column(row, 2)?,
+                        row.get::<_, Option<String>>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, String>(5)?,
+                    ))
+                },
+            )?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // Shifts keyed by (teacher absence or request, date, substitute), with
+        // the organization of their first request and their time windows
+        type Shift = ((String, String, Option<String>), String, Vec<(NaiveTime, NaiveTime)>);
+        let mut shifts: Vec<Shift> = Vec::new();
+        for (organization_id, start_time, end_time, substitute_id, shift_id, date) in pending {
+            if !subtree.contains(&organization_id) {
+                continue;
+            }
+            let key = (shift_id, date, substitute_id);
+            match shifts.iter_mut().find(|(shift_key, _, _)| *shift_key == key) {
+                Some((_, _, windows)) => windows.push((start_time, end_time)),
+                None => shifts.push((key, organization_id, vec![(start_time, end_time)])),
+            }
+        }
+        for ((_, _, substitute_id), organization_id, windows) in shifts {
+            let requests = windows.len() as u32;
+            let cost =
+                payroll.estimate(&organization_id, covered_hours(windows), substitute_id.as_deref(), fallback_rate)?;
+            match cost {
+                Some(cost) => spend.entry(organization_id).or_default().1 += cost,
+                None => unpriced_requests += requests,
+            }
+        }
+
+        let mut by_organization = Vec::new();
+        for (organization_id, (actual_spend, committed_spend)) in spend {
+            let organization_name = organizations
+                .find_by_id(&organization_id)?
+                .map(|org| org.name)
+                .unwrap_or_default();
+            by_organization.push(OrganizationSpend {
+                organization_id,
+                organization_name,
+                actual_spend,
+                committed_spend,
+            });
+        }
+        by_organization.sort_by(|a, b| a.organization_name.cmp(&b.organization_name));
+
+        let actual_spend: f64 = by_organization.iter().map(|org| org.actual_spend).sum();
+        let committed_spend: f64 = by_organization.iter().map(|org| org.committed_spend).sum();
+        let projected_spend = actual_spend + committed_spend;
+        Ok(BudgetUsage {
+            remaining: budget.amount - projected_spend,
+            budget,
+            actual_spend,
+            committed_spend,
+            projected_spend,
+            unpriced_requests,
+            by_organization,
+        })
+    }
+
+    /// Usage of each of the organization's own budgets.
+    pub fn usage_for_organization(&self, organization_id: &str) -> anyhow::Result<Vec<BudgetUsage>> {
+        self.list_for_organization(organization_id)?
+            .into_iter()
+            .map(|budget| self.usage(budget))
+            .collect()
+    }
+
+    /// Budgets of the class's organization and its ancestors that `request`
+    /// would take over their amount, for warning before it's submitted.
+    pub fn warnings(&self, request: &CreateSubstituteRequestRequest) -> anyhow::Result<Vec<BudgetWarning>> {
+        let class = ClassRepository::new(self.conn)
+            .find_by_id(&request.class_id)?
+            .ok_or_else(|| anyhow::anyhow!("Class not found"))?;
+        let cost = PayrollRepository::new(self.conn).estimate(
+            &class.organization_id,
+            hours(request.end_time - request.start_time),
+            None,
+            self.estimated_hourly_rate()?,
+        )?;
+        let Some(request_cost) = cost else {
+            return Ok(Vec::new());
+        };
+
+        let mut warnings = Vec::new();
+        for organization in OrganizationRepository::new(self.conn).ancestors(&class.organization_id)? {
+            for budget in self.covering(&organization.id, request.date_needed)? {
+                let usage = self.usage(budget)?;
+                let projected_spend = usage.projected_spend + request_cost;
+                if projected_spend > usage.budget.amount {
+                    warnings.push(BudgetWarning {
+                        overage: projected_spend - usage.budget.amount,
+                        budget: usage.budget,
+                        projected_spend,
+                        request_cost,
+                    });
+                }
+            }
+        }
+
+        Ok(warnings)
+    }
+
+    fn covering(&self, organization_id: &str, date: NaiveDate) -> anyhow::Result<Vec<SubstituteBudget>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_budgets WHERE organization_id = ?1 AND ?2 BETWEEN period_start AND period_end",
+            SubstituteBudget::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, (organization_id, &timestamp::format_date(&date)))?)
+    }
+
+    fn estimated_hourly_rate(&self) -> anyhow::Result<Option<f64>> {
+        Ok(SettingsRepository::new(self.conn)
+            .get(ESTIMATED_HOURLY_RATE)?
+            .and_then(|setting| setting.value.trim().parse().ok()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::{ReportAbsenceRequest, SetPayPolicyRequest};
+    use crate::repository::fixtures::{future_date, insert_class, insert_organization, insert_user, test_connection};
+    use crate::repository::{AttendanceRepository, SubstituteRequestRepository, TeacherAbsenceRepository};
+    use chrono::{Local, TimeZone};
+
+    fn date(value: &str) -> NaiveDate {
+        future_date(value)
+    }
+
+    fn request(class_id: &str, day: &str) -> CreateSubstituteRequestRequest {
+        CreateSubstituteRequestRequest {
+            class_id: class_id.to_string(),
+            date_needed: date(day),
+            start_time: timestamp::parse_time("08:00").unwrap(),
+            end_time: timestamp::parse_time("15:00").unwrap(),
+            reason: None,
+            special_instructions: None,
+        }
+    }
+
+    fn budget(organization_id: &str, amount: f64) -> CreateBudgetRequest {
+        CreateBudgetRequest {
+            organization_id: organization_id.to_string(),
+            name: "FY2030".to_string(),
+            period_start: date("2029-07-01"),
+            period_end: date("2030-06-30"),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_usage_rolls_up_and_warns() {
+        let conn = test_connection();
+        insert_organization(&conn, "district");
+        insert_organization(&conn, "north");
+        insert_organization(&conn, "south");
+        conn.execute(
+            "UPDATE organizations SET parent_organization_id = 'district' WHERE id IN ('north', 'south')",
+            [],
+        )
+        .unwrap();
+        insert_class(&conn, "north-math", "north");
+        insert_class(&conn, "south-math", "south");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        PayrollRepository::new(&conn)
+            .set_policy(SetPayPolicyRequest {
+                organization_id: "district".to_string(),
+                use_actual_hours: None,
+                rounding_minutes: None,
+                rounding_mode: None,
+                half_day_rate: None,
+                half_day_max_hours: None,
+                full_day_rate: Some(150.0),
+                long_term_after_days: None,
+                long_term_multiplier: None,
+            })
+            .unwrap();
+
+        let requests = SubstituteRequestRepository::new(&conn);
+        let completed = requests.create("manager-1".to_string(), request("north-math", "2030-01-14")).unwrap();
+        requests.update_status(&completed.id, "filled", Some("sub-1")).unwrap();
+        let check_in = date("2030-01-14").and_time(timestamp::parse_time("08:00").unwrap());
+        let attendance = AttendanceRepository::new(&conn);
+        attendance
+            .check_in(&completed.id, "sub-1", Local.from_local_datetime(&check_in).unwrap().with_timezone(&Utc))
+            .unwrap();
+        attendance
+            .check_out(
+                &completed.id,
+                "sub-1",
+                Local.from_local_datetime(&(check_in + chrono::Duration::hours(7))).unwrap().with_timezone(&Utc),
+            )
+            .unwrap();
+        requests.create("manager-1".to_string(), request("south-math", "2030-02-14")).unwrap();
+        // Outside the fiscal period
+        requests.create("manager-1".to_string(), request("south-math", "2030-08-14")).unwrap();
+
+        let repo = BudgetRepository::new(&conn);
+        let district = repo.create(budget("district", 400.0)).unwrap();
+        repo.create(budget("north", 1000.0)).unwrap();
+        assert!(repo.create(budget("district", 100.0)).is_err());
+
+        let usage = repo.usage(district.clone()).unwrap();
+        assert_eq!(usage.actual_spend, 150.0);
+        assert_eq!(usage.committed_spend, 150.0);
+        assert_eq!(usage.remaining, 100.0);
+        assert_eq!(usage.by_organization.len(), 2);
+        assert_eq!(usage.by_organization[0].organization_id, "north");
+        assert_eq!(usage.by_organization[0].actual_spend, 150.0);
+
+        // Another full day goes over the district's budget but not the school's
+        let warnings = repo.warnings(&request("north-math", "2030-03-02")).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].budget.id, district.id);
+        assert_eq!(warnings[0].overage, 50.0);
+
+        repo.update(&district.id, UpdateBudgetRequest { name: None, amount: Some(500.0) }).unwrap();
+        assert!(repo.warnings(&request("north-math", "2030-03-02")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_teacher_absence_day_is_estimated_once() {
+        let conn = test_connection();
+        insert_organization(&conn, "school");
+        conn.execute(
+            "UPDATE organizations SET school_start_time = '08:00', school_end_time = '15:00' WHERE id = 'school'",
+            [],
+        )
+        .unwrap();
+        insert_class(&conn, "math", "school");
+        insert_class(&conn, "science", "school");
+        insert_user(&conn, "manager-1", "org_manager");
+        conn.execute_batch(
+            "INSERT INTO regular_teachers (id, user_id, organization_id) VALUES ('teacher-1', 'manager-1', 'school');
+             INSERT INTO teacher_class_assignments (id, teacher_id, class_id) VALUES ('a-1', 'teacher-1', 'math');
+             INSERT INTO teacher_class_assignments (id, teacher_id, class_id) VALUES ('a-2', 'teacher-1', 'science');",
+        )
+        .unwrap();
+        PayrollRepository::new(&conn)
+            .set_policy(SetPayPolicyRequest {
+                organization_id: "school".to_string(),
+                use_actual_hours: None,
+                rounding_minutes: None,
+                rounding_mode: None,
+                half_day_rate: None,
+                half_day_max_hours: None,
+                full_day_rate: Some(150.0),
+                long_term_after_days: None,
+                long_term_multiplier: None,
+            })
+            .unwrap();
+
+        // Two classes a day for two days
+        TeacherAbsenceRepository::new(&conn)
+            .report(
+                "manager-1".to_string(),
+                ReportAbsenceRequest {
+                    teacher_id: "teacher-1".to_string(),
+                    start_date: date("2030-01-14"),
+                    end_date: date("2030-01-15"),
+                    start_time: None,
+                    end_time: None,
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap();
+
+        let repo = BudgetRepository::new(&conn);
+        let usage = repo.usage(repo.create(budget("school", 1000.0)).unwrap()).unwrap();
+        assert_eq!(usage.committed_spend, 300.0);
+        assert_eq!(usage.unpriced_requests, 0);
+    }
+}
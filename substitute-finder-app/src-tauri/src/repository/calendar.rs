@@ -0,0 +1,356 @@
+use crate::database::models::{
+    CalendarDay, CalendarEvent, CalendarEventType, CreateCalendarEventRequest, EmergencyClosure, SubstituteRequest,
+};
+use crate::database::timestamp;
+use crate::repository::{
+    date_column, enum_column, optional_time_column, query_all, query_optional, timestamp_column, FromRow,
+    OrganizationRepository, SubstituteRequestRepository,
+};
+use chrono::{NaiveDate, Utc};
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for CalendarEvent {
+    const COLUMNS: &'static str =
+        "id, organization_id, event_type, name, start_date, end_date, dismissal_time, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(CalendarEvent {
+            id: row.get(0)?,
+            organization_id: row.get(1)?,
+            event_type: enum_column(row, 2)?,
+            name: row.get(3)?,
+            start_date: date_column(row, 4)?,
+            end_date: date_column(row, 5)?,
+            dismissal_time: optional_time_column(row, 6)?,
+            created_at: timestamp_column(row, 7)?,
+            updated_at: timestamp_column(row, 8)?,
+        })
+    }
+}
+
+fn validate_event(request: &CreateCalendarEventRequest) -> anyhow::Result<()> {
+    if request.name.trim().is_empty() {
+        anyhow::bail!("Calendar event name is required");
+    }
+    if request.end_date < request.start_date {
+        anyhow::bail!("End date must not be before start date");
+    }
+    match (request.event_type, request.dismissal_time) {
+        (CalendarEventType::EarlyRelease, None) => anyhow::bail!("Early-release days need a dismissal time"),
+        (CalendarEventType::EarlyRelease, Some(_)) | (_, None) => Ok(()),
+        (_, Some(_)) => anyhow::bail!("Only early-release days have a dismissal time"),
+    }
+}
+
+pub struct CalendarRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> CalendarRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        CalendarRepository { conn }
+    }
+
+    pub fn create(&self, request: CreateCalendarEventRequest) -> anyhow::Result<CalendarEvent> {
+        validate_event(&request)?;
+        let event = CalendarEvent {
+            id: Uuid::new_v4().to_string(),
+            organization_id: request.organization_id,
+            event_type: request.event_type,
+            name: request.name,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            dismissal_time: request.dismissal_time,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO calendar_events (id, organization_id, event_type, name, start_date, end_date, dismissal_time, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                &event.id,
+                &event.organization_id,
+                &event.event_type.to_string(),
+                &event.name,
+                &timestamp::format_date(&event.start_date),
+                &timestamp::format_date(&event.end_date),
+                &event.dismissal_time.as_ref().map(timestamp::format_time),
+                &timestamp::format(&event.created_at),
+                &timestamp::format(&event.updated_at),
+            ),
+        )?;
+
+        Ok(event)
+    }
+
+    /// Events defined directly on the organization, not inherited ones.
+    pub fn list_by_organization(&self, organization_id: &str) -> anyhow::Result<Vec<CalendarEvent>> {
+        let sql = format!(
+            "SELECT {} FROM calendar_events WHERE organization_id = ?1 ORDER BY start_date",
+            CalendarEvent::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [organization_id])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<CalendarEvent>> {
+        let sql = format!("SELECT {} FROM calendar_events WHERE id = ?1", CalendarEvent::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    /// Returns `None` when no event has the given id.
+    pub fn update(
+        &self,
+        id: &str,
+        request: CreateCalendarEventRequest,
+    ) -> anyhow::Result<Option<CalendarEvent>> {
+        validate_event(&request)?;
+        self.conn.execute(
+            "UPDATE calendar_events SET organization_id = ?1, event_type = ?2, name = ?3, start_date = ?4,
+             end_date = ?5, dismissal_time = ?6, updated_at = ?7 WHERE id = ?8",
+            (
+                &request.organization_id,
+                &request.event_type.to_string(),
+                &request.name,
+                &timestamp::format_date(&request.start_date),
+                &timestamp::format_date(&request.end_date),
+                &request.dismissal_time.as_ref().map(timestamp::format_time),
+                &timestamp::now(),
+                id,
+            ),
+        )?;
+
+        self.find_by_id(id)
+    }
+
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute("DELETE FROM calendar_event_exclusions WHERE event_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM calendar_events WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Opts the organization and its children out of an inherited event, or
+    /// back in when `excluded` is false.
+    pub fn set_excluded(&self, organization_id: &str, event_id: &str, excluded: bool) -> anyhow::Result<()> {
+        if excluded {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO calendar_event_exclusions (organization_id, event_id) VALUES (?1, ?2)",
+                (organization_id, event_id),
+            )?;
+        } else {
+            self.conn.execute(
+                "DELETE FROM calendar_event_exclusions WHERE organization_id = ?1 AND event_id = ?2",
+                (organization_id, event_id),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Events overlapping `from..=to` on the organization's own calendar and
+    /// those of its ancestors, minus any that it or an organization between it
+    /// and the defining ancestor has opted out of.
+    pub fn effective_events(
+        &self,
+        organization_id: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> anyhow::Result<Vec<CalendarEvent>> {
+        let chain = OrganizationRepository::new(self.conn).ancestors(organization_id)?;
+        let events_sql = format!(
+            "SELECT {} FROM calendar_events
+             WHERE organization_id = ?1 AND start_date <= ?3 AND end_date >= ?2",
+            CalendarEvent::COLUMNS
+        );
+        let from = timestamp::format_date(&from);
+        let to = timestamp::format_date(&to);
+
+        let exclusions: Vec<(String, String)> = {
+            let mut stmt = self.conn.prepare("SELECT organization_id, event_id FROM calendar_event_exclusions")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+
+        let mut events = Vec::new();
+        for (depth, organization) in chain.iter().enumerate() {
+            for event in query_all::<CalendarEvent, _>(self.conn, &events_sql, [&organization.id, &from, &to])? {
+                let excluded = chain[..depth].iter().any(|below| {
+                    exclusions
+                        .iter()
+                        .any(|(org_id, event_id)| org_id == &below.id && event_id == &event.id)
+                });
+                if !excluded {
+                    events.push(event);
+                }
+            }
+        }
+
+        events.sort_by_key(|event| event.start_date);
+        Ok(events)
+    }
+
+    pub fn day(&self, organization_id: &str, date: NaiveDate) -> anyhow::Result<CalendarDay> {
+        let events = self.effective_events(organization_id, date, date)?;
+        Ok(CalendarDay {
+            date,
+            is_closed: events.iter().any(|event| event.event_type.is_closed()),
+            events,
+        })
+    }
+
+    /// The holiday or closure shutting the organization on `date`, if any.
+    pub fn closure_on(&self, organization_id: &str, date: NaiveDate) -> anyhow::Result<Option<CalendarEvent>> {
+        Ok(self
+            .effective_events(organization_id, date, date)?
+            .into_iter()
+            .find(|event| event.event_type.is_closed()))
+    }
+
+    /// Records a closure for `date` and cancels every open or filled request
+    /// on that date for classes anywhere under the organization.
+    pub fn declare_emergency_closure(
+        &self,
+        organization_id: &str,
+        date: NaiveDate,
+        name: String,
+    ) -> anyhow::Result<EmergencyClosure> {
+        let organization_ids = OrganizationRepository::new(self.conn).subtree_ids(organization_id)?;
+        let placeholders = vec!["?"; organization_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE date_needed = ? AND status IN ('open', 'filled')
+               AND class_id IN (SELECT id FROM classes WHERE organization_id IN ({}))",
+            SubstituteRequest::COLUMNS,
+            placeholders
+        );
+        let mut params = vec![timestamp::format_date(&date)];
+        params.extend(organization_ids);
+        let affected: Vec<SubstituteRequest> =
+            query_all(self.conn, &sql, rusqlite::params_from_iter(params.iter()))?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let event = self.create(CreateCalendarEventRequest {
+            organization_id: organization_id.to_string(),
+            event_type: CalendarEventType::Closure,
+            name,
+            start_date: date,
+            end_date: date,
+            dismissal_time: None,
+        })?;
+        let updated_at = timestamp::now();
+        for request in &affected {
+            self.conn.execute(
+                "UPDATE substitute_requests SET status = 'cancelled', updated_at = ?1 WHERE id = ?2",
+                (&updated_at, &request.id),
+            )?;
+        }
+        tx.commit()?;
+
+        let cancelled_requests = SubstituteRequestRepository::new(self.conn).reload(&affected)?;
+        Ok(EmergencyClosure { event, cancelled_requests })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::RequestStatus;
+    use crate::repository::fixtures::{insert_class, insert_organization, insert_user, test_connection};
+
+    fn date(value: &str) -> NaiveDate {
+        timestamp::parse_date(value).unwrap()
+    }
+
+    fn setup() -> Connection {
+        let conn = test_connection();
+        insert_organization(&conn, "district");
+        insert_organization(&conn, "north");
+        insert_organization(&conn, "south");
+        conn.execute(
+            "UPDATE organizations SET parent_organization_id = 'district' WHERE id IN ('north', 'south')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    fn event(organization_id: &str, event_type: CalendarEventType, start: &str, end: &str) -> CreateCalendarEventRequest {
+        CreateCalendarEventRequest {
+            organization_id: organization_id.to_string(),
+            event_type,
+            name: format!("{} event", event_type),
+            start_date: date(start),
+            end_date: date(end),
+            dismissal_time: None,
+        }
+    }
+
+    #[test]
+    fn test_events_are_inherited_and_overridable() {
+        let conn = setup();
+        let repo = CalendarRepository::new(&conn);
+
+        let winter_break = repo
+            .create(event("district", CalendarEventType::Holiday, "2030-12-23", "2031-01-03"))
+            .unwrap();
+        repo.create(event("north", CalendarEventType::Closure, "2030-12-20", "2030-12-20")).unwrap();
+
+        assert!(repo.day("north", date("2030-12-24")).unwrap().is_closed);
+        assert!(repo.day("north", date("2030-12-20")).unwrap().is_closed);
+        assert!(!repo.day("south", date("2030-12-20")).unwrap().is_closed);
+
+        // South keeps school open over the district break
+        repo.set_excluded("south", &winter_break.id, true).unwrap();
+        assert!(!repo.day("south", date("2030-12-24")).unwrap().is_closed);
+        assert!(repo.day("district", date("2030-12-24")).unwrap().is_closed);
+
+        repo.set_excluded("south", &winter_break.id, false).unwrap();
+        assert!(repo.closure_on("south", date("2030-12-24")).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_validation() {
+        let conn = setup();
+        let repo = CalendarRepository::new(&conn);
+
+        assert!(repo.create(event("district", CalendarEventType::Term, "2030-06-01", "2030-01-01")).is_err());
+        assert!(repo.create(event("district", CalendarEventType::EarlyRelease, "2030-06-01", "2030-06-01")).is_err());
+
+        let mut early = event("district", CalendarEventType::EarlyRelease, "2030-06-01", "2030-06-01");
+        early.dismissal_time = Some(timestamp::parse_time("12:30").unwrap());
+        let created = repo.create(early).unwrap();
+        let day = repo.day("north", date("2030-06-01")).unwrap();
+        assert!(!day.is_closed);
+        assert_eq!(day.events[0].id, created.id);
+    }
+
+    #[test]
+    fn test_emergency_closure_cancels_requests_in_subtree() {
+        let conn = setup();
+        insert_class(&conn, "north-math", "north");
+        insert_class(&conn, "south-math", "south");
+        insert_user(&conn, "manager-1", "org_manager");
+        conn.execute_batch(
+            "INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, status)
+             VALUES ('r1', 'north-math', 'manager-1', '2030-02-04', '08:00', '15:00', 'open'),
+                    ('r2', 'south-math', 'manager-1', '2030-02-04', '08:00', '15:00', 'open'),
+                    ('r3', 'north-math', 'manager-1', '2030-02-05', '08:00', '15:00', 'open');",
+        )
+        .unwrap();
+        let repo = CalendarRepository::new(&conn);
+
+        let closure = repo
+            .declare_emergency_closure("north", date("2030-02-04"), "Snow day".to_string())
+            .unwrap();
+        assert_eq!(closure.cancelled_requests.len(), 1);
+        assert_eq!(closure.cancelled_requests[0].id, "r1");
+        assert!(matches!(closure.cancelled_requests[0].status, RequestStatus::Cancelled));
+        assert!(repo.day("north", date("2030-02-04")).unwrap().is_closed);
+
+        let requests = SubstituteRequestRepository::new(&conn);
+        assert!(matches!(requests.find_by_id("r2").unwrap().unwrap().status, RequestStatus::Open));
+        assert!(matches!(requests.find_by_id("r3").unwrap().unwrap().status, RequestStatus::Open));
+    }
+}
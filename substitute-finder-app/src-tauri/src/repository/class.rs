@@ -0,0 +1,136 @@
+use crate::database::models::{Class, CreateClassRequest, VocabularyKind};
+use crate::database::timestamp;
+use crate::repository::{query_all, query_optional, timestamp_column, FromRow, VocabularyRepository};
+use chrono::Utc;
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for Class {
+    const COLUMNS: &'static str =
+        "id, name, organization_id, subject, grade_level, room_number, description, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Class {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            organization_id: row.get(2)?,
+            subject: row.get(3)?,
+            grade_level: row.get(4)?,
+            room_number: row.get(5)?,
+            description: row.get(6)?,
+            created_at: timestamp_column(row, 7)?,
+            updated_at: timestamp_column(row, 8)?,
+        })
+    }
+}
+
+pub struct ClassRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> ClassRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        ClassRepository { conn }
+    }
+
+    /// `subject` and `grade_level` must come from the class vocabulary.
+    pub fn create(&self, request: CreateClassRequest) -> anyhow::Result<Class> {
+        let vocabulary = VocabularyRepository::new(self.conn);
+        vocabulary.ensure_allowed(VocabularyKind::Subject, request.subject.as_deref(), None)?;
+        vocabulary.ensure_allowed(VocabularyKind::GradeBand, request.grade_level.as_deref(), None)?;
+
+        let class = Class {
+            id: Uuid::new_v4().to_string(),
+            name: request.name,
+            organization_id: request.organization_id,
+            subject: request.subject,
+            grade_level: request.grade_level,
+            room_number: request.room_number,
+            description: request.description,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO classes (id, name, organization_id, subject, grade_level, room_number, description, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                &class.id,
+                &class.name,
+                &class.organization_id,
+                &class.subject,
+                &class.grade_level,
+                &class.room_number,
+                &class.description,
+                &timestamp::format(&class.created_at),
+                &timestamp::format(&class.updated_at),
+            ),
+        )?;
+
+        Ok(class)
+    }
+
+    pub fn list(&self) -> anyhow::Result<Vec<Class>> {
+        let sql = format!("SELECT {} FROM classes ORDER BY name", Class::COLUMNS);
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    pub fn list_by_organization(&self, organization_id: &str) -> anyhow::Result<Vec<Class>> {
+        let sql = format!(
+            "SELECT {} FROM classes WHERE organization_id = ?1 ORDER BY name",
+            Class::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [organization_id])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<Class>> {
+        let sql = format!("SELECT {} FROM classes WHERE id = ?1", Class::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    /// Returns `None` when no class has the given id. A retired subject or
+    /// grade level can be kept but not newly chosen.
+    /// The organization's class with this name, ignoring case.
+    pub fn find_by_name(&self, organization_id: &str, name: &str) -> anyhow::Result<Option<Class>> {
+        let sql = format!(
+            "SELECT {} FROM classes WHERE organization_id = ?1 AND lower(name) = lower(?2)",
+            Class::COLUMNS
+        );
+        Ok(query_optional(self.conn, &sql, [organization_id, name])?)
+    }
+
+    pub fn update(&self, id: &str, request: CreateClassRequest) -> anyhow::Result<Option<Class>> {
+        let Some(current) = self.find_by_id(id)? else {
+            return Ok(None);
+        };
+        let vocabulary = VocabularyRepository::new(self.conn);
+        vocabulary.ensure_allowed(VocabularyKind::Subject, request.subject.as_deref(), current.subject.as_deref())?;
+        vocabulary.ensure_allowed(
+            VocabularyKind::GradeBand,
+            request.grade_level.as_deref(),
+            current.grade_level.as_deref(),
+        )?;
+
+        self.conn.execute(
+            "UPDATE classes SET name = ?1, organization_id = ?2, subject = ?3,
+             grade_level = ?4, room_number = ?5, description = ?6, updated_at = ?7 WHERE id = ?8",
+            (
+                &request.name,
+                &request.organization_id,
+                &request.subject,
+                &request.grade_level,
+                &request.room_number,
+                &request.description,
+                &timestamp::now(),
+                id,
+            ),
+        )?;
+
+        self.find_by_id(id)
+    }
+
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM classes WHERE id = ?1", [id])?;
+        Ok(())
+    }
+}
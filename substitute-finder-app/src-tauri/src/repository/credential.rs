@@ -0,0 +1,314 @@
+use crate::database::models::{
+    CreateCredentialRequest, CredentialType, ExpiringCredential, SubstituteCredential, VerificationStatus,
+};
+use crate::database::timestamp;
+use crate::repository::setting::REQUIRED_CREDENTIALS;
+use crate::repository::{
+    date_column, enum_column, optional_date_column, optional_timestamp_column, query_all, query_optional,
+    timestamp_column, FromRow, SettingsRepository, UserRepository,
+};
+use chrono::{NaiveDate, Utc};
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for SubstituteCredential {
+    const COLUMNS: &'static str = "id, substitute_id, credential_type, description, issued_on, expires_on, verification_status, verified_by, verified_at, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(SubstituteCredential {
+            id: row.get(0)?,
+            substitute_id: row.get(1)?,
+            credential_type: enum_column(row, 2)?,
+            description: row.get(3)?,
+            issued_on: date_column(row, 4)?,
+            expires_on: optional_date_column(row, 5)?,
+            verification_status: enum_column(row, 6)?,
+            verified_by: row.get(7)?,
+            verified_at: optional_timestamp_column(row, 8)?,
+            created_at: timestamp_column(row, 9)?,
+            updated_at: timestamp_column(row, 10)?,
+        })
+    }
+}
+
+/// Parses a comma-separated list of credential types such as
+/// `teaching_license,cpr`. Blank means none.
+pub fn parse_credential_types(value: &str) -> anyhow::Result<Vec<CredentialType>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Substitutes' credentials and the checks that keep lapsed ones off
+/// assignments.
+pub struct CredentialRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> CredentialRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        CredentialRepository { conn }
+    }
+
+    pub fn create(&self, request: CreateCredentialRequest) -> anyhow::Result<SubstituteCredential> {
+        if request.expires_on.is_some_and(|expires_on| expires_on < request.issued_on) {
+            anyhow::bail!("Credential must not expire before it is issued");
+        }
+
+        let now = Utc::now();
+        let credential = SubstituteCredential {
+            id: Uuid::new_v4().to_string(),
+            substitute_id: request.substitute_id,
+            credential_type: request.credential_type,
+            description: request.description,
+            issued_on: request.issued_on,
+            expires_on: request.expires_on,
+            verification_status: VerificationStatus::Pending,
+            verified_by: None,
+            verified_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.conn.execute(
+            "INSERT INTO substitute_credentials (id, substitute_id, credential_type, description, issued_on, expires_on, verification_status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                &credential.id,
+                &credential.substitute_id,
+                &credential.credential_type.to_string(),
+                &credential.description,
+                &timestamp::format_date(&credential.issued_on),
+                &credential.expires_on.as_ref().map(timestamp::format_date),
+                &credential.verification_status.to_string(),
+                &timestamp::format(&credential.created_at),
+                &timestamp::format(&credential.updated_at),
+            ),
+        )?;
+
+        Ok(credential)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<SubstituteCredential>> {
+        let sql = format!("SELECT {} FROM substitute_credentials WHERE id = ?1", SubstituteCredential::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    pub fn list_for_substitute(&self, substitute_id: &str) -> anyhow::Result<Vec<SubstituteCredential>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_credentials WHERE substitute_id = ?1 ORDER BY credential_type, issued_on DESC",
+            SubstituteCredential::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [substitute_id])?)
+    }
+
+    /// Records the outcome of checking a credential. Returns `None` when no
+    /// credential has the given id.
+    pub fn set_verification(
+        &self,
+        id: &str,
+        status: VerificationStatus,
+        verified_by: &str,
+    ) -> anyhow::Result<Option<SubstituteCredential>> {
+        let now = timestamp::now();
+        let (verified_by, verified_at) = match status {
+            VerificationStatus::Pending => (None, None),
+            _ => (Some(verified_by), Some(now.as_str())),
+        };
+        self.conn.execute(
+            "UPDATE substitute_credentials SET verification_status = ?1, verified_by = ?2, verified_at = ?3, updated_at = ?4
+             WHERE id = ?5",
+            (&status.to_string(), verified_by, verified_at, &now, id),
+        )?;
+
+        self.find_by_id(id)
+    }
+
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM substitute_credentials WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Why the substitute can't work on `date` for lack of a credential in the
+    /// `required_credentials` setting, one problem per missing type. Empty
+    /// when every required credential is current.
+    pub fn lapsed(&self, substitute_id: &str, date: NaiveDate) -> anyhow::Result<Vec<String>> {
+        let required = match SettingsRepository::new(self.conn).get(REQUIRED_CREDENTIALS)? {
+            Some(setting) => parse_credential_types(&setting.value)?,
+            None => Vec::new(),
+        };
+        if required.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let held = self.list_for_substitute(substitute_id)?;
+        let mut problems = Vec::new();
+        for credential_type in required {
+            let of_type: Vec<&SubstituteCredential> = held
+                .iter()
+                .filter(|credential| credential.credential_type == credential_type)
+                .collect();
+            if of_type.iter().any(|credential| credential.is_valid_on(date)) {
+                continue;
+            }
+            let expired = of_type
+                .iter()
+                .filter(|credential| credential.verification_status == VerificationStatus::Verified)
+                .filter_map(|credential| credential.expires_on)
+                .filter(|expires_on| *expires_on < date)
+                .max();
+            problems.push(match expired {
+                Some(expires_on) => format!(
+                    "{} expired on {}",
+                    credential_type.label(),
+                    timestamp::format_date(&expires_on)
+                ),
+                None if of_type.is_empty() => format!("no {} on file", credential_type.label()),
+                None => format!("{} is not verified", credential_type.label()),
+            });
+        }
+        Ok(problems)
+    }
+
+    /// The substitutes from `substitute_ids` holding every required
+    /// credential on `date`.
+    pub fn qualified_for(&self, substitute_ids: Vec<String>, date: NaiveDate) -> anyhow::Result<Vec<String>> {
+        let mut qualified = Vec::new();
+        for substitute_id in substitute_ids {
+            if self.lapsed(&substitute_id, date)?.is_empty() {
+                qualified.push(substitute_id);
+            }
+        }
+        Ok(qualified)
+    }
+
+    /// Verified credentials expiring between `today` and `days` from now,
+    /// soonest first. Credentials already renewed by a later-expiring one of
+    /// the same kind are left out.
+    pub fn expiring(&self, today: NaiveDate, days: u32) -> anyhow::Result<Vec<ExpiringCredential>> {
+        let until = today + chrono::Duration::days(i64::from(days));
+        let sql = format!(
+            "SELECT {} FROM substitute_credentials c
+             WHERE verification_status = 'verified' AND expires_on BETWEEN ?1 AND ?2
+               AND NOT EXISTS (
+                   SELECT 1 FROM substitute_credentials renewal
+                   WHERE renewal.substitute_id = c.substitute_id
+                     AND renewal.credential_type = c.credential_type
+                     AND renewal.description IS c.description
+                     AND renewal.verification_status = 'verified'
+                     AND (renewal.expires_on IS NULL OR renewal.expires_on > c.expires_on)
+               )
+             ORDER BY expires_on",
+            SubstituteCredential::COLUMNS
+        );
+        let credentials: Vec<SubstituteCredential> = query_all(
+            self.conn,
+            &sql,
+            (timestamp::format_date(&today), timestamp::format_date(&until)),
+        )?;
+
+        let users = UserRepository::new(self.conn);
+        let mut report = Vec::new();
+        for credential in credentials {
+            let Some(user) = users.find_by_id(&credential.substitute_id)? else {
+                continue;
+            };
+            let days_remaining = credential
+                .expires_on
+                .map(|expires_on| (expires_on - today).num_days())
+                .unwrap_or_default();
+            report.push(ExpiringCredential {
+                credential,
+                substitute_name: format!("{}, {}", user.last_name, user.first_name),
+                substitute_email: user.email,
+                days_remaining,
+            });
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::CreateSubstituteRequestRequest;
+    use crate::repository::fixtures::{future_date, insert_class, insert_organization, insert_user, test_connection};
+    use crate::repository::SubstituteRequestRepository;
+
+    fn date(value: &str) -> NaiveDate {
+        future_date(value)
+    }
+
+    fn credential(substitute_id: &str, credential_type: CredentialType, expires_on: &str) -> CreateCredentialRequest {
+        CreateCredentialRequest {
+            substitute_id: substitute_id.to_string(),
+            credential_type,
+            description: None,
+            issued_on: date("2028-01-01"),
+            expires_on: Some(date(expires_on)),
+        }
+    }
+
+    #[test]
+    fn test_lapsed_credentials_block_assignment() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "class-1", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        insert_user(&conn, "sub-2", "substitute");
+        let repo = CredentialRepository::new(&conn);
+        let subs = || vec!["sub-1".to_string(), "sub-2".to_string()];
+
+        // Nothing is required until the setting names it
+        assert_eq!(repo.qualified_for(subs(), date("2030-03-01")).unwrap().len(), 2);
+        SettingsRepository::new(&conn)
+            .set(REQUIRED_CREDENTIALS, "background_check, cpr", None)
+            .unwrap();
+
+        for substitute_id in ["sub-1", "sub-2"] {
+            let check = repo
+                .create(credential(substitute_id, CredentialType::BackgroundCheck, "2031-01-01"))
+                .unwrap();
+            repo.set_verification(&check.id, VerificationStatus::Verified, "manager-1").unwrap();
+        }
+        let cpr = repo.create(credential("sub-1", CredentialType::Cpr, "2030-02-28")).unwrap();
+        assert_eq!(repo.lapsed("sub-1", date("2030-02-01")).unwrap(), vec!["CPR certification is not verified"]);
+        repo.set_verification(&cpr.id, VerificationStatus::Verified, "manager-1").unwrap();
+        assert!(repo.lapsed("sub-1", date("2030-02-01")).unwrap().is_empty());
+        assert_eq!(repo.lapsed("sub-1", date("2030-03-01")).unwrap(), vec![format!("CPR certification expired on {}", timestamp::format_date(&date("2030-02-28")))]);
+        assert_eq!(repo.lapsed("sub-2", date("2030-03-01")).unwrap(), vec!["no CPR certification on file"]);
+        assert!(repo.qualified_for(subs(), date("2030-03-01")).unwrap().is_empty());
+
+        let request = SubstituteRequestRepository::new(&conn)
+            .create(
+                "manager-1".to_string(),
+                CreateSubstituteRequestRequest {
+                    class_id: "class-1".to_string(),
+                    date_needed: date("2030-03-01"),
+                    start_time: timestamp::parse_time("08:00").unwrap(),
+                    end_time: timestamp::parse_time("15:00").unwrap(),
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap();
+        let error = SubstituteRequestRepository::new(&conn)
+            .update_status(&request.id, "filled", Some("sub-1"))
+            .unwrap_err();
+        assert!(error.to_string().contains("CPR certification expired"));
+
+        // The report covers the window and drops credentials already renewed
+        let report = repo.expiring(date("2030-02-01"), 30).unwrap();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].credential.id, cpr.id);
+        assert_eq!(report[0].days_remaining, 27);
+        let renewal = repo.create(credential("sub-1", CredentialType::Cpr, "2032-02-28")).unwrap();
+        repo.set_verification(&renewal.id, VerificationStatus::Verified, "manager-1").unwrap();
+        assert!(repo.expiring(date("2030-02-01"), 30).unwrap().is_empty());
+        assert!(repo.lapsed("sub-1", date("2030-03-01")).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::database::models::{
+    AssignmentFeedback, CreateFeedbackRequest, RequestStatus, ReviewerRole, SubstituteRating,
+};
+use crate::database::timestamp;
+use crate::repository::{enum_column, json_column, query_all, timestamp_column, FromRow, SubstituteRequestRepository};
+use chrono::{NaiveDateTime, Utc};
+use rusqlite::{Connection, Row};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+impl FromRow for AssignmentFeedback {
+    const COLUMNS: &'static str = "id, request_id, reviewer_id, reviewer_role, rating, comments, flags, created_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(AssignmentFeedback {
+            id: row.get(0)?,
+            request_id: row.get(1)?,
+            reviewer_id: row.get(2)?,
+            reviewer_role: enum_column(row, 3)?,
+            rating: row.get(4)?,
+            comments: row.get(5)?,
+            flags: json_column(row, 6)?,
+            created_at: timestamp_column(row, 7)?,
+        })
+    }
+}
+
+/// Ratings left by both sides once an assignment is over.
+pub struct FeedbackRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> FeedbackRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        FeedbackRepository { conn }
+    }
+
+    /// Only assigned requests whose end time has passed as of `now` take
+    /// feedback, and each side can leave it once.
+    pub fn create(&self, request: CreateFeedbackRequest, now: NaiveDateTime) -> anyhow::Result<AssignmentFeedback> {
+        if !(1..=5).contains(&request.rating) {
+            anyhow::bail!("Rating must be between 1 and 5");
+        }
+
+        let substitute_request = SubstituteRequestRepository::new(self.conn)
+            .find_by_id(&request.request_id)?
+            .ok_or_else(|| anyhow::anyhow!("Substitute request not found"))?;
+        let substitute_id = match (&substitute_request.status, &substitute_request.assigned_substitute_id) {
+            (
+                RequestStatus::Filled | RequestStatus::InProgress | RequestStatus::Completed | RequestStatus::NoShow,
+                Some(substitute_id),
+            ) => substitute_id,
+            _ => anyhow::bail!("Only assigned requests can be rated"),
+        };
+        if substitute_request.date_needed.and_time(substitute_request.end_time) > now {
+            anyhow::bail!("Feedback opens once the assignment has ended");
+        }
+
+        let reviewer_role = if request.reviewer_id == substitute_request.requested_by {
+            ReviewerRole::Requester
+        } else if &request.reviewer_id == substitute_id {
+            ReviewerRole::Substitute
+        } else {
+            anyhow::bail!("Only the requester or the assigned substitute can leave feedback");
+        };
+
+        let mut flags: Vec<String> = request
+            .flags
+            .iter()
+            .map(|flag| flag.trim().to_string())
+            .filter(|flag| !flag.is_empty())
+            .collect();
+        flags.sort();
+        flags.dedup();
+
+        let feedback = AssignmentFeedback {
+            id: Uuid::new_v4().to_string(),
+            request_id: request.request_id,
+            reviewer_id: request.reviewer_id,
+            reviewer_role,
+            rating: request.rating,
+            comments: request.comments,
+            flags,
+            created_at: Utc::now(),
+        };
+
+        let already_left: bool = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM assignment_feedback WHERE request_id = ?1 AND reviewer_role = ?2)",
+            (&feedback.request_id, &feedback.reviewer_role.to_string()),
+            |row| row.get(0),
+        )?;
+        if already_left {
+            anyhow::bail!("Feedback for this assignment has already been left");
+        }
+
+        self.conn.execute(
+            "INSERT INTO assignment_feedback (id, request_id, reviewer_id, reviewer_role, rating, comments, flags, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (
+                &feedback.id,
+                &feedback.request_id,
+                &feedback.reviewer_id,
+                &feedback.reviewer_role.to_string(),
+                &feedback.rating,
+                &feedback.comments,
+                &serde_json::to_string(&feedback.flags)?,
+                &timestamp::format(&feedback.created_at),
+            ),
+        )?;
+
+        Ok(feedback)
+    }
+
+    pub fn list_for_request(&self, request_id: &str) -> anyhow::Result<Vec<AssignmentFeedback>> {
+        let sql = format!(
+            "SELECT {} FROM assignment_feedback WHERE request_id = ?1 ORDER BY created_at",
+            AssignmentFeedback::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [request_id])?)
+    }
+
+    /// Requesters' feedback on the substitute, newest first.
+    pub fn list_for_substitute(&self, substitute_id: &str) -> anyhow::Result<Vec<AssignmentFeedback>> {
+        let sql = format!(
+            "SELECT {} FROM assignment_feedback
+             WHERE reviewer_role = 'requester'
+               AND request_id IN (SELECT id FROM substitute_requests WHERE assigned_substitute_id = ?1)
+             ORDER BY created_at DESC",
+            AssignmentFeedback::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [substitute_id])?)
+    }
+
+    pub fn substitute_rating(&self, substitute_id: &str) -> anyhow::Result<SubstituteRating> {
+        let feedback = self.list_for_substitute(substitute_id)?;
+
+        let mut flag_counts = BTreeMap::new();
+        for flag in feedback.iter().flat_map(|entry| &entry.flags) {
+            *flag_counts.entry(flag.clone()).or_insert(0) += 1;
+        }
+        let total: u32 = feedback.iter().map(|entry| u32::from(entry.rating)).sum();
+        let average_rating = match feedback.len() {
+            0 => None,
+            count => Some(f64::from(total) / count as f64),
+        };
+
+        Ok(SubstituteRating {
+            substitute_id: substitute_id.to_string(),
+            average_rating,
+            rating_count: feedback.len() as u32,
+            flag_counts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::CreateSubstituteRequestRequest;
+    use crate::repository::fixtures::{future_date, insert_class, insert_organization, insert_user, test_connection};
+
+    fn at(value: &str) -> NaiveDateTime {
+        let (date, time) = value.split_once(' ').unwrap();
+        future_date(date).and_time(timestamp::parse_time(time).unwrap())
+    }
+
+    fn feedback(request_id: &str, reviewer_id: &str, rating: u8, flags: &[&str]) -> CreateFeedbackRequest {
+        CreateFeedbackRequest {
+            request_id: request_id.to_string(),
+            reviewer_id: reviewer_id.to_string(),
+            rating,
+            comments: None,
+            flags: flags.iter().map(|flag| flag.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_feedback_after_assignment() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "class-1", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        let requests = SubstituteRequestRepository::new(&conn);
+        let repo = FeedbackRepository::new(&conn);
+
+        let mut ids = Vec::new();
+        for date in ["2030-01-14", "2030-01-15"] {
+            let request = requests
+                .create(
+                    "manager-1".to_string(),
+                    CreateSubstituteRequestRequest {
+                        class_id: "class-1".to_string(),
+                        date_needed: future_date(date),
+                        start_time: timestamp::parse_time("08:00").unwrap(),
+                        end_time: timestamp::parse_time("15:00").unwrap(),
+                        reason: None,
+                        special_instructions: None,
+                    },
+                )
+                .unwrap();
+            ids.push(request.id);
+        }
+        let after = at("2030-01-16 09:00");
+
+        // Open requests and unfinished assignments can't be rated
+        assert!(repo.create(feedback(&ids[0], "manager-1", 5, &[]), after).is_err());
+        requests.update_status(&ids[0], "filled", Some("sub-1")).unwrap();
+        requests.update_status(&ids[1], "filled", Some("sub-1")).unwrap();
+        assert!(repo.create(feedback(&ids[0], "manager-1", 5, &[]), at("2030-01-14 14:00")).is_err());
+        assert!(repo.create(feedback(&ids[0], "manager-1", 6, &[]), after).is_err());
+
+        let first = repo
+            .create(feedback(&ids[0], "manager-1", 5, &["excellent classroom management"]), after)
+            .unwrap();
+        assert_eq!(first.reviewer_role, ReviewerRole::Requester);
+        assert!(repo.create(feedback(&ids[0], "manager-1", 4, &[]), after).is_err());
+        let own = repo.create(feedback(&ids[0], "sub-1", 2, &[]), after).unwrap();
+        assert_eq!(own.reviewer_role, ReviewerRole::Substitute);
+        repo.create(feedback(&ids[1], "manager-1", 2, &["left no notes", " left no notes "]), after)
+            .unwrap();
+
+        assert_eq!(repo.list_for_request(&ids[0]).unwrap().len(), 2);
+        let rating = repo.substitute_rating("sub-1").unwrap();
+        assert_eq!(rating.rating_count, 2);
+        assert_eq!(rating.average_rating, Some(3.5));
+        assert_eq!(rating.flag_counts["left no notes"], 1);
+        assert!(repo.substitute_rating("sub-2").unwrap().average_rating.is_none());
+    }
+}
@@ -0,0 +1,203 @@
+pub mod organization;
+pub mod class;
+pub mod user;
+pub mod substitute_request;
+pub mod webhook;
+pub mod absence;
+pub mod teacher;
+pub mod teacher_absence;
+pub mod bell_schedule;
+pub mod calendar;
+pub mod setting;
+pub mod unavailability;
+pub mod preference;
+pub mod feedback;
+pub mod attendance;
+pub mod payroll;
+pub mod budget;
+pub mod credential;
+pub mod vocabulary;
+pub mod requirement;
+pub mod response;
+pub mod report;
+pub mod roster;
+pub mod absence_link;
+
+pub use organization::OrganizationRepository;
+pub use class::ClassRepository;
+pub use user::UserRepository;
+pub use substitute_request::SubstituteRequestRepository;
+pub use webhook::WebhookRepository;
+pub use absence::AbsenceRepository;
+pub use teacher::RegularTeacherRepository;
+pub use teacher_absence::TeacherAbsenceRepository;
+pub use bell_schedule::BellScheduleRepository;
+pub use calendar::CalendarRepository;
+pub use setting::SettingsRepository;
+pub use unavailability::UnavailabilityRepository;
+pub use preference::PreferenceRepository;
+pub use feedback::FeedbackRepository;
+pub use attendance::AttendanceRepository;
+pub use payroll::PayrollRepository;
+pub use budget::BudgetRepository;
+pub use credential::CredentialRepository;
+pub use vocabulary::VocabularyRepository;
+pub use requirement::RequirementRepository;
+pub use response::ResponseRepository;
+pub use report::ReportRepository;
+pub use roster::RosterRepository;
+pub use absence_link::AbsenceLinkRepository;
+
+use crate::database::timestamp;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use rusqlite::types::Type;
+use rusqlite::Row;
+use std::str::FromStr;
+
+/// Maps a row selected with [`FromRow::COLUMNS`] into a model.
+pub trait FromRow: Sized {
+    /// Column list, in the order `from_row` reads them.
+    const COLUMNS: &'static str;
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self>;
+}
+
+fn conversion_error(idx: usize, error: anyhow::Error) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, error.into())
+}
+
+/// Reads a timestamp column, failing on values that aren't a recognised format
+/// rather than substituting the current time.
+pub(crate) fn timestamp_column(row: &Row, idx: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let value: String = row.get(idx)?;
+    timestamp::parse(&value).map_err(|e| conversion_error(idx, e))
+}
+
+pub(crate) fn optional_timestamp_column(row: &Row, idx: usize) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    match row.get::<_, Option<String>>(idx)? {
+        Some(value) => timestamp::parse(&value).map(Some).map_err(|e| conversion_error(idx, e)),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn date_column(row: &Row, idx: usize) -> rusqlite::Result<NaiveDate> {
+    let value: String = row.get(idx)?;
+    timestamp::parse_date(&value).map_err(|e| conversion_error(idx, e))
+}
+
+pub(crate) fn optional_date_column(row: &Row, idx: usize) -> rusqlite::Result<Option<NaiveDate>> {
+    match row.get::<_, Option<String>>(idx)? {
+        Some(value) => timestamp::parse_date(&value).map(Some).map_err(|e| conversion_error(idx, e)),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn time_column(row: &Row, idx: usize) -> rusqlite::Result<NaiveTime> {
+    let value: String = row.get(idx)?;
+    timestamp::parse_time(&value).map_err(|e| conversion_error(idx, e))
+}
+
+pub(crate) fn optional_time_column(row: &Row, idx: usize) -> rusqlite::Result<Option<NaiveTime>> {
+    match row.get::<_, Option<String>>(idx)? {
+        Some(value) => timestamp::parse_time(&value).map(Some).map_err(|e| conversion_error(idx, e)),
+        None => Ok(None),
+    }
+}
+
+/// Reads a text column into one of the model enums, failing on unknown values
+/// rather than substituting a default.
+pub(crate) fn enum_column<T>(row: &Row, idx: usize) -> rusqlite::Result<T>
+where
+    T: FromStr<Err = anyhow::Error>,
+{
+    let value: String = row.get(idx)?;
+    value.parse().map_err(|e| conversion_error(idx, e))
+}
+
+/// Reads a JSON-encoded text column.
+pub(crate) fn json_column<T: serde::de::DeserializeOwned>(row: &Row, idx: usize) -> rusqlite::Result<T> {
+    let value: String = row.get(idx)?;
+    serde_json::from_str(&value).map_err(|e| conversion_error(idx, e.into()))
+}
+
+/// Runs `sql` and maps every returned row with `T::from_row`.
+pub(crate) fn query_all<T: FromRow, P: rusqlite::Params>(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    params: P,
+) -> rusqlite::Result<Vec<T>> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params, T::from_row)?;
+    rows.collect()
+}
+
+/// Runs `sql` and maps the first row, if any, with `T::from_row`.
+pub(crate) fn query_optional<T: FromRow, P: rusqlite::Params>(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    params: P,
+) -> rusqlite::Result<Option<T>> {
+    match conn.query_row(sql, params, T::from_row) {
+        Ok(value) => Ok(Some(value)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use crate::database::timestamp;
+    use chrono::{Datelike, Local, NaiveDate};
+    use rusqlite::Connection;
+
+    /// `value`, a date from 2029 on, moved forward by whole 28-year cycles
+    /// until it's still at least a year away. Weekdays and leap years repeat
+    /// every 28 years until 2100, so a test keeps the calendar it was written
+    /// against while its dates never fall into the past.
+    pub fn future_date(value: &str) -> NaiveDate {
+        let date = timestamp::parse_date(value).unwrap();
+        let next_year = Local::now().date_naive() + chrono::Duration::days(366);
+        let mut years = 0;
+        while NaiveDate::from_ymd_opt(2029 + years, 1, 1).unwrap() < next_year {
+            years += 28;
+        }
+        date.with_year(date.year() + years).unwrap()
+    }
+
+    /// [`future_date`] formatted for SQL and assertions.
+    pub fn future_day(value: &str) -> String {
+        timestamp::format_date(&future_date(value))
+    }
+
+    /// In-memory database with the schema applied.
+    pub fn test_connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../database/schema.sql")).unwrap();
+        conn
+    }
+
+    pub fn insert_organization(conn: &Connection, id: &str) {
+        conn.execute(
+            "INSERT INTO organizations (id, name) VALUES (?1, ?1)",
+            [id],
+        )
+        .unwrap();
+    }
+
+    pub fn insert_class(conn: &Connection, id: &str, organization_id: &str) {
+        conn.execute(
+            "INSERT INTO classes (id, name, organization_id) VALUES (?1, ?1, ?2)",
+            [id, organization_id],
+        )
+        .unwrap();
+    }
+
+    pub fn insert_user(conn: &Connection, id: &str, role: &str) {
+        conn.execute(
+            "INSERT INTO users (id, username, password_hash, email, first_name, last_name, role)
+             VALUES (?1, ?1, 'hashed_password', ?1 || '@example.com', 'Test', ?1, ?2)",
+            [id, role],
+        )
+        .unwrap();
+    }
+}
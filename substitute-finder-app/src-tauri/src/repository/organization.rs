@@ -0,0 +1,172 @@
+use crate::database::models::{CreateOrganizationRequest, Organization};
+use crate::database::timestamp;
+use crate::repository::{optional_time_column, query_all, query_optional, timestamp_column, FromRow};
+use chrono::{NaiveTime, Utc};
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for Organization {
+    const COLUMNS: &'static str =
+        "id, name, parent_organization_id, description, contact_email, contact_phone, address, school_start_time, school_end_time, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Organization {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            parent_organization_id: row.get(2)?,
+            description: row.get(3)?,
+            contact_email: row.get(4)?,
+            contact_phone: row.get(5)?,
+            address: row.get(6)?,
+            school_start_time: optional_time_column(row, 7)?,
+            school_end_time: optional_time_column(row, 8)?,
+            created_at: timestamp_column(row, 9)?,
+            updated_at: timestamp_column(row, 10)?,
+        })
+    }
+}
+
+pub struct OrganizationRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> OrganizationRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        OrganizationRepository { conn }
+    }
+
+    pub fn create(&self, request: CreateOrganizationRequest) -> anyhow::Result<Organization> {
+        let organization = Organization {
+            id: Uuid::new_v4().to_string(),
+            name: request.name,
+            parent_organization_id: request.parent_organization_id,
+            description: request.description,
+            contact_email: request.contact_email,
+            contact_phone: request.contact_phone,
+            address: request.address,
+            school_start_time: request.school_start_time,
+            school_end_time: request.school_end_time,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO organizations (id, name, parent_organization_id, description, contact_email, contact_phone, address, school_start_time, school_end_time, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            (
+                &organization.id,
+                &organization.name,
+                &organization.parent_organization_id,
+                &organization.description,
+                &organization.contact_email,
+                &organization.contact_phone,
+                &organization.address,
+                &organization.school_start_time.as_ref().map(timestamp::format_time),
+                &organization.school_end_time.as_ref().map(timestamp::format_time),
+                &timestamp::format(&organization.created_at),
+                &timestamp::format(&organization.updated_at),
+            ),
+        )?;
+
+        Ok(organization)
+    }
+
+    pub fn list(&self) -> anyhow::Result<Vec<Organization>> {
+        let sql = format!("SELECT {} FROM organizations ORDER BY name", Organization::COLUMNS);
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<Organization>> {
+        let sql = format!("SELECT {} FROM organizations WHERE id = ?1", Organization::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    /// Organizations with this name, ignoring case. Names needn't be unique.
+    pub fn find_by_name(&self, name: &str) -> anyhow::Result<Vec<Organization>> {
+        let sql = format!(
+            "SELECT {} FROM organizations WHERE lower(name) = lower(?1) ORDER BY id",
+            Organization::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [name])?)
+    }
+
+    /// Returns `None` when no organization has the given id.
+    pub fn update(
+        &self,
+        id: &str,
+        request: CreateOrganizationRequest,
+    ) -> anyhow::Result<Option<Organization>> {
+        self.conn.execute(
+            "UPDATE organizations SET name = ?1, parent_organization_id = ?2, description = ?3,
+             contact_email = ?4, contact_phone = ?5, address = ?6, school_start_time = ?7, school_end_time = ?8,
+             updated_at = ?9 WHERE id = ?10",
+            (
+                &request.name,
+                &request.parent_organization_id,
+                &request.description,
+                &request.contact_email,
+                &request.contact_phone,
+                &request.address,
+                &request.school_start_time.as_ref().map(timestamp::format_time),
+                &request.school_end_time.as_ref().map(timestamp::format_time),
+                &timestamp::now(),
+                id,
+            ),
+        )?;
+
+        self.find_by_id(id)
+    }
+
+    /// The organization followed by its parent, grandparent and so on up to the root.
+    pub fn ancestors(&self, id: &str) -> anyhow::Result<Vec<Organization>> {
+        let mut chain: Vec<Organization> = Vec::new();
+        let mut next = Some(id.to_string());
+
+        while let Some(current) = next {
+            // Guard against cycles in hand-edited data
+            if chain.iter().any(|org| org.id == current) {
+                break;
+            }
+            match self.find_by_id(&current)? {
+                Some(org) => {
+                    next = org.parent_organization_id.clone();
+                    chain.push(org);
+                }
+                None => break,
+            }
+        }
+
+        Ok(chain)
+    }
+
+    /// Ids of the organization and every organization below it in the tree.
+    pub fn subtree_ids(&self, id: &str) -> anyhow::Result<Vec<String>> {
+        // UNION (not UNION ALL) stops the recursion on cycles in hand-edited data
+        let mut stmt = self.conn.prepare(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT ?1
+                 UNION
+                 SELECT o.id FROM organizations o JOIN subtree s ON o.parent_organization_id = s.id
+             )
+             SELECT id FROM subtree",
+        )?;
+        let ids = stmt
+            .query_map([id], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(ids)
+    }
+
+    /// School hours of the organization, inherited from the nearest ancestor
+    /// that defines both a start and an end time.
+    pub fn effective_school_hours(&self, id: &str) -> anyhow::Result<Option<(NaiveTime, NaiveTime)>> {
+        Ok(self
+            .ancestors(id)?
+            .into_iter()
+            .find_map(|org| org.school_start_time.zip(org.school_end_time)))
+    }
+
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM organizations WHERE id = ?1", [id])?;
+        Ok(())
+    }
+}
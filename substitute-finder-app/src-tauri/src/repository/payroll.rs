@@ -0,0 +1,507 @@
+use crate::database::models::{
+    PayBasis, PayPolicy, SetPayPolicyRequest, SubstituteRequest, Timesheet, TimesheetEntry,
+};
+use crate::database::timestamp;
+use crate::payroll::{apportion, covered_hours, hours, price, round_hours};
+use crate::repository::{
+    enum_column, query_all, query_optional, timestamp_column, AttendanceRepository, ClassRepository, FromRow,
+    OrganizationRepository, UserRepository,
+};
+use chrono::{Local, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension, Row};
+
+impl FromRow for PayPolicy {
+    const COLUMNS: &'static str = "organization_id, use_actual_hours, rounding_minutes, rounding_mode, half_day_rate, half_day_max_hours, full_day_rate, long_term_after_days, long_term_multiplier, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(PayPolicy {
+            organization_id: row.get(0)?,
+            use_actual_hours: row.get(1)?,
+            rounding_minutes: row.get(2)?,
+            rounding_mode: enum_column(row, 3)?,
+            half_day_rate: row.get(4)?,
+            half_day_max_hours: row.get(5)?,
+            full_day_rate: row.get(6)?,
+            long_term_after_days: row.get(7)?,
+            long_term_multiplier: row.get(8)?,
+            updated_at: timestamp_column(row, 9)?,
+        })
+    }
+}
+
+fn validate_policy(policy: &PayPolicy) -> anyhow::Result<()> {
+    let rates = [policy.half_day_rate, policy.full_day_rate];
+    if rates.iter().flatten().any(|rate| *rate < 0.0) {
+        anyhow::bail!("Rates must not be negative");
+    }
+    if policy.half_day_max_hours <= 0.0 {
+        anyhow::bail!("Half-day hours must be positive");
+    }
+    if policy.long_term_multiplier <= 0.0 {
+        anyhow::bail!("Long-term multiplier must be positive");
+    }
+    Ok(())
+}
+
+/// Pay policies and the timesheets computed from them.
+pub struct PayrollRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> PayrollRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        PayrollRepository { conn }
+    }
+
+    /// Creates or replaces the organization's own policy.
+    pub fn set_policy(&self, request: SetPayPolicyRequest) -> anyhow::Result<PayPolicy> {
+        let defaults = PayPolicy::default_for(&request.organization_id);
+        let policy = PayPolicy {
+            organization_id: request.organization_id,
+            use_actual_hours: request.use_actual_hours.unwrap_or(defaults.use_actual_hours),
+            rounding_minutes: request.rounding_minutes.unwrap_or(defaults.rounding_minutes),
+            rounding_mode: request.rounding_mode.unwrap_or(defaults.rounding_mode),
+            half_day_rate: request.half_day_rate,
+            half_day_max_hours: request.half_day_max_hours.unwrap_or(defaults.half_day_max_hours),
+            full_day_rate: request.full_day_rate,
+            long_term_after_days: request.long_term_after_days,
+            long_term_multiplier: request.long_term_multiplier.unwrap_or(defaults.long_term_multiplier),
+            updated_at: Utc::now(),
+        };
+        validate_policy(&policy)?;
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO pay_policies (organization_id, use_actual_hours, rounding_minutes, rounding_mode, half_day_rate, half_day_max_hours, full_day_rate, long_term_after_days, long_term_multiplier, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                &policy.organization_id,
+                &policy.use_actual_hours,
+                &policy.rounding_minutes,
+                &policy.rounding_mode.to_string(),
+                &policy.half_day_rate,
+                &policy.half_day_max_hours,
+                &policy.full_day_rate,
+                &policy.long_term_after_days,
+                &policy.long_term_multiplier,
+                &timestamp::format(&policy.updated_at),
+            ],
+        )?;
+
+        Ok(policy)
+    }
+
+    /// The organization's own policy, not an inherited one.
+    pub fn find_policy(&self, organization_id: &str) -> anyhow::Result<Option<PayPolicy>> {
+        let sql = format!("SELECT {} FROM pay_policies WHERE organization_id = ?1", PayPolicy::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [organization_id])?)
+    }
+
+    pub fn delete_policy(&self, organization_id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM pay_policies WHERE organization_id = ?1", [organization_id])?;
+        Ok(())
+    }
+
+    /// The policy of the organization or its nearest ancestor that has one,
+    /// falling back to [`PayPolicy::default_for`].
+    pub fn effective_policy(&self, organization_id: &str) -> anyhow::Result<PayPolicy> {
+        for organization in OrganizationRepository::new(self.conn).ancestors(organization_id)? {
+            if let Some(policy) = self.find_policy(&organization.id)? {
+                return Ok(policy);
+            }
+        }
+        Ok(PayPolicy::default_for(organization_id))
+    }
+
+    /// One timesheet per substitute with completed assignments between
+    /// `period_start` and `period_end` inclusive, optionally for a single
+    /// substitute. A substitute covering several classes of one teacher
+    /// absence on a day is paid for that day once, shared across the classes.
+    pub fn timesheets(
+        &self,
+        period_start: NaiveDate,
+        period_end: NaiveDate,
+        substitute_id: Option<&str>,
+    ) -> anyhow::Result<Vec<Timesheet>> {
+        if period_end < period_start {
+            anyhow::bail!("Pay period must not end before it starts");
+        }
+
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE status = 'completed' AND assigned_substitute_id IS NOT NULL
+               AND date_needed BETWEEN ?1 AND ?2 AND (?3 IS NULL OR assigned_substitute_id = ?3)
+             ORDER BY assigned_substitute_id, date_needed, start_time",
+            SubstituteRequest::COLUMNS
+        );
+        let requests: Vec<SubstituteRequest> = query_all(
+            self.conn,
+            &sql,
+            rusqlite::params![
+                timestamp::format_date(&period_start),
+                timestamp::format_date(&period_end),
+                substitute_id
+            ],
+        )?;
+
+        // (substitute, date, teacher absence or request) and the requests
+        // worked together under it
+        let mut shifts: Vec<((String, NaiveDate, String), Vec<SubstituteRequest>)> = Vec::new();
+        for request in requests {
+            let Some(substitute_id) = request.assigned_substitute_id.clone() else {
+                continue;
+            };
+            let key = (substitute_id, request.date_needed, self.shift_id(&request)?);
+            match shifts.iter_mut().find(|(shift_key, _)| *shift_key == key) {
+                Some((_, shift)) => shift.push(request),
+                None => shifts.push((key, vec![request])),
+            }
+        }
+
+        let users = UserRepository::new(self.conn);
+        let mut timesheets: Vec<Timesheet> = Vec::new();
+        for ((substitute_id, _, _), shift) in shifts {
+            let entries = self.entries(&shift, &substitute_id)?;
+
+            if timesheets.last().is_none_or(|timesheet| timesheet.substitute_id != substitute_id) {
+                let user = users
+                    .find_by_id(&substitute_id)?
+                    .ok_or_else(|| anyhow::anyhow!("Substitute {} not found", substitute_id))?;
+                timesheets.push(Timesheet {
+                    substitute_id: substitute_id.clone(),
+                    substitute_name: format!("{}, {}", user.last_name, user.first_name),
+                    substitute_email: user.email,
+                    period_start,
+                    period_end,
+                    entries: Vec::new(),
+                    total_hours: 0.0,
+                    total_amount: 0.0,
+                });
+            }
+            if let Some(timesheet) = timesheets.last_mut() {
+                for entry in entries {
+                    timesheet.total_hours += entry.paid_hours;
+                    timesheet.total_amount += entry.amount.unwrap_or(0.0);
+                    timesheet.entries.push(entry);
+                }
+            }
+        }
+
+        Ok(timesheets)
+    }
+
+    /// The teacher absence `request` is part of, or the request itself when
+    /// it stands alone.
+    fn shift_id(&self, request: &SubstituteRequest) -> anyhow::Result<String> {
+        let teacher_absence_id: Option<String> = match &request.absence_id {
+            Some(absence_id) => self
+                .conn
+                .query_row("SELECT teacher_absence_id FROM absences WHERE id = ?1", [absence_id], |row| row.get(0))
+                .optional()?
+                .flatten(),
+            None => None,
+        };
+        Ok(teacher_absence_id.unwrap_or_else(|| request.id.clone()))
+    }
+
+    /// Entries for requests worked together as one shift. The shift is priced
+    /// once on the time its requests cover, under the first one's policy, and
+    /// the amount split across them by paid hours.
+    fn entries(&self, shift: &[SubstituteRequest], substitute_id: &str) -> anyhow::Result<Vec<TimesheetEntry>> {
+        let first = shift.first().ok_or_else(|| anyhow::anyhow!("Empty shift"))?;
+        let classes = ClassRepository::new(self.conn);
+        let organizations = OrganizationRepository::new(self.conn);
+        let attendance = AttendanceRepository::new(self.conn);
+
+        let mut policy = None;
+        let mut windows = Vec::new();
+        let mut entries = Vec::new();
+        for request in shift {
+            let class = classes
+                .find_by_id(&request.class_id)?
+                .ok_or_else(|| anyhow::anyhow!("Class {} not found", request.class_id))?;
+            let organization_name = organizations
+                .find_by_id(&class.organization_id)?
+                .map(|org| org.name)
+                .unwrap_or_default();
+            let policy = match &policy {
+                Some(policy) => policy,
+                None => policy.insert(self.effective_policy(&class.organization_id)?),
+            };
+
+            let scheduled = (
+                request.date_needed.and_time(request.start_time),
+                request.date_needed.and_time(request.end_time),
+            );
+            let actual = attendance
+                .find(&request.id)?
+                .and_then(|attendance| Some((attendance.check_in_at?, attendance.check_out_at?)))
+                .map(|(check_in, check_out)| {
+                    (check_in.with_timezone(&Local).naive_local(), check_out.with_timezone(&Local).naive_local())
+                });
+            let window = match actual {
+                Some(actual) if policy.use_actual_hours => actual,
+                _ => scheduled,
+            };
+            windows.push(window);
+
+            entries.push(TimesheetEntry {
+                request_id: request.id.clone(),
+                date_needed: request.date_needed,
+                organization_id: class.organization_id,
+                organization_name,
+                class_id: class.id,
+                class_name: class.name,
+                scheduled_hours: hours(scheduled.1 - scheduled.0),
+                actual_hours: actual.map(|(start, end)| hours(end - start)),
+                paid_hours: round_hours(hours(window.1 - window.0), policy.rounding_minutes, policy.rounding_mode),
+                basis: PayBasis::Hourly,
+                rate: None,
+                multiplier: 1.0,
+                amount: None,
+            });
+        }
+        let policy = policy.ok_or_else(|| anyhow::anyhow!("Empty shift"))?;
+
+        let paid_hours = round_hours(covered_hours(windows), policy.rounding_minutes, policy.rounding_mode);
+        let multiplier = match policy.long_term_after_days {
+            Some(after_days) if self.day_of_assignment(first, substitute_id)? > after_days => {
+                policy.long_term_multiplier
+            }
+            _ => 1.0,
+        };
+        let (basis, rate, amount) = price(&policy, paid_hours, self.hourly_rate(substitute_id)?, multiplier);
+        let weights: Vec<f64> = entries.iter().map(|entry| entry.paid_hours).collect();
+        let shares = amount.map(|amount| apportion(amount, &weights));
+        for (index, entry) in entries.iter_mut().enumerate() {
+            entry.basis = basis;
+            entry.rate = rate;
+            entry.multiplier = multiplier;
+            entry.amount = shares.as_ref().map(|shares| shares[index]);
+        }
+
+        Ok(entries)
+    }
+
+    /// What `scheduled_hours` at the organization should cost under its
+    /// policy, at the substitute's rate or `fallback_rate` while nobody is
+    /// assigned. `None` when no rate applies.
+    pub fn estimate(
+        &self,
+        organization_id: &str,
+        scheduled_hours: f64,
+        substitute_id: Option<&str>,
+        fallback_rate: Option<f64>,
+    ) -> anyhow::Result<Option<f64>> {
+        let policy = self.effective_policy(organization_id)?;
+        let paid_hours = round_hours(scheduled_hours, policy.rounding_minutes, policy.rounding_mode);
+        let hourly_rate = match substitute_id {
+            Some(substitute_id) => self.hourly_rate(substitute_id)?,
+            None => fallback_rate,
+        };
+        Ok(price(&policy, paid_hours, hourly_rate, 1.0).2)
+    }
+
+    fn hourly_rate(&self, substitute_id: &str) -> anyhow::Result<Option<f64>> {
+        Ok(self
+            .conn
+            .query_row("SELECT hourly_rate FROM teachers WHERE user_id = ?1", [substitute_id], |row| row.get(0))
+            .optional()?
+            .flatten())
+    }
+
+    /// Which day of a long-term assignment `request` is for the substitute:
+    /// the number of distinct days up to and including it they've completed
+    /// on the same absence, or the same teacher absence across classes.
+    /// Standalone requests are always day one.
+    fn day_of_assignment(&self, request: &SubstituteRequest, substitute_id: &str) -> anyhow::Result<u32> {
+        let Some(absence_id) = &request.absence_id else {
+            return Ok(1);
+        };
+        let days = self.conn.query_row(
+            "SELECT COUNT(DISTINCT date_needed) FROM substitute_requests
+             WHERE assigned_substitute_id = ?1 AND status = 'completed' AND date_needed <= ?2
+               AND absence_id IN (
+                   SELECT id FROM absences
+                   WHERE id = ?3 OR teacher_absence_id = (SELECT teacher_absence_id FROM absences WHERE id = ?3)
+               )",
+            (substitute_id, &timestamp::format_date(&request.date_needed), absence_id),
+            |row| row.get(0),
+        )?;
+        Ok(days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::{AttendanceOverrideRequest, CreateAbsenceRequest, ReportAbsenceRequest};
+    use crate::repository::fixtures::{future_date, insert_class, insert_organization, insert_user, test_connection};
+    use crate::repository::{AbsenceRepository, SubstituteRequestRepository, TeacherAbsenceRepository};
+    use chrono::{Local, TimeZone};
+
+    fn date(value: &str) -> NaiveDate {
+        future_date(value)
+    }
+
+    fn at(value: &str) -> chrono::DateTime<Utc> {
+        let (day, time) = value.split_once(' ').unwrap();
+        let naive = date(day).and_time(timestamp::parse_time(time).unwrap());
+        Local.from_local_datetime(&naive).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_timesheets_apply_policy() {
+        let conn = test_connection();
+        insert_organization(&conn, "district");
+        insert_organization(&conn, "school");
+        conn.execute("UPDATE organizations SET parent_organization_id = 'district' WHERE id = 'school'", [])
+            .unwrap();
+        insert_class(&conn, "math", "school");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        conn.execute("INSERT INTO teachers (id, user_id, hourly_rate) VALUES ('t1', 'sub-1', 20.0)", [])
+            .unwrap();
+
+        let absence = AbsenceRepository::new(&conn)
+            .create(
+                "manager-1".to_string(),
+                CreateAbsenceRequest {
+                    class_id: "math".to_string(),
+                    start_date: date("2030-01-14"),
+                    end_date: Some(date("2030-01-16")),
+                    start_time: timestamp::parse_time("08:00").unwrap(),
+                    end_time: timestamp::parse_time("14:40").unwrap(),
+                    recurrence_rule: None,
+                    excluded_dates: Vec::new(),
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap();
+        let requests = SubstituteRequestRepository::new(&conn);
+        let attendance = AttendanceRepository::new(&conn);
+        for request in &absence.requests {
+            requests.update_status(&request.id, "filled", Some("sub-1")).unwrap();
+        }
+        // Day one is checked in and out; the others are completed by a
+        // manager on schedule
+        attendance.check_in(&absence.requests[0].id, "sub-1", at("2030-01-14 07:58")).unwrap();
+        attendance.check_out(&absence.requests[0].id, "sub-1", at("2030-01-14 14:05")).unwrap();
+        for (request, day) in absence.requests[1..].iter().zip(["2030-01-15", "2030-01-16"]) {
+            attendance
+                .override_attendance(AttendanceOverrideRequest {
+                    request_id: request.id.clone(),
+                    manager_id: "manager-1".to_string(),
+                    check_in_at: at(&format!("{} 08:00", day)),
+                    check_out_at: Some(at(&format!("{} 14:40", day))),
+                    reason: "Paper sign-in sheet".to_string(),
+                })
+                .unwrap();
+        }
+
+        let repo = PayrollRepository::new(&conn);
+        let sheets = repo.timesheets(date("2030-01-01"), date("2030-01-31"), None).unwrap();
+        assert_eq!(sheets.len(), 1);
+        let entries = &sheets[0].entries;
+        assert_eq!(entries.len(), 3);
+        // 6h07m actual rounds to 6h, paid hourly with no policy set
+        assert_eq!(entries[0].paid_hours, 6.0);
+        assert_eq!(entries[0].basis, PayBasis::Hourly);
+        assert_eq!(entries[0].amount, Some(120.0));
+        assert_eq!(sheets[0].substitute_name, "sub-1, Test");
+
+        // The district's policy applies to the school below it
+        repo.set_policy(SetPayPolicyRequest {
+            organization_id: "district".to_string(),
+            use_actual_hours: Some(false),
+            rounding_minutes: Some(30),
+            rounding_mode: Some(crate::database::models::RoundingMode::Up),
+            half_day_rate: None,
+            half_day_max_hours: None,
+            full_day_rate: Some(150.0),
+            long_term_after_days: Some(2),
+            long_term_multiplier: Some(1.2),
+        })
+        .unwrap();
+        let sheets = repo.timesheets(date("2030-01-01"), date("2030-01-31"), Some("sub-1")).unwrap();
+        let entries = &sheets[0].entries;
+        assert_eq!(entries[0].paid_hours, 7.0);
+        assert_eq!(entries[0].basis, PayBasis::FullDay);
+        assert_eq!(entries.iter().map(|entry| entry.amount.unwrap()).collect::<Vec<_>>(), vec![150.0, 150.0, 180.0]);
+        assert_eq!(sheets[0].total_amount, 480.0);
+
+        assert!(repo.timesheets(date("2030-02-01"), date("2030-02-14"), None).unwrap().is_empty());
+        assert!(repo.timesheets(date("2030-02-14"), date("2030-02-01"), None).is_err());
+    }
+
+    #[test]
+    fn test_teacher_absence_day_is_paid_once() {
+        let conn = test_connection();
+        insert_organization(&conn, "school");
+        conn.execute(
+            "UPDATE organizations SET school_start_time = '08:00', school_end_time = '15:00' WHERE id = 'school'",
+            [],
+        )
+        .unwrap();
+        insert_class(&conn, "math", "school");
+        insert_class(&conn, "science", "school");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        conn.execute_batch(
+            "INSERT INTO regular_teachers (id, user_id, organization_id) VALUES ('teacher-1', 'manager-1', 'school');
+             INSERT INTO teacher_class_assignments (id, teacher_id, class_id) VALUES ('a-1', 'teacher-1', 'math');
+             INSERT INTO teacher_class_assignments (id, teacher_id, class_id) VALUES ('a-2', 'teacher-1', 'science');
+             INSERT INTO bell_schedules (id, organization_id, name, is_default) VALUES ('regular', 'school', 'Regular', true);
+             INSERT INTO bell_schedule_periods (id, schedule_id, name, position, start_time, end_time)
+             VALUES ('p1', 'regular', '1', 1, '08:00', '08:50');
+             INSERT INTO class_periods (class_id, period_name) VALUES ('math', '1');",
+        )
+        .unwrap();
+
+        let teacher_absences = TeacherAbsenceRepository::new(&conn);
+        let reported = teacher_absences
+            .report(
+                "manager-1".to_string(),
+                ReportAbsenceRequest {
+                    teacher_id: "teacher-1".to_string(),
+                    start_date: date("2030-01-14"),
+                    end_date: date("2030-01-14"),
+                    start_time: None,
+                    end_time: None,
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap();
+        teacher_absences.accept(&reported.teacher_absence.id, "sub-1", None).unwrap();
+        let requests = SubstituteRequestRepository::new(&conn);
+        for request in &reported.requests {
+            requests.update_status(&request.id, "completed", Some("sub-1")).unwrap();
+        }
+
+        let repo = PayrollRepository::new(&conn);
+        repo.set_policy(SetPayPolicyRequest {
+            organization_id: "school".to_string(),
+            use_actual_hours: Some(false),
+            rounding_minutes: None,
+            rounding_mode: None,
+            half_day_rate: Some(80.0),
+            half_day_max_hours: Some(4.0),
+            full_day_rate: Some(150.0),
+            long_term_after_days: None,
+            long_term_multiplier: None,
+        })
+        .unwrap();
+
+        // Science's school day overlaps math's first period, so the day is
+        // one full day shared between the two classes by their hours
+        let sheets = repo.timesheets(date("2030-01-01"), date("2030-01-31"), None).unwrap();
+        let entries = &sheets[0].entries;
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.basis == PayBasis::FullDay));
+        let mut paid_hours: Vec<f64> = entries.iter().map(|entry| entry.paid_hours).collect();
+        paid_hours.sort_by(f64::total_cmp);
+        assert_eq!(paid_hours, vec![0.75, 7.0]);
+        assert_eq!(sheets[0].total_amount, 150.0);
+    }
+}
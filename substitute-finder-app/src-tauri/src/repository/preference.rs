@@ -0,0 +1,362 @@
+use crate::database::models::{
+    CreateSubstitutePreferenceRequest, OfferTiers, PreferenceType, SubstitutePreference, SubstituteRequest,
+};
+use crate::database::timestamp;
+use crate::repository::{
+    date_column, enum_column, optional_date_column, query_all, query_optional, timestamp_column, ClassRepository,
+    CredentialRepository, FeedbackRepository, FromRow, OrganizationRepository, RequirementRepository,
+    SubstituteRequestRepository, UnavailabilityRepository,
+};
+use chrono::{Local, Utc};
+use rusqlite::{Connection, OptionalExtension, Row};
+use uuid::Uuid;
+
+impl FromRow for SubstitutePreference {
+    const COLUMNS: &'static str =
+        "id, substitute_id, organization_id, teacher_id, preference, reason, effective_from, effective_until, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(SubstitutePreference {
+            id: row.get(0)?,
+            substitute_id: row.get(1)?,
+            organization_id: row.get(2)?,
+            teacher_id: row.get(3)?,
+            preference: enum_column(row, 4)?,
+            reason: row.get(5)?,
+            effective_from: date_column(row, 6)?,
+            effective_until: optional_date_column(row, 7)?,
+            created_at: timestamp_column(row, 8)?,
+            updated_at: timestamp_column(row, 9)?,
+        })
+    }
+}
+
+/// Ranking score of a substitute nobody has rated yet, midway on the 1-5
+/// scale so newcomers sit between well and poorly rated substitutes.
+pub const UNRATED_SCORE: f64 = 3.0;
+
+/// Preferred and do-not-send substitute lists, kept per organization and per
+/// regular teacher.
+pub struct PreferenceRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> PreferenceRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        PreferenceRepository { conn }
+    }
+
+    pub fn create(&self, request: CreateSubstitutePreferenceRequest) -> anyhow::Result<SubstitutePreference> {
+        if request.organization_id.is_some() == request.teacher_id.is_some() {
+            anyhow::bail!("A preference belongs to either an organization or a teacher");
+        }
+        let effective_from = request.effective_from.unwrap_or_else(|| Local::now().date_naive());
+        if request.effective_until.is_some_and(|until| until < effective_from) {
+            anyhow::bail!("Effective until must not be before effective from");
+        }
+
+        let preference = SubstitutePreference {
+            id: Uuid::new_v4().to_string(),
+            substitute_id: request.substitute_id,
+            organization_id: request.organization_id,
+            teacher_id: request.teacher_id,
+            preference: request.preference,
+            reason: request.reason,
+            effective_from,
+            effective_until: request.effective_until,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO substitute_preferences (id, substitute_id, organization_id, teacher_id, preference, reason, effective_from, effective_until, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                &preference.id,
+                &preference.substitute_id,
+                &preference.organization_id,
+                &preference.teacher_id,
+                &preference.preference.to_string(),
+                &preference.reason,
+                &timestamp::format_date(&preference.effective_from),
+                &preference.effective_until.as_ref().map(timestamp::format_date),
+                &timestamp::format(&preference.created_at),
+                &timestamp::format(&preference.updated_at),
+            ],
+        )?;
+
+        Ok(preference)
+    }
+
+    /// Entries set directly on the organization, not inherited ones.
+    pub fn list_for_organization(&self, organization_id: &str) -> anyhow::Result<Vec<SubstitutePreference>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_preferences WHERE organization_id = ?1 ORDER BY preference, effective_from",
+            SubstitutePreference::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [organization_id])?)
+    }
+
+    pub fn list_for_teacher(&self, teacher_id: &str) -> anyhow::Result<Vec<SubstitutePreference>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_preferences WHERE teacher_id = ?1 ORDER BY preference, effective_from",
+            SubstitutePreference::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [teacher_id])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<SubstitutePreference>> {
+        let sql = format!("SELECT {} FROM substitute_preferences WHERE id = ?1", SubstitutePreference::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM substitute_preferences WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Entries in effect on the request's date for its class's organization
+    /// or any organization above it, and for the regular teacher it covers.
+    /// That teacher is the absent one when the request came from a teacher
+    /// absence, otherwise whoever teaches the class.
+    pub fn applicable(&self, request: &SubstituteRequest) -> anyhow::Result<Vec<SubstitutePreference>> {
+        let mut organization_ids = Vec::new();
+        if let Some(class) = ClassRepository::new(self.conn).find_by_id(&request.class_id)? {
+            organization_ids = OrganizationRepository::new(self.conn)
+                .ancestors(&class.organization_id)?
+                .into_iter()
+                .map(|org| org.id)
+                .collect();
+        }
+
+        let absent_teacher: Option<String> = match &request.absence_id {
+            Some(absence_id) => self
+                .conn
+                .query_row(
+                    "SELECT t.teacher_id FROM absences a JOIN teacher_absences t ON t.id = a.teacher_absence_id
+                     WHERE a.id = ?1",
+                    [absence_id],
+                    |row| row.get(0),
+                )
+                .optional()?,
+            None => None,
+        };
+        let teacher_ids: Vec<String> = match absent_teacher {
+            Some(teacher_id) => vec![teacher_id],
+            None => {
+                let mut stmt = self
+                    .conn
+                    .prepare("SELECT teacher_id FROM teacher_class_assignments WHERE class_id = ?1")?;
+                let ids = stmt
+                    .query_map([&request.class_id], |row| row.get(0))?
+                    .collect::<rusqlite::Result<Vec<String>>>()?;
+                ids
+            }
+        };
+
+        let sql = format!("SELECT {} FROM substitute_preferences", SubstitutePreference::COLUMNS);
+        let all: Vec<SubstitutePreference> = query_all(self.conn, &sql, [])?;
+        Ok(all
+            .into_iter()
+            .filter(|entry| entry.is_effective_on(request.date_needed))
+            .filter(|entry| {
+                entry.organization_id.as_ref().is_some_and(|id| organization_ids.contains(id))
+                    || entry.teacher_id.as_ref().is_some_and(|id| teacher_ids.contains(id))
+            })
+            .collect())
+    }
+
+    /// The entry keeping the substitute away from `request`, if any.
+    pub fn blocking(&self, substitute_id: &str, request: &SubstituteRequest) -> anyhow::Result<Option<SubstitutePreference>> {
+        Ok(self
+            .applicable(request)?
+            .into_iter()
+            .find(|entry| entry.substitute_id == substitute_id && entry.preference == PreferenceType::Blocked))
+    }
+
+    /// Splits `substitute_ids` into who hears about `request` first and who
+    /// hears after, dropping anyone blocked, unavailable, missing a required
+    /// credential, not meeting the class's requirements or already booked over
+    /// it (travel buffer included). A block wins over a
+    /// preference for the same substitute. Each tier is ordered by rating,
+    /// with unrated substitutes scored [`UNRATED_SCORE`].
+    pub fn offer_tiers(&self, substitute_ids: Vec<String>, request: &SubstituteRequest) -> anyhow::Result<OfferTiers> {
+        let entries = self.applicable(request)?;
+        let has = |substitute_id: &str, preference: PreferenceType| {
+            entries
+                .iter()
+                .any(|entry| entry.substitute_id == substitute_id && entry.preference == preference)
+        };
+
+        let requests = SubstituteRequestRepository::new(self.conn);
+        let mut tiers = OfferTiers::default();
+        let substitute_ids = CredentialRepository::new(self.conn).qualified_for(substitute_ids, request.date_needed)?;
+        let substitute_ids = RequirementRepository::new(self.conn).qualified_for(substitute_ids, request)?;
+        for substitute_id in UnavailabilityRepository::new(self.conn).available_for(substitute_ids, request)? {
+            if has(&substitute_id, PreferenceType::Blocked) {
+                continue;
+            }
+            if !requests.find_conflicts(&substitute_id, request)?.is_empty() {
+                continue;
+            }
+            if has(&substitute_id, PreferenceType::Preferred) {
+                tiers.preferred.push(substitute_id);
+            } else {
+                tiers.others.push(substitute_id);
+            }
+        }
+
+        let feedback = FeedbackRepository::new(self.conn);
+        for tier in [&mut tiers.preferred, &mut tiers.others] {
+            let mut scored = Vec::new();
+            for substitute_id in tier.drain(..) {
+                let score = feedback.substitute_rating(&substitute_id)?.average_rating.unwrap_or(UNRATED_SCORE);
+                scored.push((substitute_id, score));
+            }
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+            tier.extend(scored.into_iter().map(|(substitute_id, _)| substitute_id));
+        }
+        Ok(tiers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::{
+        CreateRegularTeacherRequest, CreateSubstituteRequestRequest, CreateTeacherClassAssignmentRequest,
+    };
+    use crate::repository::fixtures::{future_date, insert_class, insert_organization, insert_user, test_connection};
+    use crate::repository::setting::TRAVEL_BUFFER_MINUTES;
+    use crate::repository::{RegularTeacherRepository, SettingsRepository};
+
+    #[test]
+    fn test_blocked_and_preferred_substitutes() {
+        let conn = test_connection();
+        insert_organization(&conn, "district");
+        insert_organization(&conn, "school");
+        conn.execute("UPDATE organizations SET parent_organization_id = 'district' WHERE id = 'school'", [])
+            .unwrap();
+        insert_class(&conn, "math", "school");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "teacher-user", "org_manager");
+        for sub in ["sub-1", "sub-2", "sub-3", "sub-4"] {
+            insert_user(&conn, sub, "substitute");
+        }
+        let teachers = RegularTeacherRepository::new(&conn);
+        let teacher = teachers
+            .create(CreateRegularTeacherRequest {
+                user_id: "teacher-user".to_string(),
+                organization_id: "school".to_string(),
+            })
+            .unwrap();
+        teachers
+            .assign_class(CreateTeacherClassAssignmentRequest {
+                teacher_id: teacher.id.clone(),
+                class_id: "math".to_string(),
+                is_primary: true,
+            })
+            .unwrap();
+
+        let requests = SubstituteRequestRepository::new(&conn);
+        let request = requests
+            .create(
+                "manager-1".to_string(),
+                CreateSubstituteRequestRequest {
+                    class_id: "math".to_string(),
+                    date_needed: future_date("2030-03-05"),
+                    start_time: timestamp::parse_time("08:00").unwrap(),
+                    end_time: timestamp::parse_time("15:00").unwrap(),
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap();
+
+        let repo = PreferenceRepository::new(&conn);
+        let entry = |substitute_id: &str, organization_id: Option<&str>, teacher_id: Option<&str>, preference, from| {
+            CreateSubstitutePreferenceRequest {
+                substitute_id: substitute_id.to_string(),
+                organization_id: organization_id.map(str::to_string),
+                teacher_id: teacher_id.map(str::to_string),
+                preference,
+                reason: Some("Reason".to_string()),
+                effective_from: Some(future_date(from)),
+                effective_until: None,
+            }
+        };
+
+        assert!(repo
+            .create(entry("sub-1", Some("district"), Some(&teacher.id), PreferenceType::Blocked, "2030-01-01"))
+            .is_err());
+        // Blocked district-wide, preferred by the class's teacher, and a block
+        // that only starts after the request
+        repo.create(entry("sub-1", Some("district"), None, PreferenceType::Blocked, "2030-01-01")).unwrap();
+        repo.create(entry("sub-2", None, Some(&teacher.id), PreferenceType::Preferred, "2030-01-01")).unwrap();
+        repo.create(entry("sub-3", Some("school"), None, PreferenceType::Blocked, "2030-04-01")).unwrap();
+
+        let subs = ["sub-1", "sub-2", "sub-3", "sub-4"].map(str::to_string).to_vec();
+        let tiers = repo.offer_tiers(subs, &request).unwrap();
+        assert_eq!(tiers.preferred, vec!["sub-2"]);
+        assert_eq!(tiers.others, vec!["sub-3", "sub-4"]);
+
+        let error = requests.update_status(&request.id, "filled", Some("sub-1")).unwrap_err();
+        assert!(error.to_string().contains("blocked"));
+        assert!(requests.update_status(&request.id, "filled", Some("sub-3")).unwrap().is_some());
+        assert_eq!(repo.list_for_organization("district").unwrap().len(), 1);
+        assert_eq!(repo.list_for_teacher(&teacher.id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_double_booked_preferred_substitute_is_not_offered() {
+        let conn = test_connection();
+        insert_organization(&conn, "north");
+        insert_organization(&conn, "south");
+        insert_class(&conn, "math", "north");
+        insert_class(&conn, "art", "south");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        insert_user(&conn, "sub-2", "substitute");
+        SettingsRepository::new(&conn).set(TRAVEL_BUFFER_MINUTES, "30", None).unwrap();
+
+        let requests = SubstituteRequestRepository::new(&conn);
+        let create = |class_id: &str, start: &str, end: &str| {
+            requests
+                .create(
+                    "manager-1".to_string(),
+                    CreateSubstituteRequestRequest {
+                        class_id: class_id.to_string(),
+                        date_needed: future_date("2030-03-05"),
+                        start_time: timestamp::parse_time(start).unwrap(),
+                        end_time: timestamp::parse_time(end).unwrap(),
+                        reason: None,
+                        special_instructions: None,
+                    },
+                )
+                .unwrap()
+        };
+        // sub-1 is booked at another school until 10:45, inside the travel
+        // buffer before the 11:00 start
+        let morning = create("art", "08:00", "10:45");
+        requests.update_status(&morning.id, "filled", Some("sub-1")).unwrap();
+        let request = create("math", "11:00", "15:00");
+
+        let repo = PreferenceRepository::new(&conn);
+        for substitute_id in ["sub-1", "sub-2"] {
+            repo.create(CreateSubstitutePreferenceRequest {
+                substitute_id: substitute_id.to_string(),
+                organization_id: Some("north".to_string()),
+                teacher_id: None,
+                preference: PreferenceType::Preferred,
+                reason: None,
+                effective_from: None,
+                effective_until: None,
+            })
+            .unwrap();
+        }
+
+        let tiers = repo.offer_tiers(vec!["sub-1".to_string(), "sub-2".to_string()], &request).unwrap();
+        assert_eq!(tiers.preferred, vec!["sub-2"]);
+        assert!(tiers.others.is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::database::models::{
+    CoverageReport, FillRateReport, ReasonCount, ReportFilter, SubstituteStats, WeekdayCount,
+};
+use crate::database::timestamp;
+use rusqlite::{Connection, Params};
+
+/// Requests needed in the filter's date range at the filter's organization
+/// and those below it, or at every organization when it has none. Takes the
+/// range as ?1 and ?2 and the organization as ?3.
+const SCOPE: &str = "WITH RECURSIVE subtree(id) AS (
+         SELECT id FROM organizations WHERE ?3 IS NULL OR id = ?3
+         UNION
+         SELECT o.id FROM organizations o JOIN subtree s ON o.parent_organization_id = s.id
+     ),
+     scoped AS (
+         SELECT r.* FROM substitute_requests r JOIN classes c ON c.id = r.class_id
+         WHERE c.organization_id IN (SELECT id FROM subtree) AND r.date_needed BETWEEN ?1 AND ?2
+     )";
+
+/// Statuses of requests a substitute was found for.
+const FILLED: &str = "('filled', 'in_progress', 'completed', 'no_show')";
+
+/// Aggregate reports over substitute requests, computed in SQL.
+pub struct ReportRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> ReportRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        ReportRepository { conn }
+    }
+
+    fn scope_params(filter: &ReportFilter) -> anyhow::Result<(String, String, Option<&str>)> {
+        if filter.to < filter.from {
+            anyhow::bail!("Report range ends before it starts");
+        }
+        Ok((
+            timestamp::format_date(&filter.from),
+            timestamp::format_date(&filter.to),
+            filter.organization_id.as_deref(),
+        ))
+    }
+
+    pub fn coverage(&self, filter: ReportFilter) -> anyhow::Result<CoverageReport> {
+        Ok(CoverageReport {
+            fill_rate: self.fill_rate(&filter)?,
+            unfilled_by_reason: self.unfilled_by_reason(&filter)?,
+            by_weekday: self.by_weekday(&filter)?,
+            filter,
+        })
+    }
+
+    pub fn fill_rate(&self, filter: &ReportFilter) -> anyhow::Result<FillRateReport> {
+        let sql = format!(
+            "{SCOPE}
+             SELECT COUNT(*),
+                    COALESCE(SUM(status IN {FILLED}), 0),
+                    COALESCE(SUM(status = 'open'), 0),
+                    COALESCE(SUM(status = 'cancelled'), 0)
+             FROM scoped"
+        );
+        let (total_requests, filled_requests, open_requests, cancelled_requests) =
+            self.conn.query_row(&sql, Self::scope_params(filter)?, |row| {
+                Ok((row.get::<_, u32>(0)?, row.get::<_, u32>(1)?, row.get::<_, u32>(2)?, row.get::<_, u32>(3)?))
+            })?;
+
+        // Median of the wait between a request being made and its assigned
+        // substitute accepting, averaging the middle pair on an even count
+        let sql = format!(
+            "{SCOPE},
+             waits AS (
+                 SELECT (julianday(resp.response_time) - julianday(s.created_at)) * 1440 AS minutes
+                 FROM scoped s
+                 JOIN substitute_responses resp
+                     ON resp.request_id = s.id AND resp.substitute_id = s.assigned_substitute_id
+                 WHERE s.status IN {FILLED} AND resp.response = 'accepted'
+             ),
+             ranked AS (
+                 SELECT minutes, ROW_NUMBER() OVER (ORDER BY minutes) AS n, COUNT(*) OVER () AS total FROM waits
+             )
+             SELECT AVG(minutes) FROM ranked WHERE n IN ((total + 1) / 2, (total + 2) / 2)"
+        );
+        let median_minutes_to_fill = self
+            .conn
+            .query_row(&sql, Self::scope_params(filter)?, |row| row.get::<_, Option<f64>>(0))?
+            .map(|minutes| (minutes * 10.0).round() / 10.0);
+
+        let considered = total_requests - cancelled_requests;
+        Ok(FillRateReport {
+            total_requests,
+            filled_requests,
+            open_requests,
+            cancelled_requests,
+            fill_rate: (considered > 0).then(|| f64::from(filled_requests) / f64::from(considered)),
+            median_minutes_to_fill,
+        })
+    }
+
+    pub fn unfilled_by_reason(&self, filter: &ReportFilter) -> anyhow::Result<Vec<ReasonCount>> {
+        let sql = format!(
+            "{SCOPE}
+             SELECT COALESCE(NULLIF(TRIM(reason), ''), '(no reason)') AS label, COUNT(*) AS count
+             FROM scoped WHERE status = 'open'
+             GROUP BY label ORDER BY count DESC, label"
+        );
+        self.query(&sql, Self::scope_params(filter)?, |row| {
+            Ok(ReasonCount { reason: row.get(0)?, count: row.get(1)? })
+        })
+    }
+
+    /// Requests by the weekday they are needed on, leaving out cancelled ones.
+    /// Weekdays without requests are omitted.
+    pub fn by_weekday(&self, filter: &ReportFilter) -> anyhow::Result<Vec<WeekdayCount>> {
+        let sql = format!(
+            "{SCOPE}
+             SELECT CAST(strftime('%w', date_needed) AS INTEGER) AS weekday, COUNT(*), SUM(status IN {FILLED})
+             FROM scoped WHERE status != 'cancelled'
+             GROUP BY weekday ORDER BY weekday"
+        );
+        self.query(&sql, Self::scope_params(filter)?, |row| {
+            Ok(WeekdayCount { weekday: row.get(0)?, total: row.get(1)?, filled: row.get(2)? })
+        })
+    }
+
+    /// Substitutes who were assigned or responded to requests in scope, most
+    /// assignments first.
+    pub fn substitutes(&self, filter: &ReportFilter, limit: u32) -> anyhow::Result<Vec<SubstituteStats>> {
+        let sql = format!(
+            "{SCOPE},
+             assigned AS (
+                 SELECT assigned_substitute_id AS substitute_id, COUNT(*) AS assignments
+                 FROM scoped WHERE status IN {FILLED} AND assigned_substitute_id IS NOT NULL
+                 GROUP BY assigned_substitute_id
+             ),
+             responded AS (
+                 SELECT resp.substitute_id,
+                        SUM(resp.response = 'accepted') AS accepted,
+                        SUM(resp.response = 'declined') AS declined
+                 FROM substitute_responses resp JOIN scoped s ON s.id = resp.request_id
+                 GROUP BY resp.substitute_id
+             ),
+             stats AS (
+                 SELECT u.id, u.last_name || ', ' || u.first_name AS name,
+                        COALESCE(a.assignments, 0) AS assignments,
+                        COALESCE(r.accepted, 0) AS accepted,
+                        COALESCE(r.declined, 0) AS declined
+                 FROM users u
+                 LEFT JOIN assigned a ON a.substitute_id = u.id
+                 LEFT JOIN responded r ON r.substitute_id = u.id
+                 WHERE a.substitute_id IS NOT NULL OR r.substitute_id IS NOT NULL
+             )
+             SELECT id, name, assignments, accepted, declined,
+                    CASE WHEN accepted + declined > 0 THEN CAST(declined AS REAL) / (accepted + declined) END
+             FROM stats
+             ORDER BY assignments DESC, declined, name
+             LIMIT ?4"
+        );
+        let (from, to, organization_id) = Self::scope_params(filter)?;
+        self.query(&sql, (from, to, organization_id, limit), |row| {
+            Ok(SubstituteStats {
+                substitute_id: row.get(0)?,
+                substitute_name: row.get(1)?,
+                assignments: row.get(2)?,
+                accepted: row.get(3)?,
+                declined: row.get(4)?,
+                decline_rate: row.get(5)?,
+            })
+        })
+    }
+
+    fn query<T, P: Params>(
+        &self,
+        sql: &str,
+        params: P,
+        map: impl FnMut(&rusqlite::Row) -> rusqlite::Result<T>,
+    ) -> anyhow::Result<Vec<T>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map(params, map)?.collect::<rusqlite::Result<Vec<T>>>()?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::fixtures::{insert_class, insert_organization, insert_user, test_connection};
+
+    fn filter(organization_id: Option<&str>) -> ReportFilter {
+        ReportFilter {
+            from: timestamp::parse_date("2030-01-01").unwrap(),
+            to: timestamp::parse_date("2030-01-31").unwrap(),
+            organization_id: organization_id.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_coverage_and_substitute_reports() {
+        let conn = test_connection();
+        insert_organization(&conn, "district");
+        insert_organization(&conn, "north");
+        insert_organization(&conn, "south");
+        conn.execute("UPDATE organizations SET parent_organization_id = 'district' WHERE id = 'north'", [])
+            .unwrap();
+        insert_class(&conn, "north-math", "north");
+        insert_class(&conn, "south-math", "south");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        insert_user(&conn, "sub-2", "substitute");
+        // 2030-01-14 is a Monday, 2030-01-15 a Tuesday
+        conn.execute_batch(
+            "INSERT INTO substitute_requests
+                 (id, class_id, requested_by, date_needed, start_time, end_time, reason, status,
+                  assigned_substitute_id, created_at)
+             VALUES
+                 ('r1', 'north-math', 'manager-1', '2030-01-14', '08:00', '15:00', 'Sick', 'filled',
+                  'sub-1', '2030-01-10T08:00:00.000Z'),
+                 ('r2', 'north-math', 'manager-1', '2030-01-14', '08:00', '15:00', 'Sick', 'completed',
+                  'sub-1', '2030-01-10T08:00:00.000Z'),
+                 ('r3', 'north-math', 'manager-1', '2030-01-15', '08:00', '15:00', ' ', 'open',
+                  NULL, '2030-01-10T08:00:00.000Z'),
+                 ('r4', 'north-math', 'manager-1', '2030-01-15', '08:00', '15:00', 'Sick', 'cancelled',
+                  NULL, '2030-01-10T08:00:00.000Z'),
+                 ('r5', 'south-math', 'manager-1', '2030-01-15', '08:00', '15:00', 'Training', 'open',
+                  NULL, '2030-01-10T08:00:00.000Z'),
+                 ('r6', 'north-math', 'manager-1', '2030-02-04', '08:00', '15:00', 'Sick', 'open',
+                  NULL, '2030-01-10T08:00:00.000Z');
+             INSERT INTO substitute_responses (id, request_id, substitute_id, response, response_time) VALUES
+                 ('a1', 'r1', 'sub-1', 'accepted', '2030-01-10T08:30:00.000Z'),
+                 ('a2', 'r2', 'sub-1', 'accepted', '2030-01-10T09:30:00.000Z'),
+                 ('d1', 'r1', 'sub-2', 'declined', '2030-01-10T08:10:00.000Z'),
+                 ('d2', 'r3', 'sub-2', 'declined', '2030-01-10T08:20:00.000Z');",
+        )
+        .unwrap();
+        let reports = ReportRepository::new(&conn);
+
+        let report = reports.coverage(filter(Some("district"))).unwrap();
+        let fill = &report.fill_rate;
+        assert_eq!(
+            (fill.total_requests, fill.filled_requests, fill.open_requests, fill.cancelled_requests),
+            (4, 2, 1, 1)
+        );
+        assert!((fill.fill_rate.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(fill.median_minutes_to_fill, Some(60.0));
+        assert_eq!(report.unfilled_by_reason.len(), 1);
+        assert_eq!(report.unfilled_by_reason[0].reason, "(no reason)");
+        let weekdays: Vec<_> = report.by_weekday.iter().map(|w| (w.weekday, w.total, w.filled)).collect();
+        assert_eq!(weekdays, vec![(1, 2, 2), (2, 1, 0)]);
+
+        let everywhere = reports.coverage(filter(None)).unwrap();
+        assert_eq!(everywhere.fill_rate.total_requests, 5);
+        let reasons: Vec<_> = everywhere.unfilled_by_reason.iter().map(|r| r.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["(no reason)", "Training"]);
+
+        let substitutes = reports.substitutes(&filter(Some("north")), 10).unwrap();
+        let stats: Vec<_> = substitutes
+            .iter()
+            .map(|s| (s.substitute_id.as_str(), s.assignments, s.accepted, s.declined, s.decline_rate))
+            .collect();
+        assert_eq!(stats, vec![("sub-1", 2, 2, 0, Some(0.0)), ("sub-2", 0, 0, 2, Some(1.0))]);
+        assert_eq!(substitutes[0].substitute_name, "sub-1, Test");
+        assert_eq!(reports.substitutes(&filter(Some("north")), 1).unwrap().len(), 1);
+
+        let mut backwards = filter(None);
+        backwards.to = timestamp::parse_date("2029-12-01").unwrap();
+        assert!(reports.substitutes(&backwards, 10).is_err());
+        assert!(reports.coverage(backwards).is_err());
+    }
+}
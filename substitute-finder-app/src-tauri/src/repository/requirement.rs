@@ -0,0 +1,362 @@
+use crate::database::models::{
+    ClassRequirement, CreateClassRequirementRequest, CredentialType, RequirementOverride, RequirementOverrideRequest,
+    RequestStatus, SubstituteRequest, VocabularyKind,
+};
+use crate::database::timestamp;
+use crate::repository::{
+    json_column, query_all, query_optional, timestamp_column, CredentialRepository, FromRow,
+    SubstituteRequestRepository, VocabularyRepository,
+};
+use chrono::Utc;
+use rusqlite::types::Type;
+use rusqlite::{Connection, OptionalExtension, Row};
+use uuid::Uuid;
+
+impl FromRow for ClassRequirement {
+    const COLUMNS: &'static str = "id, class_id, credential_type, credential_description, subject, created_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let credential_type = match row.get::<_, Option<String>>(2)? {
+            Some(value) => Some(
+                value
+                    .parse::<CredentialType>()
+                    .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, Type::Text, e.into()))?,
+            ),
+            None => None,
+        };
+        Ok(ClassRequirement {
+            id: row.get(0)?,
+            class_id: row.get(1)?,
+            credential_type,
+            credential_description: row.get(3)?,
+            subject: row.get(4)?,
+            created_at: timestamp_column(row, 5)?,
+        })
+    }
+}
+
+impl FromRow for RequirementOverride {
+    const COLUMNS: &'static str =
+        "id, request_id, substitute_id, approved_by, justification, unmet_requirements, created_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(RequirementOverride {
+            id: row.get(0)?,
+            request_id: row.get(1)?,
+            substitute_id: row.get(2)?,
+            approved_by: row.get(3)?,
+            justification: row.get(4)?,
+            unmet_requirements: json_column(row, 5)?,
+            created_at: timestamp_column(row, 6)?,
+        })
+    }
+}
+
+/// Per-class requirements and the admin overrides that waive them.
+pub struct RequirementRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> RequirementRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        RequirementRepository { conn }
+    }
+
+    pub fn create(&self, request: CreateClassRequirementRequest) -> anyhow::Result<ClassRequirement> {
+        let credential_description = request
+            .credential_description
+            .map(|description| description.trim().to_string())
+            .filter(|description| !description.is_empty());
+        match (&request.credential_type, &request.subject) {
+            (Some(_), None) => {}
+            (None, Some(subject)) => {
+                if credential_description.is_some() {
+                    anyhow::bail!("A subject requirement can't have a credential description");
+                }
+                VocabularyRepository::new(self.conn).ensure_allowed(VocabularyKind::Subject, Some(subject), None)?;
+            }
+            _ => anyhow::bail!("Give either a credential type or a subject"),
+        }
+
+        let requirement = ClassRequirement {
+            id: Uuid::new_v4().to_string(),
+            class_id: request.class_id,
+            credential_type: request.credential_type,
+            credential_description,
+            subject: request.subject,
+            created_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO class_requirements (id, class_id, credential_type, credential_description, subject, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            (
+                &requirement.id,
+                &requirement.class_id,
+                &requirement.credential_type.map(|credential_type| credential_type.to_string()),
+                &requirement.credential_description,
+                &requirement.subject,
+                &timestamp::format(&requirement.created_at),
+            ),
+        )?;
+
+        Ok(requirement)
+    }
+
+    pub fn list_for_class(&self, class_id: &str) -> anyhow::Result<Vec<ClassRequirement>> {
+        let sql = format!(
+            "SELECT {} FROM class_requirements WHERE class_id = ?1 ORDER BY created_at",
+            ClassRequirement::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [class_id])?)
+    }
+
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM class_requirements WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// The class's requirements the substitute doesn't meet on the day of
+    /// `request`, described for display.
+    pub fn unmet(&self, substitute_id: &str, request: &SubstituteRequest) -> anyhow::Result<Vec<String>> {
+        let requirements = self.list_for_class(&request.class_id)?;
+        if requirements.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let credentials = CredentialRepository::new(self.conn).list_for_substitute(substitute_id)?;
+        let subjects = self.subjects(substitute_id)?;
+        let mut unmet = Vec::new();
+        for requirement in requirements {
+            let met = match (&requirement.credential_type, &requirement.subject) {
+                (Some(credential_type), _) => credentials.iter().any(|credential| {
+                    credential.credential_type == *credential_type
+                        && credential.is_valid_on(request.date_needed)
+                        && requirement.credential_description.as_ref().is_none_or(|wanted| {
+                            credential
+                                .description
+                                .as_ref()
+                                .is_some_and(|description| description.eq_ignore_ascii_case(wanted))
+                        })
+                }),
+                (None, Some(subject)) => subjects.iter().any(|held| held.eq_ignore_ascii_case(subject)),
+                (None, None) => true,
+            };
+            if !met {
+                unmet.push(requirement.describe());
+            }
+        }
+        Ok(unmet)
+    }
+
+    /// Fails when the substitute misses a requirement of the class and no
+    /// admin has overridden it for this request.
+    pub fn ensure_met(&self, substitute_id: &str, request: &SubstituteRequest) -> anyhow::Result<()> {
+        let outstanding = self.outstanding(substitute_id, request)?;
+        if !outstanding.is_empty() {
+            anyhow::bail!("Substitute doesn't meet this class's requirements: {}", outstanding.join("; "));
+        }
+        Ok(())
+    }
+
+    /// The substitutes from `substitute_ids` who meet every requirement of the
+    /// class, or have been let off them for `request`.
+    pub fn qualified_for(&self, substitute_ids: Vec<String>, request: &SubstituteRequest) -> anyhow::Result<Vec<String>> {
+        let mut qualified = Vec::new();
+        for substitute_id in substitute_ids {
+            if self.outstanding(&substitute_id, request)?.is_empty() {
+                qualified.push(substitute_id);
+            }
+        }
+        Ok(qualified)
+    }
+
+    /// [`Self::unmet`], except empty when an override covers the substitute.
+    fn outstanding(&self, substitute_id: &str, request: &SubstituteRequest) -> anyhow::Result<Vec<String>> {
+        let unmet = self.unmet(substitute_id, request)?;
+        if !unmet.is_empty() && self.find_override(&request.id, substitute_id)?.is_some() {
+            return Ok(Vec::new());
+        }
+        Ok(unmet)
+    }
+
+    pub fn find_override(&self, request_id: &str, substitute_id: &str) -> anyhow::Result<Option<RequirementOverride>> {
+        let sql = format!(
+            "SELECT {} FROM requirement_overrides WHERE request_id = ?1 AND substitute_id = ?2",
+            RequirementOverride::COLUMNS
+        );
+        Ok(query_optional(self.conn, &sql, [request_id, substitute_id])?)
+    }
+
+    pub fn list_overrides_for_request(&self, request_id: &str) -> anyhow::Result<Vec<RequirementOverride>> {
+        let sql = format!(
+            "SELECT {} FROM requirement_overrides WHERE request_id = ?1 ORDER BY created_at",
+            RequirementOverride::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [request_id])?)
+    }
+
+    /// Records the justification for assigning a substitute who misses the
+    /// class's requirements and fills the request with them. Every other
+    /// check on the assignment still applies. The caller makes sure
+    /// `admin_id` is an admin.
+    pub fn override_and_assign(
+        &self,
+        request: RequirementOverrideRequest,
+    ) -> anyhow::Result<(RequirementOverride, SubstituteRequest)> {
+        if request.justification.trim().is_empty() {
+            anyhow::bail!("A justification is required to override class requirements");
+        }
+        let requests = SubstituteRequestRepository::new(self.conn);
+        let substitute_request = requests
+            .find_by_id(&request.request_id)?
+            .ok_or_else(|| anyhow::anyhow!("Substitute request not found"))?;
+        if !matches!(substitute_request.status, RequestStatus::Open) {
+            anyhow::bail!("Only open requests can be assigned");
+        }
+        let unmet = self.unmet(&request.substitute_id, &substitute_request)?;
+        if unmet.is_empty() {
+            anyhow::bail!("Substitute already meets this class's requirements");
+        }
+
+        let record = RequirementOverride {
+            id: Uuid::new_v4().to_string(),
+            request_id: request.request_id,
+            substitute_id: request.substitute_id,
+            approved_by: request.admin_id,
+            justification: request.justification.trim().to_string(),
+            unmet_requirements: unmet,
+            created_at: Utc::now(),
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO requirement_overrides (id, request_id, substitute_id, approved_by, justification, unmet_requirements, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (
+                &record.id,
+                &record.request_id,
+                &record.substitute_id,
+                &record.approved_by,
+                &record.justification,
+                &serde_json::to_string(&record.unmet_requirements)?,
+                &timestamp::format(&record.created_at),
+            ),
+        )?;
+        let assigned = requests
+            .update_status(&record.request_id, &RequestStatus::Filled.to_string(), Some(&record.substitute_id))?
+            .ok_or_else(|| anyhow::anyhow!("Substitute request not found"))?;
+        tx.commit()?;
+
+        Ok((record, assigned))
+    }
+
+    /// Subjects on the substitute's teacher profile, if they have one.
+    fn subjects(&self, substitute_id: &str) -> anyhow::Result<Vec<String>> {
+        let subjects: Option<Option<String>> = self
+            .conn
+            .query_row("SELECT subjects FROM teachers WHERE user_id = ?1", [substitute_id], |row| row.get(0))
+            .optional()?;
+        Ok(match subjects.flatten() {
+            Some(json) => serde_json::from_str(&json).unwrap_or_default(),
+            None => Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::{CreateCredentialRequest, CreateSubstituteRequestRequest, VerificationStatus};
+    use crate::repository::fixtures::{future_date, insert_class, insert_organization, insert_user, test_connection};
+
+    fn endorsement(substitute_id: &str, description: &str) -> CreateCredentialRequest {
+        CreateCredentialRequest {
+            substitute_id: substitute_id.to_string(),
+            credential_type: CredentialType::SubjectEndorsement,
+            description: Some(description.to_string()),
+            issued_on: future_date("2028-01-01"),
+            expires_on: None,
+        }
+    }
+
+    #[test]
+    fn test_requirements_gate_assignment_unless_overridden() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "sped", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "admin-1", "admin");
+        for substitute_id in ["sub-1", "sub-2", "sub-3"] {
+            insert_user(&conn, substitute_id, "substitute");
+        }
+        conn.execute(
+            "INSERT INTO teachers (id, user_id, subjects) VALUES ('t1', 'sub-1', '[\"special education\"]'),
+                 ('t2', 'sub-2', '[\"Mathematics\"]')",
+            [],
+        )
+        .unwrap();
+        let credentials = CredentialRepository::new(&conn);
+        for (substitute_id, description) in [("sub-1", "Special Education"), ("sub-2", "Mathematics")] {
+            let credential = credentials.create(endorsement(substitute_id, description)).unwrap();
+            credentials
+                .set_verification(&credential.id, VerificationStatus::Verified, "manager-1")
+                .unwrap();
+        }
+
+        let repo = RequirementRepository::new(&conn);
+        let requirement = |credential_type: Option<CredentialType>, description: Option<&str>, subject: Option<&str>| {
+            CreateClassRequirementRequest {
+                class_id: "sped".to_string(),
+                credential_type,
+                credential_description: description.map(str::to_string),
+                subject: subject.map(str::to_string),
+            }
+        };
+        assert!(repo.create(requirement(None, None, None)).is_err());
+        assert!(repo.create(requirement(None, None, Some("Underwater Basket Weaving"))).is_err());
+        repo.create(requirement(Some(CredentialType::SubjectEndorsement), Some("special education"), None))
+            .unwrap();
+        repo.create(requirement(None, None, Some("Special Education"))).unwrap();
+
+        let requests = SubstituteRequestRepository::new(&conn);
+        let request = requests
+            .create(
+                "manager-1".to_string(),
+                CreateSubstituteRequestRequest {
+                    class_id: "sped".to_string(),
+                    date_needed: future_date("2030-01-15"),
+                    start_time: timestamp::parse_time("08:00").unwrap(),
+                    end_time: timestamp::parse_time("15:00").unwrap(),
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap();
+
+        assert!(repo.unmet("sub-1", &request).unwrap().is_empty());
+        assert_eq!(
+            repo.unmet("sub-2", &request).unwrap(),
+            vec!["subject endorsement (special education)", "Special Education as a subject"]
+        );
+        let subs = vec!["sub-1".to_string(), "sub-2".to_string(), "sub-3".to_string()];
+        assert_eq!(repo.qualified_for(subs, &request).unwrap(), vec!["sub-1"]);
+
+        let error = requests.update_status(&request.id, "filled", Some("sub-2")).unwrap_err();
+        assert!(error.to_string().contains("requirements"));
+
+        let override_request = |justification: &str| RequirementOverrideRequest {
+            request_id: request.id.clone(),
+            substitute_id: "sub-2".to_string(),
+            admin_id: "admin-1".to_string(),
+            justification: justification.to_string(),
+        };
+        assert!(repo.override_and_assign(override_request("  ")).is_err());
+        let (record, assigned) = repo
+            .override_and_assign(override_request("Only certified sub out sick; aide in the room"))
+            .unwrap();
+        assert_eq!(record.unmet_requirements.len(), 2);
+        assert_eq!(assigned.assigned_substitute_id.as_deref(), Some("sub-2"));
+        assert_eq!(repo.list_overrides_for_request(&request.id).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::database::models::{ResponseType, SubstituteResponse};
+use crate::database::timestamp;
+use crate::repository::{enum_column, query_all, query_optional, timestamp_column, FromRow};
+use chrono::Utc;
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for SubstituteResponse {
+    const COLUMNS: &'static str = "id, request_id, substitute_id, response, response_time, notes";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(SubstituteResponse {
+            id: row.get(0)?,
+            request_id: row.get(1)?,
+            substitute_id: row.get(2)?,
+            response: enum_column(row, 3)?,
+            response_time: timestamp_column(row, 4)?,
+            notes: row.get(5)?,
+        })
+    }
+}
+
+/// Substitutes' answers to requests, kept for response-time and decline-rate
+/// reporting.
+pub struct ResponseRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> ResponseRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        ResponseRepository { conn }
+    }
+
+    /// Records the substitute's latest answer to the request, replacing any
+    /// earlier one.
+    pub fn record(
+        &self,
+        request_id: &str,
+        substitute_id: &str,
+        response: ResponseType,
+        notes: Option<&str>,
+    ) -> anyhow::Result<SubstituteResponse> {
+        self.conn.execute(
+            "INSERT INTO substitute_responses (id, request_id, substitute_id, response, response_time, notes)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT(request_id, substitute_id) DO UPDATE SET response = excluded.response,
+                 response_time = excluded.response_time, notes = excluded.notes",
+            (
+                &Uuid::new_v4().to_string(),
+                request_id,
+                substitute_id,
+                &response.to_string(),
+                &timestamp::format(&Utc::now()),
+                notes,
+            ),
+        )?;
+
+        let sql = format!(
+            "SELECT {} FROM substitute_responses WHERE request_id = ?1 AND substitute_id = ?2",
+            SubstituteResponse::COLUMNS
+        );
+        query_optional(self.conn, &sql, [request_id, substitute_id])?
+            .ok_or_else(|| anyhow::anyhow!("Response was not saved"))
+    }
+
+    pub fn list_for_request(&self, request_id: &str) -> anyhow::Result<Vec<SubstituteResponse>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_responses WHERE request_id = ?1 ORDER BY response_time",
+            SubstituteResponse::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [request_id])?)
+    }
+}
@@ -0,0 +1,56 @@
+use crate::database::models::RosterEntity;
+use crate::database::timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+/// Links between OneRoster sourcedIds and the records imported from them.
+pub struct RosterRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> RosterRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        RosterRepository { conn }
+    }
+
+    /// Id of the record imported from `sourced_id`.
+    pub fn find_link(&self, entity: RosterEntity, sourced_id: &str) -> anyhow::Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT local_id FROM roster_links WHERE entity = ?1 AND sourced_id = ?2",
+                (&entity.to_string(), sourced_id),
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// The sourcedId `local_id` was imported from, if any.
+    pub fn find_sourced_id(&self, entity: RosterEntity, local_id: &str) -> anyhow::Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT sourced_id FROM roster_links WHERE entity = ?1 AND local_id = ?2",
+                (&entity.to_string(), local_id),
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    pub fn save_link(&self, entity: RosterEntity, sourced_id: &str, local_id: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT INTO roster_links (entity, sourced_id, local_id, updated_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(entity, sourced_id) DO UPDATE SET local_id = excluded.local_id,
+                 updated_at = excluded.updated_at",
+            (&entity.to_string(), sourced_id, local_id, &timestamp::now()),
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_link(&self, entity: RosterEntity, sourced_id: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            "DELETE FROM roster_links WHERE entity = ?1 AND sourced_id = ?2",
+            (&entity.to_string(), sourced_id),
+        )?;
+        Ok(())
+    }
+}
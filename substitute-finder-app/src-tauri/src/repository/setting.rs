@@ -0,0 +1,102 @@
+use crate::database::models::Setting;
+use crate::database::timestamp;
+use crate::repository::{query_all, query_optional, timestamp_column, FromRow};
+use rusqlite::{Connection, Row};
+
+/// Minutes a substitute needs between assignments at different organizations.
+pub const TRAVEL_BUFFER_MINUTES: &str = "travel_buffer_minutes";
+
+/// Minutes preferred substitutes have a new request to themselves.
+pub const PREFERRED_OFFER_MINUTES: &str = "preferred_offer_minutes";
+
+/// Minutes after the start time before an assignment nobody checked in to is
+/// a no-show, and after the end time before a missing check-out is assumed.
+pub const NO_SHOW_GRACE_MINUTES: &str = "no_show_grace_minutes";
+
+/// Minutes a check-in or check-out can be off schedule before it's flagged.
+pub const CHECK_IN_TOLERANCE_MINUTES: &str = "check_in_tolerance_minutes";
+
+/// Comma-separated column keys of the payroll CSV export.
+pub const PAYROLL_CSV_COLUMNS: &str = "payroll_csv_columns";
+
+/// Hourly rate assumed for requests nobody has accepted yet. Blank when
+/// they shouldn't be projected.
+pub const ESTIMATED_HOURLY_RATE: &str = "estimated_hourly_rate";
+
+/// Comma-separated credential types every substitute must hold to be
+/// offered or accept a request.
+pub const REQUIRED_CREDENTIALS: &str = "required_credentials";
+
+/// Local port the calendar subscription feed listens on. Blank when the
+/// feed is off; changes take effect on the next start.
+pub const CALENDAR_FEED_PORT: &str = "calendar_feed_port";
+
+/// Secret every calendar feed URL carries. Changing it revokes existing
+/// subscriptions.
+pub const CALENDAR_FEED_TOKEN: &str = "calendar_feed_token";
+
+/// Automatic daily backups to keep; 0 turns them off.
+pub const BACKUP_DAILY_KEEP: &str = "backup_daily_keep";
+
+/// Automatic weekly backups to keep; 0 turns them off.
+pub const BACKUP_WEEKLY_KEEP: &str = "backup_weekly_keep";
+
+impl FromRow for Setting {
+    const COLUMNS: &'static str = "key, value, description, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Setting {
+            key: row.get(0)?,
+            value: row.get(1)?,
+            description: row.get(2)?,
+            updated_at: timestamp_column(row, 3)?,
+        })
+    }
+}
+
+pub struct SettingsRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> SettingsRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        SettingsRepository { conn }
+    }
+
+    pub fn list(&self) -> anyhow::Result<Vec<Setting>> {
+        let sql = format!("SELECT {} FROM settings ORDER BY key", Setting::COLUMNS);
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    pub fn get(&self, key: &str) -> anyhow::Result<Option<Setting>> {
+        let sql = format!("SELECT {} FROM settings WHERE key = ?1", Setting::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [key])?)
+    }
+
+    /// Inserts or replaces the setting. A `None` description keeps the existing one.
+    pub fn set(&self, key: &str, value: &str, description: Option<&str>) -> anyhow::Result<Setting> {
+        self.conn.execute(
+            "INSERT INTO settings (key, value, description, updated_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value,
+                 description = COALESCE(excluded.description, settings.description),
+                 updated_at = excluded.updated_at",
+            (key, value, description, &timestamp::now()),
+        )?;
+
+        self.get(key)?
+            .ok_or_else(|| anyhow::anyhow!("Setting not found after update"))
+    }
+
+    /// Integer setting, or `default` when unset. A value that isn't an integer
+    /// is an error rather than silently ignored.
+    pub fn get_i64(&self, key: &str, default: i64) -> anyhow::Result<i64> {
+        match self.get(key)? {
+            Some(setting) => setting
+                .value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Setting {} must be a whole number, got {:?}", key, setting.value)),
+            None => Ok(default),
+        }
+    }
+}
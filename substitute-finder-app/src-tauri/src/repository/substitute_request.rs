@@ -0,0 +1,685 @@
+use crate::database::models::{
+    CreatePeriodSubstituteRequest, CreateSubstituteRequestRequest, RequestStatus, ResponseType, SubstituteRequest,
+};
+use crate::database::timestamp;
+use crate::repository::{
+    date_column, enum_column, query_all, query_optional, time_column, timestamp_column, BellScheduleRepository,
+    CalendarRepository, ClassRepository, CredentialRepository, FromRow, OrganizationRepository, PreferenceRepository,
+    RequirementRepository, ResponseRepository, SettingsRepository, UnavailabilityRepository,
+};
+use crate::repository::setting::TRAVEL_BUFFER_MINUTES;
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for SubstituteRequest {
+    const COLUMNS: &'static str =
+        "id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, absence_id, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(SubstituteRequest {
+            id: row.get(0)?,
+            class_id: row.get(1)?,
+            requested_by: row.get(2)?,
+            date_needed: date_column(row, 3)?,
+            start_time: time_column(row, 4)?,
+            end_time: time_column(row, 5)?,
+            reason: row.get(6)?,
+            special_instructions: row.get(7)?,
+            status: enum_column(row, 8)?,
+            assigned_substitute_id: row.get(9)?,
+            absence_id: row.get(10)?,
+            created_at: timestamp_column(row, 11)?,
+            updated_at: timestamp_column(row, 12)?,
+        })
+    }
+}
+
+/// Returned when an assignment would double-book a substitute. Lists every
+/// request it clashes with.
+#[derive(Debug)]
+pub struct AssignmentConflict {
+    pub conflicts: Vec<SubstituteRequest>,
+}
+
+impl std::fmt::Display for AssignmentConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let clashes: Vec<String> = self
+            .conflicts
+            .iter()
+            .map(|request| {
+                format!(
+                    "{} on {} {}-{}",
+                    request.id,
+                    timestamp::format_date(&request.date_needed),
+                    timestamp::format_time(&request.start_time),
+                    timestamp::format_time(&request.end_time)
+                )
+            })
+            .collect();
+        write!(f, "Substitute is already assigned to overlapping request {}", clashes.join(", "))
+    }
+}
+
+impl std::error::Error for AssignmentConflict {}
+
+pub struct SubstituteRequestRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> SubstituteRequestRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        SubstituteRequestRepository { conn }
+    }
+
+    pub fn create(
+        &self,
+        requested_by: String,
+        request: CreateSubstituteRequestRequest,
+    ) -> anyhow::Result<SubstituteRequest> {
+        self.validate_schedule(
+            &request.class_id,
+            request.date_needed,
+            request.start_time,
+            request.end_time,
+            Local::now().naive_local(),
+        )?;
+
+        self.insert(requested_by, request, None)
+    }
+
+    /// Creates a request covering class periods, with times taken from the
+    /// bell schedule active on the requested date.
+    pub fn create_for_periods(
+        &self,
+        requested_by: String,
+        request: CreatePeriodSubstituteRequest,
+    ) -> anyhow::Result<SubstituteRequest> {
+        let (start_time, end_time) = BellScheduleRepository::new(self.conn).period_window(
+            &request.class_id,
+            request.date_needed,
+            &request.period_names,
+        )?;
+
+        self.create(
+            requested_by,
+            CreateSubstituteRequestRequest {
+                class_id: request.class_id,
+                date_needed: request.date_needed,
+                start_time,
+                end_time,
+                reason: request.reason,
+                special_instructions: request.special_instructions,
+            },
+        )
+    }
+
+    /// Inserts an already validated request, optionally as a child of an absence.
+    pub fn insert(
+        &self,
+        requested_by: String,
+        request: CreateSubstituteRequestRequest,
+        absence_id: Option<&str>,
+    ) -> anyhow::Result<SubstituteRequest> {
+        let substitute_request = SubstituteRequest {
+            id: Uuid::new_v4().to_string(),
+            class_id: request.class_id,
+            requested_by,
+            date_needed: request.date_needed,
+            start_time: request.start_time,
+            end_time: request.end_time,
+            reason: request.reason,
+            special_instructions: request.special_instructions,
+            status: RequestStatus::Open,
+            assigned_substitute_id: None,
+            absence_id: absence_id.map(str::to_string),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, reason, special_instructions, status, assigned_substitute_id, absence_id, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            (
+                &substitute_request.id,
+                &substitute_request.class_id,
+                &substitute_request.requested_by,
+                &timestamp::format_date(&substitute_request.date_needed),
+                &timestamp::format_time(&substitute_request.start_time),
+                &timestamp::format_time(&substitute_request.end_time),
+                &substitute_request.reason,
+                &substitute_request.special_instructions,
+                &substitute_request.status.to_string(),
+                &substitute_request.assigned_substitute_id,
+                &substitute_request.absence_id,
+                &timestamp::format(&substitute_request.created_at),
+                &timestamp::format(&substitute_request.updated_at),
+            ),
+        )?;
+
+        Ok(substitute_request)
+    }
+
+    /// Rejects a schedule that ends before it starts, has already finished as
+    /// of `now`, falls on a day the class's organization is closed, or falls
+    /// outside its school hours.
+    pub fn validate_schedule(
+        &self,
+        class_id: &str,
+        date_needed: NaiveDate,
+        start_time: NaiveTime,
+        end_time: NaiveTime,
+        now: NaiveDateTime,
+    ) -> anyhow::Result<()> {
+        if end_time <= start_time {
+            anyhow::bail!("End time must be after start time");
+        }
+
+        if date_needed.and_time(end_time) <= now {
+            anyhow::bail!("Cannot request a substitute for a date or time in the past");
+        }
+
+        let class = ClassRepository::new(self.conn)
+            .find_by_id(class_id)?
+            .ok_or_else(|| anyhow::anyhow!("Class not found"))?;
+
+        if let Some(closure) = CalendarRepository::new(self.conn).closure_on(&class.organization_id, date_needed)? {
+            anyhow::bail!(
+                "School is closed on {} ({})",
+                timestamp::format_date(&date_needed),
+                closure.name
+            );
+        }
+
+        if let Some((opens, closes)) =
+            OrganizationRepository::new(self.conn).effective_school_hours(&class.organization_id)?
+        {
+            if start_time < opens || end_time > closes {
+                anyhow::bail!(
+                    "Requested time {}-{} is outside school hours {}-{}",
+                    timestamp::format_time(&start_time),
+                    timestamp::format_time(&end_time),
+                    timestamp::format_time(&opens),
+                    timestamp::format_time(&closes)
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn list(&self) -> anyhow::Result<Vec<SubstituteRequest>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_requests ORDER BY date_needed, start_time",
+            SubstituteRequest::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    pub fn list_by_status(&self, status: &str) -> anyhow::Result<Vec<SubstituteRequest>> {
+        let status = status.parse::<RequestStatus>()?.to_string();
+        let sql = format!(
+            "SELECT {} FROM substitute_requests WHERE status = ?1 ORDER BY date_needed, start_time",
+            SubstituteRequest::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [&status])?)
+    }
+
+    /// Requests visible to a user: substitutes see open requests and their own
+    /// assignments, org managers see what they requested, admins see everything.
+    pub fn list_for_user(&self, user_id: &str, user_role: &str) -> anyhow::Result<Vec<SubstituteRequest>> {
+        match user_role {
+            "substitute" => {
+                let sql = format!(
+                    "SELECT {} FROM substitute_requests
+                     WHERE status = 'open' OR assigned_substitute_id = ?1
+                     ORDER BY date_needed, start_time",
+                    SubstituteRequest::COLUMNS
+                );
+                Ok(query_all(self.conn, &sql, [user_id])?)
+            }
+            "org_manager" => {
+                let sql = format!(
+                    "SELECT {} FROM substitute_requests
+                     WHERE requested_by = ?1
+                     ORDER BY date_needed, start_time",
+                    SubstituteRequest::COLUMNS
+                );
+                Ok(query_all(self.conn, &sql, [user_id])?)
+            }
+            _ => self.list(),
+        }
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<SubstituteRequest>> {
+        let sql = format!("SELECT {} FROM substitute_requests WHERE id = ?1", SubstituteRequest::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    /// Fetches the current state of each request, skipping any since deleted.
+    pub fn reload(&self, requests: &[SubstituteRequest]) -> anyhow::Result<Vec<SubstituteRequest>> {
+        requests
+            .iter()
+            .filter_map(|request| self.find_by_id(&request.id).transpose())
+            .collect()
+    }
+
+    /// Requests the substitute is booked on, filled or under way, that
+    /// overlap `request`.
+    /// Requests at a different organization also clash when they leave less
+    /// than the configured travel buffer between them. Classes of the same
+    /// teacher absence never clash, since one substitute covers that whole day.
+    pub fn find_conflicts(
+        &self,
+        substitute_id: &str,
+        request: &SubstituteRequest,
+    ) -> anyhow::Result<Vec<SubstituteRequest>> {
+        let teacher_absence_id: Option<String> = match &request.absence_id {
+            Some(absence_id) => self.conn.query_row(
+                "SELECT teacher_absence_id FROM absences WHERE id = ?1",
+                [absence_id],
+                |row| row.get(0),
+            )?,
+            None => None,
+        };
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE assigned_substitute_id = ?1 AND status IN ('filled', 'in_progress', 'completed') AND date_needed = ?2 AND id != ?3
+               AND NOT (?4 IS NOT NULL AND absence_id IN (SELECT id FROM absences WHERE teacher_absence_id = ?4))
+             ORDER BY start_time",
+            SubstituteRequest::COLUMNS
+        );
+        let candidates: Vec<SubstituteRequest> = query_all(
+            self.conn,
+            &sql,
+            rusqlite::params![
+                substitute_id,
+                timestamp::format_date(&request.date_needed),
+                request.id,
+                teacher_absence_id
+            ],
+        )?;
+        if candidates.is_empty() {
+            return Ok(candidates);
+        }
+
+        let classes = ClassRepository::new(self.conn);
+        let organization_of = |class_id: &str| -> anyhow::Result<Option<String>> {
+            Ok(classes.find_by_id(class_id)?.map(|class| class.organization_id))
+        };
+        let organization = organization_of(&request.class_id)?;
+        let buffer = Duration::minutes(SettingsRepository::new(self.conn).get_i64(TRAVEL_BUFFER_MINUTES, 0)?);
+        let start = request.date_needed.and_time(request.start_time);
+        let end = request.date_needed.and_time(request.end_time);
+
+        let mut conflicts = Vec::new();
+        for other in candidates {
+            let gap = if organization_of(&other.class_id)? == organization {
+                Duration::zero()
+            } else {
+                buffer
+            };
+            let other_start = other.date_needed.and_time(other.start_time);
+            let other_end = other.date_needed.and_time(other.end_time);
+            if start < other_end + gap && other_start < end + gap {
+                conflicts.push(other);
+            }
+        }
+
+        Ok(conflicts)
+    }
+
+    /// Fails when the substitute lacks a required credential, misses one of
+    /// the class's requirements without an override, is on a do-not-send list
+    /// for `request` or has blocked out any of it, and with
+    /// [`AssignmentConflict`] when they're already booked over it.
+    pub fn ensure_available(&self, substitute_id: &str, request: &SubstituteRequest) -> anyhow::Result<()> {
+        let lapsed = CredentialRepository::new(self.conn).lapsed(substitute_id, request.date_needed)?;
+        if !lapsed.is_empty() {
+            anyhow::bail!("Substitute is missing a required credential: {}", lapsed.join("; "));
+        }
+        RequirementRepository::new(self.conn).ensure_met(substitute_id, request)?;
+        if let Some(entry) = PreferenceRepository::new(self.conn).blocking(substitute_id, request)? {
+            match &entry.reason {
+                Some(reason) => anyhow::bail!("Substitute is blocked for this class ({})", reason),
+                None => anyhow::bail!("Substitute is blocked for this class"),
+            }
+        }
+        if let Some(entry) = UnavailabilityRepository::new(self.conn).blocking(substitute_id, request)?.first() {
+            match &entry.reason {
+                Some(reason) => anyhow::bail!(
+                    "Substitute is unavailable on {} ({})",
+                    timestamp::format_date(&request.date_needed),
+                    reason
+                ),
+                None => anyhow::bail!(
+                    "Substitute is unavailable on {}",
+                    timestamp::format_date(&request.date_needed)
+                ),
+            }
+        }
+
+        let conflicts = self.find_conflicts(substitute_id, request)?;
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(AssignmentConflict { conflicts }.into())
+        }
+    }
+
+    /// Returns `None` when no request has the given id. Unknown statuses are
+    /// rejected, and filling a request checks the substitute is free.
+    pub fn update_status(
+        &self,
+        id: &str,
+        status: &str,
+        assigned_substitute_id: Option<&str>,
+    ) -> anyhow::Result<Option<SubstituteRequest>> {
+        let status: RequestStatus = status.parse()?;
+        let filling = matches!(status, RequestStatus::Filled);
+        if let (Some(substitute_id), Some(request)) = (assigned_substitute_id, self.find_by_id(id)?) {
+            if filling {
+                self.ensure_available(substitute_id, &request)?;
+            }
+        }
+
+        // Only an open request can be filled, so of two substitutes accepting
+        // the same request the second finds it taken
+        let updated = self.conn.execute(
+            "UPDATE substitute_requests SET status = ?1, assigned_substitute_id = ?2, updated_at = ?3
+             WHERE id = ?4 AND (NOT ?5 OR status = 'open')",
+            (status.to_string(), assigned_substitute_id, &timestamp::now(), id, filling),
+        )?;
+        if filling && updated == 0 && self.find_by_id(id)?.is_some() {
+            anyhow::bail!("This request is no longer open");
+        }
+        if let Some(substitute_id) = assigned_substitute_id {
+            if filling {
+                ResponseRepository::new(self.conn).record(id, substitute_id, ResponseType::Accepted, None)?;
+            }
+        }
+
+        self.find_by_id(id)
+    }
+
+    /// Deletes the request along with its responses, attendance, feedback,
+    /// requirement overrides and notification log.
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for table in [
+            "substitute_responses",
+            "assignment_attendance",
+            "assignment_feedback",
+            "requirement_overrides",
+            "notifications_log",
+        ] {
+            self.conn.execute(&format!("DELETE FROM {} WHERE request_id = ?1", table), [id])?;
+        }
+        self.conn.execute("DELETE FROM substitute_requests WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::fixtures::{future_date, future_day, insert_class, insert_organization, insert_user, test_connection};
+
+    fn setup() -> Connection {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "class-1", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "manager-2", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        conn
+    }
+
+    fn time(value: &str) -> NaiveTime {
+        timestamp::parse_time(value).unwrap()
+    }
+
+    fn create_request(date_needed: &str) -> CreateSubstituteRequestRequest {
+        CreateSubstituteRequestRequest {
+            class_id: "class-1".to_string(),
+            date_needed: future_date(date_needed),
+            start_time: time("08:00"),
+            end_time: time("15:00"),
+            reason: Some("Sick Leave".to_string()),
+            special_instructions: None,
+        }
+    }
+
+    #[test]
+    fn test_create_and_find() {
+        let conn = setup();
+        let repo = SubstituteRequestRepository::new(&conn);
+
+        let created = repo.create("manager-1".to_string(), create_request("2030-01-15")).unwrap();
+        let found = repo.find_by_id(&created.id).unwrap().unwrap();
+
+        assert_eq!(found.class_id, "class-1");
+        assert_eq!(found.requested_by, "manager-1");
+        assert!(matches!(found.status, RequestStatus::Open));
+        assert!(repo.find_by_id("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_create_rejects_invalid_schedules() {
+        let conn = setup();
+        let repo = SubstituteRequestRepository::new(&conn);
+
+        let mut backwards = create_request("2030-01-15");
+        backwards.end_time = time("07:00");
+        let error = repo.create("manager-1".to_string(), backwards).unwrap_err();
+        assert!(error.to_string().contains("End time must be after start time"));
+
+        let mut past = create_request("2030-01-15");
+        past.date_needed = timestamp::parse_date("2000-01-15").unwrap();
+        let error = repo.create("manager-1".to_string(), past).unwrap_err();
+        assert!(error.to_string().contains("in the past"));
+
+        conn.execute(
+            "INSERT INTO calendar_events (id, organization_id, event_type, name, start_date, end_date)
+             VALUES ('snow', 'org-1', 'closure', 'Snow day', ?1, ?1)",
+            [future_day("2030-01-16")],
+        )
+        .unwrap();
+        let error = repo.create("manager-1".to_string(), create_request("2030-01-16")).unwrap_err();
+        let closed = format!("School is closed on {} (Snow day)", future_day("2030-01-16"));
+        assert!(error.to_string().contains(&closed));
+
+        let mut unknown_class = create_request("2030-01-15");
+        unknown_class.class_id = "missing".to_string();
+        assert!(repo.create("manager-1".to_string(), unknown_class).is_err());
+    }
+
+    #[test]
+    fn test_create_respects_inherited_school_hours() {
+        let conn = setup();
+        conn.execute_batch(
+            "INSERT INTO organizations (id, name, school_start_time, school_end_time) VALUES ('district', 'District', '07:30', '15:30');
+             UPDATE organizations SET parent_organization_id = 'district' WHERE id = 'org-1';"
+        ).unwrap();
+        let repo = SubstituteRequestRepository::new(&conn);
+
+        assert!(repo.create("manager-1".to_string(), create_request("2030-01-15")).is_ok());
+
+        let mut late = create_request("2030-01-15");
+        late.end_time = time("17:00");
+        let error = repo.create("manager-1".to_string(), late).unwrap_err();
+        assert!(error.to_string().contains("outside school hours 07:30-15:30"));
+    }
+
+    #[test]
+    fn test_create_for_periods_uses_active_schedule() {
+        let conn = setup();
+        conn.execute_batch(
+            "INSERT INTO bell_schedules (id, organization_id, name, is_default) VALUES ('regular', 'org-1', 'Regular', true);
+             INSERT INTO bell_schedule_periods (id, schedule_id, name, position, start_time, end_time)
+             VALUES ('p1', 'regular', '1', 1, '08:00', '08:50'), ('p2', 'regular', '2', 2, '09:00', '09:50');
+             INSERT INTO class_periods (class_id, period_name) VALUES ('class-1', '2');"
+        ).unwrap();
+        let repo = SubstituteRequestRepository::new(&conn);
+
+        let request = repo
+            .create_for_periods(
+                "manager-1".to_string(),
+                CreatePeriodSubstituteRequest {
+                    class_id: "class-1".to_string(),
+                    date_needed: future_date("2030-01-15"),
+                    period_names: Vec::new(),
+                    reason: None,
+                    special_instructions: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(request.start_time, time("09:00"));
+        assert_eq!(request.end_time, time("09:50"));
+    }
+
+    #[test]
+    fn test_legacy_timestamps_are_accepted() {
+        let conn = setup();
+        conn.execute(
+            "INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, status, created_at, updated_at)
+             VALUES ('legacy', 'class-1', 'manager-1', '2030-01-15', '08:00', '15:00', 'open', '2024-09-01 14:30:00', datetime('now'))",
+            [],
+        ).unwrap();
+
+        let request = SubstituteRequestRepository::new(&conn).find_by_id("legacy").unwrap().unwrap();
+        assert_eq!(timestamp::format(&request.created_at), "2024-09-01T14:30:00.000Z");
+    }
+
+    #[test]
+    fn test_corrupt_timestamp_is_an_error() {
+        let conn = setup();
+        conn.execute(
+            "INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, status, created_at, updated_at)
+             VALUES ('corrupt', 'class-1', 'manager-1', '2030-01-15', '08:00', '15:00', 'open', 'not a date', 'not a date')",
+            [],
+        ).unwrap();
+
+        let repo = SubstituteRequestRepository::new(&conn);
+        assert!(repo.find_by_id("corrupt").is_err());
+        assert!(repo.list().is_err());
+    }
+
+    #[test]
+    fn test_update_status_and_list_for_user() {
+        let conn = setup();
+        let repo = SubstituteRequestRepository::new(&conn);
+
+        let first = repo.create("manager-1".to_string(), create_request("2030-01-15")).unwrap();
+        let second = repo.create("manager-2".to_string(), create_request("2030-01-16")).unwrap();
+
+        let filled = repo.update_status(&first.id, "filled", Some("sub-1")).unwrap().unwrap();
+        assert!(matches!(filled.status, RequestStatus::Filled));
+        assert_eq!(filled.assigned_substitute_id.as_deref(), Some("sub-1"));
+
+        let for_sub = repo.list_for_user("sub-1", "substitute").unwrap();
+        assert_eq!(for_sub.len(), 2);
+
+        let for_other_sub = repo.list_for_user("sub-2", "substitute").unwrap();
+        assert_eq!(for_other_sub.len(), 1);
+        assert_eq!(for_other_sub[0].id, second.id);
+
+        let for_manager = repo.list_for_user("manager-1", "org_manager").unwrap();
+        assert_eq!(for_manager.len(), 1);
+        assert_eq!(for_manager[0].id, first.id);
+
+        assert_eq!(repo.list_by_status("open").unwrap().len(), 1);
+        assert!(repo.update_status("missing", "filled", None).unwrap().is_none());
+
+        assert!(repo.update_status(&second.id, "archived", None).is_err());
+        assert!(repo.list_by_status("archived").is_err());
+        assert_eq!(repo.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_only_an_open_request_can_be_accepted() {
+        let conn = setup();
+        insert_user(&conn, "sub-2", "substitute");
+        let repo = SubstituteRequestRepository::new(&conn);
+        let request = repo.create("manager-1".to_string(), create_request("2030-01-15")).unwrap();
+
+        repo.update_status(&request.id, "filled", Some("sub-1")).unwrap();
+        let error = repo.update_status(&request.id, "filled", Some("sub-2")).unwrap_err();
+        assert!(error.to_string().contains("no longer open"));
+
+        let kept = repo.find_by_id(&request.id).unwrap().unwrap();
+        assert_eq!(kept.assigned_substitute_id.as_deref(), Some("sub-1"));
+        let accepted = ResponseRepository::new(&conn).list_for_request(&request.id).unwrap();
+        assert_eq!(accepted.len(), 1);
+    }
+
+    #[test]
+    fn test_delete_accepted_request_removes_dependents() {
+        let conn = setup();
+        let repo = SubstituteRequestRepository::new(&conn);
+        let request = repo.create("manager-1".to_string(), create_request("2030-01-15")).unwrap();
+        repo.update_status(&request.id, "filled", Some("sub-1")).unwrap();
+        conn.execute(
+            "INSERT INTO assignment_attendance (request_id, substitute_id) VALUES (?1, 'sub-1')",
+            [&request.id],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO notifications_log (id, user_id, request_id, notification_type, status)
+             VALUES ('notification-1', 'sub-1', ?1, 'email', 'sent')",
+            [&request.id],
+        )
+        .unwrap();
+
+        repo.delete(&request.id).unwrap();
+        assert!(repo.find_by_id(&request.id).unwrap().is_none());
+        let responses: i64 = conn
+            .query_row("SELECT COUNT(*) FROM substitute_responses WHERE request_id = ?1", [&request.id], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(responses, 0);
+    }
+
+    #[test]
+    fn test_double_booking_is_rejected() {
+        let conn = setup();
+        insert_organization(&conn, "org-2");
+        insert_class(&conn, "class-2", "org-2");
+        let repo = SubstituteRequestRepository::new(&conn);
+
+        let morning = |class_id: &str, start: &str, end: &str| {
+            let mut request = create_request("2030-01-15");
+            request.class_id = class_id.to_string();
+            request.start_time = time(start);
+            request.end_time = time(end);
+            repo.create("manager-1".to_string(), request).unwrap()
+        };
+        let first = morning("class-1", "08:00", "10:00");
+        let overlapping = morning("class-1", "09:30", "11:00");
+        let adjacent = morning("class-1", "10:00", "11:00");
+        let elsewhere = morning("class-2", "10:15", "11:00");
+
+        repo.update_status(&first.id, "filled", Some("sub-1")).unwrap();
+
+        let error = repo.update_status(&overlapping.id, "filled", Some("sub-1")).unwrap_err();
+        let conflict = error.downcast_ref::<AssignmentConflict>().unwrap();
+        assert_eq!(conflict.conflicts[0].id, first.id);
+        assert!(error.to_string().contains(&first.id));
+
+        // Back to back is fine at the same school, but not across schools once
+        // a travel buffer is configured
+        assert!(repo.find_conflicts("sub-1", &adjacent).unwrap().is_empty());
+        assert!(repo.find_conflicts("sub-1", &elsewhere).unwrap().is_empty());
+        SettingsRepository::new(&conn).set(TRAVEL_BUFFER_MINUTES, "30", None).unwrap();
+        assert_eq!(repo.find_conflicts("sub-1", &elsewhere).unwrap().len(), 1);
+        assert!(repo.find_conflicts("sub-1", &adjacent).unwrap().is_empty());
+
+        // Other substitutes and cancelled assignments don't count
+        assert!(repo.find_conflicts("sub-2", &overlapping).unwrap().is_empty());
+        repo.update_status(&first.id, "cancelled", Some("sub-1")).unwrap();
+        assert!(repo.update_status(&overlapping.id, "filled", Some("sub-1")).unwrap().is_some());
+    }
+}
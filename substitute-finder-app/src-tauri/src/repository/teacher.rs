@@ -0,0 +1,219 @@
+use crate::database::models::{
+    Class, CreateRegularTeacherRequest, CreateTeacherClassAssignmentRequest, RegularTeacher, TeacherClassAssignment,
+};
+use crate::database::timestamp;
+use crate::repository::{query_all, query_optional, timestamp_column, FromRow};
+use chrono::Utc;
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for RegularTeacher {
+    const COLUMNS: &'static str = "id, user_id, organization_id, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(RegularTeacher {
+            id: row.get(0)?,
+            user_id: row.get(1)?,
+            organization_id: row.get(2)?,
+            created_at: timestamp_column(row, 3)?,
+            updated_at: timestamp_column(row, 4)?,
+        })
+    }
+}
+
+impl FromRow for TeacherClassAssignment {
+    const COLUMNS: &'static str = "id, teacher_id, class_id, is_primary, created_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(TeacherClassAssignment {
+            id: row.get(0)?,
+            teacher_id: row.get(1)?,
+            class_id: row.get(2)?,
+            is_primary: row.get(3)?,
+            created_at: timestamp_column(row, 4)?,
+        })
+    }
+}
+
+/// Regular (non-substitute) teachers and the classes they teach.
+pub struct RegularTeacherRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> RegularTeacherRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        RegularTeacherRepository { conn }
+    }
+
+    pub fn create(&self, request: CreateRegularTeacherRequest) -> anyhow::Result<RegularTeacher> {
+        let teacher = RegularTeacher {
+            id: Uuid::new_v4().to_string(),
+            user_id: request.user_id,
+            organization_id: request.organization_id,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO regular_teachers (id, user_id, organization_id, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            (
+                &teacher.id,
+                &teacher.user_id,
+                &teacher.organization_id,
+                &timestamp::format(&teacher.created_at),
+                &timestamp::format(&teacher.updated_at),
+            ),
+        )?;
+
+        Ok(teacher)
+    }
+
+    pub fn list(&self) -> anyhow::Result<Vec<RegularTeacher>> {
+        let sql = format!("SELECT {} FROM regular_teachers ORDER BY created_at", RegularTeacher::COLUMNS);
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<RegularTeacher>> {
+        let sql = format!("SELECT {} FROM regular_teachers WHERE id = ?1", RegularTeacher::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    /// The user's teacher record, the oldest if they have several.
+    pub fn find_by_user(&self, user_id: &str) -> anyhow::Result<Option<RegularTeacher>> {
+        let sql = format!(
+            "SELECT {} FROM regular_teachers WHERE user_id = ?1 ORDER BY created_at LIMIT 1",
+            RegularTeacher::COLUMNS
+        );
+        Ok(query_optional(self.conn, &sql, [user_id])?)
+    }
+
+    /// Returns `None` when no teacher has the given id.
+    pub fn update(
+        &self,
+        id: &str,
+        request: CreateRegularTeacherRequest,
+    ) -> anyhow::Result<Option<RegularTeacher>> {
+        self.conn.execute(
+            "UPDATE regular_teachers SET user_id = ?1, organization_id = ?2, updated_at = ?3 WHERE id = ?4",
+            (&request.user_id, &request.organization_id, &timestamp::now(), id),
+        )?;
+
+        self.find_by_id(id)
+    }
+
+    /// Deletes the teacher together with their class assignments.
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute("DELETE FROM teacher_class_assignments WHERE teacher_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM regular_teachers WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn assign_class(
+        &self,
+        request: CreateTeacherClassAssignmentRequest,
+    ) -> anyhow::Result<TeacherClassAssignment> {
+        let assignment = TeacherClassAssignment {
+            id: Uuid::new_v4().to_string(),
+            teacher_id: request.teacher_id,
+            class_id: request.class_id,
+            is_primary: request.is_primary,
+            created_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO teacher_class_assignments (id, teacher_id, class_id, is_primary, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            (
+                &assignment.id,
+                &assignment.teacher_id,
+                &assignment.class_id,
+                &assignment.is_primary,
+                &timestamp::format(&assignment.created_at),
+            ),
+        )?;
+
+        Ok(assignment)
+    }
+
+    pub fn find_assignment(&self, id: &str) -> anyhow::Result<Option<TeacherClassAssignment>> {
+        let sql = format!("SELECT {} FROM teacher_class_assignments WHERE id = ?1", TeacherClassAssignment::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    pub fn list_assignments(&self, teacher_id: &str) -> anyhow::Result<Vec<TeacherClassAssignment>> {
+        let sql = format!(
+            "SELECT {} FROM teacher_class_assignments WHERE teacher_id = ?1 ORDER BY created_at",
+            TeacherClassAssignment::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [teacher_id])?)
+    }
+
+    pub fn remove_assignment(&self, id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM teacher_class_assignments WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// Every class the teacher is assigned to, by name.
+    pub fn classes_for_teacher(&self, teacher_id: &str) -> anyhow::Result<Vec<Class>> {
+        let sql = format!(
+            "SELECT {} FROM classes
+             WHERE id IN (SELECT class_id FROM teacher_class_assignments WHERE teacher_id = ?1)
+             ORDER BY name",
+            Class::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [teacher_id])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::fixtures::{insert_class, insert_organization, insert_user, test_connection};
+
+    #[test]
+    fn test_teacher_crud_and_assignments() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "math", "org-1");
+        insert_class(&conn, "art", "org-1");
+        insert_user(&conn, "teacher-user", "org_manager");
+        let repo = RegularTeacherRepository::new(&conn);
+
+        let teacher = repo
+            .create(CreateRegularTeacherRequest {
+                user_id: "teacher-user".to_string(),
+                organization_id: "org-1".to_string(),
+            })
+            .unwrap();
+        for class_id in ["math", "art"] {
+            repo.assign_class(CreateTeacherClassAssignmentRequest {
+                teacher_id: teacher.id.clone(),
+                class_id: class_id.to_string(),
+                is_primary: true,
+            })
+            .unwrap();
+        }
+
+        let classes: Vec<String> = repo.classes_for_teacher(&teacher.id).unwrap().into_iter().map(|c| c.id).collect();
+        assert_eq!(classes, vec!["art", "math"]);
+
+        // The same class can't be assigned twice
+        assert!(repo
+            .assign_class(CreateTeacherClassAssignmentRequest {
+                teacher_id: teacher.id.clone(),
+                class_id: "math".to_string(),
+                is_primary: false,
+            })
+            .is_err());
+
+        let assignments = repo.list_assignments(&teacher.id).unwrap();
+        repo.remove_assignment(&assignments[0].id).unwrap();
+        assert_eq!(repo.list_assignments(&teacher.id).unwrap().len(), 1);
+
+        repo.delete(&teacher.id).unwrap();
+        assert!(repo.find_by_id(&teacher.id).unwrap().is_none());
+    }
+}
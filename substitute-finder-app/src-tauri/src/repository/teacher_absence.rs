@@ -0,0 +1,467 @@
+use crate::database::models::{
+    AbsenceStatus, CreateAbsenceRequest, ReportAbsenceRequest, ResponseType, SubstituteRequest, TeacherAbsence,
+    TeacherAbsenceWithRequests,
+};
+use crate::database::timestamp;
+use crate::repository::{
+    date_column, enum_column, query_all, query_optional, timestamp_column, AbsenceRepository, BellScheduleRepository,
+    FromRow, OrganizationRepository, RegularTeacherRepository, ResponseRepository, SubstituteRequestRepository,
+};
+use chrono::{Local, NaiveDate, Utc};
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for TeacherAbsence {
+    const COLUMNS: &'static str =
+        "id, teacher_id, reported_by, start_date, end_date, reason, status, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(TeacherAbsence {
+            id: row.get(0)?,
+            teacher_id: row.get(1)?,
+            reported_by: row.get(2)?,
+            start_date: date_column(row, 3)?,
+            end_date: date_column(row, 4)?,
+            reason: row.get(5)?,
+            status: enum_column(row, 6)?,
+            created_at: timestamp_column(row, 7)?,
+            updated_at: timestamp_column(row, 8)?,
+        })
+    }
+}
+
+pub struct TeacherAbsenceRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> TeacherAbsenceRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        TeacherAbsenceRepository { conn }
+    }
+
+    /// Records the absence and, in one transaction, an [`Absence`] with daily
+    /// requests for every class the teacher is assigned to. Without explicit
+    /// times a class attached to periods is covered for those periods under
+    /// the bell schedule active each day, and any other class for the school day.
+    ///
+    /// [`Absence`]: crate::database::models::Absence
+    pub fn report(
+        &self,
+        reported_by: String,
+        request: ReportAbsenceRequest,
+    ) -> anyhow::Result<TeacherAbsenceWithRequests> {
+        let teachers = RegularTeacherRepository::new(self.conn);
+        teachers
+            .find_by_id(&request.teacher_id)?
+            .ok_or_else(|| anyhow::anyhow!("Teacher not found"))?;
+        let classes = teachers.classes_for_teacher(&request.teacher_id)?;
+        if classes.is_empty() {
+            anyhow::bail!("Teacher is not assigned to any classes");
+        }
+
+        let teacher_absence = TeacherAbsence {
+            id: Uuid::new_v4().to_string(),
+            teacher_id: request.teacher_id,
+            reported_by,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            reason: request.reason,
+            status: AbsenceStatus::Active,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            "INSERT INTO teacher_absences (id, teacher_id, reported_by, start_date, end_date, reason, status, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                &teacher_absence.id,
+                &teacher_absence.teacher_id,
+                &teacher_absence.reported_by,
+                &timestamp::format_date(&teacher_absence.start_date),
+                &timestamp::format_date(&teacher_absence.end_date),
+                &teacher_absence.reason,
+                &teacher_absence.status.to_string(),
+                &timestamp::format(&teacher_absence.created_at),
+                &timestamp::format(&teacher_absence.updated_at),
+            ),
+        )?;
+
+        let absences = AbsenceRepository::new(self.conn);
+        let organizations = OrganizationRepository::new(self.conn);
+        let schedules = BellScheduleRepository::new(self.conn);
+        let explicit_times = request.start_time.zip(request.end_time);
+        let mut requests = Vec::new();
+        for class in classes {
+            let by_period = explicit_times.is_none() && !schedules.class_periods(&class.id)?.is_empty();
+            let (start_time, end_time) = match explicit_times {
+                Some(times) => times,
+                None if by_period => schedules.period_window(&class.id, teacher_absence.start_date, &[])?,
+                None => organizations
+                    .effective_school_hours(&class.organization_id)?
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("No school hours are set for {}; give a start and end time", class.name)
+                    })?,
+            };
+            let created = absences.insert(
+                teacher_absence.reported_by.clone(),
+                CreateAbsenceRequest {
+                    class_id: class.id,
+                    start_date: teacher_absence.start_date,
+                    end_date: Some(teacher_absence.end_date),
+                    start_time,
+                    end_time,
+                    recurrence_rule: None,
+                    excluded_dates: Vec::new(),
+                    reason: teacher_absence.reason.clone(),
+                    special_instructions: request.special_instructions.clone(),
+                },
+                Some(&teacher_absence.id),
+            )?;
+            for mut child in created.requests {
+                if by_period {
+                    // Days on another schedule, such as early release, move the periods
+                    let window = schedules.period_window(&child.class_id, child.date_needed, &[])?;
+                    if window != (child.start_time, child.end_time) {
+                        (child.start_time, child.end_time) = window;
+                        self.conn.execute(
+                            "UPDATE substitute_requests SET start_time = ?1, end_time = ?2 WHERE id = ?3",
+                            (
+                                &timestamp::format_time(&child.start_time),
+                                &timestamp::format_time(&child.end_time),
+                                &child.id,
+                            ),
+                        )?;
+                    }
+                }
+                requests.push(child);
+            }
+        }
+        tx.commit()?;
+
+        requests.sort_by_key(|r| (r.date_needed, r.start_time));
+        Ok(TeacherAbsenceWithRequests { teacher_absence, requests })
+    }
+
+    pub fn list(&self) -> anyhow::Result<Vec<TeacherAbsence>> {
+        let sql = format!("SELECT {} FROM teacher_absences ORDER BY start_date", TeacherAbsence::COLUMNS);
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    pub fn list_for_teacher(&self, teacher_id: &str) -> anyhow::Result<Vec<TeacherAbsence>> {
+        let sql = format!(
+            "SELECT {} FROM teacher_absences WHERE teacher_id = ?1 ORDER BY start_date",
+            TeacherAbsence::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [teacher_id])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<TeacherAbsence>> {
+        let sql = format!("SELECT {} FROM teacher_absences WHERE id = ?1", TeacherAbsence::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    pub fn find_with_requests(&self, id: &str) -> anyhow::Result<Option<TeacherAbsenceWithRequests>> {
+        let Some(teacher_absence) = self.find_by_id(id)? else {
+            return Ok(None);
+        };
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE absence_id IN (SELECT id FROM absences WHERE teacher_absence_id = ?1)
+             ORDER BY date_needed, start_time",
+            SubstituteRequest::COLUMNS
+        );
+        let requests = query_all(self.conn, &sql, [id])?;
+        Ok(Some(TeacherAbsenceWithRequests { teacher_absence, requests }))
+    }
+
+    /// Sets the reason on the absence and its classes, and the reason and
+    /// instructions on the remaining open or filled days, keeping whoever
+    /// fills them. Returns the absence with the days updated, or `None` when
+    /// no absence has the given id.
+    pub fn update_details(
+        &self,
+        id: &str,
+        reason: Option<String>,
+        special_instructions: Option<String>,
+    ) -> anyhow::Result<Option<TeacherAbsenceWithRequests>> {
+        if self.find_by_id(id)?.is_none() {
+            return Ok(None);
+        }
+
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE absence_id IN (SELECT id FROM absences WHERE teacher_absence_id = ?1)
+               AND date_needed >= ?2 AND status IN ('open', 'filled')
+             ORDER BY date_needed, start_time",
+            SubstituteRequest::COLUMNS
+        );
+        let remaining: Vec<SubstituteRequest> =
+            query_all(self.conn, &sql, [id, &timestamp::format_date(&Local::now().date_naive())])?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let updated_at = timestamp::now();
+        self.conn.execute(
+            "UPDATE teacher_absences SET reason = ?1, updated_at = ?2 WHERE id = ?3",
+            (&reason, &updated_at, id),
+        )?;
+        self.conn.execute(
+            "UPDATE absences SET reason = ?1, special_instructions = ?2, updated_at = ?3 WHERE teacher_absence_id = ?4",
+            (&reason, &special_instructions, &updated_at, id),
+        )?;
+        for request in &remaining {
+            self.conn.execute(
+                "UPDATE substitute_requests SET reason = ?1, special_instructions = ?2, updated_at = ?3 WHERE id = ?4",
+                (&reason, &special_instructions, &updated_at, &request.id),
+            )?;
+        }
+        tx.commit()?;
+
+        let teacher_absence = self
+            .find_by_id(id)?
+            .ok_or_else(|| anyhow::anyhow!("Teacher absence not found after update"))?;
+        let requests = SubstituteRequestRepository::new(self.conn).reload(&remaining)?;
+        Ok(Some(TeacherAbsenceWithRequests { teacher_absence, requests }))
+    }
+
+    /// Moves the filled days of absence `from` onto absence `to` wherever `to`
+    /// has an open request for the same class, date and times, deleting that
+    /// request in their place. Returns the moved requests and the ids of the
+    /// deleted ones.
+    pub fn carry_over_filled(&self, from: &str, to: &str) -> anyhow::Result<(Vec<SubstituteRequest>, Vec<String>)> {
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE absence_id IN (SELECT id FROM absences WHERE teacher_absence_id = ?1) AND status = ?2
+             ORDER BY date_needed, start_time",
+            SubstituteRequest::COLUMNS
+        );
+        let filled: Vec<SubstituteRequest> = query_all(self.conn, &sql, [from, "filled"])?;
+        let open: Vec<SubstituteRequest> = query_all(self.conn, &sql, [to, "open"])?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let updated_at = timestamp::now();
+        let mut moved = Vec::new();
+        let mut replaced = Vec::new();
+        for request in filled {
+            let Some(replacement) = open.iter().find(|open| {
+                (&open.class_id, open.date_needed, open.start_time, open.end_time)
+                    == (&request.class_id, request.date_needed, request.start_time, request.end_time)
+            }) else {
+                continue;
+            };
+            self.conn.execute("DELETE FROM substitute_requests WHERE id = ?1", [&replacement.id])?;
+            self.conn.execute(
+                "UPDATE substitute_requests SET absence_id = ?1, updated_at = ?2 WHERE id = ?3",
+                (&replacement.absence_id, &updated_at, &request.id),
+            )?;
+            replaced.push(replacement.id.clone());
+            moved.push(request);
+        }
+        tx.commit()?;
+
+        let moved = SubstituteRequestRepository::new(self.conn).reload(&moved)?;
+        Ok((moved, replaced))
+    }
+
+    /// Cancels the remaining days of every class covered by the absence.
+    /// Returns the cancelled requests, or `None` when no absence has the given id.
+    pub fn cancel(&self, id: &str) -> anyhow::Result<Option<Vec<SubstituteRequest>>> {
+        if self.find_by_id(id)?.is_none() {
+            return Ok(None);
+        }
+
+        let absence_ids: Vec<String> = {
+            let mut stmt = self.conn.prepare("SELECT id FROM absences WHERE teacher_absence_id = ?1")?;
+            let ids = stmt.query_map([id], |row| row.get(0))?.collect::<rusqlite::Result<_>>()?;
+            ids
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        let absences = AbsenceRepository::new(self.conn);
+        let mut cancelled = Vec::new();
+        for absence_id in &absence_ids {
+            cancelled.extend(absences.cancel_remaining(absence_id)?);
+        }
+        self.conn.execute(
+            "UPDATE teacher_absences SET status = ?1, updated_at = ?2 WHERE id = ?3",
+            (AbsenceStatus::Cancelled.to_string(), &timestamp::now(), id),
+        )?;
+        tx.commit()?;
+
+        SubstituteRequestRepository::new(self.conn).reload(&cancelled).map(Some)
+    }
+
+    /// Assigns one substitute to every open class request of the absence, on
+    /// `date` only or on all remaining days when `date` is `None`.
+    pub fn accept(
+        &self,
+        id: &str,
+        substitute_id: &str,
+        date: Option<NaiveDate>,
+    ) -> anyhow::Result<Vec<SubstituteRequest>> {
+        let teacher_absence = self
+            .find_by_id(id)?
+            .ok_or_else(|| anyhow::anyhow!("Teacher absence not found"))?;
+        if teacher_absence.status == AbsenceStatus::Cancelled {
+            anyhow::bail!("Cannot accept a cancelled absence");
+        }
+
+        let today = timestamp::format_date(&Local::now().date_naive());
+        let date = date.as_ref().map(timestamp::format_date);
+        let sql = format!(
+            "SELECT {} FROM substitute_requests
+             WHERE absence_id IN (SELECT id FROM absences WHERE teacher_absence_id = ?1)
+               AND status = 'open' AND date_needed >= ?2 AND (?3 IS NULL OR date_needed = ?3)
+             ORDER BY date_needed, start_time",
+            SubstituteRequest::COLUMNS
+        );
+        let open: Vec<SubstituteRequest> =
+            query_all(self.conn, &sql, rusqlite::params![id, today, date])?;
+        if open.is_empty() {
+            anyhow::bail!("No open class requests left for this absence");
+        }
+
+        let requests = SubstituteRequestRepository::new(self.conn);
+        let responses = ResponseRepository::new(self.conn);
+        let tx = self.conn.unchecked_transaction()?;
+        let updated_at = timestamp::now();
+        for request in &open {
+            requests.ensure_available(substitute_id, request)?;
+            self.conn.execute(
+                "UPDATE substitute_requests SET status = 'filled', assigned_substitute_id = ?1, updated_at = ?2 WHERE id = ?3",
+                (substitute_id, &updated_at, &request.id),
+            )?;
+            responses.record(&request.id, substitute_id, ResponseType::Accepted, None)?;
+        }
+        tx.commit()?;
+
+        SubstituteRequestRepository::new(self.conn).reload(&open)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::RequestStatus;
+    use crate::repository::fixtures::{
+        future_date, future_day, insert_class, insert_organization, insert_user, test_connection,
+    };
+
+    fn date(value: &str) -> NaiveDate {
+        future_date(value)
+    }
+
+    fn fixture() -> Connection {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        conn.execute(
+            "UPDATE organizations SET school_start_time = '08:00', school_end_time = '15:00' WHERE id = 'org-1'",
+            [],
+        )
+        .unwrap();
+        insert_class(&conn, "math", "org-1");
+        insert_class(&conn, "science", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        conn.execute_batch(
+            "INSERT INTO regular_teachers (id, user_id, organization_id) VALUES ('teacher-1', 'manager-1', 'org-1');
+             INSERT INTO teacher_class_assignments (id, teacher_id, class_id) VALUES ('a-1', 'teacher-1', 'math');
+             INSERT INTO teacher_class_assignments (id, teacher_id, class_id) VALUES ('a-2', 'teacher-1', 'science');",
+        )
+        .unwrap();
+        insert_user(&conn, "sub-1", "substitute");
+        conn
+    }
+
+    fn report(start: &str, end: &str) -> ReportAbsenceRequest {
+        ReportAbsenceRequest {
+            teacher_id: "teacher-1".to_string(),
+            start_date: date(start),
+            end_date: date(end),
+            start_time: None,
+            end_time: None,
+            reason: Some("Conference".to_string()),
+            special_instructions: None,
+        }
+    }
+
+    #[test]
+    fn test_report_creates_requests_for_every_class() {
+        let conn = fixture();
+        conn.execute_batch(
+            "INSERT INTO bell_schedules (id, organization_id, name, is_default) VALUES ('regular', 'org-1', 'Regular', true);
+             INSERT INTO bell_schedules (id, organization_id, name, is_default) VALUES ('early', 'org-1', 'Early', false);
+             INSERT INTO bell_schedule_periods (id, schedule_id, name, position, start_time, end_time)
+             VALUES ('r1', 'regular', '1', 1, '08:00', '08:50'), ('r2', 'regular', '2', 2, '09:00', '09:50'),
+                    ('e1', 'early', '1', 1, '08:00', '08:30'), ('e2', 'early', '2', 2, '08:35', '09:05');
+             INSERT INTO class_periods (class_id, period_name) VALUES ('math', '2');",
+        )
+        .unwrap();
+        BellScheduleRepository::new(&conn)
+            .set_schedule_for_date("org-1", date("2030-01-14"), Some("early"))
+            .unwrap();
+        let repo = TeacherAbsenceRepository::new(&conn);
+
+        // Friday 2030-01-11 through Monday 2030-01-14, an early release day
+        let reported = repo.report("manager-1".to_string(), report("2030-01-11", "2030-01-14")).unwrap();
+        let times: Vec<(String, String, String, String)> = reported
+            .requests
+            .iter()
+            .map(|r| {
+                (
+                    timestamp::format_date(&r.date_needed),
+                    r.class_id.clone(),
+                    timestamp::format_time(&r.start_time),
+                    timestamp::format_time(&r.end_time),
+                )
+            })
+            .collect();
+        let expected = [
+            ("2030-01-11", "science", "08:00", "15:00"),
+            ("2030-01-11", "math", "09:00", "09:50"),
+            ("2030-01-14", "science", "08:00", "15:00"),
+            ("2030-01-14", "math", "08:35", "09:05"),
+        ];
+        let expected: Vec<(String, String, String, String)> = expected
+            .iter()
+            .map(|(day, class, start, end)| (future_day(day), class.to_string(), start.to_string(), end.to_string()))
+            .collect();
+        assert_eq!(times, expected);
+
+        let found = repo.find_with_requests(&reported.teacher_absence.id).unwrap().unwrap();
+        assert_eq!(found.requests.len(), 4);
+        assert_eq!(repo.list_for_teacher("teacher-1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_report_requires_classes_and_hours() {
+        let conn = fixture();
+        let repo = TeacherAbsenceRepository::new(&conn);
+
+        conn.execute("UPDATE organizations SET school_start_time = NULL", []).unwrap();
+        let error = repo.report("manager-1".to_string(), report("2030-01-11", "2030-01-11")).unwrap_err();
+        assert!(error.to_string().contains("No school hours"));
+        assert!(repo.list().unwrap().is_empty());
+
+        conn.execute("DELETE FROM teacher_class_assignments", []).unwrap();
+        assert!(repo.report("manager-1".to_string(), report("2030-01-11", "2030-01-11")).is_err());
+    }
+
+    #[test]
+    fn test_one_substitute_covers_the_day_then_cancel() {
+        let conn = fixture();
+        let repo = TeacherAbsenceRepository::new(&conn);
+        let reported = repo.report("manager-1".to_string(), report("2030-01-14", "2030-01-15")).unwrap();
+        let id = &reported.teacher_absence.id;
+
+        let accepted = repo.accept(id, "sub-1", Some(date("2030-01-14"))).unwrap();
+        assert_eq!(accepted.len(), 2);
+        assert!(accepted.iter().all(|r| matches!(r.status, RequestStatus::Filled)));
+        assert!(repo.accept(id, "sub-1", Some(date("2030-01-14"))).is_err());
+
+        let cancelled = repo.cancel(id).unwrap().unwrap();
+        assert_eq!(cancelled.len(), 4);
+        assert!(cancelled.iter().all(|r| matches!(r.status, RequestStatus::Cancelled)));
+        assert_eq!(repo.find_by_id(id).unwrap().unwrap().status, AbsenceStatus::Cancelled);
+    }
+}
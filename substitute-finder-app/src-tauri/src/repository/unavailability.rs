@@ -0,0 +1,217 @@
+use crate::database::models::{CreateUnavailabilityRequest, SubstituteRequest, SubstituteUnavailability};
+use crate::database::timestamp;
+use crate::repository::{date_column, optional_time_column, query_all, query_optional, timestamp_column, FromRow};
+use chrono::Utc;
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for SubstituteUnavailability {
+    const COLUMNS: &'static str = "id, substitute_id, start_date, end_date, start_time, end_time, reason, created_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(SubstituteUnavailability {
+            id: row.get(0)?,
+            substitute_id: row.get(1)?,
+            start_date: date_column(row, 2)?,
+            end_date: date_column(row, 3)?,
+            start_time: optional_time_column(row, 4)?,
+            end_time: optional_time_column(row, 5)?,
+            reason: row.get(6)?,
+            created_at: timestamp_column(row, 7)?,
+        })
+    }
+}
+
+impl SubstituteUnavailability {
+    /// Whether this blocks any part of `request`.
+    pub fn blocks(&self, request: &SubstituteRequest) -> bool {
+        if request.date_needed < self.start_date || request.date_needed > self.end_date {
+            return false;
+        }
+        match (self.start_time, self.end_time) {
+            (Some(start), Some(end)) => start < request.end_time && request.start_time < end,
+            _ => true,
+        }
+    }
+}
+
+/// Time off substitutes have entered for themselves.
+pub struct UnavailabilityRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> UnavailabilityRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        UnavailabilityRepository { conn }
+    }
+
+    pub fn create(
+        &self,
+        substitute_id: &str,
+        request: CreateUnavailabilityRequest,
+    ) -> anyhow::Result<SubstituteUnavailability> {
+        let end_date = request.end_date.unwrap_or(request.start_date);
+        if end_date < request.start_date {
+            anyhow::bail!("End date must not be before start date");
+        }
+        match (request.start_time, request.end_time) {
+            (Some(start), Some(end)) if end <= start => anyhow::bail!("End time must be after start time"),
+            (Some(_), None) | (None, Some(_)) => anyhow::bail!("Give both a start and end time, or neither"),
+            _ => {}
+        }
+
+        let entry = SubstituteUnavailability {
+            id: Uuid::new_v4().to_string(),
+            substitute_id: substitute_id.to_string(),
+            start_date: request.start_date,
+            end_date,
+            start_time: request.start_time,
+            end_time: request.end_time,
+            reason: request.reason,
+            created_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO substitute_unavailability (id, substitute_id, start_date, end_date, start_time, end_time, reason, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (
+                &entry.id,
+                &entry.substitute_id,
+                &timestamp::format_date(&entry.start_date),
+                &timestamp::format_date(&entry.end_date),
+                &entry.start_time.as_ref().map(timestamp::format_time),
+                &entry.end_time.as_ref().map(timestamp::format_time),
+                &entry.reason,
+                &timestamp::format(&entry.created_at),
+            ),
+        )?;
+
+        Ok(entry)
+    }
+
+    pub fn list_for_substitute(&self, substitute_id: &str) -> anyhow::Result<Vec<SubstituteUnavailability>> {
+        let sql = format!(
+            "SELECT {} FROM substitute_unavailability WHERE substitute_id = ?1 ORDER BY start_date, start_time",
+            SubstituteUnavailability::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [substitute_id])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<SubstituteUnavailability>> {
+        let sql = format!("SELECT {} FROM substitute_unavailability WHERE id = ?1", SubstituteUnavailability::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    /// Only removes the substitute's own entries. Returns `false` when they
+    /// have none with the given id.
+    pub fn delete(&self, substitute_id: &str, id: &str) -> anyhow::Result<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM substitute_unavailability WHERE id = ?1 AND substitute_id = ?2",
+            [id, substitute_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// The substitute's entries that block any part of `request`.
+    pub fn blocking(
+        &self,
+        substitute_id: &str,
+        request: &SubstituteRequest,
+    ) -> anyhow::Result<Vec<SubstituteUnavailability>> {
+        let date = timestamp::format_date(&request.date_needed);
+        let sql = format!(
+            "SELECT {} FROM substitute_unavailability
+             WHERE substitute_id = ?1 AND start_date <= ?2 AND end_date >= ?2
+             ORDER BY start_time",
+            SubstituteUnavailability::COLUMNS
+        );
+        let entries: Vec<SubstituteUnavailability> = query_all(self.conn, &sql, [substitute_id, date.as_str()])?;
+        Ok(entries.into_iter().filter(|entry| entry.blocks(request)).collect())
+    }
+
+    /// The substitutes from `substitute_ids` who haven't blocked out `request`.
+    pub fn available_for(
+        &self,
+        substitute_ids: Vec<String>,
+        request: &SubstituteRequest,
+    ) -> anyhow::Result<Vec<String>> {
+        let mut available = Vec::new();
+        for substitute_id in substitute_ids {
+            if self.blocking(&substitute_id, request)?.is_empty() {
+                available.push(substitute_id);
+            }
+        }
+        Ok(available)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::CreateSubstituteRequestRequest;
+    use crate::repository::fixtures::{future_date, insert_class, insert_organization, insert_user, test_connection};
+    use crate::repository::SubstituteRequestRepository;
+
+    fn day_off(start: &str, end: Option<&str>, times: Option<(&str, &str)>) -> CreateUnavailabilityRequest {
+        CreateUnavailabilityRequest {
+            start_date: future_date(start),
+            end_date: end.map(future_date),
+            start_time: times.map(|(start, _)| timestamp::parse_time(start).unwrap()),
+            end_time: times.map(|(_, end)| timestamp::parse_time(end).unwrap()),
+            reason: Some("Vacation".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_unavailable_substitutes_are_skipped() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "class-1", "org-1");
+        insert_user(&conn, "manager-1", "org_manager");
+        insert_user(&conn, "sub-1", "substitute");
+        insert_user(&conn, "sub-2", "substitute");
+        let repo = UnavailabilityRepository::new(&conn);
+        let requests = SubstituteRequestRepository::new(&conn);
+
+        let request = |date: &str, start: &str, end: &str| {
+            requests
+                .create(
+                    "manager-1".to_string(),
+                    CreateSubstituteRequestRequest {
+                        class_id: "class-1".to_string(),
+                        date_needed: future_date(date),
+                        start_time: timestamp::parse_time(start).unwrap(),
+                        end_time: timestamp::parse_time(end).unwrap(),
+                        reason: None,
+                        special_instructions: None,
+                    },
+                )
+                .unwrap()
+        };
+        let in_range = request("2030-06-04", "08:00", "15:00");
+        let after = request("2030-06-11", "08:00", "15:00");
+        let morning = request("2030-06-12", "08:00", "11:00");
+        let afternoon = request("2030-06-12", "12:00", "15:00");
+
+        assert!(repo.create("sub-1", day_off("2030-06-10", Some("2030-06-03"), None)).is_err());
+        assert!(repo.create("sub-1", day_off("2030-06-12", None, Some(("13:00", "09:00")))).is_err());
+        repo.create("sub-1", day_off("2030-06-03", Some("2030-06-10"), None)).unwrap();
+        let dentist = repo.create("sub-1", day_off("2030-06-12", None, Some(("09:00", "11:30")))).unwrap();
+
+        let subs = || vec!["sub-1".to_string(), "sub-2".to_string()];
+        assert_eq!(repo.available_for(subs(), &in_range).unwrap(), vec!["sub-2"]);
+        assert_eq!(repo.available_for(subs(), &after).unwrap().len(), 2);
+        assert_eq!(repo.available_for(subs(), &morning).unwrap(), vec!["sub-2"]);
+        assert_eq!(repo.available_for(subs(), &afternoon).unwrap().len(), 2);
+
+        let error = requests.update_status(&in_range.id, "filled", Some("sub-1")).unwrap_err();
+        assert!(error.to_string().contains("unavailable"));
+        assert!(requests.update_status(&in_range.id, "filled", Some("sub-2")).unwrap().is_some());
+
+        // Substitutes can only remove their own entries
+        assert!(!repo.delete("sub-2", &dentist.id).unwrap());
+        assert!(repo.delete("sub-1", &dentist.id).unwrap());
+        assert_eq!(repo.list_for_substitute("sub-1").unwrap().len(), 1);
+        assert_eq!(repo.available_for(subs(), &morning).unwrap().len(), 2);
+    }
+}
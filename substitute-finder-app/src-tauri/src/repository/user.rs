@@ -0,0 +1,235 @@
+use crate::database::models::{CreateUserRequest, User};
+use crate::database::timestamp;
+use crate::repository::{enum_column, query_all, query_optional, timestamp_column, FromRow};
+use chrono::Utc;
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for User {
+    const COLUMNS: &'static str =
+        "id, username, password_hash, email, first_name, last_name, role, organization_id, is_active, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(User {
+            id: row.get(0)?,
+            username: row.get(1)?,
+            password_hash: row.get(2)?,
+            email: row.get(3)?,
+            first_name: row.get(4)?,
+            last_name: row.get(5)?,
+            role: enum_column(row, 6)?,
+            organization_id: row.get(7)?,
+            is_active: row.get(8)?,
+            created_at: timestamp_column(row, 9)?,
+            updated_at: timestamp_column(row, 10)?,
+        })
+    }
+}
+
+// In a real app, you would properly hash and verify the password
+pub fn hash_password(password: &str) -> String {
+    format!("hashed_{}", password)
+}
+
+pub struct UserRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> UserRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        UserRepository { conn }
+    }
+
+    pub fn create(&self, request: CreateUserRequest) -> anyhow::Result<User> {
+        let user = User {
+            id: Uuid::new_v4().to_string(),
+            username: request.username,
+            password_hash: hash_password(&request.password),
+            email: request.email,
+            first_name: request.first_name,
+            last_name: request.last_name,
+            role: request.role,
+            organization_id: request.organization_id,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO users (id, username, password_hash, email, first_name, last_name, role, organization_id, is_active, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            (
+                &user.id,
+                &user.username,
+                &user.password_hash,
+                &user.email,
+                &user.first_name,
+                &user.last_name,
+                &user.role.to_string(),
+                &user.organization_id,
+                &user.is_active,
+                &timestamp::format(&user.created_at),
+                &timestamp::format(&user.updated_at),
+            ),
+        )?;
+
+        Ok(user)
+    }
+
+    pub fn list(&self) -> anyhow::Result<Vec<User>> {
+        let sql = format!("SELECT {} FROM users ORDER BY last_name, first_name", User::COLUMNS);
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    pub fn find_by_id(&self, id: &str) -> anyhow::Result<Option<User>> {
+        let sql = format!("SELECT {} FROM users WHERE id = ?1", User::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    pub fn find_by_username(&self, username: &str) -> anyhow::Result<Option<User>> {
+        let sql = format!("SELECT {} FROM users WHERE username = ?1", User::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [username])?)
+    }
+
+    pub fn find_by_email(&self, email: &str) -> anyhow::Result<Option<User>> {
+        let sql = format!("SELECT {} FROM users WHERE lower(email) = lower(?1)", User::COLUMNS);
+        Ok(query_optional(self.conn, &sql, [email])?)
+    }
+
+    /// Updates everything but the password, which `request` may leave
+    /// empty. Returns `None` when no user has the given id.
+    pub fn update(&self, id: &str, request: CreateUserRequest) -> anyhow::Result<Option<User>> {
+        self.conn.execute(
+            "UPDATE users SET username = ?1, email = ?2, first_name = ?3, last_name = ?4, role = ?5,
+             organization_id = ?6, updated_at = ?7 WHERE id = ?8",
+            (
+                &request.username,
+                &request.email,
+                &request.first_name,
+                &request.last_name,
+                &request.role.to_string(),
+                &request.organization_id,
+                &timestamp::now(),
+                id,
+            ),
+        )?;
+
+        self.find_by_id(id)
+    }
+
+    /// Sets the subjects a substitute covers and their hourly rate, creating
+    /// their teaching profile if they have none. `None` keeps the current value.
+    pub fn save_substitute_profile(
+        &self,
+        user_id: &str,
+        subjects: Option<&[String]>,
+        hourly_rate: Option<f64>,
+    ) -> anyhow::Result<()> {
+        let subjects = subjects.map(serde_json::to_string).transpose()?;
+        let updated = self.conn.execute(
+            "UPDATE teachers SET subjects = COALESCE(?1, subjects), hourly_rate = COALESCE(?2, hourly_rate),
+             updated_at = ?3 WHERE user_id = ?4",
+            (&subjects, hourly_rate, &timestamp::now(), user_id),
+        )?;
+        if updated == 0 {
+            self.conn.execute(
+                "INSERT INTO teachers (id, user_id, subjects, hourly_rate) VALUES (?1, ?2, ?3, ?4)",
+                (&Uuid::new_v4().to_string(), user_id, &subjects, hourly_rate),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the user with their teaching profile, time off, class
+    /// preferences, credentials and notification log. Users still named on
+    /// requests, absences or assignment history can't be deleted.
+    pub fn delete(&self, id: &str) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute("DELETE FROM teachers WHERE user_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM substitute_unavailability WHERE substitute_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM substitute_preferences WHERE substitute_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM substitute_credentials WHERE substitute_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM notifications_log WHERE user_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM users WHERE id = ?1", [id])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Looks up an active user by username and plain-text password.
+    pub fn find_by_credentials(&self, username: &str, password: &str) -> anyhow::Result<Option<User>> {
+        let sql = format!(
+            "SELECT {} FROM users WHERE username = ?1 AND password_hash = ?2 AND is_active = true",
+            User::COLUMNS
+        );
+        Ok(query_optional(self.conn, &sql, [username, &hash_password(password)])?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::UserRole;
+    use crate::repository::fixtures::{insert_class, insert_organization, test_connection};
+
+    fn create_request(username: &str) -> CreateUserRequest {
+        CreateUserRequest {
+            username: username.to_string(),
+            password: "secret".to_string(),
+            email: format!("{}@example.com", username),
+            first_name: "Test".to_string(),
+            last_name: "User".to_string(),
+            role: UserRole::OrgManager,
+            organization_id: None,
+        }
+    }
+
+    #[test]
+    fn test_create_and_login() {
+        let conn = test_connection();
+        let repo = UserRepository::new(&conn);
+
+        let created = repo.create(create_request("manager")).unwrap();
+        assert_eq!(created.password_hash, "hashed_secret");
+
+        let found = repo.find_by_credentials("manager", "secret").unwrap().unwrap();
+        assert_eq!(found.id, created.id);
+        assert!(matches!(found.role, UserRole::OrgManager));
+
+        assert!(repo.find_by_credentials("manager", "wrong").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_duplicate_username_is_rejected() {
+        let conn = test_connection();
+        let repo = UserRepository::new(&conn);
+
+        repo.create(create_request("dup")).unwrap();
+        let mut second = create_request("dup");
+        second.email = "other@example.com".to_string();
+
+        assert!(repo.create(second).is_err());
+    }
+
+    #[test]
+    fn test_delete_removes_profile_but_not_referenced_users() {
+        let conn = test_connection();
+        let repo = UserRepository::new(&conn);
+
+        let substitute = repo.create(create_request("sub")).unwrap();
+        repo.save_substitute_profile(&substitute.id, Some(&["Math".to_string()]), Some(30.0)).unwrap();
+        repo.delete(&substitute.id).unwrap();
+        assert!(repo.find_by_id(&substitute.id).unwrap().is_none());
+
+        insert_organization(&conn, "org-1");
+        insert_class(&conn, "class-1", "org-1");
+        let manager = repo.create(create_request("manager")).unwrap();
+        conn.execute(
+            "INSERT INTO substitute_requests (id, class_id, requested_by, date_needed, start_time, end_time, status)
+             VALUES ('r1', 'class-1', ?1, '2030-01-15', '08:00', '15:00', 'open')",
+            [&manager.id],
+        )
+        .unwrap();
+        assert!(repo.delete(&manager.id).is_err());
+        assert!(repo.find_by_id(&manager.id).unwrap().is_some());
+    }
+}
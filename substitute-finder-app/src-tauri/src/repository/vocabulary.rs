@@ -0,0 +1,155 @@
+use crate::database::models::{SaveVocabularyTermRequest, VocabularyKind, VocabularyTerm};
+use crate::repository::{enum_column, query_all, query_optional, FromRow};
+use rusqlite::{Connection, Row};
+
+impl FromRow for VocabularyTerm {
+    const COLUMNS: &'static str = "kind, name, sort_order, is_active";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(VocabularyTerm {
+            kind: enum_column(row, 0)?,
+            name: row.get(1)?,
+            sort_order: row.get(2)?,
+            is_active: row.get(3)?,
+        })
+    }
+}
+
+/// The subjects and grade bands classes can be given.
+pub struct VocabularyRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> VocabularyRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        VocabularyRepository { conn }
+    }
+
+    pub fn list(&self, kind: VocabularyKind, include_inactive: bool) -> anyhow::Result<Vec<VocabularyTerm>> {
+        let sql = format!(
+            "SELECT {} FROM class_vocabulary WHERE kind = ?1 AND (?2 OR is_active) ORDER BY sort_order, name",
+            VocabularyTerm::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, (kind.to_string(), include_inactive))?)
+    }
+
+    pub fn find(&self, kind: VocabularyKind, name: &str) -> anyhow::Result<Option<VocabularyTerm>> {
+        let sql = format!(
+            "SELECT {} FROM class_vocabulary WHERE kind = ?1 AND name = ?2",
+            VocabularyTerm::COLUMNS
+        );
+        Ok(query_optional(self.conn, &sql, (kind.to_string(), name))?)
+    }
+
+    /// Adds a term or updates an existing one's order and active flag.
+    pub fn save(&self, request: SaveVocabularyTermRequest) -> anyhow::Result<VocabularyTerm> {
+        let name = request.name.trim().to_string();
+        if name.is_empty() {
+            anyhow::bail!("Name is required");
+        }
+        let existing = self.find(request.kind, &name)?;
+        let sort_order = match (request.sort_order, &existing) {
+            (Some(sort_order), _) => sort_order,
+            (None, Some(term)) => term.sort_order,
+            (None, None) => self.conn.query_row(
+                "SELECT COALESCE(MAX(sort_order), 0) + 1 FROM class_vocabulary WHERE kind = ?1",
+                [request.kind.to_string()],
+                |row| row.get(0),
+            )?,
+        };
+        let term = VocabularyTerm {
+            kind: request.kind,
+            name,
+            sort_order,
+            is_active: request
+                .is_active
+                .unwrap_or(existing.as_ref().is_none_or(|term| term.is_active)),
+        };
+
+        self.conn.execute(
+            "INSERT OR REPLACE INTO class_vocabulary (kind, name, sort_order, is_active) VALUES (?1, ?2, ?3, ?4)",
+            (&term.kind.to_string(), &term.name, &term.sort_order, &term.is_active),
+        )?;
+
+        Ok(term)
+    }
+
+    /// Fails unless `value` is an active term, or is `current` so classes can
+    /// keep a term after it's retired.
+    pub fn ensure_allowed(
+        &self,
+        kind: VocabularyKind,
+        value: Option<&str>,
+        current: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let Some(value) = value else {
+            return Ok(());
+        };
+        if Some(value) == current {
+            return Ok(());
+        }
+        match self.find(kind, value)? {
+            Some(term) if term.is_active => Ok(()),
+            _ => {
+                let label = match kind {
+                    VocabularyKind::Subject => "subject",
+                    VocabularyKind::GradeBand => "grade level",
+                };
+                anyhow::bail!("Unknown {}: {}", label, value)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::models::CreateClassRequest;
+    use crate::repository::fixtures::{insert_organization, test_connection};
+    use crate::repository::ClassRepository;
+
+    fn class(subject: &str, grade_level: &str) -> CreateClassRequest {
+        CreateClassRequest {
+            name: "Robotics".to_string(),
+            organization_id: "org-1".to_string(),
+            subject: Some(subject.to_string()),
+            grade_level: Some(grade_level.to_string()),
+            room_number: None,
+            description: None,
+        }
+    }
+
+    #[test]
+    fn test_classes_use_the_vocabulary() {
+        let conn = test_connection();
+        insert_organization(&conn, "org-1");
+        let repo = VocabularyRepository::new(&conn);
+        let classes = ClassRepository::new(&conn);
+
+        assert!(classes.create(class("Robotics", "5th Grade")).is_err());
+        let robotics = repo
+            .save(SaveVocabularyTermRequest {
+                kind: VocabularyKind::Subject,
+                name: " Robotics ".to_string(),
+                sort_order: None,
+                is_active: None,
+            })
+            .unwrap();
+        assert_eq!(robotics.name, "Robotics");
+        assert!(robotics.sort_order > repo.find(VocabularyKind::Subject, "Other").unwrap().unwrap().sort_order);
+        let created = classes.create(class("Robotics", "5th Grade")).unwrap();
+
+        // Retiring a term keeps it on the classes that have it
+        repo.save(SaveVocabularyTermRequest {
+            kind: VocabularyKind::Subject,
+            name: "Robotics".to_string(),
+            sort_order: None,
+            is_active: Some(false),
+        })
+        .unwrap();
+        assert!(!repo.list(VocabularyKind::Subject, false).unwrap().iter().any(|term| term.name == "Robotics"));
+        assert!(classes.update(&created.id, class("Robotics", "6th Grade")).unwrap().is_some());
+        assert!(classes.create(class("Robotics", "5th Grade")).is_err());
+        assert!(classes.update(&created.id, class("Mathematics", "Grade 6")).is_err());
+    }
+}
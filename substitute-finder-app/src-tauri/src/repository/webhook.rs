@@ -0,0 +1,189 @@
+use crate::database::models::{
+    CreateWebhookSubscriptionRequest, WebhookDelivery, WebhookEventType, WebhookSubscription,
+};
+use crate::database::timestamp;
+use crate::repository::{
+    enum_column, json_column, optional_timestamp_column, query_all, query_optional, timestamp_column, FromRow,
+};
+use chrono::Utc;
+use rusqlite::{Connection, Row};
+use uuid::Uuid;
+
+impl FromRow for WebhookSubscription {
+    const COLUMNS: &'static str = "id, url, secret, event_types, is_active, created_at, updated_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(WebhookSubscription {
+            id: row.get(0)?,
+            url: row.get(1)?,
+            secret: row.get(2)?,
+            event_types: json_column(row, 3)?,
+            is_active: row.get(4)?,
+            created_at: timestamp_column(row, 5)?,
+            updated_at: timestamp_column(row, 6)?,
+        })
+    }
+}
+
+impl FromRow for WebhookDelivery {
+    const COLUMNS: &'static str =
+        "id, subscription_id, event_type, payload, status, attempts, response_status, error_message, created_at, last_attempt_at";
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(WebhookDelivery {
+            id: row.get(0)?,
+            subscription_id: row.get(1)?,
+            event_type: enum_column(row, 2)?,
+            payload: row.get(3)?,
+            status: enum_column(row, 4)?,
+            attempts: row.get(5)?,
+            response_status: row.get(6)?,
+            error_message: row.get(7)?,
+            created_at: timestamp_column(row, 8)?,
+            last_attempt_at: optional_timestamp_column(row, 9)?,
+        })
+    }
+}
+
+pub struct WebhookRepository<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> WebhookRepository<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        WebhookRepository { conn }
+    }
+
+    pub fn create_subscription(
+        &self,
+        request: CreateWebhookSubscriptionRequest,
+    ) -> anyhow::Result<WebhookSubscription> {
+        let subscription = WebhookSubscription {
+            id: Uuid::new_v4().to_string(),
+            url: request.url,
+            secret: request.secret,
+            event_types: request.event_types,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO webhook_subscriptions (id, url, secret, event_types, is_active, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (
+                &subscription.id,
+                &subscription.url,
+                &subscription.secret,
+                &serde_json::to_string(&subscription.event_types)?,
+                &subscription.is_active,
+                &timestamp::format(&subscription.created_at),
+                &timestamp::format(&subscription.updated_at),
+            ),
+        )?;
+
+        Ok(subscription)
+    }
+
+    pub fn list_subscriptions(&self) -> anyhow::Result<Vec<WebhookSubscription>> {
+        let sql = format!(
+            "SELECT {} FROM webhook_subscriptions ORDER BY created_at",
+            WebhookSubscription::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [])?)
+    }
+
+    /// Active subscriptions whose event filter includes `event`.
+    pub fn list_subscriptions_for_event(
+        &self,
+        event: WebhookEventType,
+    ) -> anyhow::Result<Vec<WebhookSubscription>> {
+        Ok(self
+            .list_subscriptions()?
+            .into_iter()
+            .filter(|s| s.is_active && s.event_types.contains(&event))
+            .collect())
+    }
+
+    pub fn find_subscription(&self, id: &str) -> anyhow::Result<Option<WebhookSubscription>> {
+        let sql = format!(
+            "SELECT {} FROM webhook_subscriptions WHERE id = ?1",
+            WebhookSubscription::COLUMNS
+        );
+        Ok(query_optional(self.conn, &sql, [id])?)
+    }
+
+    /// Returns `None` when no subscription has the given id.
+    pub fn update_subscription(
+        &self,
+        id: &str,
+        request: CreateWebhookSubscriptionRequest,
+        is_active: bool,
+    ) -> anyhow::Result<Option<WebhookSubscription>> {
+        self.conn.execute(
+            "UPDATE webhook_subscriptions SET url = ?1, secret = ?2, event_types = ?3, is_active = ?4, updated_at = ?5
+             WHERE id = ?6",
+            (
+                &request.url,
+                &request.secret,
+                &serde_json::to_string(&request.event_types)?,
+                &is_active,
+                &timestamp::now(),
+                id,
+            ),
+        )?;
+
+        self.find_subscription(id)
+    }
+
+    /// Deletes the subscription together with its delivery log.
+    pub fn delete_subscription(&self, id: &str) -> anyhow::Result<()> {
+        self.conn.execute("DELETE FROM webhook_deliveries WHERE subscription_id = ?1", [id])?;
+        self.conn.execute("DELETE FROM webhook_subscriptions WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn list_deliveries(&self, subscription_id: Option<&str>) -> anyhow::Result<Vec<WebhookDelivery>> {
+        let sql = format!(
+            "SELECT {} FROM webhook_deliveries WHERE ?1 IS NULL OR subscription_id = ?1 ORDER BY created_at DESC",
+            WebhookDelivery::COLUMNS
+        );
+        Ok(query_all(self.conn, &sql, [subscription_id])?)
+    }
+
+    pub fn insert_delivery(&self, delivery: &WebhookDelivery) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT INTO webhook_deliveries (id, subscription_id, event_type, payload, status, attempts, response_status, error_message, created_at, last_attempt_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            (
+                &delivery.id,
+                &delivery.subscription_id,
+                &delivery.event_type.to_string(),
+                &delivery.payload,
+                &delivery.status.to_string(),
+                &delivery.attempts,
+                &delivery.response_status,
+                &delivery.error_message,
+                &timestamp::format(&delivery.created_at),
+                &delivery.last_attempt_at.as_ref().map(timestamp::format),
+            ),
+        )?;
+        Ok(())
+    }
+
+    pub fn update_delivery(&self, delivery: &WebhookDelivery) -> anyhow::Result<()> {
+        self.conn.execute(
+            "UPDATE webhook_deliveries SET status = ?1, attempts = ?2, response_status = ?3, error_message = ?4, last_attempt_at = ?5
+             WHERE id = ?6",
+            (
+                &delivery.status.to_string(),
+                &delivery.attempts,
+                &delivery.response_status,
+                &delivery.error_message,
+                &delivery.last_attempt_at.as_ref().map(timestamp::format),
+                &delivery.id,
+            ),
+        )?;
+        Ok(())
+    }
+}
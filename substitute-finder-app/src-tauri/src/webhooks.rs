@@ -0,0 +1,364 @@
+use crate::database::connection::DbConnection;
+use crate::database::models::{DeliveryStatus, WebhookDelivery, WebhookEventType, WebhookSubscription};
+use crate::repository::WebhookRepository;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Header carrying the `sha256=<hex>` HMAC of the raw request body.
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// Header carrying the event name, e.g. `request.created`.
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+/// Header carrying the delivery id, which receivers can use to de-duplicate retries.
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// How often and how patiently a delivery is retried before it is marked failed.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(2),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// JSON envelope posted to subscribers.
+#[derive(Debug, Serialize)]
+pub struct WebhookPayload<'a, T: Serialize> {
+    pub id: &'a str,
+    pub event: WebhookEventType,
+    pub created_at: DateTime<Utc>,
+    pub data: &'a T,
+}
+
+/// Computes the value of the signature header for `body` using the subscription secret.
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Fires `event` to every matching subscription on a background thread so the
+/// calling command never waits on the network.
+pub fn dispatch<T: Serialize>(db: DbConnection, event: WebhookEventType, data: &T) {
+    let data = match serde_json::to_value(data) {
+        Ok(data) => data,
+        Err(e) => {
+            eprintln!("Failed to serialize webhook payload for {}: {}", event, e);
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        if let Err(e) = dispatch_blocking(&db, event, &data, &RetryPolicy::default()) {
+            eprintln!("Failed to dispatch webhook {}: {}", event, e);
+        }
+    });
+}
+
+/// Delivers `event` to every active subscription listening for it, recording each
+/// attempt in `webhook_deliveries`. The connection lock is only held while reading
+/// or writing the log, never across HTTP calls.
+pub fn dispatch_blocking(
+    db: &DbConnection,
+    event: WebhookEventType,
+    data: &serde_json::Value,
+    policy: &RetryPolicy,
+) -> anyhow::Result<Vec<WebhookDelivery>> {
+    let subscriptions = {
+        let conn = db.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        WebhookRepository::new(&conn).list_subscriptions_for_event(event)?
+    };
+
+    let mut deliveries = Vec::new();
+    for subscription in subscriptions {
+        let delivery_id = Uuid::new_v4().to_string();
+        let body = serde_json::to_string(&WebhookPayload {
+            id: &delivery_id,
+            event,
+            created_at: Utc::now(),
+            data,
+        })?;
+
+        let mut delivery = WebhookDelivery {
+            id: delivery_id,
+            subscription_id: subscription.id.clone(),
+            event_type: event,
+            payload: body,
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            error_message: None,
+            created_at: Utc::now(),
+            last_attempt_at: None,
+        };
+
+        {
+            let conn = db.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+            WebhookRepository::new(&conn).insert_delivery(&delivery)?;
+        }
+
+        deliver(&subscription, &mut delivery, policy);
+
+        {
+            let conn = db.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+            WebhookRepository::new(&conn).update_delivery(&delivery)?;
+        }
+
+        deliveries.push(delivery);
+    }
+
+    Ok(deliveries)
+}
+
+/// POSTs the delivery payload, retrying with exponential backoff until a 2xx
+/// response or `policy.max_attempts` is reached.
+fn deliver(subscription: &WebhookSubscription, delivery: &mut WebhookDelivery, policy: &RetryPolicy) {
+    let agent = ureq::AgentBuilder::new().timeout(policy.timeout).build();
+    let signature = sign_payload(&subscription.secret, delivery.payload.as_bytes());
+    let mut backoff = policy.initial_backoff;
+
+    while delivery.attempts < policy.max_attempts {
+        if delivery.attempts > 0 {
+            std::thread::sleep(backoff);
+            backoff *= 2;
+        }
+
+        delivery.attempts += 1;
+        delivery.last_attempt_at = Some(Utc::now());
+
+        let result = agent
+            .post(&subscription.url)
+            .set("Content-Type", "application/json")
+            .set(SIGNATURE_HEADER, &signature)
+            .set(EVENT_HEADER, &delivery.event_type.to_string())
+            .set(DELIVERY_HEADER, &delivery.id)
+            .send_string(&delivery.payload);
+
+        match result {
+            Ok(response) => {
+                delivery.response_status = Some(response.status());
+                delivery.error_message = None;
+                delivery.status = DeliveryStatus::Delivered;
+                return;
+            }
+            Err(ureq::Error::Status(code, _)) => {
+                delivery.response_status = Some(code);
+                delivery.error_message = Some(format!("Receiver responded with HTTP {}", code));
+            }
+            Err(e) => {
+                delivery.response_status = None;
+                delivery.error_message = Some(e.to_string());
+            }
+        }
+    }
+
+    delivery.status = DeliveryStatus::Failed;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{mpsc, Arc, Mutex};
+
+    struct ReceivedRequest {
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl ReceivedRequest {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        }
+    }
+
+    /// Minimal HTTP receiver that answers each request with the next status in
+    /// `statuses` and forwards what it received over a channel.
+    fn spawn_receiver(statuses: Vec<u16>) -> (String, mpsc::Receiver<ReceivedRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut headers = Vec::new();
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).unwrap();
+                    let trimmed = line.trim_end();
+                    if trimmed.is_empty() {
+                        break;
+                    }
+                    if let Some((key, value)) = trimmed.split_once(':') {
+                        headers.push((key.trim().to_string(), value.trim().to_string()));
+                    }
+                }
+
+                let length = headers
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, value)| value.parse::<usize>().ok())
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut stream = reader.into_inner();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Test\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+
+                tx.send(ReceivedRequest {
+                    headers,
+                    body: String::from_utf8(body).unwrap(),
+                })
+                .unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    fn setup_db(url: &str, event_types: &[WebhookEventType]) -> DbConnection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("database/schema.sql")).unwrap();
+        conn.execute(
+            "INSERT INTO webhook_subscriptions (id, url, secret, event_types, is_active, created_at, updated_at)
+             VALUES ('sub-1', ?1, 'topsecret', ?2, true, ?3, ?3)",
+            (
+                url,
+                serde_json::to_string(event_types).unwrap(),
+                crate::database::timestamp::now(),
+            ),
+        )
+        .unwrap();
+        Arc::new(Mutex::new(conn))
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(10),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    #[test]
+    fn test_sign_payload_is_hex_hmac_sha256() {
+        // Reference value from RFC 4231 test case 2.
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_delivers_signed_payload_and_logs_it() {
+        let (url, rx) = spawn_receiver(vec![200]);
+        let db = setup_db(&url, &[WebhookEventType::RequestCreated]);
+        let data = serde_json::json!({ "id": "req-1", "status": "Open" });
+
+        let deliveries =
+            dispatch_blocking(&db, WebhookEventType::RequestCreated, &data, &fast_policy(3)).unwrap();
+
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].attempts, 1);
+
+        let received = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(received.header(EVENT_HEADER), Some("request.created"));
+        assert_eq!(received.header(DELIVERY_HEADER), Some(deliveries[0].id.as_str()));
+        assert_eq!(
+            received.header(SIGNATURE_HEADER),
+            Some(sign_payload("topsecret", received.body.as_bytes()).as_str())
+        );
+
+        let body: serde_json::Value = serde_json::from_str(&received.body).unwrap();
+        assert_eq!(body["event"], "request.created");
+        assert_eq!(body["data"]["id"], "req-1");
+
+        let conn = db.lock().unwrap();
+        let logged = WebhookRepository::new(&conn).list_deliveries(Some("sub-1")).unwrap();
+        assert_eq!(logged.len(), 1);
+        assert_eq!(logged[0].status, DeliveryStatus::Delivered);
+        assert_eq!(logged[0].response_status, Some(200));
+    }
+
+    #[test]
+    fn test_retries_until_success() {
+        let (url, rx) = spawn_receiver(vec![500, 503, 200]);
+        let db = setup_db(&url, &[WebhookEventType::RequestFilled]);
+
+        let deliveries = dispatch_blocking(
+            &db,
+            WebhookEventType::RequestFilled,
+            &serde_json::json!({}),
+            &fast_policy(5),
+        )
+        .unwrap();
+
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].attempts, 3);
+        assert_eq!(rx.iter().take(3).count(), 3);
+    }
+
+    #[test]
+    fn test_marks_failed_after_max_attempts() {
+        let (url, _rx) = spawn_receiver(vec![500, 500]);
+        let db = setup_db(&url, &[WebhookEventType::RequestCancelled]);
+
+        let deliveries = dispatch_blocking(
+            &db,
+            WebhookEventType::RequestCancelled,
+            &serde_json::json!({}),
+            &fast_policy(2),
+        )
+        .unwrap();
+
+        assert_eq!(deliveries[0].status, DeliveryStatus::Failed);
+        assert_eq!(deliveries[0].attempts, 2);
+        assert_eq!(deliveries[0].response_status, Some(500));
+    }
+
+    #[test]
+    fn test_skips_subscriptions_not_listening_for_event() {
+        let db = setup_db("http://127.0.0.1:9/unused", &[WebhookEventType::UserCreated]);
+
+        let deliveries = dispatch_blocking(
+            &db,
+            WebhookEventType::RequestCreated,
+            &serde_json::json!({}),
+            &fast_policy(1),
+        )
+        .unwrap();
+
+        assert!(deliveries.is_empty());
+    }
+}
@@ -25,6 +25,7 @@ export function OrganizationForm({
     description: organization?.description || '',
     contact_email: organization?.contact_email || '',
     contact_phone: organization?.contact_phone || '',
+    address: organization?.address || '',
   })
 
   const [loading, setLoading] = useState(false)
@@ -42,6 +43,7 @@ export function OrganizationForm({
         description: formData.description || undefined,
         contact_email: formData.contact_email || undefined,
         contact_phone: formData.contact_phone || undefined,
+        address: formData.address || undefined,
       }
 
       if (organization) {
@@ -147,6 +149,18 @@ export function OrganizationForm({
               />
             </div>
 
+            <div className="space-y-2 md:col-span-2">
+              <label htmlFor="address" className="text-sm font-medium">
+                Address
+              </label>
+              <Input
+                id="address"
+                value={formData.address || ''}
+                onChange={(e) => handleChange('address', e.target.value)}
+                placeholder="123 Main St, Springfield"
+              />
+            </div>
+
             <div className="space-y-2 md:col-span-2">
               <label htmlFor="description" className="text-sm font-medium">
                 Description
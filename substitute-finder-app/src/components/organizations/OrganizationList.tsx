@@ -114,6 +114,7 @@ export function OrganizationList() {
             <div className="text-sm text-muted-foreground space-y-1">
               {org.contact_email && <div>Email: {org.contact_email}</div>}
               {org.contact_phone && <div>Phone: {org.contact_phone}</div>}
+              {org.address && <div>Address: {org.address}</div>}
               <div>Created: {new Date(org.created_at).toLocaleDateString()}</div>
             </div>
           </CardContent>
@@ -115,7 +115,7 @@ export function RequestList() {
 
   const handleDeclineRequest = async (requestId: string) => {
     try {
-      await substituteApi.decline(requestId)
+      await substituteApi.decline(requestId, user?.id)
       addNotification({
         title: 'Request Declined',
         body: 'You have declined this substitute request',
@@ -48,6 +48,10 @@ export const userApi = {
 
   getAll: (): Promise<User[]> => invoke('get_users'),
 
+  update: (id: string, data: CreateUserRequest): Promise<User> => invoke('update_user', { id, request: data }),
+
+  delete: (id: string): Promise<void> => invoke('delete_user', { id }),
+
   login: (username: string, password: string): Promise<User> =>
     invoke('login', { username, password }),
 }
@@ -77,8 +81,8 @@ export const substituteApi = {
   accept: (requestId: string, substituteId: string): Promise<SubstituteRequest> =>
     invoke('accept_substitute_request', { requestId, substituteId }),
 
-  decline: (requestId: string): Promise<SubstituteRequest> =>
-    invoke('decline_substitute_request', { requestId }),
+  decline: (requestId: string, substituteId?: string, notes?: string): Promise<SubstituteRequest> =>
+    invoke('decline_substitute_request', { requestId, substituteId, notes }),
 
   getForUser: (userId: string, userRole: string): Promise<SubstituteRequest[]> =>
     invoke('get_substitute_requests_for_user', { userId, userRole }),
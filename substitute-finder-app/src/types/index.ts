@@ -5,6 +5,7 @@ export interface Organization {
   description?: string
   contact_email?: string
   contact_phone?: string
+  address?: string
   created_at: string
   updated_at: string
 }
@@ -43,8 +44,9 @@ export interface SubstituteRequest {
   end_time: string
   reason?: string
   special_instructions?: string
-  status: 'open' | 'filled' | 'cancelled'
+  status: 'open' | 'filled' | 'in_progress' | 'completed' | 'no_show' | 'cancelled'
   assigned_substitute_id?: string
+  absence_id?: string
   created_at: string
   updated_at: string
 }
@@ -55,6 +57,7 @@ export interface CreateOrganizationRequest {
   description?: string
   contact_email?: string
   contact_phone?: string
+  address?: string
 }
 
 export interface CreateClassRequest {